@@ -21,28 +21,46 @@ enum ResourceProcessingStateType {
   Pending = 'pending',
   Started = 'started',
   Failed = 'failed',
+  DeadLetter = 'dead_letter',
   Finished = 'finished'
 }
 
 enum EventBusMessageType {
-  ResourceProcessingMessage = 'ResourceProcessingMessage'
+  ResourceProcessingMessage = 'ResourceProcessingMessage',
+  PostProcessingBatchProgress = 'PostProcessingBatchProgress'
 }
 
 type ResourceProcessingState =
   | { type: ResourceProcessingStateType.Pending }
   | { type: ResourceProcessingStateType.Started }
   | { type: ResourceProcessingStateType.Failed; message: string }
+  | { type: ResourceProcessingStateType.DeadLetter; message: string }
   | { type: ResourceProcessingStateType.Finished }
 
-type EventBusMessage = {
+type ResourceProcessingMessage = {
   type: EventBusMessageType.ResourceProcessingMessage
   resource_id: string
   status: ResourceProcessingState
 }
 
+type PostProcessingBatchProgressMessage = {
+  type: EventBusMessageType.PostProcessingBatchProgress
+  batch_id: string
+  total: number
+  pending: number
+  started: number
+  finished: number
+  failed: number
+}
+
+type EventBusMessage = ResourceProcessingMessage | PostProcessingBatchProgressMessage
+
+type ProcessingPriority = 'user_initiated' | 'background_import' | 'reindex'
+
 export type SFFSOptions = {
   num_worker_threads?: number
   num_processor_threads?: number
+  processing_lanes?: Record<ProcessingPriority, { capacity: number; concurrency: number }>
   appPath?: string
   userDataPath?: string
 }
@@ -108,6 +126,7 @@ export const initSFFS = (opts?: SFFSOptions) => {
       case ResourceProcessingStateType.Finished:
         return true
       case ResourceProcessingStateType.Failed:
+      case ResourceProcessingStateType.DeadLetter:
         return typeof obj.status.message === 'string'
     }
 
@@ -125,6 +144,10 @@ export const initSFFS = (opts?: SFFSOptions) => {
       case EventBusMessageType.ResourceProcessingMessage:
         if (isResourceProcessingMessage(obj)) return obj as EventBusMessage
         throw new Error(`event bus message doesn't match type ${obj.type}`)
+      case EventBusMessageType.PostProcessingBatchProgress:
+        if (typeof obj.batch_id === 'string' && typeof obj.total === 'number')
+          return obj as EventBusMessage
+        throw new Error(`event bus message doesn't match type ${obj.type}`)
     }
 
     throw new Error(`invalid event bus message type: ${obj.type}`)
@@ -160,7 +183,8 @@ export const initSFFS = (opts?: SFFSOptions) => {
       language_setting,
       num_worker_threads,
       num_processor_threads,
-      js__backend_event_bus_callback
+      js__backend_event_bus_callback,
+      opts?.processing_lanes ? JSON.stringify(opts.processing_lanes) : undefined
     )
 
     if (ENABLE_DEBUG_PROXY) {
@@ -316,6 +340,8 @@ export class ResourceHandle {
   private writeHappened = false
   private currentHash: string
   private sffs: any
+  // set when the caller queues the post processing itself, e.g. once for a whole import
+  deferPostProcessing = false
 
   private constructor(
     fd: fsp.FileHandle,
@@ -519,7 +545,9 @@ export class ResourceHandle {
       const newHash = await this.computeResourceHash()
       if (this.currentHash !== newHash) {
         await this.sffs.js__store_upsert_resource_hash(this.resourceId, newHash)
-        await this.sffs.js__store_resource_post_process(this.resourceId)
+        if (!this.deferPostProcessing) {
+          await this.sffs.js__store_resource_post_process(this.resourceId, 'user_initiated')
+        }
       }
       this.currentHash = newHash
     }
@@ -546,7 +574,8 @@ export const initResources = (sffs: ReturnType<typeof initSFFS>, opts?: SFFSOpti
     resourceId: string,
     resourceType: string,
     resourcePath: string,
-    flags: string
+    flags: string,
+    deferPostProcessing = false
   ) {
     const resourceHandle = await ResourceHandle.open(
       sffs,
@@ -556,6 +585,7 @@ export const initResources = (sffs: ReturnType<typeof initSFFS>, opts?: SFFSOpti
       resourceType,
       resourcePath
     )
+    resourceHandle.deferPostProcessing = deferPostProcessing
     resourceHandles.set(resourceId, resourceHandle)
 
     return resourceId
@@ -590,8 +620,11 @@ export const initResources = (sffs: ReturnType<typeof initSFFS>, opts?: SFFSOpti
     resourceHandles.delete(resourceId)
   }
 
-  async function triggerPostProcessing(resourceId: string) {
-    await (sffs as any).js__store_resource_post_process(resourceId)
+  async function triggerPostProcessing(
+    resourceId: string,
+    priority: ProcessingPriority = 'user_initiated'
+  ) {
+    await (sffs as any).js__store_resource_post_process(resourceId, priority)
   }
 
   async function updateResourceHash(
@@ -0,0 +1,15 @@
+-- local folders whose files are kept in sync with resources in `space_id`
+CREATE TABLE IF NOT EXISTS watched_folders (
+    id TEXT PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    space_id TEXT NOT NULL REFERENCES spaces(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL
+);
+
+-- the resource created for each file, `path` is relative to the watched folder
+CREATE TABLE IF NOT EXISTS watched_files (
+    folder_id TEXT NOT NULL REFERENCES watched_folders(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    resource_id TEXT NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    PRIMARY KEY (folder_id, path)
+);
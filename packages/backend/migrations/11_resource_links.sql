@@ -0,0 +1,22 @@
+-- relations between resources, links found in the content of the source are replaced when it
+-- is processed again, `user` links are only changed by the user
+CREATE TABLE IF NOT EXISTS resource_links (
+    id TEXT PRIMARY KEY,
+    source_id TEXT NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    target_id TEXT NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    -- anchor text of the link or the annotated text
+    context TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS resource_links_source_id_index ON resource_links(source_id);
+CREATE INDEX IF NOT EXISTS resource_links_target_id_index ON resource_links(target_id);
+
+-- annotations were only related to their resource through their `annotates` tag
+INSERT INTO resource_links (id, source_id, target_id, kind, context, created_at)
+SELECT lower(hex(randomblob(16))), rt.resource_id, rt.tag_value, 'annotates', NULL, datetime('now')
+FROM resource_tags rt
+JOIN resources source ON source.id = rt.resource_id
+JOIN resources target ON target.id = rt.tag_value
+WHERE rt.tag_name = 'annotates' AND rt.resource_id != rt.tag_value;
@@ -0,0 +1,12 @@
+-- a snapshot of the file of a resource for each content hash it had, the snapshot is a copy
+-- of the file kept next to the resources
+CREATE TABLE IF NOT EXISTS resource_versions (
+    id TEXT PRIMARY KEY,
+    resource_id TEXT NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    snapshot_path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS resource_versions_resource_id_index ON resource_versions(resource_id);
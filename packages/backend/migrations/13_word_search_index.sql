@@ -0,0 +1,23 @@
+-- word level indexes next to the trigram ones, they match word forms and short words. they
+-- are external content tables over the trigram tables, so the text isn't stored twice and the
+-- store keeps them in sync on every write
+CREATE VIRTUAL TABLE IF NOT EXISTS resource_text_content_words USING fts5(
+    content,
+    content='resource_text_content',
+    tokenize="porter unicode61 remove_diacritics 2"
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS resource_metadata_words USING fts5(
+    name,
+    alt,
+    user_context,
+    byline,
+    content='resource_metadata',
+    tokenize="porter unicode61 remove_diacritics 2"
+);
+
+INSERT INTO resource_text_content_words(rowid, content)
+SELECT rowid, content FROM resource_text_content;
+
+INSERT INTO resource_metadata_words(rowid, name, alt, user_context, byline)
+SELECT rowid, name, alt, user_context, byline FROM resource_metadata;
@@ -0,0 +1,6 @@
+ALTER TABLE post_processing_jobs ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE post_processing_jobs ADD COLUMN max_attempts INTEGER NOT NULL DEFAULT 5;
+ALTER TABLE post_processing_jobs ADD COLUMN next_run_at TEXT;
+ALTER TABLE post_processing_jobs ADD COLUMN last_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_post_processing_jobs_next_run_at ON post_processing_jobs(next_run_at);
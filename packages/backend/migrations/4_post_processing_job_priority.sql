@@ -0,0 +1,4 @@
+ALTER TABLE post_processing_jobs ADD COLUMN priority TEXT NOT NULL DEFAULT 'user_initiated';
+ALTER TABLE post_processing_jobs ADD COLUMN batch_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_post_processing_jobs_batch_id ON post_processing_jobs(batch_id);
@@ -0,0 +1,19 @@
+-- fts5 tables can't be altered, so the metadata table is rebuilt with the new columns
+CREATE VIRTUAL TABLE IF NOT EXISTS resource_metadata_v2 USING fts5(
+    id UNINDEXED,
+    resource_id UNINDEXED,
+    name,
+    source_uri,
+    alt,
+    user_context,
+    byline,
+    published_at UNINDEXED,
+    lead_image UNINDEXED,
+    tokenize="trigram"
+);
+
+INSERT INTO resource_metadata_v2 (id, resource_id, name, source_uri, alt, user_context)
+SELECT id, resource_id, name, source_uri, alt, user_context FROM resource_metadata;
+
+DROP TABLE resource_metadata;
+ALTER TABLE resource_metadata_v2 RENAME TO resource_metadata;
@@ -0,0 +1,5 @@
+-- fts5 tables can't be altered, so the document outline is kept next to the metadata
+CREATE TABLE IF NOT EXISTS resource_metadata_outlines (
+    resource_id TEXT PRIMARY KEY REFERENCES resources(id) ON DELETE CASCADE,
+    outline TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+-- 64 bit perceptual hashes of image resources as 16 hex characters, used to find similar images
+CREATE TABLE IF NOT EXISTS resource_perceptual_hashes (
+    resource_id TEXT PRIMARY KEY REFERENCES resources(id) ON DELETE CASCADE,
+    perceptual_hash TEXT NOT NULL
+);
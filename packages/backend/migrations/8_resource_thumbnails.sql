@@ -0,0 +1,9 @@
+-- generated previews, the file name contains the content hash it was generated from
+CREATE TABLE IF NOT EXISTS resource_thumbnails (
+    resource_id TEXT PRIMARY KEY REFERENCES resources(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
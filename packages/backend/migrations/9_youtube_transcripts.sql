@@ -0,0 +1,8 @@
+-- fetched transcripts keyed by the requested language, `transcript` is the serialized transcript
+CREATE TABLE IF NOT EXISTS youtube_transcripts (
+    video_id TEXT NOT NULL,
+    language TEXT NOT NULL,
+    transcript TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (video_id, language)
+);
@@ -5,7 +5,7 @@ use crate::{
             js_tools::{tool_ids, JSToolRegistry},
         },
         llm::models::{ContextMessage, Message},
-        youtube::{fetch_transcript, is_youtube_video_url},
+        youtube::{fetch_transcript_cached, is_youtube_video_url},
     },
     store::{
         db::Database,
@@ -338,7 +338,8 @@ impl ContextManager for LLMContext {
     // TODO: can't really ignore the key in check in the trait impl but it's fine for now
     fn add_url(&mut self, _key: &str, url: &str) -> BackendResult<()> {
         let (content_type, title, content, _screenshot) = if is_youtube_video_url(url) {
-            let yt_transcript = fetch_transcript(url, self.user_lang_preference.as_deref())?;
+            let yt_transcript =
+                fetch_transcript_cached(&mut self.db, url, self.user_lang_preference.as_deref())?;
             (
                 "Context(YouTube Transcript)",
                 None,
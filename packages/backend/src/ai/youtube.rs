@@ -1,6 +1,7 @@
-use crate::BackendError;
+use crate::{store::db::Database, BackendError, BackendResult};
 use quick_xml::{events::Event, Reader};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct YoutubeTranscript {
@@ -11,6 +12,39 @@ pub struct YoutubeTranscript {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct YoutubeTranscriptMetadata {
     pub transcript_pieces: Vec<YoutubeTranscriptPiece>,
+    // language of the track the transcript was read from
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub source: Option<YoutubeTranscriptSource>,
+}
+
+// which step of the fallback chain the transcript came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum YoutubeTranscriptSource {
+    Preferred,
+    Original,
+    AutoGenerated,
+    Translated,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum YoutubeTranscriptError {
+    #[error("invalid YouTube video URL")]
+    InvalidUrl,
+    #[error("no captions available for this video")]
+    NoCaptions,
+    #[error("video is age restricted")]
+    AgeRestricted,
+    #[error("rate limited by YouTube")]
+    RateLimited,
+    #[error("video is unavailable: {0}")]
+    Unavailable(String),
+    #[error("request failed: {0}")]
+    Request(String),
+    #[error("failed to parse transcript: {0}")]
+    Parse(String),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,25 +89,143 @@ pub fn is_youtube_video_url(url: &str) -> bool {
     extract_youtube_video_id(url).is_some()
 }
 
-pub fn fetch_transcript(
-    video_url: &str,
-    preferred_lang: Option<&str>,
-) -> Result<YoutubeTranscript, BackendError> {
-    let rt = tokio::runtime::Runtime::new()
-        .map_err(|e| BackendError::GenericError(format!("Failed to create runtime: {}", e)))?;
+const DEFAULT_BASE_URL: &str = "https://www.youtube.com";
+// overrides the base URL, e.g. to run against a local fixture server
+const BASE_URL_ENV: &str = "SURF_YOUTUBE_BASE_URL";
+const DEFAULT_LANGUAGE: &str = "en";
+const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/85.0.4183.83 Safari/537.36";
+
+#[derive(Debug)]
+struct CaptionTrack {
+    base_url: String,
+    language: String,
+    auto_generated: bool,
+    translatable: bool,
+}
+
+#[derive(Debug)]
+struct TranscriptCandidate {
+    url: String,
+    language: String,
+    source: YoutubeTranscriptSource,
+}
+
+pub struct YoutubeTranscriptFetcher {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Default for YoutubeTranscriptFetcher {
+    fn default() -> Self {
+        let base_url = std::env::var(BASE_URL_ENV).unwrap_or_else(|_| DEFAULT_BASE_URL.to_owned());
+        Self::with_base_url(&base_url)
+    }
+}
+
+impl YoutubeTranscriptFetcher {
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    pub fn fetch(
+        &self,
+        video_url: &str,
+        preferred_lang: Option<&str>,
+    ) -> Result<YoutubeTranscript, YoutubeTranscriptError> {
+        let video_id =
+            extract_youtube_video_id(video_url).ok_or(YoutubeTranscriptError::InvalidUrl)?;
+        self.fetch_video(&video_id, preferred_lang.unwrap_or(DEFAULT_LANGUAGE))
+    }
+
+    // transcripts are cached per video and requested language, failures are not cached
+    // so that e.g. a rate limited request is retried the next time
+    pub fn fetch_cached(
+        &self,
+        db: &mut Database,
+        video_url: &str,
+        preferred_lang: Option<&str>,
+    ) -> BackendResult<YoutubeTranscript> {
+        let video_id =
+            extract_youtube_video_id(video_url).ok_or(YoutubeTranscriptError::InvalidUrl)?;
+        let language = preferred_lang.unwrap_or(DEFAULT_LANGUAGE);
+
+        if let Some(cached) = db.get_youtube_transcript(&video_id, language)? {
+            match serde_json::from_str(&cached) {
+                Ok(transcript) => return Ok(transcript),
+                Err(e) => {
+                    tracing::warn!("ignoring unreadable cached transcript of {video_id}: {e}")
+                }
+            }
+        }
+
+        let transcript = self.fetch_video(&video_id, language)?;
+        let mut tx = db.begin()?;
+        Database::upsert_youtube_transcript_tx(
+            &mut tx,
+            &video_id,
+            language,
+            &serde_json::to_string(&transcript)?,
+        )?;
+        tx.commit()?;
+        Ok(transcript)
+    }
+
+    fn fetch_video(
+        &self,
+        video_id: &str,
+        preferred_lang: &str,
+    ) -> Result<YoutubeTranscript, YoutubeTranscriptError> {
+        let player = self.fetch_player(video_id)?;
+        check_playability(&player)?;
 
-    // Extract video ID
-    let video_id = extract_youtube_video_id(video_url)
-        .ok_or_else(|| BackendError::GenericError("Invalid YouTube video URL".to_string()))?;
+        let tracks = caption_tracks(&player);
+        let mut last_error = YoutubeTranscriptError::NoCaptions;
+        // a track can come back empty or be gone, in that case the next one is tried
+        for candidate in transcript_candidates(&tracks, preferred_lang) {
+            let xml = match self.get(&self.resolve_url(&candidate.url)) {
+                Ok(xml) => xml,
+                Err(YoutubeTranscriptError::RateLimited) => {
+                    return Err(YoutubeTranscriptError::RateLimited)
+                }
+                Err(e) => {
+                    tracing::debug!("failed to fetch {:?} transcript: {e}", candidate.source);
+                    last_error = e;
+                    continue;
+                }
+            };
+            let transcript_pieces = parse_transcript_xml(&xml)?;
+            if transcript_pieces.is_empty() {
+                continue;
+            }
+
+            let transcript = transcript_pieces
+                .iter()
+                .map(|piece| piece.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            return Ok(YoutubeTranscript {
+                transcript,
+                metadata: YoutubeTranscriptMetadata {
+                    transcript_pieces,
+                    language: Some(candidate.language),
+                    source: Some(candidate.source),
+                },
+            });
+        }
+        Err(last_error)
+    }
 
-    // Fetch player data
-    let player_data = rt.block_on(async {
-        reqwest::Client::new()
-            .post("https://www.youtube.com/youtubei/v1/player")
+    fn fetch_player(&self, video_id: &str) -> Result<Value, YoutubeTranscriptError> {
+        let response = self
+            .client
+            .post(format!("{}/youtubei/v1/player", self.base_url))
             .header("Content-Type", "application/json")
-            .header("Origin", "https://www.youtube.com")
-            .header("Referer", format!("https://www.youtube.com/watch?v={}", video_id))
-            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/85.0.4183.83 Safari/537.36")
+            .header("Origin", DEFAULT_BASE_URL)
+            .header("Referer", format!("{DEFAULT_BASE_URL}/watch?v={video_id}"))
+            .header("User-Agent", USER_AGENT)
             .json(&serde_json::json!({
                 "context": {
                     "client": {
@@ -84,153 +236,451 @@ pub fn fetch_transcript(
                 "videoId": video_id
             }))
             .send()
-            .await
-            .map_err(|e| BackendError::GenericError(format!("Failed to fetch player data: {}", e)))?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(|e| BackendError::GenericError(format!("Failed to parse player data: {}", e)))
-    })?;
-
-    // Get captions data
-    let captions = player_data
-        .get("captions")
-        .and_then(|c| c.get("playerCaptionsTracklistRenderer"))
-        .and_then(|p| p.get("captionTracks"))
-        .ok_or_else(|| {
-            BackendError::GenericError("No captions found for this video".to_string())
-        })?;
-
-    // Find the best caption track
-    let tracks = captions.as_array().unwrap();
-    if tracks.is_empty() {
-        return Err(BackendError::GenericError(
-            "No caption tracks found".to_string(),
-        ));
+            .map_err(|e| YoutubeTranscriptError::Request(e.to_string()))?;
+        let body = read_response(response)?;
+        serde_json::from_str(&body).map_err(|e| YoutubeTranscriptError::Parse(e.to_string()))
     }
 
-    let preferred_lang = preferred_lang.unwrap_or("en");
+    fn get(&self, url: &str) -> Result<String, YoutubeTranscriptError> {
+        let response = self
+            .client
+            .get(url)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .map_err(|e| YoutubeTranscriptError::Request(e.to_string()))?;
+        read_response(response)
+    }
 
-    // First try: find track in preferred language (prioritize manual over auto-generated)
-    let mut selected_track = tracks
-        .iter()
-        .find(|track| {
-            let lang_code = track
-                .get("languageCode")
-                .and_then(|l| l.as_str())
-                .unwrap_or("");
-            let is_auto = track
-                .get("kind")
-                .and_then(|k| k.as_str())
-                .map(|k| k == "asr")
-                .unwrap_or(false);
-            lang_code == preferred_lang && !is_auto
-        })
-        .or_else(|| {
-            tracks.iter().find(|track| {
-                let lang_code = track
-                    .get("languageCode")
-                    .and_then(|l| l.as_str())
-                    .unwrap_or("");
-                lang_code == preferred_lang
-            })
-        });
+    // caption urls in the player response are absolute youtube urls or relative paths,
+    // both are pointed at the configured base URL
+    fn resolve_url(&self, url: &str) -> String {
+        match url.strip_prefix(DEFAULT_BASE_URL) {
+            Some(path) => format!("{}{path}", self.base_url),
+            None if url.starts_with('/') => format!("{}{url}", self.base_url),
+            None => url.to_owned(),
+        }
+    }
+}
 
-    // Second try: find any non-auto-generated track
-    if selected_track.is_none() {
-        selected_track = tracks.iter().find(|track| {
-            let is_auto = track
-                .get("kind")
-                .and_then(|k| k.as_str())
-                .map(|k| k == "asr")
-                .unwrap_or(false);
-            !is_auto
-        });
+fn read_response(response: reqwest::blocking::Response) -> Result<String, YoutubeTranscriptError> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(YoutubeTranscriptError::RateLimited);
     }
+    if !status.is_success() {
+        return Err(YoutubeTranscriptError::Request(format!(
+            "{} returned {status}",
+            response.url()
+        )));
+    }
+    response
+        .text()
+        .map_err(|e| YoutubeTranscriptError::Request(e.to_string()))
+}
 
-    // Final fallback: use first available track
-    let selected_track = selected_track.unwrap_or(&tracks[0]);
+fn check_playability(player: &Value) -> Result<(), YoutubeTranscriptError> {
+    let status = player
+        .pointer("/playabilityStatus/status")
+        .and_then(Value::as_str)
+        .unwrap_or("OK");
+    let reason = player
+        .pointer("/playabilityStatus/reason")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let lowercase_reason = reason.to_lowercase();
 
-    // Get caption URL
-    let caption_url = selected_track
-        .get("baseUrl")
-        .and_then(|u| u.as_str())
-        .ok_or_else(|| BackendError::GenericError("Failed to get caption URL".to_string()))?;
+    match status {
+        "OK" => Ok(()),
+        "AGE_VERIFICATION_REQUIRED" | "AGE_CHECK_REQUIRED" => {
+            Err(YoutubeTranscriptError::AgeRestricted)
+        }
+        "LOGIN_REQUIRED" if lowercase_reason.contains("confirm your age") => {
+            Err(YoutubeTranscriptError::AgeRestricted)
+        }
+        // youtube asks to sign in when it thinks the requests come from a bot
+        "LOGIN_REQUIRED" if lowercase_reason.contains("not a bot") => {
+            Err(YoutubeTranscriptError::RateLimited)
+        }
+        _ if reason.is_empty() => Err(YoutubeTranscriptError::Unavailable(status.to_owned())),
+        _ => Err(YoutubeTranscriptError::Unavailable(reason.to_owned())),
+    }
+}
 
-    // Fetch transcript XML
-    let xml_text = rt.block_on(async {
-        reqwest::Client::new()
-            .get(caption_url)
-            .send()
-            .await
-            .map_err(|e| BackendError::GenericError(format!("Failed to fetch transcript: {}", e)))?
-            .text()
-            .await
-            .map_err(|e| BackendError::GenericError(format!("Failed to read transcript: {}", e)))
-    })?;
-
-    // Parse XML into transcript
-    let mut transcript_pieces = Vec::new();
-    let mut full_text = String::new();
-
-    // Use quick-xml to parse the XML
-    let mut reader = Reader::from_str(&xml_text);
+fn caption_tracks(player: &Value) -> Vec<CaptionTrack> {
+    player
+        .pointer("/captions/playerCaptionsTracklistRenderer/captionTracks")
+        .and_then(Value::as_array)
+        .map(|tracks| {
+            tracks
+                .iter()
+                .filter_map(|track| {
+                    Some(CaptionTrack {
+                        base_url: track.get("baseUrl")?.as_str()?.to_owned(),
+                        language: track.get("languageCode")?.as_str()?.to_owned(),
+                        auto_generated: track.get("kind").and_then(Value::as_str) == Some("asr"),
+                        translatable: track
+                            .get("isTranslatable")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// `en-US` matches a preferred `en` and the other way around
+fn language_matches(language: &str, preferred: &str) -> bool {
+    let primary = |language: &str| {
+        language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+    primary(language) == primary(preferred)
+}
+
+// the order tracks are tried in: a manual track in the preferred language, a manual
+// track in the language the video was recorded in, the auto generated track and
+// finally a youtube translation into the preferred language
+fn transcript_candidates(tracks: &[CaptionTrack], preferred: &str) -> Vec<TranscriptCandidate> {
+    let manual = || tracks.iter().filter(|track| !track.auto_generated);
+    // the speech recognition track is always in the spoken language
+    let original_language = tracks
+        .iter()
+        .find(|track| track.auto_generated)
+        .or_else(|| manual().next())
+        .map(|track| track.language.as_str());
+
+    let mut candidates: Vec<TranscriptCandidate> = vec![];
+    let mut push = |track: &CaptionTrack, source: YoutubeTranscriptSource| {
+        if !candidates.iter().any(|c| c.url == track.base_url) {
+            candidates.push(TranscriptCandidate {
+                url: track.base_url.clone(),
+                language: track.language.clone(),
+                source,
+            });
+        }
+    };
+    for track in manual().filter(|track| language_matches(&track.language, preferred)) {
+        push(track, YoutubeTranscriptSource::Preferred);
+    }
+    for track in manual().filter(|track| Some(track.language.as_str()) == original_language) {
+        push(track, YoutubeTranscriptSource::Original);
+    }
+    for track in tracks.iter().filter(|track| track.auto_generated) {
+        push(track, YoutubeTranscriptSource::AutoGenerated);
+    }
+
+    let translatable = manual()
+        .chain(tracks.iter().filter(|track| track.auto_generated))
+        .find(|track| track.translatable);
+    if let Some(track) = translatable {
+        let separator = if track.base_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        candidates.push(TranscriptCandidate {
+            url: format!("{}{separator}tlang={preferred}", track.base_url),
+            language: preferred.to_owned(),
+            source: YoutubeTranscriptSource::Translated,
+        });
+    }
+    candidates
+}
+
+// handles both the legacy `<text start="1.2" dur="3.4">` format in seconds and the
+// `<p t="1200" d="3400">` format in milliseconds whose text is split into `<s>` segments
+fn parse_transcript_xml(xml: &str) -> Result<Vec<YoutubeTranscriptPiece>, YoutubeTranscriptError> {
+    let mut reader = Reader::from_str(xml);
     let mut buf = Vec::new();
-    let mut in_text = false;
-    let mut current_start = 0.0;
-    let mut current_duration = 0.0;
-    let mut current_text = String::new();
+    let mut pieces = Vec::new();
+    let mut current: Option<(f64, f64, String)> = None;
 
     loop {
         match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                if e.name().as_ref() == b"text" {
-                    in_text = true;
-                    // Parse start and duration attributes
-                    for attr in e.attributes().flatten() {
-                        match attr.key.as_ref() {
-                            b"start" => {
-                                if let Ok(start) = String::from_utf8_lossy(&attr.value).parse() {
-                                    current_start = start;
-                                }
-                            }
-                            b"dur" => {
-                                if let Ok(duration) = String::from_utf8_lossy(&attr.value).parse() {
-                                    current_duration = duration;
-                                }
-                            }
-                            _ => {}
-                        }
+            Ok(Event::Start(ref e)) if matches!(e.name().as_ref(), b"text" | b"p") => {
+                let scale = if e.name().as_ref() == b"p" {
+                    1000.0
+                } else {
+                    1.0
+                };
+                let mut start = 0.0;
+                let mut duration = 0.0;
+                for attr in e.attributes().flatten() {
+                    let value = String::from_utf8_lossy(&attr.value).parse::<f64>().ok();
+                    match (attr.key.as_ref(), value) {
+                        (b"start" | b"t", Some(value)) => start = value / scale,
+                        (b"dur" | b"d", Some(value)) => duration = value / scale,
+                        _ => {}
                     }
                 }
+                current = Some((start, duration, String::new()));
             }
-            Ok(Event::Text(e)) if in_text => {
-                let text = e.unescape().unwrap().into_owned();
-                current_text = text.clone();
-                full_text.push_str(&text);
-                full_text.push(' ');
+            Ok(Event::Text(e)) => {
+                if let Some((_, _, text)) = current.as_mut() {
+                    let value = e
+                        .unescape()
+                        .map_err(|e| YoutubeTranscriptError::Parse(e.to_string()))?;
+                    text.push_str(&value);
+                }
             }
-            Ok(Event::End(ref e)) if e.name().as_ref() == b"text" => {
-                in_text = false;
-                transcript_pieces.push(YoutubeTranscriptPiece {
-                    text: current_text.clone(),
-                    start: current_start,
-                    duration: current_duration,
-                });
+            Ok(Event::End(ref e)) if matches!(e.name().as_ref(), b"text" | b"p") => {
+                if let Some((start, duration, text)) = current.take() {
+                    // captions are escaped twice, e.g. `&amp;#39;`
+                    let text = html_escape::decode_html_entities(&text)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if !text.is_empty() {
+                        pieces.push(YoutubeTranscriptPiece {
+                            text,
+                            start,
+                            duration,
+                        });
+                    }
+                }
             }
             Ok(Event::Eof) => break,
-            Err(e) => {
-                return Err(BackendError::GenericError(format!(
-                    "Error parsing XML: {}",
-                    e
-                )))
-            }
+            Err(e) => return Err(YoutubeTranscriptError::Parse(e.to_string())),
             _ => (),
         }
         buf.clear();
     }
+    Ok(pieces)
+}
+
+pub fn fetch_transcript(
+    video_url: &str,
+    preferred_lang: Option<&str>,
+) -> Result<YoutubeTranscript, BackendError> {
+    Ok(YoutubeTranscriptFetcher::default().fetch(video_url, preferred_lang)?)
+}
+
+pub fn fetch_transcript_cached(
+    db: &mut Database,
+    video_url: &str,
+    preferred_lang: Option<&str>,
+) -> BackendResult<YoutubeTranscript> {
+    YoutubeTranscriptFetcher::default().fetch_cached(db, video_url, preferred_lang)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    const VIDEO_URL: &str = "https://www.youtube.com/watch?v=abc123";
+
+    // answers every request with the first route whose prefix matches the request path,
+    // returns the base URL and the request paths seen so far
+    fn serve(routes: Vec<(&'static str, u16, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = requests.clone();
 
-    Ok(YoutubeTranscript {
-        transcript: full_text.trim().to_string(),
-        metadata: YoutubeTranscriptMetadata { transcript_pieces },
-    })
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_owned();
+                let (status, body) = routes
+                    .iter()
+                    .find(|(prefix, _, _)| path.starts_with(prefix))
+                    .map(|(_, status, body)| (*status, body.as_str()))
+                    .unwrap_or((404, ""));
+                seen.lock().unwrap().push(path);
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Fixture\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        (base_url, requests)
+    }
+
+    fn player(tracks: serde_json::Value) -> String {
+        serde_json::json!({
+            "playabilityStatus": { "status": "OK" },
+            "captions": { "playerCaptionsTracklistRenderer": { "captionTracks": tracks } }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_fetch_prefers_manual_track_in_preferred_language() {
+        let (base_url, _) = serve(vec![
+            (
+                "/youtubei/v1/player",
+                200,
+                player(serde_json::json!([
+                    { "baseUrl": "https://www.youtube.com/api/timedtext?v=abc123&lang=en&kind=asr", "languageCode": "en", "kind": "asr" },
+                    { "baseUrl": "/api/timedtext?v=abc123&lang=de", "languageCode": "de" },
+                    { "baseUrl": "/api/timedtext?v=abc123&lang=en-GB", "languageCode": "en-GB" }
+                ])),
+            ),
+            (
+                "/api/timedtext?v=abc123&lang=en-GB",
+                200,
+                r#"<timedtext format="3"><body><p t="1500" d="2000"><s>it&amp;#39;s</s><s> here</s></p><p t="4000" d="1000">
+again</p></body></timedtext>"#
+                    .to_owned(),
+            ),
+        ]);
+
+        let transcript = YoutubeTranscriptFetcher::with_base_url(&base_url)
+            .fetch(VIDEO_URL, Some("en"))
+            .unwrap();
+        assert_eq!(transcript.transcript, "it's here again");
+        assert_eq!(transcript.metadata.language.as_deref(), Some("en-GB"));
+        assert_eq!(
+            transcript.metadata.source,
+            Some(YoutubeTranscriptSource::Preferred)
+        );
+        let first = &transcript.metadata.transcript_pieces[0];
+        assert_eq!((first.start, first.duration), (1.5, 2.0));
+    }
+
+    #[test]
+    fn test_fetch_falls_back_to_translation() {
+        let (base_url, requests) = serve(vec![
+            (
+                "/youtubei/v1/player",
+                200,
+                player(serde_json::json!([
+                    { "baseUrl": "/api/timedtext?v=abc123&lang=fr&kind=asr", "languageCode": "fr", "kind": "asr" },
+                    { "baseUrl": "/api/timedtext?v=abc123&lang=fr", "languageCode": "fr", "isTranslatable": true }
+                ])),
+            ),
+            (
+                "/api/timedtext?v=abc123&lang=fr&tlang=de",
+                200,
+                r#"<transcript><text start="0.5" dur="1.25">hallo</text></transcript>"#.to_owned(),
+            ),
+            // the original track is empty and the auto generated one is gone
+            (
+                "/api/timedtext?v=abc123&lang=fr&kind=asr",
+                404,
+                String::new(),
+            ),
+            (
+                "/api/timedtext?v=abc123&lang=fr",
+                200,
+                "<transcript></transcript>".to_owned(),
+            ),
+        ]);
+
+        let transcript = YoutubeTranscriptFetcher::with_base_url(&base_url)
+            .fetch(VIDEO_URL, Some("de"))
+            .unwrap();
+        assert_eq!(transcript.transcript, "hallo");
+        assert_eq!(transcript.metadata.language.as_deref(), Some("de"));
+        assert_eq!(
+            transcript.metadata.source,
+            Some(YoutubeTranscriptSource::Translated)
+        );
+        assert_eq!(
+            requests.lock().unwrap()[1..],
+            [
+                "/api/timedtext?v=abc123&lang=fr",
+                "/api/timedtext?v=abc123&lang=fr&kind=asr",
+                "/api/timedtext?v=abc123&lang=fr&tlang=de",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let fetch = |status: u16, body: String| {
+            let (base_url, _) = serve(vec![("/youtubei/v1/player", status, body)]);
+            YoutubeTranscriptFetcher::with_base_url(&base_url).fetch(VIDEO_URL, None)
+        };
+
+        let age_restricted = serde_json::json!({
+            "playabilityStatus": { "status": "LOGIN_REQUIRED", "reason": "Sign in to confirm your age" }
+        });
+        assert!(matches!(
+            fetch(200, age_restricted.to_string()),
+            Err(YoutubeTranscriptError::AgeRestricted)
+        ));
+        assert!(matches!(
+            fetch(429, String::new()),
+            Err(YoutubeTranscriptError::RateLimited)
+        ));
+        assert!(matches!(
+            fetch(200, player(serde_json::json!([]))),
+            Err(YoutubeTranscriptError::NoCaptions)
+        ));
+        assert!(matches!(
+            YoutubeTranscriptFetcher::with_base_url("http://127.0.0.1:1")
+                .fetch("https://example.com", None),
+            Err(YoutubeTranscriptError::InvalidUrl)
+        ));
+    }
+
+    #[test]
+    fn test_fetch_cached_is_keyed_by_language() {
+        let (base_url, requests) = serve(vec![
+            (
+                "/youtubei/v1/player",
+                200,
+                player(serde_json::json!([
+                    { "baseUrl": "/api/timedtext?v=abc123&lang=en", "languageCode": "en" }
+                ])),
+            ),
+            (
+                "/api/timedtext",
+                200,
+                r#"<transcript><text start="0" dur="1">cached</text></transcript>"#.to_owned(),
+            ),
+        ]);
+        let dir = tempdir().unwrap();
+        let mut db = Database::new(&dir.path().join("test.db").to_string_lossy(), true).unwrap();
+        let fetcher = YoutubeTranscriptFetcher::with_base_url(&base_url);
+
+        let first = fetcher
+            .fetch_cached(&mut db, VIDEO_URL, Some("en"))
+            .unwrap();
+        let again = fetcher
+            .fetch_cached(&mut db, "https://youtu.be/abc123", Some("en"))
+            .unwrap();
+        assert_eq!(first.transcript, "cached");
+        assert_eq!(again.transcript, "cached");
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        fetcher
+            .fetch_cached(&mut db, VIDEO_URL, Some("de"))
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
 }
@@ -41,6 +41,7 @@ pub enum WorkerMessage {
 pub enum HistoryMessage {
     CreateHistoryEntry(HistoryEntry),
     GetAllHistoryEntries(Option<usize>),
+    GetHistoryEntriesPage(PageRequest),
     GetHistoryEntry(String),
     RemoveHistoryEntry(String),
     UpdateHistoryEntry(HistoryEntry),
@@ -91,7 +92,18 @@ pub enum SpaceMessage {
         order_by: Option<String>,
         limit: Option<usize>,
     },
+    GetSpaceEntriesPage {
+        space_id: String,
+        sort_by: Option<String>,
+        order_by: Option<String>,
+        page: PageRequest,
+    },
     DeleteSpaceEntries(Vec<DeleteSpaceEntryInput>),
+    // writes the space and its nested spaces to a folder as markdown files
+    ExportMarkdownVault {
+        space_id: String,
+        path: String,
+    },
     MoveSpace {
         space_id: String,
         new_parent_space_id: String,
@@ -112,11 +124,18 @@ pub enum ResourceMessage {
     },
     GetResource(String, bool),
     RemoveResources(Vec<String>),
-    RemoveResourcesByTags(Vec<ResourceTagFilter>),
+    RemoveResourcesByTags(ResourceFilter),
     RecoverResource(String),
-    ListResourcesByTags(Vec<ResourceTagFilter>),
-    ListResourcesByTagsNoSpace(Vec<ResourceTagFilter>),
-    ListAllResourcesAndSpaces(Vec<ResourceTagFilter>),
+    ListResourcesByTags {
+        filter: ResourceFilter,
+        include_facets: bool,
+        page: Option<PageRequest>,
+    },
+    ListResourcesByTagsNoSpace {
+        filter: ResourceFilter,
+        page: Option<PageRequest>,
+    },
+    ListAllResourcesAndSpaces(ResourceFilter),
     SearchResources(SearchResourcesParams),
     UpdateResource(Resource),
     UpdateResourceMetadata(ResourceMetadata),
@@ -132,13 +151,111 @@ pub enum ResourceMessage {
     },
     GetResourceHash(String),
     DeleteResourceHash(String),
+    ApplyExtractedResourceMetadata {
+        resource_id: String,
+        metadata: ExtractedResourceMetadata,
+    },
+    // replaces the values of tags with the same names, e.g. image capture details
+    SetExtractedResourceTags {
+        resource_id: String,
+        tags: Vec<ResourceTag>,
+    },
+    // replaces the previous thumbnail and removes its file
+    SetResourceThumbnail(ResourceThumbnail),
+    UpsertResourcePerceptualHash {
+        resource_id: String,
+        hash: u64,
+    },
+    ListSimilarImageResources {
+        resource_id: String,
+        max_distance: Option<u32>,
+    },
+    // path of an `.eml` or `.mbox` file
+    ImportMail(String),
+    ApplyResourceFrontmatter {
+        resource_id: String,
+        frontmatter: ResourceFrontmatter,
+    },
+    // resource id, returns the markdown with the metadata and tags in its frontmatter
+    ExportResourceMarkdown(String),
+    // path of a folder of markdown files
+    ImportMarkdownVault(String),
+    // path of a local folder whose files are kept in sync with resources in the space,
+    // a space is created for the folder without one
+    AddWatchedFolder {
+        path: String,
+        space_id: Option<String>,
+    },
+    RemoveWatchedFolder(String),
+    ListWatchedFolders,
+    // paths relative to the folder, the empty path syncs the whole folder
+    SyncWatchedFolder {
+        folder_id: String,
+        paths: Vec<String>,
+    },
+    ResumeWatchedFolders,
+    SetExtractedResourceLinks {
+        resource_id: String,
+        links: Vec<ExtractedResourceLink>,
+    },
+    // links from the user, links found in notes and annotations are extracted
+    CreateResourceLink {
+        source_id: String,
+        target_id: String,
+        context: Option<String>,
+    },
+    RemoveResourceLink(String),
+    ListOutgoingResourceLinks(String),
+    ListResourceBacklinks(String),
+    // the resources within `hops` links of the resource, in either direction
+    GetResourceGraph {
+        resource_id: String,
+        hops: usize,
+    },
+    // newest first
+    ListResourceVersions(String),
+    GetResourceVersionContent(String),
+    DiffResourceVersions {
+        from_version_id: String,
+        to_version_id: String,
+    },
+    RestoreResourceVersion(String),
+    // groups of resources saved more than once, `include_similar` also compares their text
+    FindDuplicateResources {
+        include_similar: bool,
+    },
+    MergeResources {
+        target_id: String,
+        source_ids: Vec<String>,
+    },
+    GetSearchLanguage,
+    // rebuilds the word search index with the stemming of the language
+    SetSearchLanguage(SearchLanguage),
     // ---
-    PostProcessJob(String),
+    PostProcessJob {
+        resource_id: String,
+        priority: ProcessingPriority,
+    },
+    BatchPostProcessJob {
+        resource_ids: Vec<String>,
+        priority: ProcessingPriority,
+    },
+    SendPostProcessingBatchProgress(String),
     SetPostProcessingState {
         id: String,
         state: ResourceProcessingState,
     },
-    FailActivePostProcessingJobs,
+    RecordPostProcessingFailure {
+        id: String,
+        error: String,
+    },
+    ListPostProcessingJobs {
+        state: Option<String>,
+        limit: Option<usize>,
+    },
+    RetryPostProcessingJob(String),
+    DispatchDuePostProcessingJobs,
+    ResumePostProcessingJobs,
 }
 
 #[derive(Debug)]
@@ -234,6 +351,14 @@ pub enum MiscMessage {
     UnregisterTool {
         tool_id: String,
     },
+    RegisterExtractor {
+        name: String,
+        mime_types: Vec<String>,
+        callback: Root<JsFunction>,
+    },
+    UnregisterExtractor {
+        name: String,
+    },
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -243,6 +368,7 @@ pub enum EventBusMessage {
         resource_id: String,
         status: ResourceProcessingState,
     },
+    PostProcessingBatchProgress(PostProcessingBatchProgress),
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -27,6 +27,60 @@ pub fn register_exported_functions(cx: &mut ModuleContext) -> NeonResult<()> {
         js_list_all_resources_and_spaces,
     )?;
     cx.export_function("js__store_resource_post_process", js_resource_post_process)?;
+    cx.export_function(
+        "js__store_batch_resource_post_process",
+        js_batch_resource_post_process,
+    )?;
+    cx.export_function(
+        "js__store_list_post_processing_jobs",
+        js_list_post_processing_jobs,
+    )?;
+    cx.export_function(
+        "js__store_retry_post_processing_job",
+        js_retry_post_processing_job,
+    )?;
+    cx.export_function(
+        "js__store_list_similar_image_resources",
+        js_list_similar_image_resources,
+    )?;
+    cx.export_function("js__store_import_mail", js_import_mail)?;
+    cx.export_function("js__store_import_markdown_vault", js_import_markdown_vault)?;
+    cx.export_function("js__store_add_watched_folder", js_add_watched_folder)?;
+    cx.export_function("js__store_remove_watched_folder", js_remove_watched_folder)?;
+    cx.export_function("js__store_list_watched_folders", js_list_watched_folders)?;
+    cx.export_function("js__store_sync_watched_folder", js_sync_watched_folder)?;
+    cx.export_function("js__store_create_resource_link", js_create_resource_link)?;
+    cx.export_function("js__store_remove_resource_link", js_remove_resource_link)?;
+    cx.export_function("js__store_list_resource_links", js_list_resource_links)?;
+    cx.export_function(
+        "js__store_list_resource_backlinks",
+        js_list_resource_backlinks,
+    )?;
+    cx.export_function("js__store_get_resource_graph", js_get_resource_graph)?;
+    cx.export_function(
+        "js__store_list_resource_versions",
+        js_list_resource_versions,
+    )?;
+    cx.export_function("js__store_get_resource_version", js_get_resource_version)?;
+    cx.export_function(
+        "js__store_diff_resource_versions",
+        js_diff_resource_versions,
+    )?;
+    cx.export_function(
+        "js__store_restore_resource_version",
+        js_restore_resource_version,
+    )?;
+    cx.export_function(
+        "js__store_find_duplicate_resources",
+        js_find_duplicate_resources,
+    )?;
+    cx.export_function("js__store_merge_resources", js_merge_resources)?;
+    cx.export_function("js__store_get_search_language", js_get_search_language)?;
+    cx.export_function("js__store_set_search_language", js_set_search_language)?;
+    cx.export_function(
+        "js__store_export_resource_markdown",
+        js_export_resource_markdown,
+    )?;
     cx.export_function("js__store_update_resource", js_update_resource)?;
     cx.export_function(
         "js__store_update_resource_metadata",
@@ -58,6 +112,10 @@ pub fn register_exported_functions(cx: &mut ModuleContext) -> NeonResult<()> {
         "js__store_get_all_history_entries",
         js_get_all_history_entries,
     )?;
+    cx.export_function(
+        "js__store_get_history_entries_page",
+        js_get_history_entries_page,
+    )?;
     cx.export_function(
         "js__store_search_history_entries_by_hostname_prefix",
         js_search_history_entries_by_hostname_prefix,
@@ -94,12 +152,20 @@ pub fn register_exported_functions(cx: &mut ModuleContext) -> NeonResult<()> {
     cx.export_function("js__store_delete_space", js_delete_space)?;
     cx.export_function("js__store_create_space_entries", js_create_space_entries)?;
     cx.export_function("js__store_get_space_entries", js_get_space_entries)?;
+    cx.export_function(
+        "js__store_get_space_entries_page",
+        js_get_space_entries_page,
+    )?;
     cx.export_function("js__store_delete_space_entries", js_delete_space_entries)?;
     cx.export_function(
         "js__store_delete_entries_in_space_by_entry_ids",
         js_delete_entries_in_space_by_entry_ids,
     )?;
     cx.export_function("js__store_move_space", js_move_space)?;
+    cx.export_function(
+        "js__store_export_space_markdown_vault",
+        js_export_space_markdown_vault,
+    )?;
 
     cx.export_function("js__store_upsert_resource_hash", js_upsert_resource_hash)?;
     cx.export_function("js__store_get_resource_hash", js_get_resource_hash)?;
@@ -267,6 +333,35 @@ fn js_get_space_entries(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn js_get_space_entries_page(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let space_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let sort_by = cx.argument_opt(2).and_then(|arg| {
+        arg.downcast::<JsString, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_string| js_string.value(&mut cx))
+    });
+    let order_by = cx.argument_opt(3).and_then(|arg| {
+        arg.downcast::<JsString, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_string| js_string.value(&mut cx))
+    });
+    let page = page_request_argument(&mut cx, 4).unwrap_or_default();
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::SpaceMessage(SpaceMessage::GetSpaceEntriesPage {
+            space_id,
+            sort_by,
+            order_by,
+            page,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
 fn js_delete_space_entries(mut cx: FunctionContext) -> JsResult<JsPromise> {
     let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
 
@@ -351,6 +446,20 @@ fn js_move_space(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn js_export_space_markdown_vault(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let space_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let path = cx.argument::<JsString>(2)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::SpaceMessage(SpaceMessage::ExportMarkdownVault { space_id, path }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
 fn js_create_resource(mut cx: FunctionContext) -> JsResult<JsPromise> {
     let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
 
@@ -437,8 +546,8 @@ fn js_remove_resources_by_tags(mut cx: FunctionContext) -> JsResult<JsPromise> {
         .argument_opt(1)
         .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
         .map(|js_string| js_string.value(&mut cx));
-    let resource_tags: Vec<models::ResourceTagFilter> = match resource_tags_json
-        .map(|json_str| serde_json::from_str(&json_str))
+    let resource_tags = match resource_tags_json
+        .map(|json_str| models::ResourceFilter::from_json(&json_str))
         .transpose()
     {
         Ok(Some(tags)) => tags,
@@ -473,18 +582,28 @@ fn js_list_resources_by_tags(mut cx: FunctionContext) -> JsResult<JsPromise> {
         .argument_opt(1)
         .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
         .map(|js_string| js_string.value(&mut cx));
-    let resource_tags: Vec<models::ResourceTagFilter> = match resource_tags_json
-        .map(|json_str| serde_json::from_str(&json_str))
+    let resource_tags = match resource_tags_json
+        .map(|json_str| models::ResourceFilter::from_json(&json_str))
         .transpose()
     {
         Ok(Some(tags)) => tags,
         Ok(None) => return cx.throw_error("Resource tags must be provided"),
         Err(err) => return cx.throw_error(err.to_string()),
     };
+    let include_facets = cx
+        .argument_opt(2)
+        .and_then(|arg| arg.downcast::<JsBoolean, FunctionContext>(&mut cx).ok())
+        .map(|arg| arg.value(&mut cx))
+        .unwrap_or(false);
+    let page = page_request_argument(&mut cx, 3);
 
     let (deferred, promise) = cx.promise();
     tunnel.worker_send_js(
-        WorkerMessage::ResourceMessage(ResourceMessage::ListResourcesByTags(resource_tags)),
+        WorkerMessage::ResourceMessage(ResourceMessage::ListResourcesByTags {
+            filter: resource_tags,
+            include_facets,
+            page,
+        }),
         deferred,
     );
 
@@ -498,18 +617,22 @@ fn js_list_resources_by_tags_no_space(mut cx: FunctionContext) -> JsResult<JsPro
         .argument_opt(1)
         .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
         .map(|js_string| js_string.value(&mut cx));
-    let resource_tags: Vec<models::ResourceTagFilter> = match resource_tags_json
-        .map(|json_str| serde_json::from_str(&json_str))
+    let resource_tags = match resource_tags_json
+        .map(|json_str| models::ResourceFilter::from_json(&json_str))
         .transpose()
     {
         Ok(Some(tags)) => tags,
         Ok(None) => return cx.throw_error("Resource tags must be provided"),
         Err(err) => return cx.throw_error(err.to_string()),
     };
+    let page = page_request_argument(&mut cx, 2);
 
     let (deferred, promise) = cx.promise();
     tunnel.worker_send_js(
-        WorkerMessage::ResourceMessage(ResourceMessage::ListResourcesByTagsNoSpace(resource_tags)),
+        WorkerMessage::ResourceMessage(ResourceMessage::ListResourcesByTagsNoSpace {
+            filter: resource_tags,
+            page,
+        }),
         deferred,
     );
 
@@ -523,8 +646,8 @@ fn js_list_all_resources_and_spaces(mut cx: FunctionContext) -> JsResult<JsPromi
         .argument_opt(1)
         .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
         .map(|js_string| js_string.value(&mut cx));
-    let resource_tags: Vec<models::ResourceTagFilter> = match resource_tags_json
-        .map(|json_str| serde_json::from_str(&json_str))
+    let resource_tags = match resource_tags_json
+        .map(|json_str| models::ResourceFilter::from_json(&json_str))
         .transpose()
     {
         Ok(Some(tags)) => tags,
@@ -549,8 +672,8 @@ fn js_search_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
         .argument_opt(2)
         .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
         .map(|js_string| js_string.value(&mut cx));
-    let resource_tag_filters: Option<Vec<models::ResourceTagFilter>> = match resource_tags_json
-        .map(|json_str| serde_json::from_str(&json_str))
+    let resource_tag_filters = match resource_tags_json
+        .map(|json_str| models::ResourceFilter::from_json(&json_str))
         .transpose()
     {
         Ok(tags) => tags,
@@ -588,6 +711,16 @@ fn js_search_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
             .ok()
             .map(|js_number| js_number.value(&mut cx) as i64)
     });
+    let snippet_length = cx.argument_opt(9).and_then(|arg| {
+        arg.downcast::<JsNumber, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_number| js_number.value(&mut cx) as usize)
+    });
+    let include_facets = cx.argument_opt(10).and_then(|arg| {
+        arg.downcast::<JsBoolean, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_boolean| js_boolean.value(&mut cx))
+    });
 
     let (deferred, promise) = cx.promise();
     tunnel.worker_send_js(
@@ -600,6 +733,8 @@ fn js_search_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
             include_annotations,
             space_id,
             keyword_limit,
+            snippet_length,
+            include_facets,
         })),
         deferred,
     );
@@ -607,13 +742,132 @@ fn js_search_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn processing_priority_argument(
+    cx: &mut FunctionContext,
+    index: usize,
+) -> NeonResult<models::ProcessingPriority> {
+    let priority = cx
+        .argument_opt(index)
+        .and_then(|arg| arg.downcast::<JsString, FunctionContext>(cx).ok())
+        .map(|js_string| js_string.value(cx));
+    match priority {
+        Some(priority) => match priority.parse() {
+            Ok(priority) => Ok(priority),
+            Err(_) => cx.throw_error(format!("invalid processing priority: {priority}")),
+        },
+        None => Ok(models::ProcessingPriority::default()),
+    }
+}
+
+// the cursor from the previous page at `index` and the page size after it, listings are only
+// paginated when one of them is given
+fn page_request_argument(cx: &mut FunctionContext, index: usize) -> Option<models::PageRequest> {
+    let cursor = cx
+        .argument_opt(index)
+        .and_then(|arg| arg.downcast::<JsString, FunctionContext>(cx).ok())
+        .map(|js_string| js_string.value(cx));
+    let limit = cx
+        .argument_opt(index + 1)
+        .and_then(|arg| arg.downcast::<JsNumber, FunctionContext>(cx).ok())
+        .map(|js_number| js_number.value(cx) as usize);
+    if cursor.is_none() && limit.is_none() {
+        return None;
+    }
+    Some(models::PageRequest {
+        cursor,
+        limit: limit.unwrap_or(models::DEFAULT_PAGE_LIMIT).max(1),
+    })
+}
+
 fn js_resource_post_process(mut cx: FunctionContext) -> JsResult<JsPromise> {
     let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
     let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let priority = processing_priority_argument(&mut cx, 2)?;
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::PostProcessJob {
+            resource_id,
+            priority,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_batch_resource_post_process(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_ids_json = cx.argument::<JsString>(1)?.value(&mut cx);
+    let priority = processing_priority_argument(&mut cx, 2)?;
+
+    let resource_ids: Vec<String> = match serde_json::from_str(&resource_ids_json) {
+        Ok(resource_ids) => resource_ids,
+        Err(err) => return cx.throw_error(err.to_string()),
+    };
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::BatchPostProcessJob {
+            resource_ids,
+            priority,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_post_processing_jobs(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let state = cx.argument_opt(1).and_then(|arg| {
+        arg.downcast::<JsString, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_string| js_string.value(&mut cx))
+    });
+    let limit = cx.argument_opt(2).and_then(|arg| {
+        arg.downcast::<JsNumber, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_number| js_number.value(&mut cx) as usize)
+    });
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ListPostProcessingJobs { state, limit }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_retry_post_processing_job(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let job_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::RetryPostProcessingJob(job_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_similar_image_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let max_distance = cx.argument_opt(2).and_then(|arg| {
+        arg.downcast::<JsNumber, FunctionContext>(&mut cx)
+            .ok()
+            .map(|js_number| js_number.value(&mut cx) as u32)
+    });
 
     let (deferred, promise) = cx.promise();
     tunnel.worker_send_js(
-        WorkerMessage::ResourceMessage(ResourceMessage::PostProcessJob(resource_id)),
+        WorkerMessage::ResourceMessage(ResourceMessage::ListSimilarImageResources {
+            resource_id,
+            max_distance,
+        }),
         deferred,
     );
 
@@ -709,6 +963,19 @@ fn js_get_all_history_entries(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn js_get_history_entries_page(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let page = page_request_argument(&mut cx, 1).unwrap_or_default();
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::HistoryMessage(HistoryMessage::GetHistoryEntriesPage(page)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
 fn js_search_history_entries_by_hostname_prefix(mut cx: FunctionContext) -> JsResult<JsPromise> {
     let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
 
@@ -789,6 +1056,310 @@ fn js_import_browser_bookmarks(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn js_import_mail(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let path = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ImportMail(path)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_import_markdown_vault(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let path = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ImportMarkdownVault(path)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_add_watched_folder(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let path = cx.argument::<JsString>(1)?.value(&mut cx);
+    let space_id = cx
+        .argument_opt(2)
+        .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
+        .map(|arg| arg.value(&mut cx));
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::AddWatchedFolder { path, space_id }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_remove_watched_folder(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let folder_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::RemoveWatchedFolder(folder_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_watched_folders(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ListWatchedFolders),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+// checks every file of the folder, changes are normally picked up by the watcher
+fn js_sync_watched_folder(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let folder_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::SyncWatchedFolder {
+            folder_id,
+            paths: vec![String::new()],
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_create_resource_link(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let source_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let target_id = cx.argument::<JsString>(2)?.value(&mut cx);
+    let context = cx
+        .argument_opt(3)
+        .and_then(|arg| arg.downcast::<JsString, FunctionContext>(&mut cx).ok())
+        .map(|arg| arg.value(&mut cx));
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::CreateResourceLink {
+            source_id,
+            target_id,
+            context,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_remove_resource_link(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let link_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::RemoveResourceLink(link_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_resource_links(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ListOutgoingResourceLinks(resource_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_resource_backlinks(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ListResourceBacklinks(resource_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_get_resource_graph(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let hops = cx
+        .argument_opt(2)
+        .and_then(|arg| arg.downcast::<JsNumber, FunctionContext>(&mut cx).ok())
+        .map(|arg| arg.value(&mut cx) as usize)
+        .unwrap_or(1);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::GetResourceGraph { resource_id, hops }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_list_resource_versions(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ListResourceVersions(resource_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_get_resource_version(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let version_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::GetResourceVersionContent(version_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_diff_resource_versions(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let from_version_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let to_version_id = cx.argument::<JsString>(2)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::DiffResourceVersions {
+            from_version_id,
+            to_version_id,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_restore_resource_version(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let version_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::RestoreResourceVersion(version_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_find_duplicate_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let include_similar = cx
+        .argument_opt(1)
+        .and_then(|arg| arg.downcast::<JsBoolean, FunctionContext>(&mut cx).ok())
+        .map(|arg| arg.value(&mut cx))
+        .unwrap_or(false);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::FindDuplicateResources { include_similar }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_merge_resources(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let target_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let source_ids = cx.argument::<JsArray>(2)?.to_vec(&mut cx)?;
+    let source_ids = source_ids
+        .iter()
+        .map(|value| {
+            Ok(value
+                .downcast_or_throw::<JsString, FunctionContext>(&mut cx)?
+                .value(&mut cx))
+        })
+        .collect::<NeonResult<Vec<String>>>()?;
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::MergeResources {
+            target_id,
+            source_ids,
+        }),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_get_search_language(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::GetSearchLanguage),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_set_search_language(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let language = cx.argument::<JsString>(1)?.value(&mut cx);
+    let language: models::SearchLanguage = match language.parse() {
+        Ok(language) => language,
+        Err(_) => return cx.throw_error(format!("invalid search language: {language}")),
+    };
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::SetSearchLanguage(language)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
+fn js_export_resource_markdown(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
+    let resource_id = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::ResourceMessage(ResourceMessage::ExportResourceMarkdown(resource_id)),
+        deferred,
+    );
+
+    Ok(promise)
+}
+
 fn js_update_resource(mut cx: FunctionContext) -> JsResult<JsPromise> {
     let tunnel = cx.argument::<JsBox<WorkerTunnel>>(0)?;
     let resource_json = cx.argument::<JsString>(1)?.value(&mut cx);
@@ -17,6 +17,8 @@ pub fn register_exported_functions(cx: &mut ModuleContext) -> NeonResult<()> {
         "js__claude_agent_register_runner",
         js_register_claude_agent_runner,
     )?;
+    cx.export_function("js__backend_register_extractor", js_register_extractor)?;
+    cx.export_function("js__backend_unregister_extractor", js_unregister_extractor)?;
     Ok(())
 }
 
@@ -51,6 +53,17 @@ fn js_tunnel_init(mut cx: FunctionContext) -> JsResult<JsBox<tunnel::WorkerTunne
             .map(|n| n.value(&mut cx) as usize)
     });
     let event_bus_rx_callback = cx.argument::<JsFunction>(6)?.root(&mut cx);
+    let processing_queue = match cx
+        .argument_opt(7)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .map(|js_string| js_string.value(&mut cx))
+    {
+        Some(config_json) => match serde_json::from_str(&config_json) {
+            Ok(config) => config,
+            Err(err) => return cx.throw_error(err.to_string()),
+        },
+        None => Default::default(),
+    };
 
     match std::fs::create_dir_all(&backend_root_path) {
         Ok(_) => {}
@@ -69,6 +82,7 @@ fn js_tunnel_init(mut cx: FunctionContext) -> JsResult<JsBox<tunnel::WorkerTunne
         language_setting,
         num_worker_threads,
         num_processor_threads,
+        processing_queue,
     };
     let tunnel = tunnel::WorkerTunnel::new(&mut cx, config, event_bus_rx_callback);
 
@@ -99,6 +113,41 @@ fn js_run_migration(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+fn js_register_extractor(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<tunnel::WorkerTunnel>>(0)?;
+    let name = cx.argument::<JsString>(1)?.value(&mut cx);
+    let mime_types_json = cx.argument::<JsString>(2)?.value(&mut cx);
+    let callback = cx.argument::<JsFunction>(3)?.root(&mut cx);
+
+    let mime_types: Vec<String> = match serde_json::from_str(&mime_types_json) {
+        Ok(mime_types) => mime_types,
+        Err(err) => return cx.throw_error(err.to_string()),
+    };
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::MiscMessage(MiscMessage::RegisterExtractor {
+            name,
+            mime_types,
+            callback,
+        }),
+        deferred,
+    );
+    Ok(promise)
+}
+
+fn js_unregister_extractor(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let tunnel = cx.argument::<JsBox<tunnel::WorkerTunnel>>(0)?;
+    let name = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let (deferred, promise) = cx.promise();
+    tunnel.worker_send_js(
+        WorkerMessage::MiscMessage(MiscMessage::UnregisterExtractor { name }),
+        deferred,
+    );
+    Ok(promise)
+}
+
 fn js_register_claude_agent_runner(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let tunnel = cx.argument::<JsBox<tunnel::WorkerTunnel>>(0)?;
     let runner = cx.argument::<JsFunction>(1)?.root(&mut cx);
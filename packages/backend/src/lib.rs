@@ -33,6 +33,8 @@ pub enum BackendError {
     // TODO: fix this monstrosity
     #[error("LLM Quota Depleted error: {quotas}")]
     LLMClientErrorQuotasDepleted { quotas: serde_json::Value },
+    #[error("YouTube transcript error: {0}")]
+    YoutubeTranscriptError(#[from] crate::ai::youtube::YoutubeTranscriptError),
     #[error("RAG Empty Context error: {0}")]
     RAGEmptyContextError(String),
     #[error("Cancelled error")]
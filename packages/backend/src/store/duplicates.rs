@@ -0,0 +1,250 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{current_time, ResourceLinkKind},
+    },
+    BackendResult,
+};
+
+const ANNOTATION_RESOURCE_TYPE: &str = "application/vnd.space.annotation";
+
+impl Database {
+    // annotations carry the source uri of the page they were made on, so they're left out
+    pub fn list_resource_source_uris(&self) -> BackendResult<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT R.id, M.source_uri
+            FROM resources R
+            JOIN resource_metadata M ON M.resource_id = R.id
+            WHERE R.deleted = 0 AND R.resource_type != ?1 AND M.source_uri != ''
+            ORDER BY R.created_at ASC",
+        )?;
+        let rows = stmt.query_map([ANNOTATION_RESOURCE_TYPE], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // hashes shared by more than one live resource, with their resources oldest first
+    pub fn list_duplicate_content_hashes(&self) -> BackendResult<Vec<(String, Vec<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT H.content_hash, H.resource_id
+            FROM resource_content_hashes H
+            JOIN resources R ON R.id = H.resource_id
+            WHERE R.deleted = 0 AND H.content_hash IN (
+                SELECT H2.content_hash
+                FROM resource_content_hashes H2
+                JOIN resources R2 ON R2.id = H2.resource_id
+                WHERE R2.deleted = 0
+                GROUP BY H2.content_hash
+                HAVING COUNT(*) > 1
+            )
+            ORDER BY H.content_hash ASC, R.created_at ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut groups: Vec<(String, Vec<String>)> = vec![];
+        for row in rows {
+            let (hash, resource_id) = row?;
+            match groups.last_mut() {
+                Some((last, ids)) if *last == hash => ids.push(resource_id),
+                _ => groups.push((hash, vec![resource_id])),
+            }
+        }
+        Ok(groups)
+    }
+
+    // the start of the first text content of the most recent resources
+    pub fn list_resource_text_excerpts(
+        &self,
+        limit: usize,
+        length: usize,
+    ) -> BackendResult<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT R.id, T.excerpt
+            FROM resources R
+            JOIN (
+                SELECT resource_id, substr(content, 1, ?3) AS excerpt, MIN(rowid)
+                FROM resource_text_content
+                GROUP BY resource_id
+            ) T ON T.resource_id = R.id
+            WHERE R.deleted = 0 AND R.resource_type != ?1 AND T.excerpt != ''
+            ORDER BY R.created_at DESC
+            LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![ANNOTATION_RESOURCE_TYPE, limit as i64, length as i64],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // moves the space entries, annotations and links of the source to the target and
+    // returns the moved annotations, the source itself is left to the caller
+    pub fn merge_resource_into_tx(
+        tx: &mut rusqlite::Transaction,
+        target_id: &str,
+        source_id: &str,
+    ) -> BackendResult<Vec<String>> {
+        tx.execute(
+            "INSERT INTO space_entries (id, space_id, resource_id, created_at, updated_at, manually_added)
+            SELECT lower(hex(randomblob(16))), space_id, ?1, created_at, ?3, MAX(manually_added)
+            FROM space_entries
+            WHERE resource_id = ?2
+            AND space_id NOT IN (SELECT space_id FROM space_entries WHERE resource_id = ?1)
+            GROUP BY space_id",
+            rusqlite::params![target_id, source_id, current_time()],
+        )?;
+        tx.execute(
+            "DELETE FROM space_entries WHERE resource_id = ?1",
+            [source_id],
+        )?;
+
+        let annotation_ids = {
+            let mut stmt = tx.prepare(
+                "SELECT resource_id FROM resource_tags WHERE tag_name = 'annotates' AND tag_value = ?1",
+            )?;
+            let ids = stmt.query_map([source_id], |row| row.get(0))?;
+            ids.collect::<Result<Vec<String>, _>>()?
+        };
+        tx.execute(
+            "UPDATE OR IGNORE resource_tags SET tag_value = ?1 WHERE tag_name = 'annotates' AND tag_value = ?2",
+            [target_id, source_id],
+        )?;
+
+        // links in the content of the source go with it, the ones the user made are kept
+        tx.execute(
+            "UPDATE resource_links SET target_id = ?1 WHERE target_id = ?2",
+            [target_id, source_id],
+        )?;
+        tx.execute(
+            "UPDATE resource_links SET source_id = ?1 WHERE source_id = ?2 AND kind = ?3",
+            rusqlite::params![target_id, source_id, ResourceLinkKind::User],
+        )?;
+        tx.execute("DELETE FROM resource_links WHERE source_id = target_id", [])?;
+        Ok(annotation_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{
+        random_uuid, Resource, ResourceLink, ResourceTag, Space, SpaceEntry,
+    };
+    use tempfile::tempdir;
+
+    fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        for (i, (id, resource_type)) in [
+            ("a", "application/vnd.space.link"),
+            ("b", "application/vnd.space.link"),
+            ("note", "application/vnd.space.document.space-note"),
+            ("annotation", ANNOTATION_RESOURCE_TYPE),
+        ]
+        .iter()
+        .enumerate()
+        {
+            db.create_resource(&Resource {
+                id: id.to_string(),
+                resource_path: id.to_string(),
+                resource_type: resource_type.to_string(),
+                created_at: now + chrono::Duration::seconds(i as i64),
+                updated_at: now,
+                deleted: 0,
+            })
+            .unwrap();
+        }
+        for id in ["one", "two"] {
+            db.create_space(&Space {
+                id: id.to_string(),
+                name: id.to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .unwrap();
+        }
+        (db, dir)
+    }
+
+    #[test]
+    fn test_duplicate_content_hashes() {
+        let (mut db, _dir) = setup_test_db();
+        let mut tx = db.begin().unwrap();
+        for (id, hash) in [("a", "x"), ("b", "x"), ("note", "x"), ("annotation", "y")] {
+            Database::upsert_resource_hash_tx(&mut tx, id, hash).unwrap();
+        }
+        Database::update_resource_deleted_tx(&mut tx, "note", 1).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            db.list_duplicate_content_hashes().unwrap(),
+            vec![("x".to_string(), vec!["a".to_string(), "b".to_string()])]
+        );
+    }
+
+    #[test]
+    fn test_merge_resource_into() {
+        let (mut db, _dir) = setup_test_db();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for (space_id, resource_id) in [("one", "a"), ("one", "b"), ("two", "b")] {
+            let entry = SpaceEntry {
+                id: random_uuid(),
+                space_id: space_id.to_string(),
+                resource_id: resource_id.to_string(),
+                created_at: now,
+                updated_at: now,
+                manually_added: 1,
+            };
+            Database::create_space_entry_tx(&mut tx, &entry).unwrap();
+        }
+        let tag = ResourceTag {
+            id: random_uuid(),
+            resource_id: "annotation".to_string(),
+            tag_name: "annotates".to_string(),
+            tag_value: "b".to_string(),
+        };
+        Database::create_resource_tag_tx(&mut tx, &tag).unwrap();
+        for (source, target, kind) in [
+            ("note", "b", ResourceLinkKind::LinksTo),
+            ("b", "note", ResourceLinkKind::User),
+            ("b", "a", ResourceLinkKind::User),
+        ] {
+            let link = ResourceLink {
+                id: random_uuid(),
+                source_id: source.to_string(),
+                target_id: target.to_string(),
+                kind,
+                context: None,
+                created_at: now,
+            };
+            Database::create_resource_link_tx(&mut tx, &link).unwrap();
+        }
+
+        let annotation_ids = Database::merge_resource_into_tx(&mut tx, "a", "b").unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(annotation_ids, vec!["annotation"]);
+        let mut spaces = db.list_space_ids_by_resource_id("a").unwrap();
+        spaces.sort();
+        assert_eq!(spaces, vec!["one", "two"]);
+        assert!(db.list_space_ids_by_resource_id("b").unwrap().is_empty());
+        assert_eq!(
+            db.get_resource_tag_by_name("annotation", "annotates")
+                .unwrap(),
+            Some("a".to_string())
+        );
+
+        let outgoing = db.list_outgoing_resource_links("a").unwrap();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].target_id, "note");
+        let backlinks = db.list_resource_backlinks("a").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].source_id, "note");
+    }
+}
@@ -166,8 +166,9 @@ impl Database {
 
         let placeholders = vec!["?"; row_ids.len()].join(",");
         let query = format!(
-            "SELECT M.*, R.* FROM resources R
+            "SELECT M.*, O.outline, R.* FROM resources R
             LEFT JOIN resource_metadata M on M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
             LEFT JOIN embedding_resources E ON E.resource_id = R.id
             WHERE E.rowid IN ({}) GROUP BY R.id ORDER BY {}",
             placeholders,
@@ -184,20 +185,25 @@ impl Database {
                     source_uri: row.get(3)?,
                     alt: row.get(4)?,
                     user_context: row.get(5)?,
+                    byline: row.get(6)?,
+                    published_at: row.get(7)?,
+                    lead_image: row.get(8)?,
+                    outline: row.get(9)?,
                 }),
                 resource: Resource {
-                    id: row.get(6)?,
-                    resource_path: row.get(7)?,
-                    resource_type: row.get(8)?,
-                    created_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                    deleted: row.get(11)?,
+                    id: row.get(10)?,
+                    resource_path: row.get(11)?,
+                    resource_type: row.get(12)?,
+                    created_at: row.get(13)?,
+                    updated_at: row.get(14)?,
+                    deleted: row.get(15)?,
                 },
                 text_content: None,
                 resource_tags: None,
                 resource_annotations: None,
                 post_processing_job: None,
                 space_ids: None,
+                thumbnail: None,
             })
         })?;
 
@@ -218,11 +224,12 @@ impl Database {
         let placeholders = vec!["?"; row_ids.len()].join(",");
         let query = format!(
             "SELECT
-            M.*, R.*, C.*, P.*
+            M.*, O.outline, R.*, C.*, P.*
             FROM embedding_resources E
             LEFT JOIN resource_text_content C ON E.content_id = C.rowid
             LEFT JOIN resources R ON E.resource_id = R.id
             LEFT JOIN resource_metadata M ON E.resource_id = M.resource_id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
             LEFT JOIN resource_content_hashes H ON R.id = H.resource_id
             LEFT JOIN post_processing_jobs P ON H.content_hash = P.content_hash
             WHERE E.rowid IN ({})
@@ -241,7 +248,7 @@ impl Database {
         let mut stmt = self.conn.prepare(&query)?;
         let mut results = vec![];
         let results_iter = stmt.query_map(rusqlite::params_from_iter(row_ids.iter()), |row| {
-            let job_id: Option<String> = row.get(17)?;
+            let job_id: Option<String> = row.get(21)?;
 
             Ok(CompositeResource {
                 metadata: Some(ResourceMetadata {
@@ -251,33 +258,44 @@ impl Database {
                     source_uri: row.get(3)?,
                     alt: row.get(4)?,
                     user_context: row.get(5)?,
+                    byline: row.get(6)?,
+                    published_at: row.get(7)?,
+                    lead_image: row.get(8)?,
+                    outline: row.get(9)?,
                 }),
                 resource: Resource {
-                    id: row.get(6)?,
-                    resource_path: row.get(7)?,
-                    resource_type: row.get(8)?,
-                    created_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                    deleted: row.get(11)?,
+                    id: row.get(10)?,
+                    resource_path: row.get(11)?,
+                    resource_type: row.get(12)?,
+                    created_at: row.get(13)?,
+                    updated_at: row.get(14)?,
+                    deleted: row.get(15)?,
                 },
                 text_content: Some(ResourceTextContent {
-                    id: row.get(12)?,
-                    resource_id: row.get(13)?,
-                    content: row.get(14)?,
-                    content_type: row.get(15)?,
-                    metadata: row.get(16)?,
+                    id: row.get(16)?,
+                    resource_id: row.get(17)?,
+                    content: row.get(18)?,
+                    content_type: row.get(19)?,
+                    metadata: row.get(20)?,
                 }),
                 resource_tags: None,
                 resource_annotations: None,
                 space_ids: None,
+                thumbnail: None,
                 post_processing_job: if let Some(job_id) = job_id {
                     Some(PostProcessingJob {
                         id: job_id,
-                        created_at: row.get(18)?,
-                        updated_at: row.get(19)?,
-                        resource_id: row.get(20)?,
-                        content_hash: row.get(21)?,
-                        state: row.get(22)?,
+                        created_at: row.get(22)?,
+                        updated_at: row.get(23)?,
+                        resource_id: row.get(24)?,
+                        content_hash: row.get(25)?,
+                        state: row.get(26)?,
+                        attempts: row.get(27)?,
+                        max_attempts: row.get(28)?,
+                        next_run_at: row.get(29)?,
+                        last_error: row.get(30)?,
+                        priority: row.get(31)?,
+                        batch_id: row.get(32)?,
                     })
                 } else {
                     None
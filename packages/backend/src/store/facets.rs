@@ -0,0 +1,217 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{current_time, FacetCount, InternalResourceTagNames, ResourceFacets},
+        resource_tags::sql_time,
+    },
+    BackendResult,
+};
+
+const MAX_FACET_VALUES: usize = 20;
+
+// newest first, a resource is counted in the first bucket it was created in
+const CREATED_AT_BUCKETS: [(&str, i64); 4] = [
+    ("past_day", 1),
+    ("past_week", 7),
+    ("past_month", 30),
+    ("past_year", 365),
+];
+const CREATED_AT_OLDER: &str = "older";
+
+// the ids are passed as one json array, so there is no limit on their number
+const IN_RESULT: &str = "IN (SELECT value FROM json_each(?1))";
+
+impl Database {
+    fn facet_counts(
+        &self,
+        query: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> BackendResult<Vec<FacetCount>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{} ORDER BY 3 DESC, 1 LIMIT {}",
+            query, MAX_FACET_VALUES
+        ))?;
+        let counts = stmt.query_map(params, |row| {
+            Ok(FacetCount {
+                value: row.get(0)?,
+                label: row.get(1)?,
+                count: row.get(2)?,
+            })
+        })?;
+        Ok(counts.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // `tag_name` is the tag whose values are counted as the tags of the resources
+    pub fn count_resource_facets(
+        &self,
+        resource_ids: &[String],
+        tag_name: &str,
+    ) -> BackendResult<ResourceFacets> {
+        if resource_ids.is_empty() {
+            return Ok(ResourceFacets::default());
+        }
+        let ids = serde_json::to_string(resource_ids)?;
+
+        let resource_types = self.facet_counts(
+            &format!(
+                "SELECT resource_type, NULL, COUNT(*) FROM resources
+                WHERE id {} GROUP BY resource_type",
+                IN_RESULT
+            ),
+            &[&ids],
+        )?;
+        let tag_counts = |tag_name: &str| {
+            self.facet_counts(
+                &format!(
+                    "SELECT tag_value, NULL, COUNT(DISTINCT resource_id) FROM resource_tags
+                    WHERE resource_id {} AND tag_name = ?2 AND tag_value != ''
+                    GROUP BY tag_value",
+                    IN_RESULT
+                ),
+                &[&ids, &tag_name],
+            )
+        };
+        let hostnames = tag_counts(InternalResourceTagNames::Hostname.as_str())?;
+        let tags = tag_counts(tag_name)?;
+        // blacklisted entries are resources taken out of the space
+        let spaces = self.facet_counts(
+            &format!(
+                "SELECT S.id, json_extract(S.name, '$.folderName'), COUNT(DISTINCT E.resource_id)
+                FROM space_entries E
+                INNER JOIN spaces S ON S.id = E.space_id
+                WHERE E.resource_id {} AND E.manually_added != 2
+                GROUP BY S.id",
+                IN_RESULT
+            ),
+            &[&ids],
+        )?;
+
+        let now = current_time();
+        let bucket_starts: Vec<String> = CREATED_AT_BUCKETS
+            .iter()
+            .map(|(_, days)| sql_time(&(now - chrono::Duration::days(*days))))
+            .collect();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT CASE
+                WHEN created_at >= ?2 THEN 0
+                WHEN created_at >= ?3 THEN 1
+                WHEN created_at >= ?4 THEN 2
+                WHEN created_at >= ?5 THEN 3
+                ELSE 4 END AS bucket, COUNT(*)
+            FROM resources
+            WHERE id {}
+            GROUP BY bucket
+            ORDER BY bucket",
+            IN_RESULT
+        ))?;
+        let buckets = stmt.query_map(
+            rusqlite::params![
+                ids,
+                bucket_starts[0],
+                bucket_starts[1],
+                bucket_starts[2],
+                bucket_starts[3]
+            ],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+        let mut created_at = vec![];
+        for bucket in buckets {
+            let (bucket, count) = bucket?;
+            let value = CREATED_AT_BUCKETS
+                .get(bucket as usize)
+                .map_or(CREATED_AT_OLDER, |(name, _)| name);
+            created_at.push(FacetCount {
+                value: value.to_string(),
+                label: None,
+                count,
+            });
+        }
+
+        Ok(ResourceFacets {
+            resource_types,
+            hostnames,
+            spaces,
+            created_at,
+            tags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{random_uuid, Resource, ResourceTag};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_count_resource_facets() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for (id, resource_type, days_old, hostname) in [
+            ("a", "application/pdf", 0, "example.com"),
+            ("b", "application/pdf", 3, "example.com"),
+            ("c", "application/vnd.space.article", 400, "news.org"),
+            ("d", "application/pdf", 0, "example.com"),
+        ] {
+            let created_at = now - chrono::Duration::days(days_old);
+            Database::create_resource_tx(
+                &mut tx,
+                &Resource {
+                    id: id.to_string(),
+                    resource_path: id.to_string(),
+                    resource_type: resource_type.to_string(),
+                    created_at,
+                    updated_at: created_at,
+                    deleted: 0,
+                },
+            )
+            .unwrap();
+            for (tag_name, tag_value) in [("hostname", hostname), ("hashtag", "reading")] {
+                Database::create_resource_tag_tx(
+                    &mut tx,
+                    &ResourceTag {
+                        id: random_uuid(),
+                        resource_id: id.to_string(),
+                        tag_name: tag_name.to_string(),
+                        tag_value: tag_value.to_string(),
+                    },
+                )
+                .unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        // `d` is not part of the result
+        let ids = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let facets = db.count_resource_facets(&ids, "hashtag").unwrap();
+        let count = |value: &str, count: i64| FacetCount {
+            value: value.to_string(),
+            label: None,
+            count,
+        };
+        assert_eq!(
+            facets.resource_types,
+            vec![
+                count("application/pdf", 2),
+                count("application/vnd.space.article", 1)
+            ]
+        );
+        assert_eq!(
+            facets.hostnames,
+            vec![count("example.com", 2), count("news.org", 1)]
+        );
+        assert_eq!(
+            facets.created_at,
+            vec![
+                count("past_day", 1),
+                count("past_week", 1),
+                count("older", 1)
+            ]
+        );
+        assert_eq!(facets.tags, vec![count("reading", 3)]);
+        assert!(facets.spaces.is_empty());
+    }
+}
@@ -1,5 +1,11 @@
 use super::models::*;
-use crate::{store::db::Database, BackendError, BackendResult};
+use crate::{
+    store::{
+        db::Database,
+        pagination::{keyset_condition, order_clause, page_from_rows, PageCursor},
+    },
+    BackendError, BackendResult,
+};
 use rusqlite::OptionalExtension;
 use std::str::FromStr;
 
@@ -433,6 +439,44 @@ impl Database {
         Ok(history_entries)
     }
 
+    // newest first
+    pub fn list_history_entries_page(
+        &self,
+        page: &PageRequest,
+    ) -> BackendResult<Page<HistoryEntry>> {
+        let cursor = page.page_cursor()?;
+        let condition = match cursor {
+            Some(_) => format!("WHERE {}", keyset_condition("created_at", "id", true, 1)),
+            None => String::new(),
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, entry_type, url, title, search_query, created_at, updated_at
+            FROM history_entries
+            {} {} {}",
+            condition,
+            order_clause("created_at", "id", true),
+            page.limit_clause()
+        ))?;
+        let params = cursor.map_or(vec![], |cursor| vec![cursor.sort_value, cursor.id]);
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            let entry = HistoryEntry {
+                id: row.get(0)?,
+                entry_type: row.get(1)?,
+                url: row.get(2)?,
+                title: row.get(3)?,
+                search_query: row.get(4)?,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            };
+            let cursor = PageCursor {
+                sort_value: row.get(5)?,
+                id: entry.id.clone(),
+            };
+            Ok((entry, cursor))
+        })?;
+        Ok(page_from_rows(rows.collect::<Result<Vec<_>, _>>()?, page))
+    }
+
     pub fn search_history_by_hostname(&self, url: &str) -> BackendResult<Vec<HistoryEntry>> {
         let query = "SELECT id, entry_type, url, title, search_query, created_at, updated_at
                     FROM history_entries
@@ -1,17 +1,26 @@
 pub mod ai_sessions;
 pub mod apps;
 pub mod db;
+pub mod duplicates;
 pub mod embedding_resources;
+pub mod facets;
 pub mod history_entries;
 pub mod kv;
 pub mod models;
+pub mod pagination;
 pub mod post_processing_jobs;
 pub mod resource_content_hash;
+pub mod resource_links;
 pub mod resource_metadata;
 pub mod resource_tags;
 pub mod resource_text_content;
+pub mod resource_thumbnails;
+pub mod resource_versions;
 pub mod resources;
 pub mod search;
 pub mod spaces;
+pub mod watched_folders;
+pub mod word_index;
+pub mod youtube_transcripts;
 
 mod migrations;
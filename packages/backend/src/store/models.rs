@@ -95,6 +95,12 @@ pub enum ResourceTextContentType {
     PDF,
     Post,
     YoutubeTranscript,
+    // timed transcripts from subtitle files, chunk timestamps are in seconds
+    Transcript,
+    // message bodies of imported mail
+    Email,
+    // source code split at its definitions, see `extractors::code`
+    Code,
     GenericText,
 }
 
@@ -134,6 +140,9 @@ impl ResourceTextContentType {
             content_type if content_type.starts_with("application/vnd.space.annotation") => {
                 Some(ResourceTextContentType::Annotation)
             }
+            content_type if content_type.starts_with("message/rfc822") => {
+                Some(ResourceTextContentType::Email)
+            }
             content_type if content_type.starts_with("text/") => {
                 Some(ResourceTextContentType::GenericText)
             }
@@ -156,6 +165,9 @@ impl ResourceTextContentType {
             ResourceTextContentType::ImageTags => true,
             ResourceTextContentType::ImageCaptions => true,
             ResourceTextContentType::YoutubeTranscript => true,
+            ResourceTextContentType::Transcript => true,
+            ResourceTextContentType::Email => true,
+            ResourceTextContentType::Code => true,
             ResourceTextContentType::GenericText => false,
         }
     }
@@ -433,6 +445,66 @@ impl ResourceTagFilter {
     }
 }
 
+// a tree of filters over resources, the leaves are tag filters and predicates on the
+// resource itself
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResourceFilter {
+    And {
+        filters: Vec<ResourceFilter>,
+    },
+    Or {
+        filters: Vec<ResourceFilter>,
+    },
+    Not {
+        filter: Box<ResourceFilter>,
+    },
+    Tag(ResourceTagFilter),
+    CreatedAt {
+        after: Option<chrono::DateTime<chrono::Utc>>,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    UpdatedAt {
+        after: Option<chrono::DateTime<chrono::Utc>>,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    ResourceType {
+        resource_type: String,
+        #[serde(default)]
+        prefix: bool,
+    },
+}
+
+// filters are sent either as a list of tag filters that all have to match or as a tree
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResourceFilterInput {
+    Tags(Vec<ResourceTagFilter>),
+    Filter(ResourceFilter),
+}
+
+impl ResourceFilter {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        Ok(match serde_json::from_str(json)? {
+            ResourceFilterInput::Tags(tags) => tags.into(),
+            ResourceFilterInput::Filter(filter) => filter,
+        })
+    }
+
+    // an empty list of tag filters, callers treat it as no filter at all
+    pub fn is_empty(&self) -> bool {
+        matches!(self, ResourceFilter::And { filters } if filters.is_empty())
+    }
+}
+
+impl From<Vec<ResourceTagFilter>> for ResourceFilter {
+    fn from(tags: Vec<ResourceTagFilter>) -> Self {
+        ResourceFilter::And {
+            filters: tags.into_iter().map(ResourceFilter::Tag).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ResourceMetadata {
     #[serde(default = "random_uuid")]
@@ -448,6 +520,46 @@ pub struct ResourceMetadata {
 
     #[serde(default)]
     pub user_context: String,
+
+    // filled in from the page itself by the article extractor
+    #[serde(default)]
+    pub byline: Option<String>,
+
+    #[serde(default)]
+    pub published_at: Option<String>,
+
+    #[serde(default)]
+    pub lead_image: Option<String>,
+
+    // table of contents of documents that have one, e.g. pdf bookmarks
+    #[serde(default)]
+    pub outline: Option<DocumentOutline>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentOutlineEntry {
+    pub title: String,
+    // nesting depth, top level entries are 0
+    pub level: usize,
+    pub page: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DocumentOutline(pub Vec<DocumentOutlineEntry>);
+
+impl ToSql for DocumentOutline {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        let json = serde_json::to_string(self).unwrap();
+        Ok(rusqlite::types::ToSqlOutput::from(json))
+    }
+}
+
+impl FromSql for DocumentOutline {
+    fn column_result(value: rusqlite::types::ValueRef) -> rusqlite::types::FromSqlResult<Self> {
+        let json = String::column_result(value)?;
+        serde_json::from_str(&json).map_err(|_| rusqlite::types::FromSqlError::InvalidType)
+    }
 }
 
 // TODO: what is good for semantic search?
@@ -478,6 +590,40 @@ impl EmbeddableContent for ResourceMetadata {
     }
 }
 
+// metadata an extractor found in the resource itself
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedResourceMetadata {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub published_at: Option<String>,
+    pub lead_image: Option<String>,
+    pub outline: Option<DocumentOutline>,
+}
+
+impl ExtractedResourceMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.byline.is_none()
+            && self.published_at.is_none()
+            && self.lead_image.is_none()
+            && self.outline.is_none()
+    }
+}
+
+// written by the user in the frontmatter of markdown files, unlike extracted metadata
+// these values replace what is already there
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceFrontmatter {
+    pub title: Option<String>,
+    pub source_uri: Option<String>,
+    pub byline: Option<String>,
+    pub published_at: Option<String>,
+    // `None` if there is no tags key, the existing hashtags are kept then
+    pub hashtags: Option<Vec<String>>,
+    // all other keys with scalar values, lists have an entry per value
+    pub fields: Vec<(String, String)>,
+}
+
 impl ResourceMetadata {
     pub fn get_tags(&self) -> Vec<ResourceTag> {
         let mut tags: Vec<ResourceTag> = Vec::new();
@@ -500,6 +646,71 @@ pub struct ResourceTextContentMetadata {
     pub timestamp: Option<f32>,
     pub url: Option<String>,
     pub page: Option<u32>,
+    // chapter or section title for documents that are split by their headings
+    pub section: Option<String>,
+    // recognized lines with their positions for content that was read with OCR
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<OcrResult>,
+}
+
+// pixel coordinates in the source image, origin is the top left corner
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        BoundingBox {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OcrWord {
+    pub text: String,
+    pub bbox: BoundingBox,
+    // mean probability the recognition model gave the word's characters, between 0 and 1
+    pub confidence: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OcrLine {
+    pub text: String,
+    pub bbox: BoundingBox,
+    // mean of the word confidences
+    pub confidence: f32,
+    pub words: Vec<OcrWord>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct OcrResult {
+    // language of the recognition model that was used
+    pub language: Option<String>,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub lines: Vec<OcrLine>,
+}
+
+impl OcrResult {
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 impl ToSql for ResourceTextContentMetadata {
@@ -516,6 +727,212 @@ impl FromSql for ResourceTextContentMetadata {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SimilarImageResource {
+    pub resource_id: String,
+    // number of differing bits between the perceptual hashes, 0 for identical looking images
+    pub distance: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MailImportResult {
+    // one resource per message
+    pub resource_ids: Vec<String>,
+    pub attachment_ids: Vec<String>,
+    // spaces created for threads with more than one message
+    pub space_ids: Vec<String>,
+    // post processing batch of all imported resources
+    pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VaultImportResult {
+    // one note per markdown file
+    pub resource_ids: Vec<String>,
+    // every other file of the vault
+    pub attachment_ids: Vec<String>,
+    // one space per folder, nested like the folders
+    pub space_ids: Vec<String>,
+    pub root_space_id: Option<String>,
+    // post processing batch of all imported resources
+    pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VaultExportResult {
+    pub note_paths: Vec<String>,
+    pub attachment_paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchedFolder {
+    pub id: String,
+    pub path: String,
+    // resources of the files are kept in this space
+    pub space_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchedFile {
+    pub folder_id: String,
+    // relative to the watched folder
+    pub path: String,
+    pub resource_id: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchedFolderSyncResult {
+    pub created_ids: Vec<String>,
+    pub updated_ids: Vec<String>,
+    // soft deleted, they are recovered if their file comes back
+    pub deleted_ids: Vec<String>,
+    // post processing batch of the created and updated resources
+    pub batch_id: Option<String>,
+}
+
+// how the source of a link relates to its target
+#[derive(
+    strum_macros::Display,
+    Debug,
+    Eq,
+    PartialEq,
+    EnumString,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Hash,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceLinkKind {
+    // a link in the content of a note
+    LinksTo,
+    // an annotation and the resource it was made on
+    Annotates,
+    // linked by the user, post processing leaves these alone
+    User,
+}
+
+impl ToSql for ResourceLinkKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for ResourceLinkKind {
+    fn column_result(value: rusqlite::types::ValueRef) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        ResourceLinkKind::from_str(&s).map_err(|_| rusqlite::types::FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceLink {
+    #[serde(default = "random_uuid")]
+    pub id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub kind: ResourceLinkKind,
+    // anchor text of the link or the annotated text
+    pub context: Option<String>,
+    #[serde(default = "current_time")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// a link found in the content of a resource, the resource is its source
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExtractedResourceLink {
+    pub target_id: String,
+    pub kind: ResourceLinkKind,
+    pub context: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceGraph {
+    // the resource itself first, then the others in the order they were reached
+    pub resource_ids: Vec<String>,
+    // all links between the resources of the graph
+    pub links: Vec<ResourceLink>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceVersion {
+    #[serde(default = "random_uuid")]
+    pub id: String,
+    pub resource_id: String,
+    pub content_hash: String,
+    // copy of the resource file with this content
+    pub snapshot_path: String,
+    pub size: i64,
+    #[serde(default = "current_time")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceVersionContent {
+    pub version: ResourceVersion,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceVersionDiff {
+    pub from_version_id: String,
+    pub to_version_id: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+// why the resources of a group are considered the same
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    SourceUri,
+    ContentHash,
+    SimilarText,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    // the normalized source uri or the content hash
+    pub key: Option<String>,
+    // oldest first
+    pub resource_ids: Vec<String>,
+    // lowest text similarity within the group
+    pub similarity: Option<f32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceMergeResult {
+    pub resource_id: String,
+    // moved to the trash
+    pub merged_ids: Vec<String>,
+    // annotations of the merged resources, now made on the surviving one
+    pub annotation_ids: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PostProcessingJob {
     #[serde(default = "random_uuid")]
@@ -528,15 +945,125 @@ pub struct PostProcessingJob {
     pub content_hash: String,
     #[serde(default)]
     pub state: ResourceProcessingState,
+    #[serde(default)]
+    pub attempts: i64,
+    #[serde(default = "default_post_processing_max_attempts")]
+    pub max_attempts: i64,
+    // when set, the job is waiting in the queue to be picked up by the scheduler
+    #[serde(default)]
+    pub next_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub priority: ProcessingPriority,
+    #[serde(default)]
+    pub batch_id: Option<String>,
+}
+
+pub const POST_PROCESSING_MAX_ATTEMPTS: i64 = 5;
+
+fn default_post_processing_max_attempts() -> i64 {
+    POST_PROCESSING_MAX_ATTEMPTS
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl PostProcessingJob {
+    pub fn new(resource_id: String, content_hash: String) -> Self {
+        Self {
+            id: random_uuid(),
+            created_at: current_time(),
+            updated_at: current_time(),
+            resource_id,
+            content_hash,
+            state: ResourceProcessingState::Pending,
+            attempts: 0,
+            max_attempts: POST_PROCESSING_MAX_ATTEMPTS,
+            next_run_at: None,
+            last_error: None,
+            priority: ProcessingPriority::default(),
+            batch_id: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: ProcessingPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_batch_id(mut self, batch_id: String) -> Self {
+        self.batch_id = Some(batch_id);
+        self
+    }
+}
+
+// processing lanes, in the order the processor threads drain them
+#[derive(
+    strum_macros::Display,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    EnumString,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Hash,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessingPriority {
+    #[default]
+    UserInitiated,
+    BackgroundImport,
+    Reindex,
+}
+
+impl ProcessingPriority {
+    pub const ALL: [ProcessingPriority; 3] = [
+        ProcessingPriority::UserInitiated,
+        ProcessingPriority::BackgroundImport,
+        ProcessingPriority::Reindex,
+    ];
+}
+
+impl ToSql for ProcessingPriority {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for ProcessingPriority {
+    fn column_result(value: rusqlite::types::ValueRef) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        ProcessingPriority::from_str(&s).map_err(|_| rusqlite::types::FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PostProcessingBatchProgress {
+    pub batch_id: String,
+    pub total: usize,
+    pub pending: usize,
+    pub started: usize,
+    pub finished: usize,
+    // includes jobs that ended up in the dead-letter state
+    pub failed: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "type")]
 pub enum ResourceProcessingState {
+    #[default]
     Pending,
     Started,
-    Failed { message: String },
+    Failed {
+        message: String,
+    },
+    // the job exhausted all of its attempts and won't be retried automatically
+    DeadLetter {
+        message: String,
+    },
     Finished,
 }
 
@@ -556,12 +1083,6 @@ impl FromSql for ResourceProcessingState {
     }
 }
 
-impl Default for ResourceProcessingState {
-    fn default() -> Self {
-        Self::Pending
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LegacyResourceTextContent {
     #[serde(default = "random_uuid")]
@@ -907,6 +1428,10 @@ mod tests {
             source_uri: "https://www.google.com".to_string(),
             alt: "".to_string(),
             user_context: "".to_string(),
+            byline: None,
+            published_at: None,
+            lead_image: None,
+            outline: None,
         };
         let tags = metadata.get_tags();
         assert_eq!(tags.len(), 1);
@@ -918,11 +1443,29 @@ mod tests {
     }
 }
 
-pub struct PaginatedResources {
-    pub resources: Vec<Resource>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+// `cursor` is the `next_cursor` of the previous page, without one the first page is listed
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub cursor: Option<String>,
+    pub limit: usize,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            limit: DEFAULT_PAGE_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // `None` on the last page
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -934,9 +1477,23 @@ pub struct CompositeResource {
     pub resource_annotations: Option<Vec<Resource>>,
     pub post_processing_job: Option<PostProcessingJob>,
     pub space_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub thumbnail: Option<ResourceThumbnail>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceThumbnail {
+    pub resource_id: String,
+    pub path: String,
+    // content hash of the resource when the thumbnail was generated
+    pub content_hash: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "current_time")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum SearchEngine {
     KeywordContent,
     KeywordMetadata,
@@ -944,22 +1501,62 @@ pub enum SearchEngine {
     Embeddings,
 }
 
+// stemming of the word level search index, `any` only splits the text into words
+#[derive(
+    strum_macros::Display,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    EnumString,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SearchLanguage {
+    #[default]
+    English,
+    Any,
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchResourcesParams {
     pub query: String,
-    pub resource_tag_filters: Option<Vec<ResourceTagFilter>>,
+    pub resource_tag_filters: Option<ResourceFilter>,
     pub semantic_search_enabled: Option<bool>,
     pub embeddings_distance_threshold: Option<f32>,
     pub embeddings_limit: Option<i64>,
     pub include_annotations: Option<bool>,
     pub space_id: Option<String>,
     pub keyword_limit: Option<i64>,
+    // in characters, around the first match of the snippet
+    pub snippet_length: Option<usize>,
+    pub include_facets: Option<bool>,
+}
+
+// an excerpt of a matched text content, `highlights` are the character ranges of the matches
+// in `text`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchSnippet {
+    pub content_id: String,
+    pub content_type: ResourceTextContentType,
+    pub text: String,
+    pub highlights: Vec<(usize, usize)>,
+    pub page: Option<u32>,
+    pub timestamp: Option<f32>,
+    pub section: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SearchResultItem {
     pub resource: CompositeResource,
     pub engine: SearchEngine,
+    // only for matches of the text content, best match first
+    #[serde(default)]
+    pub snippets: Vec<SearchSnippet>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -968,18 +1565,72 @@ pub struct SearchResultSpaceItem {
     pub engine: SearchEngine,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchQueryErrorKind {
+    UnterminatedQuote,
+    EmptyValue,
+    InvalidDate,
+    UnknownType,
+    UnknownSpace,
+    // more than one space has the name given with `space:`
+    AmbiguousSpace,
+    MultipleSpaces,
+    UnsupportedExclusion,
+    OnlyExclusions,
+}
+
+// `start` and `end` are character offsets of the part of the query the error is about
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchQueryError {
+    pub kind: SearchQueryErrorKind,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FacetCount {
+    pub value: String,
+    // the display name for values that are ids, like the name of a space
+    pub label: Option<String>,
+    pub count: i64,
+}
+
+// how many resources of a result share a property, the largest counts first
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ResourceFacets {
+    pub resource_types: Vec<FacetCount>,
+    pub hostnames: Vec<FacetCount>,
+    pub spaces: Vec<FacetCount>,
+    // `past_day`, `past_week`, `past_month`, `past_year` and `older`, in that order
+    pub created_at: Vec<FacetCount>,
+    // the hashtags of the resources
+    pub tags: Vec<FacetCount>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub items: Vec<SearchResultItem>,
     pub spaces: Vec<SearchResultSpaceItem>,
     pub total: i64,
     pub space_entries: Option<Vec<SpaceEntryExtended>>,
+    // a query that can't be parsed is not searched, the result only has the errors
+    #[serde(default)]
+    pub query_errors: Vec<SearchQueryError>,
+    #[serde(default)]
+    pub facets: Option<ResourceFacets>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResultSimple {
     pub items: Vec<String>,
     pub total: i64,
+    #[serde(default)]
+    pub facets: Option<ResourceFacets>,
+    // only for listings by page, `None` on the last page
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 // TODO: is there a better way to do this?
@@ -0,0 +1,252 @@
+use crate::{
+    store::models::{Page, PageRequest},
+    BackendError, BackendResult,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+// the sort value and id of the last item of a page, listings are ordered by both so items
+// with the same timestamp are neither skipped nor repeated
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub sort_value: String,
+    pub id: String,
+}
+
+impl PageCursor {
+    // encoded so the callers treat it as opaque and only hand it back
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::json!([self.sort_value, self.id]).to_string())
+    }
+
+    pub fn decode(cursor: &str) -> BackendResult<PageCursor> {
+        let invalid = || BackendError::GenericError(format!("invalid page cursor: {}", cursor));
+        let json = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let (sort_value, id): (String, String) =
+            serde_json::from_slice(&json).map_err(|_| invalid())?;
+        Ok(PageCursor { sort_value, id })
+    }
+}
+
+impl PageRequest {
+    pub fn page_cursor(&self) -> BackendResult<Option<PageCursor>> {
+        self.cursor
+            .as_deref()
+            .filter(|cursor| !cursor.is_empty())
+            .map(PageCursor::decode)
+            .transpose()
+    }
+
+    // one row more than the page is fetched to know if there is a next one
+    pub fn limit_clause(&self) -> String {
+        format!("LIMIT {}", self.limit + 1)
+    }
+}
+
+pub fn order_clause(sort_column: &str, id_column: &str, descending: bool) -> String {
+    let order = if descending { "DESC" } else { "ASC" };
+    format!("ORDER BY {0} {2}, {1} {2}", sort_column, id_column, order)
+}
+
+// the rows after the cursor in the order of the listing, the cursor values are the params
+// `?{first_param}` and the one after it
+pub fn keyset_condition(
+    sort_column: &str,
+    id_column: &str,
+    descending: bool,
+    first_param: usize,
+) -> String {
+    format!(
+        "({}, {}) {} (?{}, ?{})",
+        sort_column,
+        id_column,
+        if descending { "<" } else { ">" },
+        first_param,
+        first_param + 1
+    )
+}
+
+pub fn page_from_rows<T>(mut rows: Vec<(T, PageCursor)>, page: &PageRequest) -> Page<T> {
+    let has_next_page = rows.len() > page.limit;
+    rows.truncate(page.limit);
+    let next_cursor = match has_next_page {
+        true => rows.last().map(|(_, cursor)| cursor.encode()),
+        false => None,
+    };
+    Page {
+        items: rows.into_iter().map(|(item, _)| item).collect(),
+        next_cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{
+        db::Database,
+        models::{current_time, HistoryEntry, HistoryEntryType, Resource, ResourceFilter},
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_page_cursor() {
+        let cursor = PageCursor {
+            sort_value: "2024-05-01 10:00:00+00:00".to_string(),
+            id: "a\"b".to_string(),
+        };
+        assert_eq!(PageCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(PageCursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_history_entries_pages() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        // the same timestamp for all, the ids keep the pages apart
+        let now = current_time();
+        for id in ["a", "b", "c", "d", "e"] {
+            db.create_history_entry(&HistoryEntry {
+                id: id.to_string(),
+                entry_type: HistoryEntryType::Navigation,
+                url: Some(format!("https://{}.com", id)),
+                title: None,
+                search_query: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .unwrap();
+        }
+
+        let mut page = PageRequest {
+            cursor: None,
+            limit: 2,
+        };
+        let mut ids = vec![];
+        loop {
+            let result = db.list_history_entries_page(&page).unwrap();
+            assert!(result.items.len() <= 2);
+            ids.extend(result.items.into_iter().map(|entry| entry.id));
+            match result.next_cursor {
+                Some(cursor) => page.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(ids, vec!["e", "d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_resources_by_tags_pages() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for (i, resource_type) in [
+            "application/pdf",
+            "image/png",
+            "application/pdf",
+            "application/pdf",
+            "application/pdf",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let created_at = now - chrono::Duration::minutes(i as i64);
+            Database::create_resource_tx(
+                &mut tx,
+                &Resource {
+                    id: format!("r{}", i),
+                    resource_path: format!("r{}", i),
+                    resource_type: resource_type.to_string(),
+                    created_at,
+                    updated_at: created_at,
+                    deleted: 0,
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let filter = ResourceFilter::ResourceType {
+            resource_type: "application/pdf".to_string(),
+            prefix: false,
+        };
+        let mut page = PageRequest {
+            cursor: None,
+            limit: 3,
+        };
+        let first = db
+            .list_resources_by_tags_page(&filter, false, &page)
+            .unwrap();
+        assert_eq!(first.total, 4);
+        assert_eq!(first.items, vec!["r0", "r2", "r3"]);
+        page.cursor = first.next_cursor;
+        let second = db
+            .list_resources_by_tags_page(&filter, false, &page)
+            .unwrap();
+        assert_eq!(second.items, vec!["r4"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_resources_pages() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for (i, deleted) in [0, 0, 1, 0].iter().enumerate() {
+            let updated_at = now - chrono::Duration::minutes(i as i64);
+            Database::create_resource_tx(
+                &mut tx,
+                &Resource {
+                    id: format!("r{}", i),
+                    resource_path: format!("r{}", i),
+                    resource_type: "text/plain".to_string(),
+                    created_at: updated_at,
+                    updated_at,
+                    deleted: *deleted,
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let mut page = PageRequest {
+            cursor: None,
+            limit: 2,
+        };
+        let first = db.list_resources(0, &page).unwrap();
+        assert_eq!(
+            first
+                .items
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["r0", "r1"]
+        );
+        page.cursor = first.next_cursor;
+        let second = db.list_resources(0, &page).unwrap();
+        assert_eq!(
+            second
+                .items
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["r3"]
+        );
+        assert!(second.next_cursor.is_none());
+
+        page.cursor = None;
+        let deleted = db.list_resources(1, &page).unwrap();
+        assert_eq!(
+            deleted
+                .items
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["r2"]
+        );
+    }
+}
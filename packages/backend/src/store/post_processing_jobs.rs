@@ -1,15 +1,66 @@
 use super::models::*;
 use crate::{store::db::Database, BackendResult};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use rusqlite::OptionalExtension;
 
+const POST_PROCESSING_BASE_BACKOFF_SECS: i64 = 30;
+const POST_PROCESSING_MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+// exponential backoff for the given attempt number (starting at 1): 30s, 1m, 2m, 4m, ...
+// capped at one hour
+pub fn post_processing_backoff(attempt: i64) -> Duration {
+    let exponent = attempt.saturating_sub(1).clamp(0, 16) as u32;
+    let secs = POST_PROCESSING_BASE_BACKOFF_SECS
+        .saturating_mul(1 << exponent)
+        .min(POST_PROCESSING_MAX_BACKOFF_SECS);
+    Duration::seconds(secs)
+}
+
+// in the order `map_post_processing_job` reads them
+const POST_PROCESSING_JOB_COLUMNS: &str = "
+    id, created_at, updated_at, resource_id, content_hash, state, attempts, max_attempts,
+    next_run_at, last_error, priority, batch_id";
+
+fn map_post_processing_job(row: &rusqlite::Row) -> rusqlite::Result<PostProcessingJob> {
+    Ok(PostProcessingJob {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        updated_at: row.get(2)?,
+        resource_id: row.get(3)?,
+        content_hash: row.get(4)?,
+        state: row.get(5)?,
+        attempts: row.get(6)?,
+        max_attempts: row.get(7)?,
+        next_run_at: row.get(8)?,
+        last_error: row.get(9)?,
+        priority: row.get(10)?,
+        batch_id: row.get(11)?,
+    })
+}
+
+// only the latest job of a resource whose content hash is still current is considered live,
+// older jobs have been superseded by a newer one
+const LIVE_POST_PROCESSING_JOB_FILTER: &str = "
+    post_processing_jobs.created_at = (
+        SELECT MAX(created_at)
+        FROM post_processing_jobs P2
+        WHERE P2.resource_id = post_processing_jobs.resource_id
+    )
+    AND EXISTS (
+        SELECT 1
+        FROM resource_content_hashes
+        WHERE resource_id = post_processing_jobs.resource_id
+        AND content_hash = post_processing_jobs.content_hash
+    )";
+
 impl Database {
     pub fn get_resource_processing_state(
         &self,
         resource_id: &str,
     ) -> BackendResult<Option<PostProcessingJob>> {
         let query = "
-        SELECT P.*
+        SELECT P.id, P.created_at, P.updated_at, P.resource_id, P.content_hash, P.state,
+            P.attempts, P.max_attempts, P.next_run_at, P.last_error, P.priority, P.batch_id
         FROM resources R
         LEFT JOIN resource_content_hashes H ON R.id = H.resource_id
         LEFT JOIN post_processing_jobs P ON H.content_hash = P.content_hash
@@ -28,6 +79,12 @@ impl Database {
                         resource_id: row.get(3)?,
                         content_hash: row.get(4)?,
                         state: row.get(5)?,
+                        attempts: row.get(6)?,
+                        max_attempts: row.get(7)?,
+                        next_run_at: row.get(8)?,
+                        last_error: row.get(9)?,
+                        priority: row.get(10)?,
+                        batch_id: row.get(11)?,
                     }),
                     None => Err(rusqlite::Error::QueryReturnedNoRows),
                 }
@@ -46,14 +103,20 @@ impl Database {
 
     pub fn create_processing_job_entry(&mut self, job: &PostProcessingJob) -> BackendResult<()> {
         self.conn.execute(
-            "INSERT INTO post_processing_jobs (id, created_at, updated_at, resource_id, content_hash, state) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO post_processing_jobs (id, created_at, updated_at, resource_id, content_hash, state, attempts, max_attempts, next_run_at, last_error, priority, batch_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             rusqlite::params![
                 job.id,
                 job.created_at,
                 job.updated_at,
                 job.resource_id,
                 job.content_hash,
-                job.state
+                job.state,
+                job.attempts,
+                job.max_attempts,
+                job.next_run_at,
+                job.last_error,
+                job.priority,
+                job.batch_id
             ]
         )?;
         Ok(())
@@ -71,34 +134,427 @@ impl Database {
         Ok(())
     }
 
-    pub fn fail_active_post_processing_jobs(
+    pub fn get_post_processing_job(&self, id: &str) -> BackendResult<Option<PostProcessingJob>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {POST_PROCESSING_JOB_COLUMNS} FROM post_processing_jobs WHERE id = ?1"
+                ),
+                rusqlite::params![id],
+                map_post_processing_job,
+            )
+            .optional()
+            .map_err(|e| e.into())
+    }
+
+    // `state` filters on the state type, e.g. `pending` or `dead_letter`
+    pub fn list_post_processing_jobs(
+        &self,
+        state: Option<&str>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<PostProcessingJob>> {
+        let query = format!(
+            "
+            SELECT {POST_PROCESSING_JOB_COLUMNS}
+            FROM post_processing_jobs
+            WHERE (?1 IS NULL OR json_extract(state, '$.type') = ?1)
+            ORDER BY updated_at DESC
+            LIMIT ?2"
+        );
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+
+        let mut stmt = self.conn.prepare(&query)?;
+        let jobs = stmt
+            .query_map(rusqlite::params![state, limit], map_post_processing_job)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(jobs)
+    }
+
+    // pending jobs whose scheduled run time has passed
+    pub fn list_due_post_processing_jobs(
         &self,
-        created_at: &DateTime<Utc>,
+        now: &DateTime<Utc>,
+        limit: usize,
+    ) -> BackendResult<Vec<PostProcessingJob>> {
+        let query = format!(
+            "
+            SELECT {POST_PROCESSING_JOB_COLUMNS}
+            FROM post_processing_jobs
+            WHERE next_run_at IS NOT NULL
+            AND next_run_at <= ?1
+            AND json_extract(state, '$.type') = 'pending'
+            AND {LIVE_POST_PROCESSING_JOB_FILTER}
+            ORDER BY
+                CASE priority
+                    WHEN 'user_initiated' THEN 0
+                    WHEN 'background_import' THEN 1
+                    ELSE 2
+                END,
+                next_run_at ASC
+            LIMIT ?2"
+        );
+
+        let mut stmt = self.conn.prepare(&query)?;
+        let jobs = stmt
+            .query_map(
+                rusqlite::params![now, limit as i64],
+                map_post_processing_job,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(jobs)
+    }
+
+    // takes a scheduled job off the schedule, returns false if another worker already claimed it
+    pub fn claim_post_processing_job(&mut self, id: &str) -> BackendResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE post_processing_jobs SET next_run_at = NULL, updated_at = ?2 WHERE id = ?1 AND next_run_at IS NOT NULL",
+            rusqlite::params![id, current_time()],
+        )?;
+        Ok(updated == 1)
+    }
+
+    // (re)schedules a job without counting it as an attempt, e.g. when its processing lane is full
+    pub fn schedule_post_processing_job(
+        &mut self,
+        id: &str,
+        next_run_at: &DateTime<Utc>,
     ) -> BackendResult<()> {
-        let failed = ResourceProcessingState::Failed {
-            message: "job terminated without completion".to_owned(),
+        self.conn.execute(
+            "UPDATE post_processing_jobs SET next_run_at = ?2, updated_at = ?3 WHERE id = ?1",
+            rusqlite::params![id, next_run_at, current_time()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_post_processing_batch_progress(
+        &self,
+        batch_id: &str,
+    ) -> BackendResult<PostProcessingBatchProgress> {
+        let mut stmt = self.conn.prepare(
+            "SELECT json_extract(state, '$.type'), COUNT(*)
+            FROM post_processing_jobs
+            WHERE batch_id = ?1
+            GROUP BY 1",
+        )?;
+        let counts = stmt
+            .query_map(rusqlite::params![batch_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut progress = PostProcessingBatchProgress {
+            batch_id: batch_id.to_owned(),
+            ..Default::default()
+        };
+        for (state, count) in counts {
+            match state.as_str() {
+                "pending" => progress.pending += count,
+                "started" => progress.started += count,
+                "finished" => progress.finished += count,
+                _ => progress.failed += count,
+            }
+            progress.total += count;
+        }
+        Ok(progress)
+    }
+
+    // records a failed attempt, the job is either scheduled for a retry with backoff
+    // or moved to the dead-letter state once it has no attempts left
+    pub fn record_post_processing_job_failure(
+        &mut self,
+        id: &str,
+        error: &str,
+    ) -> BackendResult<ResourceProcessingState> {
+        let tx = self.begin()?;
+        let (attempts, max_attempts): (i64, i64) = tx.query_row(
+            "SELECT attempts, max_attempts FROM post_processing_jobs WHERE id = ?1",
+            rusqlite::params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let now = current_time();
+        let attempts = attempts + 1;
+        let (state, next_run_at) = if attempts >= max_attempts {
+            (
+                ResourceProcessingState::DeadLetter {
+                    message: error.to_owned(),
+                },
+                None,
+            )
+        } else {
+            (
+                ResourceProcessingState::Pending,
+                Some(now + post_processing_backoff(attempts)),
+            )
         };
 
-        let update_query = r#"
+        tx.execute(
+            "UPDATE post_processing_jobs
+            SET state = ?2, attempts = ?3, next_run_at = ?4, last_error = ?5, updated_at = ?6
+            WHERE id = ?1",
+            rusqlite::params![id, state, attempts, next_run_at, error, now],
+        )?;
+        tx.commit()?;
+        Ok(state)
+    }
+
+    // puts the job back into a fresh pending state with all of its attempts available,
+    // returns false for a superseded job so it can't overwrite the results of a newer one
+    pub fn reset_post_processing_job(&mut self, id: &str) -> BackendResult<bool> {
+        let query = format!(
+            "
+            UPDATE post_processing_jobs
+            SET state = ?2, attempts = 0, next_run_at = NULL, last_error = NULL, updated_at = ?3
+            WHERE id = ?1
+            AND {LIVE_POST_PROCESSING_JOB_FILTER}"
+        );
+        let updated = self.conn.execute(
+            &query,
+            rusqlite::params![id, ResourceProcessingState::Pending, current_time()],
+        )?;
+        Ok(updated == 1)
+    }
+
+    // schedules the live jobs that were pending or running when the previous session ended,
+    // a job that was interrupted mid-run counts that run as a failed attempt
+    pub fn resume_post_processing_jobs(&mut self, created_at: &DateTime<Utc>) -> BackendResult<()> {
+        let message = "job terminated without completion";
+        let now = current_time();
+        let tx = self.begin()?;
+
+        let dead_letter_query = format!(
+            "
             UPDATE post_processing_jobs
             SET state = ?1,
-                updated_at = datetime('now')
-            WHERE created_at < ?2
-            AND created_at = (
-                SELECT MAX(created_at)
-                FROM post_processing_jobs P2
-                WHERE P2.resource_id = post_processing_jobs.resource_id
-            )
-            AND (state LIKE '%pending%' OR state LIKE '%started%')
-            AND EXISTS (
-                SELECT 1
-                FROM resource_content_hashes
-                WHERE resource_id = post_processing_jobs.resource_id
-                AND content_hash = post_processing_jobs.content_hash
-            )"#;
+                attempts = attempts + 1,
+                next_run_at = NULL,
+                last_error = ?2,
+                updated_at = ?3
+            WHERE created_at < ?4
+            AND json_extract(state, '$.type') = 'started'
+            AND attempts + 1 >= max_attempts
+            AND {LIVE_POST_PROCESSING_JOB_FILTER}"
+        );
+        tx.execute(
+            &dead_letter_query,
+            rusqlite::params![
+                ResourceProcessingState::DeadLetter {
+                    message: message.to_owned()
+                },
+                message,
+                now,
+                created_at
+            ],
+        )?;
 
-        self.conn
-            .execute(update_query, rusqlite::params![failed, created_at])?;
+        let resume_query = format!(
+            "
+            UPDATE post_processing_jobs
+            SET attempts = attempts + (json_extract(state, '$.type') = 'started'),
+                last_error = CASE
+                    WHEN json_extract(state, '$.type') = 'started' THEN ?2
+                    ELSE last_error
+                END,
+                state = ?1,
+                next_run_at = COALESCE(next_run_at, ?3),
+                updated_at = ?3
+            WHERE created_at < ?4
+            AND json_extract(state, '$.type') IN ('pending', 'started')
+            AND {LIVE_POST_PROCESSING_JOB_FILTER}"
+        );
+        tx.execute(
+            &resume_query,
+            rusqlite::params![ResourceProcessingState::Pending, message, now, created_at],
+        )?;
+
+        tx.commit()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{Resource, POST_PROCESSING_MAX_ATTEMPTS};
+    use tempfile::tempdir;
+
+    fn setup_test_db() -> Database {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        Database::new(&db_path.to_string_lossy(), true).unwrap()
+    }
+
+    fn create_test_job(db: &mut Database) -> PostProcessingJob {
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: "resource1".to_string(),
+            resource_path: "resource1".to_string(),
+            resource_type: "application/pdf".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted: 0,
+        })
+        .unwrap();
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_hash_tx(&mut tx, "resource1", "hash1").unwrap();
+        tx.commit().unwrap();
+
+        let job = PostProcessingJob::new("resource1".to_string(), "hash1".to_string());
+        db.create_processing_job_entry(&job).unwrap();
+        job
+    }
+
+    #[test]
+    fn test_post_processing_backoff() {
+        assert_eq!(post_processing_backoff(1), Duration::seconds(30));
+        assert_eq!(post_processing_backoff(2), Duration::seconds(60));
+        assert_eq!(post_processing_backoff(4), Duration::seconds(240));
+        assert_eq!(post_processing_backoff(10), Duration::seconds(3600));
+        assert_eq!(post_processing_backoff(1000), Duration::seconds(3600));
+    }
+
+    #[test]
+    fn test_record_failure_retries_then_dead_letters() {
+        let mut db = setup_test_db();
+        let job = create_test_job(&mut db);
+
+        for attempt in 1..POST_PROCESSING_MAX_ATTEMPTS {
+            let state = db
+                .record_post_processing_job_failure(&job.id, "boom")
+                .unwrap();
+            assert!(matches!(state, ResourceProcessingState::Pending));
+
+            let stored = db.get_post_processing_job(&job.id).unwrap().unwrap();
+            assert_eq!(stored.attempts, attempt);
+            assert_eq!(stored.last_error.as_deref(), Some("boom"));
+            assert!(stored.next_run_at.unwrap() > stored.updated_at);
+        }
+
+        let state = db
+            .record_post_processing_job_failure(&job.id, "boom")
+            .unwrap();
+        assert!(matches!(state, ResourceProcessingState::DeadLetter { .. }));
+
+        let stored = db.get_post_processing_job(&job.id).unwrap().unwrap();
+        assert!(stored.next_run_at.is_none());
+        assert_eq!(
+            db.list_post_processing_jobs(Some("dead_letter"), None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(db
+            .list_post_processing_jobs(Some("pending"), None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_due_jobs_are_claimed_once() {
+        let mut db = setup_test_db();
+        let job = create_test_job(&mut db);
+        db.record_post_processing_job_failure(&job.id, "boom")
+            .unwrap();
+
+        assert!(db
+            .list_due_post_processing_jobs(&current_time(), 10)
+            .unwrap()
+            .is_empty());
+
+        let later = current_time() + Duration::hours(2);
+        let due = db.list_due_post_processing_jobs(&later, 10).unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(db.claim_post_processing_job(&job.id).unwrap());
+        assert!(!db.claim_post_processing_job(&job.id).unwrap());
+        assert!(db
+            .list_due_post_processing_jobs(&later, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_due_jobs_are_ordered_by_priority() {
+        let mut db = setup_test_db();
+        let job = create_test_job(&mut db);
+        let now = current_time();
+        db.schedule_post_processing_job(&job.id, &now).unwrap();
+
+        db.create_resource(&Resource {
+            id: "resource2".to_string(),
+            resource_path: "resource2".to_string(),
+            resource_type: "application/pdf".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted: 0,
+        })
+        .unwrap();
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_hash_tx(&mut tx, "resource2", "hash2").unwrap();
+        tx.commit().unwrap();
+        let import_job = PostProcessingJob::new("resource2".to_string(), "hash2".to_string())
+            .with_priority(ProcessingPriority::BackgroundImport)
+            .with_batch_id("batch1".to_string());
+        db.create_processing_job_entry(&import_job).unwrap();
+        db.schedule_post_processing_job(&import_job.id, &(now - Duration::minutes(1)))
+            .unwrap();
+
+        let due = db.list_due_post_processing_jobs(&now, 10).unwrap();
+        assert_eq!(
+            due.iter().map(|job| job.priority).collect::<Vec<_>>(),
+            vec![
+                ProcessingPriority::UserInitiated,
+                ProcessingPriority::BackgroundImport
+            ]
+        );
+
+        db.set_post_processing_job_state(import_job.id.clone(), ResourceProcessingState::Finished)
+            .unwrap();
+        let progress = db.get_post_processing_batch_progress("batch1").unwrap();
+        assert_eq!(progress.total, 1);
+        assert_eq!(progress.finished, 1);
+    }
+
+    #[test]
+    fn test_reset_only_live_jobs() {
+        let mut db = setup_test_db();
+        let job = create_test_job(&mut db);
+        db.record_post_processing_job_failure(&job.id, "boom")
+            .unwrap();
+
+        // the content changed and a new job was created for it
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_hash_tx(&mut tx, "resource1", "hash2").unwrap();
+        tx.commit().unwrap();
+        let mut newer_job = PostProcessingJob::new("resource1".to_string(), "hash2".to_string());
+        newer_job.created_at = job.created_at + Duration::seconds(1);
+        db.create_processing_job_entry(&newer_job).unwrap();
+
+        assert!(!db.reset_post_processing_job(&job.id).unwrap());
+        let stored = db.get_post_processing_job(&job.id).unwrap().unwrap();
+        assert_eq!(stored.attempts, 1);
+        assert_eq!(stored.last_error.as_deref(), Some("boom"));
+
+        db.record_post_processing_job_failure(&newer_job.id, "boom")
+            .unwrap();
+        assert!(db.reset_post_processing_job(&newer_job.id).unwrap());
+        let stored = db.get_post_processing_job(&newer_job.id).unwrap().unwrap();
+        assert_eq!(stored.attempts, 0);
+        assert!(stored.last_error.is_none());
+    }
+
+    #[test]
+    fn test_resume_interrupted_jobs() {
+        let mut db = setup_test_db();
+        let job = create_test_job(&mut db);
+        db.set_post_processing_job_state(job.id.clone(), ResourceProcessingState::Started)
+            .unwrap();
+
+        db.resume_post_processing_jobs(&(current_time() + Duration::seconds(1)))
+            .unwrap();
+
+        let stored = db.get_post_processing_job(&job.id).unwrap().unwrap();
+        assert!(matches!(stored.state, ResourceProcessingState::Pending));
+        assert_eq!(stored.attempts, 1);
+        assert!(stored.next_run_at.is_some());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{store::db::Database, BackendResult};
+use crate::{
+    store::{db::Database, models::SimilarImageResource},
+    BackendResult,
+};
 
 use rusqlite::OptionalExtension;
 
@@ -33,4 +36,134 @@ impl Database {
         )?;
         Ok(())
     }
+
+    pub fn upsert_resource_perceptual_hash_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+        hash: u64,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO resource_perceptual_hashes (resource_id, perceptual_hash) VALUES (?, ?)",
+            [resource_id, &format!("{hash:016x}")],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_resource_perceptual_hash(&self, resource_id: &str) -> BackendResult<Option<u64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT perceptual_hash FROM resource_perceptual_hashes WHERE resource_id = ?",
+        )?;
+        let hash: Option<String> = stmt.query_row([resource_id], |row| row.get(0)).optional()?;
+        Ok(hash.and_then(|hash| u64::from_str_radix(&hash, 16).ok()))
+    }
+
+    // sqlite has no popcount, so the hamming distance is computed here,
+    // the hashes are small enough to scan them all
+    pub fn list_similar_image_resources(
+        &self,
+        resource_id: &str,
+        max_distance: u32,
+    ) -> BackendResult<Vec<SimilarImageResource>> {
+        let hash = match self.get_resource_perceptual_hash(resource_id)? {
+            Some(hash) => hash,
+            None => return Ok(vec![]),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT H.resource_id, H.perceptual_hash
+            FROM resource_perceptual_hashes H
+            JOIN resources R ON R.id = H.resource_id
+            WHERE H.resource_id != ?1 AND R.deleted = 0",
+        )?;
+        let rows = stmt.query_map([resource_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut similar = Vec::new();
+        for row in rows {
+            let (other_id, other_hash) = row?;
+            let other_hash = match u64::from_str_radix(&other_hash, 16) {
+                Ok(other_hash) => other_hash,
+                Err(_) => continue,
+            };
+            let distance = (hash ^ other_hash).count_ones();
+            if distance <= max_distance {
+                similar.push(SimilarImageResource {
+                    resource_id: other_id,
+                    distance,
+                });
+            }
+        }
+        similar.sort_by(|a, b| {
+            a.distance
+                .cmp(&b.distance)
+                .then_with(|| a.resource_id.cmp(&b.resource_id))
+        });
+        Ok(similar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{current_time, Resource};
+    use tempfile::tempdir;
+
+    fn setup_test_db() -> Database {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        Database::new(&db_path.to_string_lossy(), true).unwrap()
+    }
+
+    fn create_image(db: &mut Database, id: &str, hash: u64, deleted: i32) {
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: id.to_string(),
+            resource_path: id.to_string(),
+            resource_type: "image/png".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted,
+        })
+        .unwrap();
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_perceptual_hash_tx(&mut tx, id, hash).unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn test_list_similar_image_resources() {
+        let mut db = setup_test_db();
+        let hash = 0xF0F0_F0F0_1234_5678;
+        create_image(&mut db, "original", hash, 0);
+        create_image(&mut db, "copy", hash, 0);
+        create_image(&mut db, "resized", hash ^ 0b101, 0);
+        create_image(&mut db, "unrelated", !hash, 0);
+        create_image(&mut db, "deleted", hash, 1);
+
+        assert_eq!(
+            db.get_resource_perceptual_hash("original").unwrap(),
+            Some(hash)
+        );
+
+        let similar = db.list_similar_image_resources("original", 10).unwrap();
+        assert_eq!(
+            similar,
+            vec![
+                SimilarImageResource {
+                    resource_id: "copy".to_string(),
+                    distance: 0,
+                },
+                SimilarImageResource {
+                    resource_id: "resized".to_string(),
+                    distance: 2,
+                },
+            ]
+        );
+
+        assert!(db
+            .list_similar_image_resources("missing", 10)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,300 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{
+            current_time, random_uuid, ExtractedResourceLink, ResourceGraph, ResourceLink,
+            ResourceLinkKind,
+        },
+    },
+    BackendResult,
+};
+
+use std::collections::HashSet;
+
+const RESOURCE_LINK_COLUMNS: &str =
+    "L.id, L.source_id, L.target_id, L.kind, L.context, L.created_at";
+
+// links of deleted resources are kept so they come back when the resource is recovered
+const LIVE_RESOURCE_LINKS: &str = "resource_links L
+    JOIN resources S ON S.id = L.source_id AND S.deleted = 0
+    JOIN resources T ON T.id = L.target_id AND T.deleted = 0";
+
+fn resource_link_from_row(row: &rusqlite::Row) -> rusqlite::Result<ResourceLink> {
+    Ok(ResourceLink {
+        id: row.get(0)?,
+        source_id: row.get(1)?,
+        target_id: row.get(2)?,
+        kind: row.get(3)?,
+        context: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+impl Database {
+    pub fn create_resource_link_tx(
+        tx: &mut rusqlite::Transaction,
+        link: &ResourceLink,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT INTO resource_links (id, source_id, target_id, kind, context, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                link.id,
+                link.source_id,
+                link.target_id,
+                link.kind,
+                link.context,
+                link.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_resource_link_tx(tx: &mut rusqlite::Transaction, id: &str) -> BackendResult<()> {
+        tx.execute("DELETE FROM resource_links WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // replaces the links found in the content of the source, links to the source itself and
+    // repeated links to the same target are dropped
+    pub fn set_extracted_resource_links_tx(
+        tx: &mut rusqlite::Transaction,
+        source_id: &str,
+        links: &[ExtractedResourceLink],
+    ) -> BackendResult<()> {
+        tx.execute(
+            "DELETE FROM resource_links WHERE source_id = ?1 AND kind != ?2",
+            rusqlite::params![source_id, ResourceLinkKind::User],
+        )?;
+
+        let mut seen = HashSet::new();
+        let now = current_time();
+        for link in links {
+            if link.target_id == source_id || link.kind == ResourceLinkKind::User {
+                continue;
+            }
+            if !seen.insert((link.target_id.as_str(), link.kind)) {
+                continue;
+            }
+            let link = ResourceLink {
+                id: random_uuid(),
+                source_id: source_id.to_owned(),
+                target_id: link.target_id.clone(),
+                kind: link.kind,
+                context: link.context.clone(),
+                created_at: now,
+            };
+            Self::create_resource_link_tx(tx, &link)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_resource_link(&self, id: &str) -> BackendResult<Option<ResourceLink>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_LINK_COLUMNS} FROM resource_links L WHERE L.id = ?1"
+        ))?;
+        let mut links = stmt.query_map([id], resource_link_from_row)?;
+        Ok(links.next().transpose()?)
+    }
+
+    pub fn list_outgoing_resource_links(
+        &self,
+        resource_id: &str,
+    ) -> BackendResult<Vec<ResourceLink>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_LINK_COLUMNS} FROM {LIVE_RESOURCE_LINKS}
+            WHERE L.source_id = ?1 ORDER BY L.created_at ASC, L.id ASC"
+        ))?;
+        let links = stmt.query_map([resource_id], resource_link_from_row)?;
+        Ok(links.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn list_resource_backlinks(&self, resource_id: &str) -> BackendResult<Vec<ResourceLink>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_LINK_COLUMNS} FROM {LIVE_RESOURCE_LINKS}
+            WHERE L.target_id = ?1 ORDER BY L.created_at ASC, L.id ASC"
+        ))?;
+        let links = stmt.query_map([resource_id], resource_link_from_row)?;
+        Ok(links.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn list_resource_links_of(&self, resource_ids: &[String]) -> BackendResult<Vec<ResourceLink>> {
+        let placeholders = vec!["?"; resource_ids.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_LINK_COLUMNS} FROM {LIVE_RESOURCE_LINKS}
+            WHERE L.source_id IN ({placeholders}) OR L.target_id IN ({placeholders})
+            ORDER BY L.created_at ASC, L.id ASC"
+        ))?;
+        let params = resource_ids.iter().chain(resource_ids.iter());
+        let links = stmt.query_map(rusqlite::params_from_iter(params), resource_link_from_row)?;
+        Ok(links.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // the resources reachable within `hops` links in either direction, once `max_resources`
+    // are reached no further resources are added
+    pub fn get_resource_graph(
+        &self,
+        resource_id: &str,
+        hops: usize,
+        max_resources: usize,
+    ) -> BackendResult<ResourceGraph> {
+        let mut graph = ResourceGraph {
+            resource_ids: vec![resource_id.to_owned()],
+            links: vec![],
+        };
+        let mut reached: HashSet<String> = HashSet::from([resource_id.to_owned()]);
+        let mut frontier = vec![resource_id.to_owned()];
+
+        for _ in 0..hops {
+            if frontier.is_empty() || graph.resource_ids.len() >= max_resources {
+                break;
+            }
+            let mut next = vec![];
+            for link in self.list_resource_links_of(&frontier)? {
+                for id in [&link.source_id, &link.target_id] {
+                    if graph.resource_ids.len() < max_resources && reached.insert(id.clone()) {
+                        graph.resource_ids.push(id.clone());
+                        next.push(id.clone());
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        // the links between the resources of the last hop and the ones already reached
+        let mut seen = HashSet::new();
+        for link in self.list_resource_links_of(&graph.resource_ids)? {
+            if reached.contains(&link.source_id)
+                && reached.contains(&link.target_id)
+                && seen.insert(link.id.clone())
+            {
+                graph.links.push(link);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::Resource;
+    use tempfile::tempdir;
+
+    fn setup_test_db(ids: &[&str]) -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        for id in ids {
+            db.create_resource(&Resource {
+                id: id.to_string(),
+                resource_path: id.to_string(),
+                resource_type: "application/vnd.space.document.space-note".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            })
+            .unwrap();
+        }
+        (db, dir)
+    }
+
+    fn extracted(target_id: &str, kind: ResourceLinkKind) -> ExtractedResourceLink {
+        ExtractedResourceLink {
+            target_id: target_id.to_string(),
+            kind,
+            context: Some(format!("about {target_id}")),
+        }
+    }
+
+    fn targets(links: &[ResourceLink]) -> Vec<&str> {
+        links.iter().map(|link| link.target_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_extracted_links_keep_user_links() {
+        let (mut db, _dir) = setup_test_db(&["a", "b", "c", "d"]);
+
+        let mut tx = db.begin().unwrap();
+        Database::create_resource_link_tx(
+            &mut tx,
+            &ResourceLink {
+                id: "user".to_string(),
+                source_id: "a".to_string(),
+                target_id: "d".to_string(),
+                kind: ResourceLinkKind::User,
+                context: None,
+                created_at: current_time(),
+            },
+        )
+        .unwrap();
+        let links = [
+            extracted("b", ResourceLinkKind::LinksTo),
+            extracted("b", ResourceLinkKind::LinksTo),
+            extracted("a", ResourceLinkKind::LinksTo),
+            extracted("c", ResourceLinkKind::LinksTo),
+        ];
+        Database::set_extracted_resource_links_tx(&mut tx, "a", &links).unwrap();
+        tx.commit().unwrap();
+
+        let mut outgoing = db.list_outgoing_resource_links("a").unwrap();
+        outgoing.sort_by(|a, b| a.target_id.cmp(&b.target_id));
+        assert_eq!(targets(&outgoing), vec!["b", "c", "d"]);
+        assert_eq!(outgoing[0].context.as_deref(), Some("about b"));
+
+        let mut tx = db.begin().unwrap();
+        let links = [extracted("c", ResourceLinkKind::LinksTo)];
+        Database::set_extracted_resource_links_tx(&mut tx, "a", &links).unwrap();
+        tx.commit().unwrap();
+
+        let mut outgoing = db.list_outgoing_resource_links("a").unwrap();
+        outgoing.sort_by(|a, b| a.target_id.cmp(&b.target_id));
+        assert_eq!(targets(&outgoing), vec!["c", "d"]);
+        assert_eq!(outgoing[1].kind, ResourceLinkKind::User);
+
+        let backlinks = db.list_resource_backlinks("c").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].source_id, "a");
+    }
+
+    #[test]
+    fn test_resource_graph() {
+        // a -> b -> c -> d, e -> b, and f is deleted
+        let (mut db, _dir) = setup_test_db(&["a", "b", "c", "d", "e", "f"]);
+        let mut tx = db.begin().unwrap();
+        for (source, targets) in [
+            ("a", vec!["b"]),
+            ("b", vec!["c", "f"]),
+            ("c", vec!["d"]),
+            ("e", vec!["b"]),
+        ] {
+            let links = targets
+                .into_iter()
+                .map(|target| extracted(target, ResourceLinkKind::LinksTo))
+                .collect::<Vec<_>>();
+            Database::set_extracted_resource_links_tx(&mut tx, source, &links).unwrap();
+        }
+        Database::update_resource_deleted_tx(&mut tx, "f", 1).unwrap();
+        tx.commit().unwrap();
+
+        let graph = db.get_resource_graph("a", 2, 100).unwrap();
+        let mut ids = graph.resource_ids.clone();
+        ids.sort();
+        assert_eq!(graph.resource_ids[0], "a");
+        assert_eq!(ids, vec!["a", "b", "c", "e"]);
+        let mut links = graph
+            .links
+            .iter()
+            .map(|link| format!("{}->{}", link.source_id, link.target_id))
+            .collect::<Vec<_>>();
+        links.sort();
+        assert_eq!(links, vec!["a->b", "b->c", "e->b"]);
+
+        let graph = db.get_resource_graph("a", 3, 3).unwrap();
+        assert_eq!(graph.resource_ids.len(), 3);
+        assert_eq!(
+            db.get_resource_graph("d", 0, 100).unwrap().resource_ids,
+            vec!["d"]
+        );
+    }
+}
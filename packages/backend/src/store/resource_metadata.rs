@@ -8,9 +8,13 @@ impl Database {
         resource_metadata: &ResourceMetadata,
     ) -> BackendResult<()> {
         tx.execute(
-            "INSERT INTO resource_metadata (id, resource_id, name, source_uri, alt, user_context) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![resource_metadata.id, resource_metadata.resource_id, resource_metadata.name, resource_metadata.source_uri, resource_metadata.alt, resource_metadata.user_context]
+            "INSERT INTO resource_metadata (id, resource_id, name, source_uri, alt, user_context, byline, published_at, lead_image) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![resource_metadata.id, resource_metadata.resource_id, resource_metadata.name, resource_metadata.source_uri, resource_metadata.alt, resource_metadata.user_context, resource_metadata.byline, resource_metadata.published_at, resource_metadata.lead_image]
         )?;
+        Self::index_metadata_words(tx, "rowid = ?1", [tx.last_insert_rowid()])?;
+        if let Some(outline) = &resource_metadata.outline {
+            Self::set_resource_outline_tx(tx, &resource_metadata.resource_id, outline, true)?;
+        }
         Ok(())
     }
 
@@ -18,24 +22,119 @@ impl Database {
         tx: &mut rusqlite::Transaction,
         resource_metadata: &ResourceMetadata,
     ) -> BackendResult<()> {
+        Self::unindex_metadata_words(tx, "id = ?1", [&resource_metadata.id])?;
         tx.execute(
-            "UPDATE resource_metadata SET resource_id = ?2, name = ?3, source_uri = ?4, alt = ?5, user_context=?6 WHERE id = ?1",
-            rusqlite::params![resource_metadata.id, resource_metadata.resource_id, resource_metadata.name, resource_metadata.source_uri, resource_metadata.alt, resource_metadata.user_context]
+            "UPDATE resource_metadata SET resource_id = ?2, name = ?3, source_uri = ?4, alt = ?5, user_context=?6, byline = ?7, published_at = ?8, lead_image = ?9 WHERE id = ?1",
+            rusqlite::params![resource_metadata.id, resource_metadata.resource_id, resource_metadata.name, resource_metadata.source_uri, resource_metadata.alt, resource_metadata.user_context, resource_metadata.byline, resource_metadata.published_at, resource_metadata.lead_image]
         )?;
+        Self::index_metadata_words(tx, "id = ?1", [&resource_metadata.id])?;
+        if let Some(outline) = &resource_metadata.outline {
+            Self::set_resource_outline_tx(tx, &resource_metadata.resource_id, outline, true)?;
+        }
 
         Self::touch_resource_tx(tx, &resource_metadata.resource_id)?;
 
         Ok(())
     }
 
+    // only fills in fields that are still empty, so values set by the user or by the
+    // page capture always win over what the extractor found
+    pub fn apply_extracted_resource_metadata_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+        metadata: &ExtractedResourceMetadata,
+    ) -> BackendResult<()> {
+        Self::unindex_metadata_words(tx, "resource_id = ?1", [resource_id])?;
+        let updated = tx.execute(
+            "UPDATE resource_metadata SET
+                name = CASE WHEN name = '' AND ?2 IS NOT NULL THEN ?2 ELSE name END,
+                byline = COALESCE(NULLIF(byline, ''), ?3),
+                published_at = COALESCE(NULLIF(published_at, ''), ?4),
+                lead_image = COALESCE(NULLIF(lead_image, ''), ?5)
+            WHERE resource_id = ?1",
+            rusqlite::params![
+                resource_id,
+                metadata.title,
+                metadata.byline,
+                metadata.published_at,
+                metadata.lead_image
+            ],
+        )?;
+        Self::index_metadata_words(tx, "resource_id = ?1", [resource_id])?;
+        if updated > 0 {
+            if let Some(outline) = &metadata.outline {
+                Self::set_resource_outline_tx(tx, resource_id, outline, false)?;
+            }
+            Self::touch_resource_tx(tx, resource_id)?;
+        }
+        Ok(())
+    }
+
+    // extracted outlines pass `replace: false` so they never overwrite an existing one
+    fn set_resource_outline_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+        outline: &DocumentOutline,
+        replace: bool,
+    ) -> BackendResult<()> {
+        let on_conflict = if replace {
+            "DO UPDATE SET outline = excluded.outline"
+        } else {
+            "DO NOTHING"
+        };
+        tx.execute(
+            &format!(
+                "INSERT INTO resource_metadata_outlines (resource_id, outline) VALUES (?1, ?2)
+                ON CONFLICT(resource_id) {}",
+                on_conflict
+            ),
+            rusqlite::params![resource_id, outline],
+        )?;
+        Ok(())
+    }
+
+    // frontmatter values are written by the user, so they replace whatever is there
+    pub fn apply_resource_frontmatter_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+        frontmatter: &ResourceFrontmatter,
+    ) -> BackendResult<()> {
+        Self::unindex_metadata_words(tx, "resource_id = ?1", [resource_id])?;
+        let updated = tx.execute(
+            "UPDATE resource_metadata SET
+                name = COALESCE(?2, name),
+                source_uri = COALESCE(?3, source_uri),
+                byline = COALESCE(?4, byline),
+                published_at = COALESCE(?5, published_at)
+            WHERE resource_id = ?1",
+            rusqlite::params![
+                resource_id,
+                frontmatter.title,
+                frontmatter.source_uri,
+                frontmatter.byline,
+                frontmatter.published_at
+            ],
+        )?;
+        Self::index_metadata_words(tx, "resource_id = ?1", [resource_id])?;
+        if updated > 0 {
+            Self::touch_resource_tx(tx, resource_id)?;
+        }
+        Ok(())
+    }
+
     pub fn remove_resource_metadata_tx(
         tx: &mut rusqlite::Transaction,
         id: &str,
     ) -> BackendResult<()> {
+        Self::unindex_metadata_words(tx, "resource_id = ?1", [id])?;
         tx.execute(
             "DELETE FROM resource_metadata WHERE resource_id = ?1",
             rusqlite::params![id],
         )?;
+        tx.execute(
+            "DELETE FROM resource_metadata_outlines WHERE resource_id = ?1",
+            rusqlite::params![id],
+        )?;
         Ok(())
     }
 
@@ -43,7 +142,10 @@ impl Database {
         &self,
         resource_id: &str,
     ) -> BackendResult<Option<ResourceMetadata>> {
-        let query = "SELECT id, resource_id, name, source_uri, alt, user_context FROM resource_metadata WHERE resource_id = ?1 LIMIT 1";
+        let query = "SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, M.byline, M.published_at, M.lead_image, O.outline
+            FROM resource_metadata M
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
+            WHERE M.resource_id = ?1 LIMIT 1";
         self.conn
             .query_row(query, rusqlite::params![resource_id], |row| {
                 Ok(ResourceMetadata {
@@ -53,6 +155,10 @@ impl Database {
                     source_uri: row.get(3)?,
                     alt: row.get(4)?,
                     user_context: row.get(5)?,
+                    byline: row.get(6)?,
+                    published_at: row.get(7)?,
+                    lead_image: row.get(8)?,
+                    outline: row.get(9)?,
                 })
             })
             .optional()
@@ -65,8 +171,9 @@ impl Database {
     ) -> BackendResult<Vec<CompositeResource>> {
         let placeholders = vec!["?"; resource_ids.len()].join(",");
         let query = format!(
-            "SELECT M.*, R.* FROM resources R
+            "SELECT M.*, O.outline, R.* FROM resources R
             LEFT JOIN resource_metadata M ON M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
             WHERE R.id IN ({}) ORDER BY R.created_at DESC",
             placeholders
         );
@@ -82,20 +189,25 @@ impl Database {
                         source_uri: row.get(3)?,
                         alt: row.get(4)?,
                         user_context: row.get(5)?,
+                        byline: row.get(6)?,
+                        published_at: row.get(7)?,
+                        lead_image: row.get(8)?,
+                        outline: row.get(9)?,
                     }),
                     resource: Resource {
-                        id: row.get(6)?,
-                        resource_path: row.get(7)?,
-                        resource_type: row.get(8)?,
-                        created_at: row.get(9)?,
-                        updated_at: row.get(10)?,
-                        deleted: row.get(11)?,
+                        id: row.get(10)?,
+                        resource_path: row.get(11)?,
+                        resource_type: row.get(12)?,
+                        created_at: row.get(13)?,
+                        updated_at: row.get(14)?,
+                        deleted: row.get(15)?,
                     },
                     text_content: None,
                     resource_tags: None,
                     resource_annotations: None,
                     post_processing_job: None,
                     space_ids: None,
+                    thumbnail: None,
                 })
             })?;
 
@@ -105,3 +217,159 @@ impl Database {
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn setup_test_db() -> Database {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        Database::new(&db_path.to_string_lossy(), true).unwrap()
+    }
+
+    fn create_test_metadata(db: &mut Database, name: &str, byline: Option<&str>) {
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: "resource1".to_string(),
+            resource_path: "resource1".to_string(),
+            resource_type: "text/html".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted: 0,
+        })
+        .unwrap();
+        let mut tx = db.begin().unwrap();
+        Database::create_resource_metadata_tx(
+            &mut tx,
+            &ResourceMetadata {
+                id: "metadata1".to_string(),
+                resource_id: "resource1".to_string(),
+                name: name.to_string(),
+                source_uri: "https://example.com/post".to_string(),
+                alt: "".to_string(),
+                user_context: "".to_string(),
+                byline: byline.map(|b| b.to_string()),
+                published_at: None,
+                lead_image: None,
+                outline: None,
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn test_extracted_metadata_only_fills_empty_fields() {
+        let mut db = setup_test_db();
+        create_test_metadata(&mut db, "", Some("Jane Doe"));
+
+        let mut tx = db.begin().unwrap();
+        Database::apply_extracted_resource_metadata_tx(
+            &mut tx,
+            "resource1",
+            &ExtractedResourceMetadata {
+                title: Some("Extracted title".to_string()),
+                byline: Some("Someone Else".to_string()),
+                published_at: Some("2024-03-01T10:00:00Z".to_string()),
+                lead_image: Some("https://example.com/lead.jpg".to_string()),
+                outline: Some(DocumentOutline(vec![DocumentOutlineEntry {
+                    title: "Introduction".to_string(),
+                    level: 0,
+                    page: Some(1),
+                }])),
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let metadata = db
+            .get_resource_metadata_by_resource_id("resource1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.name, "Extracted title");
+        assert_eq!(metadata.byline.as_deref(), Some("Jane Doe"));
+        assert_eq!(
+            metadata.published_at.as_deref(),
+            Some("2024-03-01T10:00:00Z")
+        );
+        assert_eq!(
+            metadata.lead_image.as_deref(),
+            Some("https://example.com/lead.jpg")
+        );
+        assert_eq!(
+            metadata.outline.map(|outline| outline.0[0].title.clone()),
+            Some("Introduction".to_string())
+        );
+    }
+
+    #[test]
+    fn test_outline_is_stored_next_to_the_metadata() {
+        let mut db = setup_test_db();
+        create_test_metadata(&mut db, "Report", None);
+        let outline = |title: &str| ExtractedResourceMetadata {
+            outline: Some(DocumentOutline(vec![DocumentOutlineEntry {
+                title: title.to_string(),
+                level: 0,
+                page: None,
+            }])),
+            ..Default::default()
+        };
+        let count_outlines = |db: &Database| -> i64 {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM resource_metadata_outlines",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        let mut tx = db.begin().unwrap();
+        Database::apply_extracted_resource_metadata_tx(&mut tx, "resource1", &outline("First"))
+            .unwrap();
+        Database::apply_extracted_resource_metadata_tx(&mut tx, "resource1", &outline("Second"))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let metadata = db
+            .get_resource_metadata_by_resource_id("resource1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metadata.outline.map(|outline| outline.0[0].title.clone()),
+            Some("First".to_string())
+        );
+        assert_eq!(count_outlines(&db), 1);
+
+        let mut tx = db.begin().unwrap();
+        Database::remove_resource_metadata_tx(&mut tx, "resource1").unwrap();
+        tx.commit().unwrap();
+        assert_eq!(count_outlines(&db), 0);
+    }
+
+    #[test]
+    fn test_update_can_clear_extracted_fields() {
+        let mut db = setup_test_db();
+        create_test_metadata(&mut db, "Saved page", Some("Jane Doe"));
+
+        let mut metadata = db
+            .get_resource_metadata_by_resource_id("resource1")
+            .unwrap()
+            .unwrap();
+        metadata.name = "Renamed".to_string();
+        metadata.byline = None;
+
+        let mut tx = db.begin().unwrap();
+        Database::update_resource_metadata_tx(&mut tx, &metadata).unwrap();
+        tx.commit().unwrap();
+
+        let metadata = db
+            .get_resource_metadata_by_resource_id("resource1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.name, "Renamed");
+        assert_eq!(metadata.byline, None);
+    }
+}
@@ -4,30 +4,105 @@ use crate::{BackendError, BackendResult};
 use rusqlite::OptionalExtension;
 use std::collections::HashMap;
 
-pub fn list_resource_ids_by_tags_query(
-    tag_filters: &Vec<ResourceTagFilter>,
-    param_start_index: usize,
-) -> (String, Vec<String>) {
-    let mut query = String::from("");
-    let mut i = 0;
-    let n = tag_filters.len();
-    let mut params: Vec<String> = Vec::new();
-    for filter in tag_filters {
-        let (where_clause, tag_value) = filter
-            .get_sql_filter_with_value((i + 1 + param_start_index, i + 2 + param_start_index));
-
-        query = format!(
-            "{}SELECT resource_id FROM resource_tags WHERE ({})",
-            query, where_clause,
-        );
-        if i < 2 * (n - 1) {
-            query = format!("{} INTERSECT ", query);
+// the format rusqlite stores `DateTime<Utc>` values in, so they compare as strings
+pub fn sql_time(time: &chrono::DateTime<chrono::Utc>) -> String {
+    time.format("%F %T%.f%:z").to_string()
+}
+
+fn push_param(params: &mut Vec<String>, value: String) -> usize {
+    params.push(value);
+    params.len()
+}
+
+fn time_range_condition(
+    column: &str,
+    after: &Option<chrono::DateTime<chrono::Utc>>,
+    before: &Option<chrono::DateTime<chrono::Utc>>,
+    params: &mut Vec<String>,
+) -> String {
+    let mut conditions = vec![];
+    if let Some(after) = after {
+        let i = push_param(params, sql_time(after));
+        conditions.push(format!("R.{} >= ?{}", column, i));
+    }
+    if let Some(before) = before {
+        let i = push_param(params, sql_time(before));
+        conditions.push(format!("R.{} < ?{}", column, i));
+    }
+    if conditions.is_empty() {
+        return "1".to_owned();
+    }
+    format!("({})", conditions.join(" AND "))
+}
+
+// compiles the filter into a condition on `resources R`, the parameters are numbered after the
+// ones already in `params` so the condition can be embedded in a larger query
+pub fn resource_filter_condition(filter: &ResourceFilter, params: &mut Vec<String>) -> String {
+    match filter {
+        ResourceFilter::And { filters } | ResourceFilter::Or { filters } => {
+            let (separator, empty) = match filter {
+                ResourceFilter::And { .. } => (" AND ", "1"),
+                _ => (" OR ", "0"),
+            };
+            if filters.is_empty() {
+                return empty.to_owned();
+            }
+            let conditions: Vec<String> = filters
+                .iter()
+                .map(|filter| resource_filter_condition(filter, params))
+                .collect();
+            format!("({})", conditions.join(separator))
+        }
+        ResourceFilter::Not { filter } => {
+            format!("NOT {}", resource_filter_condition(filter, params))
+        }
+        ResourceFilter::Tag(tag) => match tag.op {
+            ResourceTagFilterOp::NotExists => {
+                let i = push_param(params, tag.tag_name.clone());
+                format!(
+                    "NOT EXISTS (SELECT 1 FROM resource_tags WHERE resource_id = R.id AND tag_name = ?{})",
+                    i
+                )
+            }
+            _ => {
+                let (where_clause, tag_value) =
+                    tag.get_sql_filter_with_value((params.len() + 1, params.len() + 2));
+                params.push(tag.tag_name.clone());
+                params.push(tag_value);
+                format!(
+                    "EXISTS (SELECT 1 FROM resource_tags WHERE resource_id = R.id AND {})",
+                    where_clause
+                )
+            }
+        },
+        ResourceFilter::CreatedAt { after, before } => {
+            time_range_condition("created_at", after, before, params)
+        }
+        ResourceFilter::UpdatedAt { after, before } => {
+            time_range_condition("updated_at", after, before, params)
+        }
+        ResourceFilter::ResourceType {
+            resource_type,
+            prefix,
+        } => {
+            let i = push_param(params, resource_type.clone());
+            if *prefix {
+                format!("substr(R.resource_type, 1, length(?{0})) = ?{0}", i)
+            } else {
+                format!("R.resource_type = ?{}", i)
+            }
         }
-        i += 2;
-        params.push(filter.tag_name.clone());
-        params.push(tag_value);
     }
-    (query, params)
+}
+
+pub fn list_resource_ids_by_filter_query(
+    filter: &ResourceFilter,
+    params: &mut Vec<String>,
+) -> String {
+    format!(
+        "SELECT R.id FROM resources R WHERE {}",
+        resource_filter_condition(filter, params)
+    )
 }
 
 impl Database {
@@ -196,16 +271,28 @@ impl Database {
         Ok(())
     }
 
-    pub fn list_resource_ids_by_tags(
+    pub fn remove_resource_tags_by_tag_name_prefix_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+        prefix: &str,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "DELETE FROM resource_tags WHERE resource_id = ?1 AND substr(tag_name, 1, length(?2)) = ?2",
+            rusqlite::params![resource_id, prefix],
+        )?;
+
+        Self::touch_resource_tx(tx, resource_id)?;
+
+        Ok(())
+    }
+
+    fn list_resource_ids_by_query(
         &self,
-        tags: &Vec<ResourceTagFilter>,
+        query: &str,
+        params: &[String],
     ) -> BackendResult<Vec<String>> {
         let mut result = Vec::new();
-        if tags.is_empty() {
-            return Ok(result);
-        }
-        let (query, params) = list_resource_ids_by_tags_query(tags, 0);
-        let mut stmt = self.conn.prepare(&query)?;
+        let mut stmt = self.conn.prepare(query)?;
         let resource_ids =
             stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| row.get(0))?;
         for resource_id in resource_ids {
@@ -214,129 +301,176 @@ impl Database {
         Ok(result)
     }
 
+    pub fn list_resource_ids_by_tags(&self, filter: &ResourceFilter) -> BackendResult<Vec<String>> {
+        if filter.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut params = Vec::new();
+        let query = list_resource_ids_by_filter_query(filter, &mut params);
+        self.list_resource_ids_by_query(&query, &params)
+    }
+
     pub fn list_resource_ids_by_tags_space_id(
         &self,
-        tags: &Vec<ResourceTagFilter>,
+        filter: &ResourceFilter,
         space_id: &str,
     ) -> BackendResult<Vec<String>> {
-        if tags.is_empty() {
+        if filter.is_empty() {
             return self.list_resource_ids_by_space_id(space_id);
         }
-        let mut result = Vec::new();
-        let (mut query, mut params) = list_resource_ids_by_tags_query(tags, 0);
-
-        query = format!(
-            "{} INTERSECT SELECT resource_id FROM space_entries WHERE space_id = ?",
-            query
+        let mut params = vec![space_id.to_string()];
+        let query = format!(
+            "{} AND R.id IN (SELECT resource_id FROM space_entries WHERE space_id = ?1)",
+            list_resource_ids_by_filter_query(filter, &mut params)
         );
-        params.push(space_id.to_string());
-
-        let mut stmt = self.conn.prepare(&query)?;
-        let resource_ids =
-            stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| row.get(0))?;
-        for resource_id in resource_ids {
-            result.push(resource_id?);
-        }
-        Ok(result)
+        self.list_resource_ids_by_query(&query, &params)
     }
 
     pub fn list_resource_ids_by_tags_no_space(
         &self,
-        tags: &Vec<ResourceTagFilter>,
+        filter: &ResourceFilter,
     ) -> BackendResult<Vec<String>> {
-        if tags.is_empty() {
+        if filter.is_empty() {
             return Ok(Vec::new());
         }
-        let mut result = Vec::new();
-        let (query, params) = list_resource_ids_by_tags_query(tags, 0);
-
-        let final_query = format!(
-        "SELECT rt.resource_id FROM ({}) rt 
-         JOIN resources r ON rt.resource_id = r.id 
-         WHERE rt.resource_id NOT IN (SELECT resource_id FROM space_entries WHERE manually_added = 1)
-         ORDER BY r.created_at DESC",
-        query
-    );
-
-        let mut stmt = self.conn.prepare(&final_query)?;
-        let resource_ids =
-            stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| row.get(0))?;
-        for resource_id in resource_ids {
-            result.push(resource_id?);
-        }
-        Ok(result)
+        let mut params = Vec::new();
+        let query = format!(
+            "{} AND R.id NOT IN (SELECT resource_id FROM space_entries WHERE manually_added = 1)
+            ORDER BY R.created_at DESC",
+            list_resource_ids_by_filter_query(filter, &mut params)
+        );
+        self.list_resource_ids_by_query(&query, &params)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
+
+    fn tag(tag_name: &str, tag_value: &str, op: ResourceTagFilterOp) -> ResourceFilter {
+        ResourceFilter::Tag(ResourceTagFilter {
+            tag_name: tag_name.to_string(),
+            tag_value: tag_value.to_string(),
+            op,
+        })
+    }
 
     #[test]
-    fn test_list_resource_ids_by_tags_query() {
-        let tags = vec![ResourceTagFilter {
-            tag_name: "tag1".to_string(),
-            tag_value: "value1".to_string(),
-            op: ResourceTagFilterOp::Eq,
-        }];
-        let (query, params) = list_resource_ids_by_tags_query(&tags, 0);
+    fn test_resource_filter_condition() {
+        let filter = ResourceFilter::from_json(
+            r#"[{"tag_name": "tag1", "tag_value": "value1"}, {"tag_name": "tag2", "tag_value": "", "op": "notexists"}]"#,
+        )
+        .unwrap();
+        let mut params = vec!["space".to_string()];
         assert_eq!(
-            query,
-            "SELECT resource_id FROM resource_tags WHERE (tag_name = ?1 AND tag_value = ?2)"
+            resource_filter_condition(&filter, &mut params),
+            "(EXISTS (SELECT 1 FROM resource_tags WHERE resource_id = R.id AND tag_name = ?2 AND tag_value = ?3) AND NOT EXISTS (SELECT 1 FROM resource_tags WHERE resource_id = R.id AND tag_name = ?4))"
         );
-        assert_eq!(params, vec!["tag1", "value1"]);
-
-        let tags = vec![
-            ResourceTagFilter {
-                tag_name: "tag1".to_string(),
-                tag_value: "value1".to_string(),
-                op: ResourceTagFilterOp::Eq,
-            },
-            ResourceTagFilter {
-                tag_name: "tag2".to_string(),
-                tag_value: "value2".to_string(),
-                op: ResourceTagFilterOp::Ne,
-            },
-            ResourceTagFilter {
-                tag_name: "tag3".to_string(),
-                tag_value: "value".to_string(),
-                op: ResourceTagFilterOp::Prefix,
-            },
-            ResourceTagFilter {
-                tag_name: "tag4".to_string(),
-                tag_value: "".to_string(),
-                op: ResourceTagFilterOp::NotExists,
-            },
-            ResourceTagFilter {
-                tag_name: "tag5".to_string(),
-                tag_value: "value".to_string(),
-                op: ResourceTagFilterOp::Suffix,
-            },
-        ];
-        let (query, params) = list_resource_ids_by_tags_query(&tags, 0);
+        assert_eq!(params, vec!["space", "tag1", "value1", "tag2"]);
+
+        let filter = ResourceFilter::from_json(
+            r#"{"type": "or", "filters": [
+                {"type": "tag", "tag_name": "tag1", "tag_value": "value", "op": "prefix"},
+                {"type": "not", "filter": {"type": "resource_type", "resource_type": "image/", "prefix": true}}
+            ]}"#,
+        )
+        .unwrap();
+        let mut params = vec![];
         assert_eq!(
-            query,
-            "SELECT resource_id FROM resource_tags WHERE (tag_name = ?1 AND tag_value = ?2) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?3 AND tag_value != ?4) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?5 AND tag_value LIKE ?6) INTERSECT SELECT resource_id FROM resource_tags WHERE (resource_id NOT IN (SELECT resource_id FROM resource_tags WHERE tag_name = ?7 AND tag_name = ?8)) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?9 AND tag_value LIKE ?10)"
+            resource_filter_condition(&filter, &mut params),
+            "(EXISTS (SELECT 1 FROM resource_tags WHERE resource_id = R.id AND tag_name = ?1 AND tag_value LIKE ?2) OR NOT substr(R.resource_type, 1, length(?3)) = ?3)"
         );
+        assert_eq!(params, vec!["tag1", "value%", "image/"]);
+    }
+
+    #[test]
+    fn test_list_resource_ids_by_filter() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for (i, (id, resource_type, tags)) in [
+            ("a", "image/png", vec![("color", "red")]),
+            (
+                "b",
+                "image/jpeg",
+                vec![("color", "blue"), ("starred", "true")],
+            ),
+            (
+                "c",
+                "application/pdf",
+                vec![("color", "red"), ("starred", "true")],
+            ),
+        ]
+        .iter()
+        .enumerate()
+        {
+            Database::create_resource_tx(
+                &mut tx,
+                &Resource {
+                    id: id.to_string(),
+                    resource_path: id.to_string(),
+                    resource_type: resource_type.to_string(),
+                    created_at: now + chrono::Duration::days(i as i64),
+                    updated_at: now,
+                    deleted: 0,
+                },
+            )
+            .unwrap();
+            for (tag_name, tag_value) in tags {
+                let tag = ResourceTag {
+                    id: random_uuid(),
+                    resource_id: id.to_string(),
+                    tag_name: tag_name.to_string(),
+                    tag_value: tag_value.to_string(),
+                };
+                Database::create_resource_tag_tx(&mut tx, &tag).unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let list = |filter: ResourceFilter| {
+            let mut ids = db.list_resource_ids_by_tags(&filter).unwrap();
+            ids.sort();
+            ids
+        };
         assert_eq!(
-            params,
-            vec![
-                "tag1", "value1", "tag2", "value2", "tag3", "value%", "tag4", "tag4", "tag5",
-                "%value"
-            ]
+            list(ResourceFilter::Or {
+                filters: vec![
+                    tag("color", "blue", ResourceTagFilterOp::Eq),
+                    ResourceFilter::And {
+                        filters: vec![
+                            tag("color", "red", ResourceTagFilterOp::Eq),
+                            tag("starred", "", ResourceTagFilterOp::NotExists),
+                        ],
+                    },
+                ],
+            }),
+            vec!["a", "b"]
         );
-
-        let (query, params) = list_resource_ids_by_tags_query(&tags, 2);
         assert_eq!(
-            query,
-            "SELECT resource_id FROM resource_tags WHERE (tag_name = ?3 AND tag_value = ?4) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?5 AND tag_value != ?6) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?7 AND tag_value LIKE ?8) INTERSECT SELECT resource_id FROM resource_tags WHERE (resource_id NOT IN (SELECT resource_id FROM resource_tags WHERE tag_name = ?9 AND tag_name = ?10)) INTERSECT SELECT resource_id FROM resource_tags WHERE (tag_name = ?11 AND tag_value LIKE ?12)"
+            list(ResourceFilter::And {
+                filters: vec![
+                    ResourceFilter::ResourceType {
+                        resource_type: "image/".to_string(),
+                        prefix: true,
+                    },
+                    ResourceFilter::CreatedAt {
+                        after: Some(now + chrono::Duration::hours(12)),
+                        before: None,
+                    },
+                ],
+            }),
+            vec!["b"]
         );
         assert_eq!(
-            params,
-            vec![
-                "tag1", "value1", "tag2", "value2", "tag3", "value%", "tag4", "tag4", "tag5",
-                "%value"
-            ]
+            list(ResourceFilter::Not {
+                filter: Box::new(tag("starred", "true", ResourceTagFilterOp::Eq)),
+            }),
+            vec!["a"]
         );
+        assert!(list(ResourceFilter::Or { filters: vec![] }).is_empty());
     }
 }
@@ -17,6 +17,11 @@ impl Database {
                 resource_text_content.metadata,
             ],
         )?;
+        Self::index_text_content_words(
+            &self.conn,
+            "rowid = ?1",
+            [self.conn.last_insert_rowid()],
+        )?;
         Ok(())
     }
 
@@ -116,6 +121,7 @@ impl Database {
                 resource_text_content.metadata,
             ],
         )?;
+        Self::index_text_content_words(tx, "rowid = ?1", [tx.last_insert_rowid()])?;
         Ok(())
     }
 
@@ -123,6 +129,7 @@ impl Database {
         tx: &mut rusqlite::Transaction,
         resource_text_content: &ResourceTextContent,
     ) -> BackendResult<()> {
+        Self::unindex_text_content_words(tx, "id = ?1", [&resource_text_content.id])?;
         tx.execute(
             "UPDATE resource_text_content SET resource_id = ?2, content = ?3 WHERE id = ?1",
             rusqlite::params![
@@ -131,6 +138,7 @@ impl Database {
                 resource_text_content.content
             ],
         )?;
+        Self::index_text_content_words(tx, "id = ?1", [&resource_text_content.id])?;
         Ok(())
     }
 
@@ -138,6 +146,7 @@ impl Database {
         tx: &mut rusqlite::Transaction,
         id: &str,
     ) -> BackendResult<()> {
+        Self::unindex_text_content_words(tx, "resource_id = ?1", [id])?;
         tx.execute(
             "DELETE FROM resource_text_content WHERE resource_id = ?1",
             rusqlite::params![id],
@@ -152,6 +161,11 @@ impl Database {
         contents: &[String],
         metadatas: &[ResourceTextContentMetadata],
     ) -> BackendResult<Vec<i64>> {
+        Self::unindex_text_content_words(
+            tx,
+            "resource_id = ?1 AND content_type = ?2",
+            rusqlite::params![resource_id, content_type],
+        )?;
         tx.execute(
             "DELETE FROM resource_text_content WHERE resource_id = ?1 AND content_type = ?2",
             rusqlite::params![resource_id, content_type],
@@ -162,7 +176,9 @@ impl Database {
                 "INSERT INTO resource_text_content (id, resource_id, content, content_type, metadata) VALUES (?1, ?2, ?3, ?4, ?5)",
                 rusqlite::params![random_uuid(), resource_id, content, content_type, metadata],
             )?;
-            rowids.push(tx.last_insert_rowid());
+            let rowid = tx.last_insert_rowid();
+            Self::index_text_content_words(tx, "rowid = ?1", [rowid])?;
+            rowids.push(rowid);
         }
         Ok(rowids)
     }
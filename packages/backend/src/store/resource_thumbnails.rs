@@ -0,0 +1,113 @@
+use crate::{
+    store::{db::Database, models::ResourceThumbnail},
+    BackendResult,
+};
+
+use rusqlite::OptionalExtension;
+
+impl Database {
+    pub fn upsert_resource_thumbnail_tx(
+        tx: &mut rusqlite::Transaction,
+        thumbnail: &ResourceThumbnail,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO resource_thumbnails (resource_id, path, content_hash, width, height, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                thumbnail.resource_id,
+                thumbnail.path,
+                thumbnail.content_hash,
+                thumbnail.width,
+                thumbnail.height,
+                thumbnail.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_resource_thumbnail(
+        &self,
+        resource_id: &str,
+    ) -> BackendResult<Option<ResourceThumbnail>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT resource_id, path, content_hash, width, height, created_at FROM resource_thumbnails WHERE resource_id = ?1",
+        )?;
+        let thumbnail = stmt
+            .query_row([resource_id], |row| {
+                Ok(ResourceThumbnail {
+                    resource_id: row.get(0)?,
+                    path: row.get(1)?,
+                    content_hash: row.get(2)?,
+                    width: row.get(3)?,
+                    height: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })
+            .optional()?;
+        Ok(thumbnail)
+    }
+
+    pub fn delete_resource_thumbnail_tx(
+        tx: &mut rusqlite::Transaction,
+        resource_id: &str,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "DELETE FROM resource_thumbnails WHERE resource_id = ?1",
+            [resource_id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{current_time, Resource};
+    use tempfile::tempdir;
+
+    fn setup_test_db() -> Database {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        Database::new(&db_path.to_string_lossy(), true).unwrap()
+    }
+
+    fn thumbnail(content_hash: &str) -> ResourceThumbnail {
+        ResourceThumbnail {
+            resource_id: "resource1".to_string(),
+            path: format!("/thumbnails/resource1-{content_hash}.jpg"),
+            content_hash: content_hash.to_string(),
+            width: 320,
+            height: 240,
+            created_at: current_time(),
+        }
+    }
+
+    #[test]
+    fn test_resource_thumbnail_is_replaced() {
+        let mut db = setup_test_db();
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: "resource1".to_string(),
+            resource_path: "resource1".to_string(),
+            resource_type: "image/png".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted: 0,
+        })
+        .unwrap();
+
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_thumbnail_tx(&mut tx, &thumbnail("aaaa")).unwrap();
+        Database::upsert_resource_thumbnail_tx(&mut tx, &thumbnail("bbbb")).unwrap();
+        tx.commit().unwrap();
+
+        let stored = db.get_resource_thumbnail("resource1").unwrap().unwrap();
+        assert_eq!(stored.content_hash, "bbbb");
+        assert_eq!(stored.path, "/thumbnails/resource1-bbbb.jpg");
+        assert_eq!((stored.width, stored.height), (320, 240));
+
+        let mut tx = db.begin().unwrap();
+        Database::delete_resource_thumbnail_tx(&mut tx, "resource1").unwrap();
+        tx.commit().unwrap();
+        assert!(db.get_resource_thumbnail("resource1").unwrap().is_none());
+    }
+}
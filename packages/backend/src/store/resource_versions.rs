@@ -0,0 +1,146 @@
+use crate::{
+    store::{db::Database, models::ResourceVersion},
+    BackendResult,
+};
+
+use rusqlite::OptionalExtension;
+
+const RESOURCE_VERSION_COLUMNS: &str =
+    "id, resource_id, content_hash, snapshot_path, size, created_at";
+
+fn resource_version_from_row(row: &rusqlite::Row) -> rusqlite::Result<ResourceVersion> {
+    Ok(ResourceVersion {
+        id: row.get(0)?,
+        resource_id: row.get(1)?,
+        content_hash: row.get(2)?,
+        snapshot_path: row.get(3)?,
+        size: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+impl Database {
+    pub fn create_resource_version_tx(
+        tx: &mut rusqlite::Transaction,
+        version: &ResourceVersion,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT INTO resource_versions (id, resource_id, content_hash, snapshot_path, size, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                version.id,
+                version.resource_id,
+                version.content_hash,
+                version.snapshot_path,
+                version.size,
+                version.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_resource_version_tx(
+        tx: &mut rusqlite::Transaction,
+        id: &str,
+    ) -> BackendResult<()> {
+        tx.execute("DELETE FROM resource_versions WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_resource_version(&self, id: &str) -> BackendResult<Option<ResourceVersion>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_VERSION_COLUMNS} FROM resource_versions WHERE id = ?1"
+        ))?;
+        Ok(stmt.query_row([id], resource_version_from_row).optional()?)
+    }
+
+    // newest first
+    pub fn list_resource_versions(&self, resource_id: &str) -> BackendResult<Vec<ResourceVersion>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RESOURCE_VERSION_COLUMNS} FROM resource_versions
+            WHERE resource_id = ?1 ORDER BY created_at DESC, rowid DESC"
+        ))?;
+        let versions = stmt.query_map([resource_id], resource_version_from_row)?;
+        Ok(versions.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn get_latest_resource_version(
+        &self,
+        resource_id: &str,
+    ) -> BackendResult<Option<ResourceVersion>> {
+        Ok(self.list_resource_versions(resource_id)?.into_iter().next())
+    }
+
+    // the versions past the newest `keep`, their snapshots are removed by the caller
+    pub fn list_expired_resource_versions(
+        &self,
+        resource_id: &str,
+        keep: usize,
+    ) -> BackendResult<Vec<ResourceVersion>> {
+        Ok(self
+            .list_resource_versions(resource_id)?
+            .into_iter()
+            .skip(keep)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{current_time, Resource};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resource_versions() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: "note".to_string(),
+            resource_path: "note.html".to_string(),
+            resource_type: "application/vnd.space.document.space-note".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted: 0,
+        })
+        .unwrap();
+
+        let mut tx = db.begin().unwrap();
+        for (i, hash) in ["a", "b", "c"].iter().enumerate() {
+            let version = ResourceVersion {
+                id: format!("v{i}"),
+                resource_id: "note".to_string(),
+                content_hash: hash.to_string(),
+                snapshot_path: format!("versions/note/v{i}"),
+                size: i as i64,
+                created_at: now + chrono::Duration::seconds(i as i64),
+            };
+            Database::create_resource_version_tx(&mut tx, &version).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let ids =
+            |versions: Vec<ResourceVersion>| versions.into_iter().map(|v| v.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(db.list_resource_versions("note").unwrap()),
+            vec!["v2", "v1", "v0"]
+        );
+        assert_eq!(
+            db.get_latest_resource_version("note")
+                .unwrap()
+                .map(|v| v.content_hash),
+            Some("c".to_string())
+        );
+        assert_eq!(
+            ids(db.list_expired_resource_versions("note", 2).unwrap()),
+            vec!["v0"]
+        );
+
+        let mut tx = db.begin().unwrap();
+        Database::remove_resources_tx(&mut tx, &["note".to_string()]).unwrap();
+        tx.commit().unwrap();
+        assert!(db.list_resource_versions("note").unwrap().is_empty());
+        assert!(db.get_resource_version("v0").unwrap().is_none());
+    }
+}
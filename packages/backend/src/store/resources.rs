@@ -1,5 +1,11 @@
 use super::models::*;
-use crate::{store::db::Database, BackendResult};
+use crate::{
+    store::{
+        db::Database,
+        pagination::{keyset_condition, order_clause, page_from_rows, PageCursor},
+    },
+    BackendResult,
+};
 use rusqlite::OptionalExtension;
 
 impl Database {
@@ -88,10 +94,12 @@ impl Database {
             .optional()?)
     }
 
+    // removes the resources with everything stored for them and returns the thumbnail and
+    // snapshot files left behind, which are removed once the transaction is committed
     pub fn remove_resources_tx(
         tx: &mut rusqlite::Transaction,
         ids: &[String],
-    ) -> BackendResult<()> {
+    ) -> BackendResult<Vec<String>> {
         let placeholders = std::iter::repeat_n("?", ids.len())
             .collect::<Vec<_>>()
             .join(",");
@@ -99,35 +107,80 @@ impl Database {
         let id_params: Vec<&dyn rusqlite::ToSql> =
             ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
 
-        tx.execute(
-            &format!("DELETE FROM resources WHERE id IN ({})", placeholders),
-            &id_params[..],
-        )?;
+        let in_ids = format!("resource_id IN ({})", placeholders);
+        let files = Self::list_resource_files_tx(tx, &in_ids, &id_params[..])?;
+        Self::remove_resource_rows_tx(tx, &in_ids, &id_params[..])?;
         tx.execute(
             &format!(
-                "DELETE FROM resource_metadata WHERE resource_id IN ({})",
+                "DELETE FROM resource_links WHERE source_id IN ({0}) OR target_id IN ({0})",
                 placeholders
             ),
-            &id_params[..],
+            &[&id_params[..], &id_params[..]].concat()[..],
         )?;
         tx.execute(
-            &format!(
-                "DELETE FROM resource_text_content WHERE resource_id IN ({})",
-                placeholders
-            ),
+            &format!("DELETE FROM resources WHERE id IN ({})", placeholders),
             &id_params[..],
         )?;
 
-        Ok(())
+        Ok(files)
     }
 
-    pub fn remove_deleted_resources_tx(tx: &mut rusqlite::Transaction) -> BackendResult<()> {
-        tx.execute("DELETE FROM resource_metadata WHERE resource_id IN (SELECT id FROM resources WHERE deleted=1)", ())?;
-        tx.execute("DELETE FROM resource_text_content WHERE resource_id IN (SELECT id FROM resources WHERE deleted=1)", ())?;
+    pub fn remove_deleted_resources_tx(
+        tx: &mut rusqlite::Transaction,
+    ) -> BackendResult<Vec<String>> {
+        let deleted = "resource_id IN (SELECT id FROM resources WHERE deleted=1)";
+        let files = Self::list_resource_files_tx(tx, deleted, ())?;
+        Self::remove_resource_rows_tx(tx, deleted, ())?;
+        tx.execute("DELETE FROM resource_links WHERE source_id IN (SELECT id FROM resources WHERE deleted=1) OR target_id IN (SELECT id FROM resources WHERE deleted=1)", ())?;
         tx.execute("DELETE FROM resources WHERE deleted=1", ())?;
+        Ok(files)
+    }
+
+    // the derived rows are deleted explicitly instead of relying on `ON DELETE CASCADE`,
+    // which only runs when the connection enforces foreign keys
+    fn remove_resource_rows_tx<P: rusqlite::Params + Clone>(
+        tx: &rusqlite::Transaction,
+        condition: &str,
+        params: P,
+    ) -> BackendResult<()> {
+        Self::unindex_metadata_words(tx, condition, params.clone())?;
+        Self::unindex_text_content_words(tx, condition, params.clone())?;
+        for table in [
+            "resource_metadata",
+            "resource_metadata_outlines",
+            "resource_text_content",
+            "resource_perceptual_hashes",
+            "resource_thumbnails",
+            "resource_versions",
+            "watched_files",
+        ] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE {}", table, condition),
+                params.clone(),
+            )?;
+        }
         Ok(())
     }
 
+    fn list_resource_files_tx<P: rusqlite::Params + Clone>(
+        tx: &rusqlite::Transaction,
+        condition: &str,
+        params: P,
+    ) -> BackendResult<Vec<String>> {
+        let mut files = Vec::new();
+        for query in [
+            "SELECT path FROM resource_thumbnails",
+            "SELECT snapshot_path FROM resource_versions",
+        ] {
+            let mut stmt = tx.prepare(&format!("{} WHERE {}", query, condition))?;
+            let paths = stmt.query_map(params.clone(), |row| row.get(0))?;
+            for path in paths {
+                files.push(path?);
+            }
+        }
+        Ok(files)
+    }
+
     pub fn list_all_resources(&self, deleted: i32) -> BackendResult<Vec<Resource>> {
         let mut stmt = self.conn.prepare("SELECT id, resource_path, resource_type, created_at, updated_at, deleted FROM resources WHERE deleted = ?1 ORDER BY updated_at DESC")?;
         let resources = stmt.query_map(rusqlite::params![deleted], |row| {
@@ -159,38 +212,49 @@ impl Database {
         Ok(result)
     }
 
+    // most recently updated first
     pub fn list_resources(
         &self,
         deleted: i32,
-        limit: i64,
-        offset: i64,
-    ) -> BackendResult<PaginatedResources> {
-        let mut stmt = self.conn.prepare("SELECT id, resource_path, resource_type, created_at, updated_at, deleted FROM resources WHERE deleted = ?1 ORDER BY updated_at DESC LIMIT ?2 OFFSET ?3")?;
-        let resources = stmt.query_map(rusqlite::params![deleted, limit, offset], |row| {
-            Ok(Resource {
+        page: &PageRequest,
+    ) -> BackendResult<Page<Resource>> {
+        let cursor = page.page_cursor()?;
+        let condition = match cursor {
+            Some(_) => format!("AND {}", keyset_condition("updated_at", "id", true, 2)),
+            None => String::new(),
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, resource_path, resource_type, created_at, updated_at, deleted
+            FROM resources
+            WHERE deleted = ?1 {} {} {}",
+            condition,
+            order_clause("updated_at", "id", true),
+            page.limit_clause()
+        ))?;
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&deleted];
+        if let Some(cursor) = &cursor {
+            params.push(&cursor.sort_value);
+            params.push(&cursor.id);
+        }
+        let resources = stmt.query_map(&params[..], |row| {
+            let resource = Resource {
                 id: row.get(0)?,
                 resource_path: row.get(1)?,
                 resource_type: row.get(2)?,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
                 deleted: row.get(5)?,
-            })
+            };
+            let cursor = PageCursor {
+                sort_value: row.get(4)?,
+                id: resource.id.clone(),
+            };
+            Ok((resource, cursor))
         })?;
-        let mut result = Vec::new();
-        for resource in resources {
-            result.push(resource?);
-        }
-        let total = self.conn.query_row(
-            "SELECT COUNT(*) FROM resources WHERE deleted = ?1",
-            rusqlite::params![deleted],
-            |row| row.get(0),
-        )?;
-        Ok(PaginatedResources {
-            resources: result,
-            total,
-            limit,
-            offset,
-        })
+        Ok(page_from_rows(
+            resources.collect::<Result<Vec<_>, _>>()?,
+            page,
+        ))
     }
 
     pub fn list_resources_by_ids(
@@ -199,9 +263,10 @@ impl Database {
     ) -> BackendResult<Vec<CompositeResource>> {
         let placeholders = vec!["?"; resource_ids.len()].join(",");
         let query = format!(
-            "SELECT DISTINCT M.*, R.*, C.*, P.*
+            "SELECT DISTINCT M.*, O.outline, R.*, C.*, P.*
             FROM resources R
             LEFT JOIN resource_metadata M ON M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
             LEFT JOIN resource_text_content C ON M.resource_id = C.resource_id
             LEFT JOIN resource_content_hashes H ON R.id = H.resource_id
             LEFT JOIN post_processing_jobs P ON H.content_hash = P.content_hash
@@ -221,19 +286,19 @@ impl Database {
         let mut results = vec![];
         let results_iter =
             stmt.query_map(rusqlite::params_from_iter(resource_ids.iter()), |row| {
-                let text_content_id: Option<String> = row.get(12)?;
+                let text_content_id: Option<String> = row.get(16)?;
                 let text_content = match text_content_id {
                     Some(id) => Some(ResourceTextContent {
                         id,
-                        resource_id: row.get(13)?,
-                        content: row.get(14)?,
-                        content_type: row.get(15)?,
-                        metadata: row.get(16)?,
+                        resource_id: row.get(17)?,
+                        content: row.get(18)?,
+                        content_type: row.get(19)?,
+                        metadata: row.get(20)?,
                     }),
                     None => None,
                 };
 
-                let job_id: Option<String> = row.get(17)?;
+                let job_id: Option<String> = row.get(21)?;
 
                 Ok(CompositeResource {
                     metadata: Some(ResourceMetadata {
@@ -243,27 +308,38 @@ impl Database {
                         source_uri: row.get(3)?,
                         alt: row.get(4)?,
                         user_context: row.get(5)?,
+                        byline: row.get(6)?,
+                        published_at: row.get(7)?,
+                        lead_image: row.get(8)?,
+                        outline: row.get(9)?,
                     }),
                     resource: Resource {
-                        id: row.get(6)?,
-                        resource_path: row.get(7)?,
-                        resource_type: row.get(8)?,
-                        created_at: row.get(9)?,
-                        updated_at: row.get(10)?,
-                        deleted: row.get(11)?,
+                        id: row.get(10)?,
+                        resource_path: row.get(11)?,
+                        resource_type: row.get(12)?,
+                        created_at: row.get(13)?,
+                        updated_at: row.get(14)?,
+                        deleted: row.get(15)?,
                     },
                     text_content,
                     resource_tags: None,
                     resource_annotations: None,
                     space_ids: None,
+                    thumbnail: None,
                     post_processing_job: if let Some(job_id) = job_id {
                         Some(PostProcessingJob {
                             id: job_id,
-                            created_at: row.get(18)?,
-                            updated_at: row.get(19)?,
-                            resource_id: row.get(20)?,
-                            content_hash: row.get(21)?,
-                            state: row.get(22)?,
+                            created_at: row.get(22)?,
+                            updated_at: row.get(23)?,
+                            resource_id: row.get(24)?,
+                            content_hash: row.get(25)?,
+                            state: row.get(26)?,
+                            attempts: row.get(27)?,
+                            max_attempts: row.get(28)?,
+                            next_run_at: row.get(29)?,
+                            last_error: row.get(30)?,
+                            priority: row.get(31)?,
+                            batch_id: row.get(32)?,
                         })
                     } else {
                         None
@@ -277,3 +353,123 @@ impl Database {
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_resource_with_derived_rows(db: &mut Database, id: &str, deleted: i32) {
+        let now = current_time();
+        db.create_resource(&Resource {
+            id: id.to_string(),
+            resource_path: format!("{id}.pdf"),
+            resource_type: "application/pdf".to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted,
+        })
+        .unwrap();
+
+        let mut tx = db.begin().unwrap();
+        Database::upsert_resource_perceptual_hash_tx(&mut tx, id, 42).unwrap();
+        Database::upsert_resource_thumbnail_tx(
+            &mut tx,
+            &ResourceThumbnail {
+                resource_id: id.to_string(),
+                path: format!("thumbnails/{id}.jpg"),
+                content_hash: "hash".to_string(),
+                width: 10,
+                height: 10,
+                created_at: now,
+            },
+        )
+        .unwrap();
+        Database::upsert_watched_file_tx(
+            &mut tx,
+            &WatchedFile {
+                folder_id: "folder".to_string(),
+                path: format!("{id}.pdf"),
+                resource_id: id.to_string(),
+            },
+        )
+        .unwrap();
+        Database::create_resource_version_tx(
+            &mut tx,
+            &ResourceVersion {
+                id: format!("{id}-v1"),
+                resource_id: id.to_string(),
+                content_hash: "hash".to_string(),
+                snapshot_path: format!("versions/{id}/v1"),
+                size: 1,
+                created_at: now,
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+    }
+
+    fn count_rows(db: &Database, id: &str) -> i64 {
+        [
+            "resource_metadata",
+            "resource_text_content",
+            "resource_perceptual_hashes",
+            "resource_thumbnails",
+            "resource_versions",
+            "watched_files",
+        ]
+        .iter()
+        .map(|table| -> i64 {
+            db.conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM {} WHERE resource_id = ?1", table),
+                    [id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        })
+        .sum()
+    }
+
+    #[test]
+    fn test_removing_resources_leaves_nothing_behind() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        db.create_space(&Space {
+            id: "space".to_string(),
+            name: "Folder".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .unwrap();
+        db.create_watched_folder(&WatchedFolder {
+            id: "folder".to_string(),
+            path: "/folder".to_string(),
+            space_id: "space".to_string(),
+            created_at: now,
+        })
+        .unwrap();
+        create_resource_with_derived_rows(&mut db, "removed", 0);
+        create_resource_with_derived_rows(&mut db, "trashed", 1);
+        create_resource_with_derived_rows(&mut db, "kept", 0);
+        // nothing may be left to `ON DELETE CASCADE`
+        db.conn.execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+
+        let mut tx = db.begin().unwrap();
+        let files = Database::remove_resources_tx(&mut tx, &["removed".to_string()]).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(files, vec!["thumbnails/removed.jpg", "versions/removed/v1"]);
+        assert_eq!(count_rows(&db, "removed"), 0);
+
+        let mut tx = db.begin().unwrap();
+        let files = Database::remove_deleted_resources_tx(&mut tx).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(files, vec!["thumbnails/trashed.jpg", "versions/trashed/v1"]);
+        assert_eq!(count_rows(&db, "trashed"), 0);
+
+        assert_eq!(count_rows(&db, "kept"), 4);
+        assert!(db.get_resource("kept").unwrap().is_some());
+    }
+}
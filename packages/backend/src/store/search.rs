@@ -1,6 +1,10 @@
 use super::models::*;
 use crate::{
-    store::{db::Database, resource_tags::list_resource_ids_by_tags_query},
+    store::{
+        db::Database,
+        pagination::{keyset_condition, order_clause, page_from_rows, PageCursor},
+        resource_tags::{list_resource_ids_by_filter_query, resource_filter_condition},
+    },
     BackendResult,
 };
 
@@ -15,6 +19,158 @@ fn escape_fts_query(keyword: &str) -> String {
         .join(" ")
 }
 
+// the trigram index matches any part of a word and text without spaces between words, the
+// word index matches word forms and words shorter than a trigram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchIndex {
+    Trigram,
+    Words,
+}
+
+impl SearchIndex {
+    fn metadata_matches(&self, limit_clause: &str) -> String {
+        match self {
+            SearchIndex::Trigram => format!(
+                "SELECT *
+                FROM resource_metadata
+                WHERE resource_metadata MATCH ?1
+                ORDER BY rank {}",
+                limit_clause
+            ),
+            SearchIndex::Words => format!(
+                "SELECT M.*
+                FROM resource_metadata_words W
+                INNER JOIN resource_metadata M ON M.rowid = W.rowid
+                WHERE resource_metadata_words MATCH ?1
+                ORDER BY W.rank {}",
+                limit_clause
+            ),
+        }
+    }
+
+    fn text_content_matches(&self, limit_clause: &str) -> String {
+        match self {
+            SearchIndex::Trigram => format!(
+                "SELECT id, resource_id, content_type, metadata, rank,
+                    highlight(resource_text_content, 2, char({}), char({})) AS highlighted
+                FROM resource_text_content
+                WHERE resource_text_content MATCH ?1
+                ORDER BY rank {}",
+                HIGHLIGHT_START as u32, HIGHLIGHT_END as u32, limit_clause
+            ),
+            SearchIndex::Words => format!(
+                "SELECT C.id, C.resource_id, C.content_type, C.metadata, W.rank AS rank,
+                    highlight(resource_text_content_words, 0, char({}), char({})) AS highlighted
+                FROM resource_text_content_words W
+                INNER JOIN resource_text_content C ON C.rowid = W.rowid
+                WHERE resource_text_content_words MATCH ?1
+                ORDER BY W.rank {}",
+                HIGHLIGHT_START as u32, HIGHLIGHT_END as u32, limit_clause
+            ),
+        }
+    }
+}
+
+// scripts that are written without spaces between words, the word index can't split them
+fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // hiragana and katakana
+        | '\u{3400}'..='\u{4DBF}' // cjk extension a
+        | '\u{4E00}'..='\u{9FFF}' // cjk unified ideographs
+        | '\u{0E00}'..='\u{0EFF}' // thai and lao
+        | '\u{1000}'..='\u{109F}' // myanmar
+        | '\u{1780}'..='\u{17FF}' // khmer
+    )
+}
+
+// the indexes to search for the words of a query, in the order their results are used
+pub fn search_indexes_for_terms<'a>(terms: impl IntoIterator<Item = &'a str>) -> Vec<SearchIndex> {
+    let words: Vec<&str> = terms
+        .into_iter()
+        .flat_map(|term| term.split_whitespace())
+        .collect();
+    if words.iter().any(|word| word.chars().count() < 3) {
+        return vec![SearchIndex::Words];
+    }
+    if words
+        .iter()
+        .any(|word| word.chars().any(is_unspaced_script))
+    {
+        return vec![SearchIndex::Trigram];
+    }
+    vec![SearchIndex::Words, SearchIndex::Trigram]
+}
+
+// private use characters around the matches, so they can't be confused with the content
+const HIGHLIGHT_START: char = '\u{E000}';
+const HIGHLIGHT_END: char = '\u{E001}';
+pub const DEFAULT_SNIPPET_LENGTH: usize = 200;
+const MAX_SNIPPETS_PER_RESOURCE: usize = 3;
+
+// cuts the text with highlight markers down to about `length` characters around the first
+// match, at word boundaries, and returns it without the markers with the ranges of the matches
+fn highlighted_excerpt(highlighted: &str, length: usize) -> (String, Vec<(usize, usize)>) {
+    let mut chars: Vec<char> = vec![];
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    let mut range_start = None;
+    for c in highlighted.chars() {
+        match c {
+            HIGHLIGHT_START => range_start = Some(chars.len()),
+            HIGHLIGHT_END => {
+                if let Some(start) = range_start.take() {
+                    ranges.push((start, chars.len()));
+                }
+            }
+            c => chars.push(c),
+        }
+    }
+
+    let (first_start, first_end) = ranges.first().copied().unwrap_or((0, 0));
+    let before = length.saturating_sub(first_end - first_start) / 2;
+    let mut start = first_start.saturating_sub(before);
+    let mut end = chars.len().min(start + length.max(first_end - first_start));
+    start = start.min(end.saturating_sub(length));
+    // no partial words at the edges
+    if start > 0 && !chars[start - 1].is_whitespace() {
+        if let Some(space) = chars[start..first_start]
+            .iter()
+            .position(|c| c.is_whitespace())
+        {
+            start += space + 1;
+        }
+    }
+    if end < chars.len() && !chars[end].is_whitespace() {
+        if let Some(space) = chars[first_end..end]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+        {
+            end = first_end + space;
+        }
+    }
+    while start < first_start && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > first_end && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < chars.len() { "…" } else { "" };
+    let text = format!(
+        "{}{}{}",
+        prefix,
+        chars[start..end].iter().collect::<String>(),
+        suffix
+    );
+    let offset = prefix.chars().count();
+    let highlights = ranges
+        .into_iter()
+        .filter(|(range_start, range_end)| *range_start >= start && *range_end <= end)
+        .map(|(range_start, range_end)| (range_start - start + offset, range_end - start + offset))
+        .collect();
+    (text, highlights)
+}
+
 fn map_resource_and_metadata(
     engine: SearchEngine,
 ) -> impl FnMut(&rusqlite::Row<'_>) -> Result<SearchResultItem, rusqlite::Error> {
@@ -28,14 +184,18 @@ fn map_resource_and_metadata(
                     source_uri: row.get(3)?,
                     alt: row.get(4)?,
                     user_context: row.get(5)?,
+                    byline: row.get(6)?,
+                    published_at: row.get(7)?,
+                    lead_image: row.get(8)?,
+                    outline: row.get(9)?,
                 }),
                 resource: Resource {
-                    id: row.get(6)?,
-                    resource_path: row.get(7)?,
-                    resource_type: row.get(8)?,
-                    created_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                    deleted: row.get(11)?,
+                    id: row.get(10)?,
+                    resource_path: row.get(11)?,
+                    resource_type: row.get(12)?,
+                    created_at: row.get(13)?,
+                    updated_at: row.get(14)?,
+                    deleted: row.get(15)?,
                 },
                 text_content: None,
                 // TODO: should we populate the resource tags?
@@ -43,8 +203,10 @@ fn map_resource_and_metadata(
                 resource_annotations: None,
                 post_processing_job: None,
                 space_ids: None,
+                thumbnail: None,
             },
             engine: engine.clone(),
+            snippets: vec![],
         })
     }
 }
@@ -53,27 +215,23 @@ impl Database {
     pub fn keyword_search_metadata(
         &self,
         keyword: &str,
+        index: SearchIndex,
         filtered_resource_ids: Vec<String>,
         limit: Option<i64>,
     ) -> BackendResult<Vec<SearchResultItem>> {
         let mut results: Vec<SearchResultItem> = Vec::new();
 
         let limit_clause = limit.map_or(String::new(), |l| format!("LIMIT {}", l));
-        let inner_clause = format!(
-            "SELECT *
-              FROM resource_metadata
-              WHERE resource_metadata MATCH ?1
-              ORDER BY rank {}",
-            limit_clause
-        );
+        let inner_clause = index.metadata_matches(&limit_clause);
 
-        let match_phrase = format!("{{name user_context alt}}: {}", keyword);
+        let match_phrase = format!("{{name user_context alt byline}}: ({})", keyword);
         let base_query = format!(
-            "SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, R.* 
+            "SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, M.byline, M.published_at, M.lead_image, O.outline, R.* 
             FROM (
                 {}
             ) M
-            INNER JOIN resources R ON M.resource_id = R.id",
+            INNER JOIN resources R ON M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id",
             inner_clause
         );
 
@@ -84,7 +242,7 @@ impl Database {
             let filtered_query = format!(
                 "
                 {}
-                AND R.id IN ({})
+                WHERE R.id IN ({})
             ",
                 base_query, placeholders
             );
@@ -101,43 +259,46 @@ impl Database {
         Ok(results)
     }
 
+    // one item per resource with the snippets of its best matching text contents
     pub fn keyword_search_content(
         &self,
         keyword: &str,
+        index: SearchIndex,
         filtered_resource_ids: Vec<String>,
         limit: Option<i64>,
+        snippet_length: usize,
     ) -> BackendResult<Vec<SearchResultItem>> {
         let mut results: Vec<SearchResultItem> = Vec::new();
 
         let limit_clause = limit.map_or(String::new(), |l| format!(" LIMIT {}", l));
-        let inner_clause = format!(
-            "SELECT resource_id
-            FROM resource_text_content
-            WHERE resource_text_content MATCH ?1
-            ORDER BY rank {}",
-            limit_clause
-        );
+        let inner_clause = index.text_content_matches(&limit_clause);
 
         let base_query = format!(
             "
-            SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, R.*
+            SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, M.byline, M.published_at, M.lead_image, O.outline, R.*,
+                T.id, T.content_type, T.metadata, T.highlighted
             FROM (
                 {}
             ) T
             INNER JOIN resource_metadata M ON T.resource_id = M.resource_id
             INNER JOIN resources R ON M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
             ",
             inner_clause
         );
 
         let (query, params) = if filtered_resource_ids.is_empty() {
-            (base_query, vec![keyword.to_string()])
+            (
+                format!("{} ORDER BY T.rank", base_query),
+                vec![keyword.to_string()],
+            )
         } else {
             let placeholders = vec!["?"; filtered_resource_ids.len()].join(",");
             let filtered_query = format!(
                 "
                 {}
-                AND R.id IN ({})
+                WHERE R.id IN ({})
+                ORDER BY T.rank
             ",
                 base_query, placeholders
             );
@@ -146,11 +307,41 @@ impl Database {
             (filtered_query, params)
         };
 
-        let row_map_fn = map_resource_and_metadata(SearchEngine::KeywordContent);
+        let mut row_map_fn = map_resource_and_metadata(SearchEngine::KeywordContent);
         let mut stmt = self.conn.prepare(&query)?;
-        let items = stmt.query_map(rusqlite::params_from_iter(params.iter()), row_map_fn)?;
-        for item in items {
-            results.push(item?);
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            let item = row_map_fn(row)?;
+            let metadata: String = row.get(18)?;
+            let metadata: ResourceTextContentMetadata =
+                serde_json::from_str(&metadata).unwrap_or_default();
+            let (text, highlights) =
+                highlighted_excerpt(&row.get::<_, String>(19)?, snippet_length);
+            let snippet = SearchSnippet {
+                content_id: row.get(16)?,
+                content_type: row.get(17)?,
+                text,
+                highlights,
+                page: metadata.page,
+                timestamp: metadata.timestamp,
+                section: metadata.section,
+            };
+            Ok((item, snippet))
+        })?;
+        for row in rows {
+            let (item, snippet) = row?;
+            match results
+                .iter_mut()
+                .find(|result| result.resource.resource.id == item.resource.resource.id)
+            {
+                Some(result) if result.snippets.len() < MAX_SNIPPETS_PER_RESOURCE => {
+                    result.snippets.push(snippet)
+                }
+                Some(_) => {}
+                None => results.push(SearchResultItem {
+                    snippets: vec![snippet],
+                    ..item
+                }),
+            }
         }
 
         Ok(results)
@@ -159,26 +350,92 @@ impl Database {
     // search for resources that match the given tags and only return the resource ids
     pub fn list_resources_by_tags(
         &self,
-        tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
     ) -> BackendResult<SearchResultSimple> {
-        let filtered_resource_ids = self.list_resource_ids_by_tags(&tags)?;
+        let filtered_resource_ids = self.list_resource_ids_by_tags(&filter)?;
 
         if filtered_resource_ids.is_empty() {
             return Ok(SearchResultSimple {
                 items: vec![],
                 total: 0,
+                facets: None,
+                next_cursor: None,
             });
         }
 
         Ok(SearchResultSimple {
             total: filtered_resource_ids.len() as i64,
             items: filtered_resource_ids,
+            facets: None,
+            next_cursor: None,
+        })
+    }
+
+    // newest first, `no_space` leaves out the resources added to a space and `total` counts
+    // the matching resources of all pages
+    pub fn list_resources_by_tags_page(
+        &self,
+        filter: &ResourceFilter,
+        no_space: bool,
+        page: &PageRequest,
+    ) -> BackendResult<SearchResultSimple> {
+        if filter.is_empty() {
+            return Ok(SearchResultSimple {
+                items: vec![],
+                total: 0,
+                facets: None,
+                next_cursor: None,
+            });
+        }
+
+        let mut params: Vec<String> = Vec::new();
+        let mut condition = resource_filter_condition(filter, &mut params);
+        if no_space {
+            condition.push_str(
+                " AND R.id NOT IN (SELECT resource_id FROM space_entries WHERE manually_added = 1)",
+            );
+        }
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM resources R WHERE {}", condition),
+            rusqlite::params_from_iter(params.iter()),
+            |row| row.get(0),
+        )?;
+
+        if let Some(cursor) = page.page_cursor()? {
+            condition.push_str(&format!(
+                " AND {}",
+                keyset_condition("R.created_at", "R.id", true, params.len() + 1)
+            ));
+            params.push(cursor.sort_value);
+            params.push(cursor.id);
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT R.id, R.created_at FROM resources R WHERE {} {} {}",
+            condition,
+            order_clause("R.created_at", "R.id", true),
+            page.limit_clause()
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            let id: String = row.get(0)?;
+            let cursor = PageCursor {
+                sort_value: row.get(1)?,
+                id: id.clone(),
+            };
+            Ok((id, cursor))
+        })?;
+        let page = page_from_rows(rows.collect::<Result<Vec<_>, _>>()?, page);
+
+        Ok(SearchResultSimple {
+            items: page.items,
+            total,
+            facets: None,
+            next_cursor: page.next_cursor,
         })
     }
 
     pub fn list_all_resources_and_spaces(
         &self,
-        resource_tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
     ) -> BackendResult<Vec<ResourceOrSpace>> {
         let mut combined_query = String::from(
             "SELECT id, 'Resource' as item_type, created_at FROM resources WHERE id IN (",
@@ -186,10 +443,8 @@ impl Database {
 
         let mut params: Vec<String> = Vec::new();
 
-        if !resource_tags.is_empty() {
-            let (tag_query, tag_params) = list_resource_ids_by_tags_query(&resource_tags, 0);
-            combined_query.push_str(&tag_query);
-            params.extend(tag_params);
+        if !filter.is_empty() {
+            combined_query.push_str(&list_resource_ids_by_filter_query(&filter, &mut params));
         } else {
             combined_query.push_str("SELECT id FROM resources WHERE deleted = 0");
         }
@@ -229,29 +484,83 @@ impl Database {
     // list all resources that are not in a space by list of tags
     pub fn list_resources_by_tags_no_space(
         &self,
-        tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
     ) -> BackendResult<SearchResultSimple> {
-        let filtered_resource_ids = self.list_resource_ids_by_tags_no_space(&tags)?;
+        let filtered_resource_ids = self.list_resource_ids_by_tags_no_space(&filter)?;
 
         if filtered_resource_ids.is_empty() {
             return Ok(SearchResultSimple {
                 items: vec![],
                 total: 0,
+                facets: None,
+                next_cursor: None,
             });
         }
 
         Ok(SearchResultSimple {
             total: filtered_resource_ids.len() as i64,
             items: filtered_resource_ids,
+            facets: None,
+            next_cursor: None,
         })
     }
 
+    // the most recent of the given resources, for searches that only have filters
+    pub fn list_search_items_by_ids(
+        &self,
+        resource_ids: &[String],
+        limit: Option<i64>,
+    ) -> BackendResult<Vec<SearchResultItem>> {
+        let mut results: Vec<SearchResultItem> = Vec::new();
+        if resource_ids.is_empty() {
+            return Ok(results);
+        }
+        let placeholders = vec!["?"; resource_ids.len()].join(",");
+        let limit_clause = limit.map_or(String::new(), |l| format!("LIMIT {}", l));
+        let query = format!(
+            "SELECT M.id, M.resource_id, M.name, M.source_uri, M.alt, M.user_context, M.byline, M.published_at, M.lead_image, O.outline, R.*
+            FROM resources R
+            INNER JOIN resource_metadata M ON M.resource_id = R.id
+            LEFT JOIN resource_metadata_outlines O ON O.resource_id = M.resource_id
+            WHERE R.id IN ({})
+            ORDER BY R.created_at DESC {}",
+            placeholders, limit_clause
+        );
+        let row_map_fn = map_resource_and_metadata(SearchEngine::KeywordMetadata);
+        let mut stmt = self.conn.prepare(&query)?;
+        let items = stmt.query_map(rusqlite::params_from_iter(resource_ids.iter()), row_map_fn)?;
+        for item in items {
+            results.push(item?);
+        }
+        Ok(results)
+    }
+
     pub fn search_resources(
         &self,
         keyword: &str,
         filtered_resource_ids: &Option<Vec<String>>,
         include_annotations: bool,
         keyword_limit: Option<i64>,
+    ) -> BackendResult<SearchResult> {
+        self.search_resources_by_match(
+            Some(&escape_fts_query(keyword)),
+            &search_indexes_for_terms(keyword.split_whitespace()),
+            filtered_resource_ids,
+            include_annotations,
+            keyword_limit,
+            DEFAULT_SNIPPET_LENGTH,
+        )
+    }
+
+    // `match_expression` is an fts5 query, without one the filtered resources are listed
+    pub fn search_resources_by_match(
+        &self,
+        match_expression: Option<&str>,
+        indexes: &[SearchIndex],
+        filtered_resource_ids: &Option<Vec<String>>,
+        include_annotations: bool,
+        keyword_limit: Option<i64>,
+        snippet_length: usize,
     ) -> BackendResult<SearchResult> {
         // The Some value in filtered_resource_ids indicates that the search MUST have the filter ids
         // so if value is Some and empty, we return an empty result
@@ -263,6 +572,8 @@ impl Database {
                         spaces: vec![],
                         total: 0,
                         space_entries: None,
+                        query_errors: vec![],
+                        facets: None,
                     });
                 }
                 ids
@@ -270,18 +581,36 @@ impl Database {
             None => &vec![],
         };
 
-        let escaped_keyword = escape_fts_query(keyword);
-        let mut results = self.keyword_search_metadata(
-            &escaped_keyword,
-            filtered_resource_ids.clone(),
-            keyword_limit,
-        )?;
-
-        results.extend(self.keyword_search_content(
-            &escaped_keyword,
-            filtered_resource_ids.clone(),
-            keyword_limit,
-        )?);
+        let mut results = match match_expression {
+            Some(match_expression) => {
+                let mut results: Vec<SearchResultItem> = vec![];
+                for index in indexes {
+                    let mut index_results = self.keyword_search_metadata(
+                        match_expression,
+                        *index,
+                        filtered_resource_ids.clone(),
+                        keyword_limit,
+                    )?;
+                    index_results.extend(self.keyword_search_content(
+                        match_expression,
+                        *index,
+                        filtered_resource_ids.clone(),
+                        keyword_limit,
+                        snippet_length,
+                    )?);
+                    // a resource found by an earlier index keeps its place
+                    index_results.retain(|item| {
+                        !results.iter().any(|result| {
+                            result.resource.resource.id == item.resource.resource.id
+                                && result.engine == item.engine
+                        })
+                    });
+                    results.extend(index_results);
+                }
+                results
+            }
+            None => self.list_search_items_by_ids(filtered_resource_ids, keyword_limit)?,
+        };
 
         if include_annotations {
             let mut annotations = self.list_resource_annotations(
@@ -301,6 +630,205 @@ impl Database {
             items: results,
             spaces: vec![],
             space_entries: None,
+            query_errors: vec![],
+            facets: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn highlighted(text: &str) -> String {
+        text.replace('[', &HIGHLIGHT_START.to_string())
+            .replace(']', &HIGHLIGHT_END.to_string())
+    }
+
+    #[test]
+    fn test_highlighted_excerpt() {
+        let text = highlighted(
+            "the borrow checker in [rust] makes sure that every [rust] reference is valid",
+        );
+        let (excerpt, highlights) = highlighted_excerpt(&text, 30);
+        assert_eq!(excerpt, "…checker in rust makes sure…");
+        assert_eq!(highlights, vec![(12, 16)]);
+
+        let (excerpt, highlights) = highlighted_excerpt(&text, 200);
+        assert_eq!(
+            excerpt,
+            "the borrow checker in rust makes sure that every rust reference is valid"
+        );
+        assert_eq!(highlights, vec![(22, 26), (49, 53)]);
+
+        let (excerpt, highlights) = highlighted_excerpt("no match here", 8);
+        assert_eq!(excerpt, "no match…");
+        assert!(highlights.is_empty());
+    }
+
+    #[test]
+    fn test_search_indexes_for_terms() {
+        assert_eq!(
+            search_indexes_for_terms(vec!["running", "shoes"]),
+            vec![SearchIndex::Words, SearchIndex::Trigram]
+        );
+        assert_eq!(
+            search_indexes_for_terms(vec!["go concurrency"]),
+            vec![SearchIndex::Words]
+        );
+        assert_eq!(
+            search_indexes_for_terms(vec!["東京タワー"]),
+            vec![SearchIndex::Trigram]
+        );
+    }
+
+    #[test]
+    fn test_keyword_search_content_snippets() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        Database::create_resource_tx(
+            &mut tx,
+            &Resource {
+                id: "pdf".to_string(),
+                resource_path: "pdf".to_string(),
+                resource_type: "application/pdf".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            },
+        )
+        .unwrap();
+        Database::create_resource_metadata_tx(
+            &mut tx,
+            &ResourceMetadata {
+                id: random_uuid(),
+                resource_id: "pdf".to_string(),
+                name: "The book".to_string(),
+                source_uri: String::new(),
+                alt: String::new(),
+                user_context: String::new(),
+                byline: None,
+                published_at: None,
+                lead_image: None,
+                outline: None,
+            },
+        )
+        .unwrap();
+        for (page, content) in [
+            (1, "nothing to see"),
+            (2, "ownership is how rust manages memory"),
+        ] {
+            Database::create_resource_text_content_tx(
+                &mut tx,
+                &ResourceTextContent {
+                    id: format!("page-{}", page),
+                    resource_id: "pdf".to_string(),
+                    content: content.to_string(),
+                    content_type: ResourceTextContentType::PDF,
+                    metadata: ResourceTextContentMetadata {
+                        page: Some(page),
+                        ..Default::default()
+                    },
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let results = db
+            .keyword_search_content(
+                &escape_fts_query("rust"),
+                SearchIndex::Trigram,
+                vec![],
+                None,
+                20,
+            )
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].snippets,
+            vec![SearchSnippet {
+                content_id: "page-2".to_string(),
+                content_type: ResourceTextContentType::PDF,
+                text: "…is how rust manages…".to_string(),
+                highlights: vec![(8, 12)],
+                page: Some(2),
+                timestamp: None,
+                section: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keyword_search_only_returns_filtered_resources() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        for id in ["inside", "outside"] {
+            Database::create_resource_tx(
+                &mut tx,
+                &Resource {
+                    id: id.to_string(),
+                    resource_path: id.to_string(),
+                    resource_type: "text/plain".to_string(),
+                    created_at: now,
+                    updated_at: now,
+                    deleted: 0,
+                },
+            )
+            .unwrap();
+            Database::create_resource_metadata_tx(
+                &mut tx,
+                &ResourceMetadata {
+                    id: random_uuid(),
+                    resource_id: id.to_string(),
+                    name: "rust notes".to_string(),
+                    source_uri: String::new(),
+                    alt: String::new(),
+                    user_context: String::new(),
+                    byline: None,
+                    published_at: None,
+                    lead_image: None,
+                    outline: None,
+                },
+            )
+            .unwrap();
+            Database::create_resource_text_content_tx(
+                &mut tx,
+                &ResourceTextContent {
+                    id: format!("{}-content", id),
+                    resource_id: id.to_string(),
+                    content: "ownership is how rust manages memory".to_string(),
+                    content_type: ResourceTextContentType::Note,
+                    metadata: ResourceTextContentMetadata::default(),
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let filter = vec!["inside".to_string()];
+        let ids = |results: Vec<SearchResultItem>| {
+            results
+                .into_iter()
+                .map(|item| item.resource.resource.id)
+                .collect::<Vec<_>>()
+        };
+        for index in [SearchIndex::Trigram, SearchIndex::Words] {
+            let metadata = db
+                .keyword_search_metadata(&escape_fts_query("rust"), index, filter.clone(), None)
+                .unwrap();
+            assert_eq!(ids(metadata), vec!["inside"]);
+            let content = db
+                .keyword_search_content(&escape_fts_query("rust"), index, filter.clone(), None, 20)
+                .unwrap();
+            assert_eq!(ids(content), vec!["inside"]);
+        }
+    }
+}
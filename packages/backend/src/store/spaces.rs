@@ -1,7 +1,88 @@
 use super::models::*;
-use crate::{store::db::Database, BackendResult};
+use crate::{
+    store::{
+        db::Database,
+        pagination::{keyset_condition, order_clause, page_from_rows, PageCursor},
+    },
+    BackendResult,
+};
 use rusqlite::OptionalExtension;
 
+// entries of the space `?1`, resources and nested spaces, with the value they are sorted by
+fn space_entries_query(sort_by: Option<&str>) -> String {
+    let (sort_field, resource_join_clause) = match sort_by {
+        Some("resource_added_to_space") => ("se.created_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
+        Some("resource_updated") => ("r.updated_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
+        Some("resource_created") => ("r.created_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
+        Some("resource_source_published") => (
+            "COALESCE(rt.tag_value, se.created_at)", 
+            "LEFT JOIN resources r ON se.resource_id = r.id \
+             LEFT JOIN resource_tags rt ON r.id = rt.resource_id AND rt.tag_name = 'sourcePublishedAt'"
+        ),
+        _ => ("se.updated_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
+    };
+
+    let resource_query = format!(
+        "SELECT 
+        se.id, 
+        se.space_id, 
+        se.resource_id as entry_id, 
+        'resource' as entry_type, 
+        se.created_at, 
+        se.updated_at, 
+        se.manually_added, 
+        r.resource_type,
+        {} as sort_value
+    FROM space_entries se 
+    {} 
+    WHERE se.space_id = ?1",
+        sort_field, resource_join_clause
+    );
+
+    let space_query = format!(
+        "SELECT 
+        ss.id, 
+        ss.parent_space_id as space_id, 
+        ss.child_space_id as entry_id, 
+        'space' as entry_type, 
+        ss.created_at, 
+        ss.updated_at, 
+        ss.manually_added, 
+        NULL as resource_type,
+        ss.{} as sort_value
+    FROM sub_space_entries ss
+    LEFT JOIN spaces s ON ss.child_space_id = s.id
+    WHERE ss.parent_space_id = ?1",
+        if sort_by == Some("resource_created") || sort_by == Some("resource_updated") {
+            "created_at"
+        } else {
+            "updated_at"
+        }
+    );
+
+    format!("{} UNION ALL {}", resource_query, space_query)
+}
+
+fn space_entry_extended_from_row(row: &rusqlite::Row) -> rusqlite::Result<SpaceEntryExtended> {
+    let entry_type_str: String = row.get(3)?;
+    let entry_type = if entry_type_str == "space" {
+        SpaceEntryType::Space
+    } else {
+        SpaceEntryType::Resource
+    };
+
+    Ok(SpaceEntryExtended {
+        id: row.get(0)?,
+        space_id: row.get(1)?,
+        entry_id: row.get(2)?,
+        entry_type,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        manually_added: row.get(6)?,
+        resource_type: row.get(7)?,
+    })
+}
+
 impl Database {
     pub fn create_space(&mut self, space: &Space) -> BackendResult<()> {
         self.conn.execute(
@@ -86,6 +167,15 @@ impl Database {
         Ok(result)
     }
 
+    // spaces whose folder name is `name` ignoring case, most recently updated first
+    pub fn list_space_ids_by_name(&self, name: &str) -> BackendResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM spaces WHERE lower(json_extract(name, '$.folderName')) = lower(?1) ORDER BY updated_at DESC",
+        )?;
+        let ids = stmt.query_map(rusqlite::params![name], |row| row.get(0))?;
+        Ok(ids.collect::<Result<Vec<String>, _>>()?)
+    }
+
     pub fn get_parent_child_spaces(
         &self,
         space_id: &str,
@@ -402,65 +492,16 @@ impl Database {
         order_by: Option<&str>,
         limit: Option<usize>,
     ) -> BackendResult<Vec<SpaceEntryExtended>> {
-        let (sort_field, resource_join_clause) = match sort_by {
-            Some("resource_added_to_space") => ("se.created_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
-            Some("resource_updated") => ("r.updated_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
-            Some("resource_created") => ("r.created_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
-            Some("resource_source_published") => (
-                "COALESCE(rt.tag_value, se.created_at)", 
-                "LEFT JOIN resources r ON se.resource_id = r.id \
-                 LEFT JOIN resource_tags rt ON r.id = rt.resource_id AND rt.tag_name = 'sourcePublishedAt'"
-            ),
-            _ => ("se.updated_at", "LEFT JOIN resources r ON se.resource_id = r.id"),
-        };
-
         let order = if order_by == Some("asc") {
             "ASC"
         } else {
             "DESC"
         };
 
-        let resource_query = format!(
-            "SELECT 
-            se.id, 
-            se.space_id, 
-            se.resource_id as entry_id, 
-            'resource' as entry_type, 
-            se.created_at, 
-            se.updated_at, 
-            se.manually_added, 
-            r.resource_type,
-            {} as sort_value
-        FROM space_entries se 
-        {} 
-        WHERE se.space_id = ?1",
-            sort_field, resource_join_clause
-        );
-
-        let space_query = format!(
-            "SELECT 
-            ss.id, 
-            ss.parent_space_id as space_id, 
-            ss.child_space_id as entry_id, 
-            'space' as entry_type, 
-            ss.created_at, 
-            ss.updated_at, 
-            ss.manually_added, 
-            NULL as resource_type,
-            ss.{} as sort_value
-        FROM sub_space_entries ss
-        LEFT JOIN spaces s ON ss.child_space_id = s.id
-        WHERE ss.parent_space_id = ?1",
-            if sort_by == Some("resource_created") || sort_by == Some("resource_updated") {
-                "created_at"
-            } else {
-                "updated_at"
-            }
-        );
-
         let mut query = format!(
-            "{} UNION ALL {} ORDER BY sort_value {}",
-            resource_query, space_query, order
+            "{} ORDER BY sort_value {}",
+            space_entries_query(sort_by),
+            order
         );
 
         if let Some(limit) = limit {
@@ -469,31 +510,56 @@ impl Database {
         }
 
         let mut stmt = self.conn.prepare_cached(&query)?;
-        let space_entries = stmt.query_map(rusqlite::params![space_id], |row| {
-            let entry_type_str: String = row.get(3)?;
-            let entry_type = if entry_type_str == "space" {
-                SpaceEntryType::Space
-            } else {
-                SpaceEntryType::Resource
-            };
-
-            Ok(SpaceEntryExtended {
-                id: row.get(0)?,
-                space_id: row.get(1)?,
-                entry_id: row.get(2)?,
-                entry_type,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
-                manually_added: row.get(6)?,
-                resource_type: row.get(7)?,
-            })
-        })?;
+        let space_entries =
+            stmt.query_map(rusqlite::params![space_id], space_entry_extended_from_row)?;
 
         space_entries
             .collect::<rusqlite::Result<Vec<_>>>()
             .map_err(Into::into)
     }
 
+    // entries without a sort value, like those of removed resources, sort below all others
+    pub fn list_space_entries_page(
+        &self,
+        space_id: &str,
+        sort_by: Option<&str>,
+        order_by: Option<&str>,
+        page: &PageRequest,
+    ) -> BackendResult<Page<SpaceEntryExtended>> {
+        let descending = order_by != Some("asc");
+        let cursor = page.page_cursor()?;
+        let condition = match cursor {
+            Some(_) => format!(
+                "WHERE {}",
+                keyset_condition("page_key", "id", descending, 2)
+            ),
+            None => String::new(),
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT * FROM (
+                SELECT E.*, COALESCE(E.sort_value, '') AS page_key FROM ({}) E
+            ) {} {} {}",
+            space_entries_query(sort_by),
+            condition,
+            order_clause("page_key", "id", descending),
+            page.limit_clause()
+        ))?;
+        let mut params = vec![space_id.to_string()];
+        if let Some(cursor) = cursor {
+            params.push(cursor.sort_value);
+            params.push(cursor.id);
+        }
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            let entry = space_entry_extended_from_row(row)?;
+            let cursor = PageCursor {
+                sort_value: row.get(9)?,
+                id: entry.id.clone(),
+            };
+            Ok((entry, cursor))
+        })?;
+        Ok(page_from_rows(rows.collect::<Result<Vec<_>, _>>()?, page))
+    }
+
     pub fn list_space_ids_by_resource_id(&self, resource_id: &str) -> BackendResult<Vec<String>> {
         let mut stmt = self.conn.prepare(
             "SELECT space_id FROM space_entries WHERE resource_id = ?1 AND manually_added = 1 ORDER BY created_at ASC",
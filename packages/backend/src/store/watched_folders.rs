@@ -0,0 +1,160 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{WatchedFile, WatchedFolder},
+    },
+    BackendResult,
+};
+
+use rusqlite::OptionalExtension;
+
+impl Database {
+    pub fn create_watched_folder(&mut self, folder: &WatchedFolder) -> BackendResult<()> {
+        self.conn.execute(
+            "INSERT INTO watched_folders (id, path, space_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![folder.id, folder.path, folder.space_id, folder.created_at],
+        )?;
+        Ok(())
+    }
+
+    fn watched_folder_from_row(row: &rusqlite::Row) -> rusqlite::Result<WatchedFolder> {
+        Ok(WatchedFolder {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            space_id: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+
+    pub fn get_watched_folder(&self, id: &str) -> BackendResult<Option<WatchedFolder>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, path, space_id, created_at FROM watched_folders WHERE id = ?1")?;
+        Ok(stmt
+            .query_row([id], Self::watched_folder_from_row)
+            .optional()?)
+    }
+
+    pub fn get_watched_folder_by_path(&self, path: &str) -> BackendResult<Option<WatchedFolder>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, space_id, created_at FROM watched_folders WHERE path = ?1",
+        )?;
+        Ok(stmt
+            .query_row([path], Self::watched_folder_from_row)
+            .optional()?)
+    }
+
+    pub fn list_watched_folders(&self) -> BackendResult<Vec<WatchedFolder>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, space_id, created_at FROM watched_folders ORDER BY created_at ASC",
+        )?;
+        let folders = stmt.query_map([], Self::watched_folder_from_row)?;
+        Ok(folders.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // the resources of its files are kept
+    pub fn remove_watched_folder_tx(tx: &mut rusqlite::Transaction, id: &str) -> BackendResult<()> {
+        tx.execute("DELETE FROM watched_files WHERE folder_id = ?1", [id])?;
+        tx.execute("DELETE FROM watched_folders WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn upsert_watched_file_tx(
+        tx: &mut rusqlite::Transaction,
+        file: &WatchedFile,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO watched_files (folder_id, path, resource_id) VALUES (?1, ?2, ?3)",
+            rusqlite::params![file.folder_id, file.path, file.resource_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_watched_files(&self, folder_id: &str) -> BackendResult<Vec<WatchedFile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT folder_id, path, resource_id FROM watched_files WHERE folder_id = ?1 ORDER BY path ASC",
+        )?;
+        let files = stmt.query_map([folder_id], |row| {
+            Ok(WatchedFile {
+                folder_id: row.get(0)?,
+                path: row.get(1)?,
+                resource_id: row.get(2)?,
+            })
+        })?;
+        Ok(files.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::{current_time, Resource, Space};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_watched_folders_and_files() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+
+        let now = current_time();
+        db.create_space(&Space {
+            id: "space".to_string(),
+            name: "Notes".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .unwrap();
+        for id in ["a", "b"] {
+            db.create_resource(&Resource {
+                id: id.to_string(),
+                resource_path: id.to_string(),
+                resource_type: "text/plain".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            })
+            .unwrap();
+        }
+
+        let folder = WatchedFolder {
+            id: "folder".to_string(),
+            path: "/home/user/notes".to_string(),
+            space_id: "space".to_string(),
+            created_at: now,
+        };
+        db.create_watched_folder(&folder).unwrap();
+        assert!(db.create_watched_folder(&folder).is_err());
+        assert_eq!(
+            db.get_watched_folder_by_path("/home/user/notes").unwrap(),
+            Some(folder.clone())
+        );
+
+        let mut tx = db.begin().unwrap();
+        for (path, resource_id) in [("b.md", "a"), ("a.md", "a"), ("b.md", "b")] {
+            let file = WatchedFile {
+                folder_id: "folder".to_string(),
+                path: path.to_string(),
+                resource_id: resource_id.to_string(),
+            };
+            Database::upsert_watched_file_tx(&mut tx, &file).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let files = db.list_watched_files("folder").unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| (file.path.as_str(), file.resource_id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("a.md", "a"), ("b.md", "b")]
+        );
+
+        let mut tx = db.begin().unwrap();
+        Database::remove_watched_folder_tx(&mut tx, "folder").unwrap();
+        tx.commit().unwrap();
+        assert!(db.list_watched_folders().unwrap().is_empty());
+        assert!(db.list_watched_files("folder").unwrap().is_empty());
+        assert!(db.get_resource("a").unwrap().is_some());
+    }
+}
@@ -0,0 +1,220 @@
+use super::models::*;
+use crate::{store::db::Database, BackendResult};
+
+// the word indexes are external content tables over the trigram tables, they only hold the
+// index and have to be told about every change: rows are removed from them before they are
+// changed or deleted and added again after they are inserted or changed
+
+fn tokenizer(language: SearchLanguage) -> &'static str {
+    match language {
+        SearchLanguage::English => "porter unicode61 remove_diacritics 2",
+        SearchLanguage::Any => "unicode61 remove_diacritics 2",
+    }
+}
+
+impl Database {
+    pub fn index_text_content_words(
+        conn: &rusqlite::Connection,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> BackendResult<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO resource_text_content_words(rowid, content)
+                SELECT rowid, content FROM resource_text_content WHERE {}",
+                condition
+            ),
+            params,
+        )?;
+        Ok(())
+    }
+
+    pub fn unindex_text_content_words(
+        conn: &rusqlite::Connection,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> BackendResult<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO resource_text_content_words(resource_text_content_words, rowid, content)
+                SELECT 'delete', rowid, content FROM resource_text_content WHERE {}",
+                condition
+            ),
+            params,
+        )?;
+        Ok(())
+    }
+
+    pub fn index_metadata_words(
+        conn: &rusqlite::Connection,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> BackendResult<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO resource_metadata_words(rowid, name, alt, user_context, byline)
+                SELECT rowid, name, alt, user_context, byline FROM resource_metadata WHERE {}",
+                condition
+            ),
+            params,
+        )?;
+        Ok(())
+    }
+
+    pub fn unindex_metadata_words(
+        conn: &rusqlite::Connection,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> BackendResult<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO resource_metadata_words(resource_metadata_words, rowid, name, alt, user_context, byline)
+                SELECT 'delete', rowid, name, alt, user_context, byline FROM resource_metadata WHERE {}",
+                condition
+            ),
+            params,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_search_language(&self) -> BackendResult<SearchLanguage> {
+        let sql: String = self.conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'resource_text_content_words'",
+            [],
+            |row| row.get(0),
+        )?;
+        if sql.contains("porter") {
+            return Ok(SearchLanguage::English);
+        }
+        Ok(SearchLanguage::Any)
+    }
+
+    // fts5 tokenizers can't be changed, so the word indexes are created again and refilled
+    pub fn set_search_language(&mut self, language: SearchLanguage) -> BackendResult<()> {
+        let tx = self.begin()?;
+        tx.execute_batch(&format!(
+            "DROP TABLE IF EXISTS resource_text_content_words;
+            DROP TABLE IF EXISTS resource_metadata_words;
+            CREATE VIRTUAL TABLE resource_text_content_words USING fts5(
+                content,
+                content='resource_text_content',
+                tokenize=\"{0}\"
+            );
+            CREATE VIRTUAL TABLE resource_metadata_words USING fts5(
+                name,
+                alt,
+                user_context,
+                byline,
+                content='resource_metadata',
+                tokenize=\"{0}\"
+            );",
+            tokenizer(language)
+        ))?;
+        Self::index_text_content_words(&tx, "1", [])?;
+        Self::index_metadata_words(&tx, "1", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn search_ids(db: &Database, keyword: &str) -> Vec<String> {
+        db.search_resources(keyword, &None, false, None)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.resource.resource.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_word_index_stays_in_sync() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+        let now = current_time();
+        let mut tx = db.begin().unwrap();
+        Database::create_resource_tx(
+            &mut tx,
+            &Resource {
+                id: "note".to_string(),
+                resource_path: "note".to_string(),
+                resource_type: "text/markdown".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            },
+        )
+        .unwrap();
+        Database::create_resource_metadata_tx(
+            &mut tx,
+            &ResourceMetadata {
+                id: random_uuid(),
+                resource_id: "note".to_string(),
+                name: "Weekend plans".to_string(),
+                source_uri: String::new(),
+                alt: String::new(),
+                user_context: String::new(),
+                byline: None,
+                published_at: None,
+                lead_image: None,
+                outline: None,
+            },
+        )
+        .unwrap();
+        Database::create_resource_text_content_tx(
+            &mut tx,
+            &ResourceTextContent {
+                id: "content".to_string(),
+                resource_id: "note".to_string(),
+                content: "we are running to the lake".to_string(),
+                content_type: ResourceTextContentType::Note,
+                metadata: ResourceTextContentMetadata::default(),
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        // stemmed and shorter than a trigram
+        assert_eq!(search_ids(&db, "runs"), vec!["note"]);
+        assert_eq!(search_ids(&db, "we"), vec!["note"]);
+
+        let mut tx = db.begin().unwrap();
+        Database::update_resource_text_content_tx(
+            &mut tx,
+            &ResourceTextContent {
+                id: "content".to_string(),
+                resource_id: "note".to_string(),
+                content: "we are swimming in the lake".to_string(),
+                content_type: ResourceTextContentType::Note,
+                metadata: ResourceTextContentMetadata::default(),
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+        assert!(search_ids(&db, "runs").is_empty());
+        assert_eq!(search_ids(&db, "swim"), vec!["note"]);
+
+        db.set_search_language(SearchLanguage::Any).unwrap();
+        assert_eq!(db.get_search_language().unwrap(), SearchLanguage::Any);
+        assert_eq!(search_ids(&db, "we"), vec!["note"]);
+        // without stemming the trigram index still finds the part of the word
+        assert_eq!(search_ids(&db, "swim"), vec!["note"]);
+
+        let mut tx = db.begin().unwrap();
+        Database::remove_resource_text_content_tx(&mut tx, "note").unwrap();
+        Database::remove_resource_metadata_tx(&mut tx, "note").unwrap();
+        tx.commit().unwrap();
+        assert!(search_ids(&db, "we").is_empty());
+        db.conn
+            .execute(
+                "INSERT INTO resource_text_content_words(resource_text_content_words) VALUES ('integrity-check')",
+                [],
+            )
+            .unwrap();
+    }
+}
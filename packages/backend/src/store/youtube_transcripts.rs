@@ -0,0 +1,64 @@
+use crate::{
+    store::{db::Database, models::current_time},
+    BackendResult,
+};
+
+use rusqlite::OptionalExtension;
+
+impl Database {
+    pub fn upsert_youtube_transcript_tx(
+        tx: &mut rusqlite::Transaction,
+        video_id: &str,
+        language: &str,
+        transcript: &str,
+    ) -> BackendResult<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO youtube_transcripts (video_id, language, transcript, created_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![video_id, language, transcript, current_time()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_youtube_transcript(
+        &self,
+        video_id: &str,
+        language: &str,
+    ) -> BackendResult<Option<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT transcript FROM youtube_transcripts WHERE video_id = ?1 AND language = ?2",
+        )?;
+        let transcript = stmt
+            .query_row(rusqlite::params![video_id, language], |row| row.get(0))
+            .optional()?;
+        Ok(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_youtube_transcripts_are_keyed_by_language() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut db = Database::new(&db_path.to_string_lossy(), true).unwrap();
+
+        let mut tx = db.begin().unwrap();
+        Database::upsert_youtube_transcript_tx(&mut tx, "abc", "en", "first").unwrap();
+        Database::upsert_youtube_transcript_tx(&mut tx, "abc", "de", "german").unwrap();
+        Database::upsert_youtube_transcript_tx(&mut tx, "abc", "en", "second").unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            db.get_youtube_transcript("abc", "en").unwrap().as_deref(),
+            Some("second")
+        );
+        assert_eq!(
+            db.get_youtube_transcript("abc", "de").unwrap().as_deref(),
+            Some("german")
+        );
+        assert!(db.get_youtube_transcript("abc", "fr").unwrap().is_none());
+    }
+}
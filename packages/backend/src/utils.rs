@@ -1,5 +1,6 @@
 use mime2ext::mime2ext;
 use regex::Regex;
+use sha2::{Digest, Sha256};
 
 const BASE62_CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
@@ -107,6 +108,14 @@ pub fn get_resource_file_extension(resource_type: &str) -> String {
         .to_string()
 }
 
+// hex encoded sha256, the same hash the app computes for resource files
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
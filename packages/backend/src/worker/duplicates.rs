@@ -0,0 +1,150 @@
+use url::Url;
+
+// query parameters that only tell where a visitor came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc",
+    "_hsmi", "ref", "ref_src", "si", "spm",
+];
+
+// the same page saved from different links gets the same key: scheme, `www.`, fragments,
+// tracking parameters, the parameter order and trailing slashes are ignored
+pub fn normalize_source_uri(uri: &str) -> Option<String> {
+    let url = Url::parse(uri.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| {
+            let key = key.to_lowercase();
+            !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+        })
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    params.sort();
+
+    let mut key = host.to_owned();
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{port}"));
+    }
+    key.push_str(url.path().trim_end_matches('/'));
+    if !params.is_empty() {
+        let query = params
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        key.push('?');
+        key.push_str(&query);
+    }
+    Some(key)
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let norm_a = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+// every pair of embeddings at least `threshold` alike, with their similarity
+pub fn similar_pairs(embeddings: &[Vec<f32>], threshold: f32) -> Vec<(usize, usize, f32)> {
+    let mut pairs = vec![];
+    for i in 0..embeddings.len() {
+        for j in i + 1..embeddings.len() {
+            let similarity = cosine_similarity(&embeddings[i], &embeddings[j]);
+            if similarity >= threshold {
+                pairs.push((i, j, similarity));
+            }
+        }
+    }
+    pairs
+}
+
+fn find_root(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut i = i;
+    while parents[i] != root {
+        let next = parents[i];
+        parents[i] = root;
+        i = next;
+    }
+    root
+}
+
+// items connected through any chain of pairs end up in one group, each group with the lowest
+// similarity of the pairs that joined it
+pub fn group_pairs(len: usize, pairs: &[(usize, usize, f32)]) -> Vec<(Vec<usize>, f32)> {
+    let mut parents: Vec<usize> = (0..len).collect();
+    for &(a, b, _) in pairs {
+        let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+        if root_a != root_b {
+            parents[root_b] = root_a;
+        }
+    }
+
+    let mut groups: Vec<(Vec<usize>, f32)> = vec![];
+    let mut group_of_root = std::collections::HashMap::new();
+    for i in 0..len {
+        let root = find_root(&mut parents, i);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push((vec![], f32::MAX));
+            groups.len() - 1
+        });
+        groups[group].0.push(i);
+    }
+    for &(a, _, similarity) in pairs {
+        let group = group_of_root[&find_root(&mut parents, a)];
+        groups[group].1 = groups[group].1.min(similarity);
+    }
+    groups.retain(|(members, _)| members.len() > 1);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_source_uri() {
+        let key = Some("example.com/articles/rust?id=7&page=2".to_owned());
+        for uri in [
+            "https://www.example.com/articles/rust/?page=2&id=7",
+            "http://example.com/articles/rust?id=7&utm_source=feed&page=2#comments",
+            "https://WWW.Example.com/articles/rust?fbclid=abc&id=7&page=2",
+        ] {
+            assert_eq!(normalize_source_uri(uri), key, "{uri}");
+        }
+        assert_ne!(
+            normalize_source_uri("https://example.com/articles/rust?id=8&page=2"),
+            key
+        );
+        assert_eq!(normalize_source_uri("surf://surf/resource/abc"), None);
+        assert_eq!(normalize_source_uri("not a url"), None);
+    }
+
+    #[test]
+    fn test_group_pairs() {
+        let embeddings = vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.99, 0.05],
+            vec![0.98, 0.1],
+            vec![0.0, 0.0],
+        ];
+        let pairs = similar_pairs(&embeddings, 0.99);
+        let groups = group_pairs(embeddings.len(), &pairs);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, vec![0, 2, 3]);
+        assert!(groups[0].1 >= 0.99 && groups[0].1 < 1.0);
+    }
+}
@@ -0,0 +1,743 @@
+use super::{
+    create_metadata_from_resource, space_data::SpaceDataExtractor, Extraction, Extractor,
+    ExtractorContext,
+};
+use crate::{
+    store::models::{random_uuid, CompositeResource, ResourceTag, ResourceTextContentType},
+    BackendResult,
+};
+
+use std::path::Path;
+
+// language of source code resources, see `CodeLanguage::as_str`
+pub const CODE_LANGUAGE_TAG: &str = "codeLanguage";
+
+// chunks are embedded as they are, so keep them around the size of the text chunks
+const MAX_CHUNK_SIZE: usize = 2000;
+// lines starting with these are statements, not function signatures
+const CONTROL_KEYWORDS: [&str; 18] = [
+    "if", "else", "for", "foreach", "while", "switch", "return", "catch", "do", "try", "case",
+    "match", "loop", "new", "throw", "await", "yield", "sizeof",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    C,
+    Cpp,
+    CSharp,
+    Go,
+    Java,
+    JavaScript,
+    Kotlin,
+    Lua,
+    Php,
+    Python,
+    Ruby,
+    Rust,
+    Scala,
+    Shell,
+    Swift,
+    TypeScript,
+}
+
+// MIME types are matched after their parameters are stripped
+const CODE_MIME_TYPES: [(&str, CodeLanguage); 30] = [
+    ("text/x-c", CodeLanguage::C),
+    ("text/x-csrc", CodeLanguage::C),
+    ("text/x-chdr", CodeLanguage::C),
+    ("text/x-c++", CodeLanguage::Cpp),
+    ("text/x-c++src", CodeLanguage::Cpp),
+    ("text/x-c++hdr", CodeLanguage::Cpp),
+    ("text/x-csharp", CodeLanguage::CSharp),
+    ("text/x-go", CodeLanguage::Go),
+    ("text/x-java", CodeLanguage::Java),
+    ("text/x-java-source", CodeLanguage::Java),
+    ("text/javascript", CodeLanguage::JavaScript),
+    ("application/javascript", CodeLanguage::JavaScript),
+    ("application/x-javascript", CodeLanguage::JavaScript),
+    ("text/x-kotlin", CodeLanguage::Kotlin),
+    ("text/x-lua", CodeLanguage::Lua),
+    ("application/x-httpd-php", CodeLanguage::Php),
+    ("application/x-php", CodeLanguage::Php),
+    ("text/x-php", CodeLanguage::Php),
+    ("text/x-python", CodeLanguage::Python),
+    ("application/x-python", CodeLanguage::Python),
+    ("text/x-ruby", CodeLanguage::Ruby),
+    ("application/x-ruby", CodeLanguage::Ruby),
+    ("text/x-rust", CodeLanguage::Rust),
+    ("text/x-scala", CodeLanguage::Scala),
+    ("application/x-sh", CodeLanguage::Shell),
+    ("text/x-sh", CodeLanguage::Shell),
+    ("text/x-shellscript", CodeLanguage::Shell),
+    ("text/x-swift", CodeLanguage::Swift),
+    ("application/typescript", CodeLanguage::TypeScript),
+    ("text/typescript", CodeLanguage::TypeScript),
+];
+
+impl CodeLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeLanguage::C => "c",
+            CodeLanguage::Cpp => "cpp",
+            CodeLanguage::CSharp => "csharp",
+            CodeLanguage::Go => "go",
+            CodeLanguage::Java => "java",
+            CodeLanguage::JavaScript => "javascript",
+            CodeLanguage::Kotlin => "kotlin",
+            CodeLanguage::Lua => "lua",
+            CodeLanguage::Php => "php",
+            CodeLanguage::Python => "python",
+            CodeLanguage::Ruby => "ruby",
+            CodeLanguage::Rust => "rust",
+            CodeLanguage::Scala => "scala",
+            CodeLanguage::Shell => "shell",
+            CodeLanguage::Swift => "swift",
+            CodeLanguage::TypeScript => "typescript",
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let mime_type = mime_type.split(';').next()?.trim().to_lowercase();
+        CODE_MIME_TYPES
+            .iter()
+            .find(|(code_mime_type, _)| *code_mime_type == mime_type)
+            .map(|(_, language)| *language)
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let language = match extension.to_lowercase().as_str() {
+            "c" | "h" => CodeLanguage::C,
+            "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => CodeLanguage::Cpp,
+            "cs" => CodeLanguage::CSharp,
+            "go" => CodeLanguage::Go,
+            "java" => CodeLanguage::Java,
+            "js" | "mjs" | "cjs" | "jsx" => CodeLanguage::JavaScript,
+            "kt" | "kts" => CodeLanguage::Kotlin,
+            "lua" => CodeLanguage::Lua,
+            "php" => CodeLanguage::Php,
+            "py" | "pyi" => CodeLanguage::Python,
+            "rb" => CodeLanguage::Ruby,
+            "rs" => CodeLanguage::Rust,
+            "scala" | "sc" => CodeLanguage::Scala,
+            "sh" | "bash" | "zsh" => CodeLanguage::Shell,
+            "swift" => CodeLanguage::Swift,
+            "ts" | "tsx" | "mts" | "cts" => CodeLanguage::TypeScript,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    // `#!/usr/bin/env python3`, `#!/bin/bash` etc.
+    fn from_shebang(source: &str) -> Option<Self> {
+        let interpreter_line = source.lines().next()?.strip_prefix("#!")?;
+        let mut parts = interpreter_line.split_whitespace();
+        let mut interpreter = parts.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            interpreter = parts.find(|part| !part.starts_with('-'))?;
+        }
+        let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let language = match interpreter {
+            "python" => CodeLanguage::Python,
+            "node" => CodeLanguage::JavaScript,
+            "sh" | "bash" | "zsh" => CodeLanguage::Shell,
+            "ruby" => CodeLanguage::Ruby,
+            "php" => CodeLanguage::Php,
+            "lua" => CodeLanguage::Lua,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    // the rest nest blocks by indentation, or at least are indented consistently enough
+    fn uses_braces(&self) -> bool {
+        !matches!(
+            self,
+            CodeLanguage::Python | CodeLanguage::Ruby | CodeLanguage::Lua
+        )
+    }
+
+    fn line_comments(&self) -> &'static [&'static str] {
+        match self {
+            CodeLanguage::Python | CodeLanguage::Ruby | CodeLanguage::Shell => &["#"],
+            CodeLanguage::Lua => &["--"],
+            CodeLanguage::Php => &["//", "#"],
+            _ => &["//"],
+        }
+    }
+
+    fn definition_keywords(&self) -> &'static [&'static str] {
+        match self {
+            CodeLanguage::C => &["struct", "enum", "union", "typedef"],
+            CodeLanguage::Cpp => &[
+                "class",
+                "struct",
+                "enum",
+                "union",
+                "namespace",
+                "template",
+                "typedef",
+            ],
+            CodeLanguage::CSharp => &[
+                "class",
+                "struct",
+                "interface",
+                "enum",
+                "record",
+                "namespace",
+                "delegate",
+            ],
+            CodeLanguage::Go => &["func", "type", "var", "const"],
+            CodeLanguage::Java => &["class", "interface", "enum", "record"],
+            CodeLanguage::JavaScript | CodeLanguage::TypeScript => &[
+                "function",
+                "function*",
+                "class",
+                "interface",
+                "type",
+                "enum",
+                "namespace",
+                "const",
+                "let",
+                "var",
+            ],
+            CodeLanguage::Kotlin => &["fun", "class", "interface", "object", "typealias"],
+            CodeLanguage::Lua => &["function"],
+            CodeLanguage::Php => &["function", "class", "interface", "trait", "enum"],
+            CodeLanguage::Python => &["def", "class"],
+            CodeLanguage::Ruby => &["def", "class", "module"],
+            CodeLanguage::Rust => &[
+                "fn",
+                "struct",
+                "enum",
+                "union",
+                "impl",
+                "trait",
+                "mod",
+                "type",
+                "const",
+                "static",
+                "macro_rules!",
+            ],
+            CodeLanguage::Scala => &["def", "class", "object", "trait", "type", "val"],
+            CodeLanguage::Shell => &["function"],
+            CodeLanguage::Swift => &[
+                "func",
+                "class",
+                "struct",
+                "enum",
+                "protocol",
+                "extension",
+                "init",
+            ],
+        }
+    }
+
+    fn is_modifier(&self, word: &str) -> bool {
+        word.starts_with("pub(")
+            || matches!(
+                word,
+                "pub"
+                    | "export"
+                    | "default"
+                    | "declare"
+                    | "async"
+                    | "unsafe"
+                    | "extern"
+                    | "public"
+                    | "private"
+                    | "protected"
+                    | "internal"
+                    | "static"
+                    | "final"
+                    | "abstract"
+                    | "sealed"
+                    | "open"
+                    | "override"
+                    | "data"
+                    | "inline"
+                    | "suspend"
+                    | "virtual"
+                    | "partial"
+                    | "readonly"
+                    | "local"
+                    | "case"
+                    | "implicit"
+                    | "lazy"
+            )
+    }
+
+    fn is_definition(&self, line: &str) -> bool {
+        let line = line.trim();
+        for word in line.split_whitespace() {
+            let word = word.split('(').next().unwrap_or_default();
+            if self.definition_keywords().contains(&word) {
+                return true;
+            }
+            if !self.is_modifier(word) {
+                break;
+            }
+        }
+        // functions and methods without a keyword, `int main(void) {` or `render() {`
+        self.uses_braces() && looks_like_signature(line)
+    }
+
+    // comments, attributes and decorators belong to the definition that follows them
+    fn is_leading_line(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        self.line_comments()
+            .iter()
+            .any(|comment| line.starts_with(comment))
+            || ["/*", "*", "#[", "@"]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+    }
+}
+
+fn looks_like_signature(line: &str) -> bool {
+    let line = line.trim_end();
+    if line.ends_with(';') || line.ends_with(',') || line.ends_with(')') && line.contains('=') {
+        return false;
+    }
+    if !line
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '~')
+    {
+        return false;
+    }
+    let first_word = line
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default();
+    if CONTROL_KEYWORDS.contains(&first_word) {
+        return false;
+    }
+    match line.find('(') {
+        Some(index) => {
+            let name = line[..index].trim_end();
+            !name.contains(['=', '.'])
+                && name
+                    .chars()
+                    .last()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+// extension of the file name, ignoring any query or fragment of urls
+fn file_name_language(name: &str) -> Option<CodeLanguage> {
+    let name = name.split(['?', '#']).next()?;
+    let extension = Path::new(name).extension()?.to_str()?;
+    CodeLanguage::from_extension(extension)
+}
+
+// the MIME type is the most reliable, plain text files are recognized by their name or shebang
+pub fn detect_language(
+    resource_type: &str,
+    file_names: &[&str],
+    source: &str,
+) -> Option<CodeLanguage> {
+    CodeLanguage::from_mime_type(resource_type)
+        .or_else(|| file_names.iter().find_map(|name| file_name_language(name)))
+        .or_else(|| CodeLanguage::from_shebang(source))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeChunk {
+    pub content: String,
+    // first line of the first definition in the chunk
+    pub section: Option<String>,
+}
+
+struct SourceLine<'a> {
+    text: &'a str,
+    // brace depth or indentation width, `None` for blank lines
+    level: Option<usize>,
+}
+
+// tracks strings and comments across lines so braces inside them are not counted
+#[derive(Default)]
+struct BraceScanner {
+    in_block_comment: bool,
+    in_string: Option<char>,
+    depth: usize,
+}
+
+impl BraceScanner {
+    fn scan(&mut self, line: &str, language: CodeLanguage) {
+        let chars: Vec<(usize, char)> = line.char_indices().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let (at, c) = chars[i];
+            let rest = &line[at..];
+            if self.in_block_comment {
+                if rest.starts_with("*/") {
+                    self.in_block_comment = false;
+                    i += 1;
+                }
+            } else if let Some(quote) = self.in_string {
+                if c == '\\' {
+                    i += 1;
+                } else if c == quote {
+                    self.in_string = None;
+                }
+            } else if language
+                .line_comments()
+                .iter()
+                .any(|comment| rest.starts_with(comment))
+                // `#` only starts a comment as its own word, not in `$#` or `a#b`
+                && (c != '#' || i == 0 || chars[i - 1].1.is_whitespace())
+            {
+                break;
+            } else if rest.starts_with("/*") && language != CodeLanguage::Shell {
+                self.in_block_comment = true;
+                i += 1;
+            } else if c == '\'' && language == CodeLanguage::Rust {
+                // char literals, anything else is a lifetime
+                if rest.starts_with("'\\") {
+                    i += chars[i + 2..]
+                        .iter()
+                        .position(|&(_, c)| c == '\'')
+                        .map_or(chars.len(), |end| end + 2);
+                } else if chars.get(i + 2).is_some_and(|&(_, c)| c == '\'') {
+                    i += 2;
+                }
+            } else if c == '"' || c == '\'' || c == '`' {
+                self.in_string = Some(c);
+            } else if c == '{' {
+                self.depth += 1;
+            } else if c == '}' {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            i += 1;
+        }
+        // only template literals and double quoted strings continue on the next line
+        if matches!(self.in_string, Some('\'')) {
+            self.in_string = None;
+        }
+    }
+}
+
+fn source_lines(source: &str, language: CodeLanguage) -> Vec<SourceLine<'_>> {
+    let mut scanner = BraceScanner::default();
+    source
+        .lines()
+        .map(|text| {
+            let level = if text.trim().is_empty() {
+                None
+            } else if language.uses_braces() {
+                Some(scanner.depth)
+            } else {
+                Some(
+                    text.chars()
+                        .take_while(|c| c.is_whitespace())
+                        .map(|c| if c == '\t' { 4 } else { 1 })
+                        .sum(),
+                )
+            };
+            if language.uses_braces() {
+                scanner.scan(text, language);
+            }
+            SourceLine { text, level }
+        })
+        .collect()
+}
+
+// splits lines into runs starting at the definitions on the given level,
+// the first run holds whatever comes before the first definition
+fn split_definitions(
+    lines: &[SourceLine],
+    level: usize,
+    language: CodeLanguage,
+) -> Vec<std::ops::Range<usize>> {
+    let mut starts = vec![0];
+    for (index, line) in lines.iter().enumerate() {
+        if line.level != Some(level) || !language.is_definition(line.text) {
+            continue;
+        }
+        let mut start = index;
+        while start > 0
+            && lines[start - 1].level == Some(level)
+            && language.is_leading_line(lines[start - 1].text)
+        {
+            start -= 1;
+        }
+        if start > *starts.last().unwrap_or(&0) {
+            starts.push(start);
+        }
+    }
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(std::iter::once(&lines.len())))
+        .map(|(&start, &end)| start..end)
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+fn join_lines(lines: &[SourceLine]) -> String {
+    lines
+        .iter()
+        .map(|line| line.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches(['\n', '\r'])
+        .to_owned()
+}
+
+fn definition_line(lines: &[SourceLine], level: usize, language: CodeLanguage) -> Option<String> {
+    lines
+        .iter()
+        .find(|line| {
+            line.level.is_some_and(|line_level| line_level <= level)
+                && language.is_definition(line.text)
+        })
+        .map(|line| line.text.trim().chars().take(120).collect())
+}
+
+// last resort for definitions too long to split any further
+fn split_by_lines(
+    lines: &[SourceLine],
+    section: Option<String>,
+    max_size: usize,
+) -> Vec<CodeChunk> {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    for line in lines {
+        let mut text = line.text;
+        while !text.is_empty() {
+            if !current.is_empty() && current.len() + 1 + text.len() > max_size {
+                chunks.push(std::mem::take(&mut current));
+            }
+            // minified code can have lines longer than a chunk
+            let mut end = text.len().min(max_size);
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&text[..end]);
+            text = &text[end..];
+        }
+    }
+    chunks.push(current);
+    chunks
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .map(|content| CodeChunk {
+            content,
+            section: section.clone(),
+        })
+        .collect()
+}
+
+fn chunk_lines(
+    lines: &[SourceLine],
+    level: usize,
+    language: CodeLanguage,
+    max_size: usize,
+) -> Vec<CodeChunk> {
+    let mut pieces = vec![];
+    for range in split_definitions(lines, level, language) {
+        let unit = &lines[range];
+        let content = join_lines(unit);
+        if content.trim().is_empty() {
+            continue;
+        }
+        let section = definition_line(unit, level, language);
+        if content.len() <= max_size {
+            pieces.push(CodeChunk { content, section });
+            continue;
+        }
+
+        // too long, try the definitions nested one level deeper, e.g. the methods of a class
+        let nested_level = unit
+            .iter()
+            .skip(1)
+            .filter_map(|line| line.level)
+            .filter(|&nested| nested > level)
+            .min();
+        match nested_level {
+            Some(nested_level) if split_definitions(unit, nested_level, language).len() > 1 => {
+                pieces.extend(chunk_lines(unit, nested_level, language, max_size))
+            }
+            _ => pieces.extend(split_by_lines(unit, section, max_size)),
+        }
+    }
+
+    // small neighbouring definitions are embedded together
+    let mut chunks: Vec<CodeChunk> = vec![];
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(last) if last.content.len() + 1 + piece.content.len() <= max_size => {
+                last.content.push('\n');
+                last.content.push_str(&piece.content);
+                if last.section.is_none() {
+                    last.section = piece.section;
+                }
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+// splits source code at its top level definitions, definitions longer than `max_size` are
+// split at the ones nested inside them
+pub fn chunk_code(source: &str, language: CodeLanguage, max_size: usize) -> Vec<CodeChunk> {
+    let lines = source_lines(source, language);
+    chunk_lines(&lines, 0, language, max_size)
+}
+
+// source files and snippets, other plain text is handled like before by the space data extractor
+pub struct CodeExtractor;
+
+impl Extractor for CodeExtractor {
+    fn name(&self) -> &str {
+        "code"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        CODE_MIME_TYPES
+            .iter()
+            .map(|(mime_type, _)| *mime_type)
+            .filter(|mime_type| !mime_type.starts_with("text/"))
+            .chain(std::iter::once("text/*"))
+            .map(|mime_type| mime_type.to_owned())
+            .collect()
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let data = std::fs::read(&resource.resource.resource_path)?;
+        let source = String::from_utf8_lossy(&data);
+
+        let mut file_names = vec![];
+        if let Some(metadata) = &resource.metadata {
+            file_names.push(metadata.name.as_str());
+            file_names.push(metadata.source_uri.as_str());
+        }
+        file_names.push(resource.resource.resource_path.as_str());
+        let language = match detect_language(&resource.resource.resource_type, &file_names, &source)
+        {
+            Some(language) => language,
+            None => return SpaceDataExtractor.extract(resource, ctx),
+        };
+
+        let mut extraction = Extraction::default();
+        for chunk in chunk_code(&source, language, MAX_CHUNK_SIZE) {
+            let mut metadata = create_metadata_from_resource(resource);
+            metadata.section = chunk.section;
+            extraction.push(ResourceTextContentType::Code, chunk.content, metadata);
+        }
+        extraction.tags.push(ResourceTag {
+            id: random_uuid(),
+            resource_id: resource.resource.id.clone(),
+            tag_name: CODE_LANGUAGE_TAG.to_owned(),
+            tag_value: language.as_str().to_owned(),
+        });
+        Ok(extraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("text/x-python; charset=utf-8", &[], ""),
+            Some(CodeLanguage::Python)
+        );
+        assert_eq!(
+            detect_language(
+                "text/plain",
+                &["", "https://example.com/src/main.rs?raw=1"],
+                ""
+            ),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(
+            detect_language("text/plain", &["deploy"], "#!/usr/bin/env bash\nset -e\n"),
+            Some(CodeLanguage::Shell)
+        );
+        assert_eq!(
+            detect_language("text/markdown", &["README.md"], "# hi"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chunk_rust_at_definitions() {
+        let source = r#"use std::fmt;
+
+/// a point
+#[derive(Debug)]
+struct Point {
+    x: i32,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}}}", self.x)
+    }
+}
+
+// not a closing brace: '}'
+fn origin<'a>() -> Point {
+    Point { x: 0 }
+}
+"#;
+        let chunks = chunk_code(source, CodeLanguage::Rust, 150);
+        let sections: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.section.as_deref())
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                Some("struct Point {"),
+                Some("impl fmt::Display for Point {"),
+                Some("fn origin<'a>() -> Point {"),
+            ]
+        );
+        assert!(chunks[0]
+            .content
+            .starts_with("use std::fmt;\n/// a point\n#[derive(Debug)]"));
+        assert!(chunks[1].content.ends_with("    }\n}"));
+        assert!(chunks[2].content.starts_with("// not a closing brace"));
+
+        // everything fits in one chunk when the limit allows it
+        assert_eq!(chunk_code(source, CodeLanguage::Rust, 2000).len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_long_python_class_at_methods() {
+        let source = "import os\n\n\nclass Store:\n    \"\"\"keeps things\"\"\"\n    @property\n    def path(self):\n        return os.getcwd()\n\n    def save(self, item):\n        self.items.append(item)\n        return len(self.items)\n\n\ndef main():\n    Store().save(1)\n";
+        let chunks = chunk_code(source, CodeLanguage::Python, 100);
+        let sections: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.section.as_deref())
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                None,
+                Some("class Store:"),
+                Some("def save(self, item):"),
+                Some("def main():"),
+            ]
+        );
+        assert_eq!(chunks[0].content, "import os");
+        // the class itself stays together with its first method
+        assert!(chunks[1]
+            .content
+            .contains("\"\"\"keeps things\"\"\"\n    @property"));
+        assert!(chunks.iter().all(|chunk| chunk.content.len() <= 100));
+    }
+}
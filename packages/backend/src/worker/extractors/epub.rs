@@ -0,0 +1,390 @@
+use super::{
+    office::{
+        attribute_value, decode_text, normalize_whitespace, open_archive, read_archive_entry,
+        Section,
+    },
+    Extraction, Extractor, ExtractorContext,
+};
+use crate::{
+    store::models::{CompositeResource, ResourceTextContentMetadata, ResourceTextContentType},
+    BackendError, BackendResult,
+};
+
+use quick_xml::{events::Event, Reader};
+use std::collections::HashMap;
+
+const EPUB_MIME_TYPE: &str = "application/epub+zip";
+
+pub struct EpubExtractor;
+
+impl Extractor for EpubExtractor {
+    fn name(&self) -> &str {
+        "epub"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![EPUB_MIME_TYPE.to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut archive = open_archive(&resource.resource.resource_path)?;
+        let container = read_archive_entry(&mut archive, "META-INF/container.xml")?;
+        let package_path = parse_container(&container)?;
+        let package = read_archive_entry(&mut archive, &package_path)?;
+
+        let mut extraction = Extraction::default();
+        for chapter_path in parse_package_spine(&package, &package_path)? {
+            // a broken chapter should not cost us the rest of the book
+            let chapter = match read_archive_entry(&mut archive, &chapter_path)
+                .and_then(|xhtml| parse_chapter(&xhtml))
+            {
+                Ok(chapter) => chapter,
+                Err(err) => {
+                    tracing::warn!("skipping epub chapter {chapter_path}: {err}");
+                    continue;
+                }
+            };
+            let content = chapter.to_content();
+            if content.is_empty() {
+                continue;
+            }
+
+            // reflowable chapters have no page numbers
+            extraction.push(
+                ResourceTextContentType::Document,
+                content,
+                ResourceTextContentMetadata {
+                    url: resource.metadata.as_ref().map(|m| m.source_uri.clone()),
+                    section: chapter.title,
+                    ..Default::default()
+                },
+            );
+        }
+        Ok(extraction)
+    }
+}
+
+// `META-INF/container.xml` points at the OPF package document
+fn parse_container(xml: &str) -> BackendResult<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if e.local_name().as_ref() == b"rootfile" =>
+            {
+                if let Some(path) = attribute_value(e, b"full-path") {
+                    return Ok(path);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(BackendError::GenericError(format!(
+                    "failed to parse container.xml: {e}"
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    Err(BackendError::GenericError(
+        "epub container has no rootfile".to_owned(),
+    ))
+}
+
+// resolves an href relative to the directory of `base_path`, hrefs are archive paths
+// so `..` can't escape the archive root
+fn resolve_href(base_path: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<&str> = base_path.split('/').collect();
+    parts.pop();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+// returns the archive paths of the reading-order (linear) spine documents
+fn parse_package_spine(xml: &str, package_path: &str) -> BackendResult<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) =
+                        (attribute_value(e, b"id"), attribute_value(e, b"href"))
+                    {
+                        manifest.insert(id, href);
+                    }
+                }
+                b"itemref" => {
+                    let linear = attribute_value(e, b"linear").unwrap_or_default();
+                    if linear != "no" {
+                        if let Some(idref) = attribute_value(e, b"idref") {
+                            spine.push(idref);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(BackendError::GenericError(format!(
+                    "failed to parse epub package: {e}"
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(spine
+        .iter()
+        .filter_map(|idref| manifest.get(idref))
+        .map(|href| resolve_href(package_path, href))
+        .collect())
+}
+
+fn is_block_element(name: &[u8]) -> bool {
+    matches!(
+        name,
+        b"p" | b"div"
+            | b"li"
+            | b"blockquote"
+            | b"pre"
+            | b"td"
+            | b"th"
+            | b"dt"
+            | b"dd"
+            | b"figcaption"
+            | b"section"
+            | b"article"
+            | b"aside"
+    )
+}
+
+fn flush_paragraph(text: &mut String, chapter: &mut Section) {
+    let paragraph = normalize_whitespace(text);
+    if !paragraph.is_empty() {
+        chapter.paragraphs.push(paragraph);
+    }
+    text.clear();
+}
+
+fn is_heading_element(name: &[u8]) -> bool {
+    matches!(name, b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6")
+}
+
+// one chapter per spine document, titled by its first heading or else its `<title>`
+fn parse_chapter(xhtml: &str) -> BackendResult<Section> {
+    let mut reader = Reader::from_str(xhtml);
+    reader.check_end_names(false);
+    let mut buf = Vec::new();
+
+    let mut chapter = Section::default();
+    let mut document_title = String::new();
+    let mut heading: Option<String> = None;
+    let mut text = String::new();
+    let mut in_title = false;
+    let mut skip_depth = 0;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name();
+                let name = name.as_ref();
+                if skip_depth > 0 || matches!(name, b"script" | b"style") {
+                    skip_depth += 1;
+                } else if name == b"title" {
+                    in_title = true;
+                } else if is_heading_element(name) {
+                    flush_paragraph(&mut text, &mut chapter);
+                    heading = Some(String::new());
+                } else if is_block_element(name) {
+                    flush_paragraph(&mut text, &mut chapter);
+                }
+            }
+            Ok(Event::Empty(ref e)) if skip_depth == 0 && e.local_name().as_ref() == b"br" => {
+                match heading.as_mut() {
+                    Some(heading) => heading.push(' '),
+                    None => text.push('\n'),
+                }
+            }
+            Ok(Event::Text(ref e)) if skip_depth == 0 => {
+                let decoded = decode_text(e);
+                if in_title {
+                    document_title.push_str(&decoded);
+                } else if let Some(heading) = heading.as_mut() {
+                    heading.push_str(&decoded);
+                } else {
+                    text.push_str(&decoded);
+                }
+            }
+            Ok(Event::CData(ref e)) if skip_depth == 0 && !in_title => {
+                text.push_str(&String::from_utf8_lossy(e));
+            }
+            Ok(Event::End(ref e)) => {
+                let name = e.local_name();
+                let name = name.as_ref();
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                } else if name == b"title" {
+                    in_title = false;
+                } else if is_heading_element(name) {
+                    if let Some(heading) = heading.take() {
+                        let heading = normalize_whitespace(&heading);
+                        if chapter.title.is_none() && !heading.is_empty() {
+                            chapter.title = Some(heading);
+                        } else if !heading.is_empty() {
+                            chapter.paragraphs.push(heading);
+                        }
+                    }
+                } else if is_block_element(name) {
+                    flush_paragraph(&mut text, &mut chapter);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(BackendError::GenericError(format!(
+                    "failed to parse chapter: {e}"
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    flush_paragraph(&mut text, &mut chapter);
+
+    if chapter.title.is_none() {
+        let document_title = normalize_whitespace(&document_title);
+        if !document_title.is_empty() {
+            chapter.title = Some(document_title);
+        }
+    }
+    Ok(chapter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_epub(path: &std::path::Path) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::FileOptions::default();
+
+        let files = [
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <manifest>
+    <item id="c2" href="text/chapter2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/chapter1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="notes" href="notes.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="c1"/>
+    <itemref idref="notes" linear="no"/>
+    <itemref idref="c2"/>
+  </spine>
+</package>"#,
+            ),
+            (
+                "OEBPS/text/chapter1.xhtml",
+                r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Ignored</title>
+<style>p { color: red; }</style></head>
+<body><h1>The&nbsp;Beginning</h1><p>It was a <em>dark</em> night.</p><p>Then it rained.</p></body></html>"#,
+            ),
+            (
+                "OEBPS/text/chapter2.xhtml",
+                r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Second</title></head>
+<body><div>Untitled text<br/>on two lines</div></body></html>"#,
+            ),
+            (
+                "OEBPS/notes.xhtml",
+                r#"<html xmlns="http://www.w3.org/1999/xhtml"><body><p>notes</p></body></html>"#,
+            ),
+        ];
+        for (name, content) in files {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "text/ch1.xhtml#start"),
+            "OEBPS/text/ch1.xhtml"
+        );
+        assert_eq!(
+            resolve_href("OEBPS/text/nav.xhtml", "../ch1.xhtml"),
+            "OEBPS/ch1.xhtml"
+        );
+        assert_eq!(resolve_href("content.opf", "ch1.xhtml"), "ch1.xhtml");
+    }
+
+    #[test]
+    fn test_epub_chapters_follow_the_spine() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.epub");
+        write_epub(&path);
+
+        let mut archive = open_archive(path.to_str().unwrap()).unwrap();
+        let container = read_archive_entry(&mut archive, "META-INF/container.xml").unwrap();
+        let package_path = parse_container(&container).unwrap();
+        let package = read_archive_entry(&mut archive, &package_path).unwrap();
+        let spine = parse_package_spine(&package, &package_path).unwrap();
+        assert_eq!(
+            spine,
+            vec!["OEBPS/text/chapter1.xhtml", "OEBPS/text/chapter2.xhtml"]
+        );
+
+        let chapters: Vec<Section> = spine
+            .iter()
+            .map(|path| parse_chapter(&read_archive_entry(&mut archive, path).unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            chapters,
+            vec![
+                Section {
+                    title: Some("The Beginning".to_string()),
+                    paragraphs: vec![
+                        "It was a dark night.".to_string(),
+                        "Then it rained.".to_string()
+                    ],
+                },
+                Section {
+                    title: Some("Second".to_string()),
+                    paragraphs: vec!["Untitled text\non two lines".to_string()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    store::models::{ResourceFrontmatter, ResourceMetadata, ResourceTag},
+    BackendError, BackendResult,
+};
+
+use serde_yaml::{Mapping, Value};
+
+pub const HASHTAG_TAG: &str = "hashtag";
+// any other frontmatter key is stored as a tag named `frontmatter.<key>`
+pub const FRONTMATTER_TAG_PREFIX: &str = "frontmatter.";
+
+// the first key found wins, the first one is also used when writing the frontmatter back
+const TITLE_KEYS: [&str; 2] = ["title", "name"];
+const SOURCE_KEYS: [&str; 4] = ["source", "url", "source_uri", "link"];
+const AUTHOR_KEYS: [&str; 2] = ["author", "byline"];
+const CREATED_KEYS: [&str; 4] = ["created", "date", "published", "created_at"];
+const TAGS_KEYS: [&str; 2] = ["tags", "tag"];
+
+// yaml and body of a document starting with a `---` line, `None` if it has no frontmatter
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+pub fn parse_markdown_with_frontmatter(content: &str) -> BackendResult<(String, Value)> {
+    match split_frontmatter(content) {
+        None => Ok((content.to_string(), Value::Null)),
+        Some((yaml, body)) => {
+            let frontmatter = serde_yaml::from_str(yaml).map_err(|e| {
+                BackendError::GenericError(format!("Failed to parse frontmatter: {}", e))
+            })?;
+            Ok((body.trim().to_string(), frontmatter))
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_owned()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Tagged(tagged) => scalar_to_string(&tagged.value),
+        _ => None,
+    }
+}
+
+// scalars and lists of scalars, maps and nested lists are skipped
+fn values_to_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(values) => values.iter().filter_map(scalar_to_string).collect(),
+        value => scalar_to_string(value).into_iter().collect(),
+    }
+}
+
+// `tags: [a, b]`, `tags: a, b` and `tags: "#a #b"` all work
+fn parse_hashtags(value: &Value) -> Vec<String> {
+    let mut hashtags: Vec<String> = vec![];
+    for value in values_to_strings(value) {
+        for hashtag in value.split([',', ' ']) {
+            let hashtag = hashtag.trim().trim_start_matches('#');
+            if !hashtag.is_empty() && !hashtags.iter().any(|h| h == hashtag) {
+                hashtags.push(hashtag.to_owned());
+            }
+        }
+    }
+    hashtags
+}
+
+pub fn resource_frontmatter(frontmatter: &Value) -> ResourceFrontmatter {
+    let mut result = ResourceFrontmatter::default();
+    let mapping = match frontmatter {
+        Value::Mapping(mapping) => mapping,
+        _ => return result,
+    };
+    let find = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| mapping.get(*key).and_then(scalar_to_string))
+    };
+    result.title = find(&TITLE_KEYS);
+    result.source_uri = find(&SOURCE_KEYS);
+    result.byline = find(&AUTHOR_KEYS);
+    result.published_at = find(&CREATED_KEYS);
+    result.hashtags = TAGS_KEYS
+        .iter()
+        .find_map(|key| mapping.get(*key))
+        .map(parse_hashtags);
+
+    let known_keys = [
+        &TITLE_KEYS[..],
+        &SOURCE_KEYS[..],
+        &AUTHOR_KEYS[..],
+        &CREATED_KEYS[..],
+        &TAGS_KEYS[..],
+    ]
+    .concat();
+    for (key, value) in mapping {
+        let key = match scalar_to_string(key) {
+            Some(key) if !known_keys.contains(&key.as_str()) => key,
+            _ => continue,
+        };
+        for value in values_to_strings(value) {
+            result.fields.push((key.clone(), value));
+        }
+    }
+    result
+}
+
+pub fn frontmatter_tags(frontmatter: &ResourceFrontmatter, resource_id: &str) -> Vec<ResourceTag> {
+    let tag = |name: String, value: &str| ResourceTag {
+        id: String::new(),
+        resource_id: resource_id.to_owned(),
+        tag_name: name,
+        tag_value: value.to_owned(),
+    };
+    frontmatter
+        .hashtags
+        .iter()
+        .flatten()
+        .map(|hashtag| tag(HASHTAG_TAG.to_owned(), hashtag))
+        .chain(
+            frontmatter
+                .fields
+                .iter()
+                .map(|(key, value)| tag(format!("{FRONTMATTER_TAG_PREFIX}{key}"), value)),
+        )
+        .collect()
+}
+
+// numbers and booleans were stored as strings, write them back unquoted
+fn field_value(value: &str) -> Value {
+    match serde_yaml::from_str::<Value>(value) {
+        Ok(parsed @ (Value::Number(_) | Value::Bool(_))) => parsed,
+        _ => Value::String(value.to_owned()),
+    }
+}
+
+fn push_values(mapping: &mut Mapping, key: &str, values: Vec<Value>) {
+    let value = match values.len() {
+        0 => return,
+        1 => values.into_iter().next().unwrap_or_default(),
+        _ => Value::Sequence(values),
+    };
+    mapping.insert(Value::String(key.to_owned()), value);
+}
+
+// the inverse of `resource_frontmatter`, `None` if there is nothing to write
+pub fn render_frontmatter(
+    metadata: Option<&ResourceMetadata>,
+    tags: &[ResourceTag],
+) -> BackendResult<Option<String>> {
+    let mut mapping = Mapping::new();
+    if let Some(metadata) = metadata {
+        let fields = [
+            (TITLE_KEYS[0], Some(&metadata.name)),
+            (SOURCE_KEYS[0], Some(&metadata.source_uri)),
+            (AUTHOR_KEYS[0], metadata.byline.as_ref()),
+            (CREATED_KEYS[0], metadata.published_at.as_ref()),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                mapping.insert(Value::String(key.to_owned()), Value::String(value.clone()));
+            }
+        }
+    }
+
+    let hashtags = tags
+        .iter()
+        .filter(|tag| tag.tag_name == HASHTAG_TAG)
+        .map(|tag| Value::String(tag.tag_value.clone()))
+        .collect::<Vec<_>>();
+    if !hashtags.is_empty() {
+        mapping.insert(
+            Value::String(TAGS_KEYS[0].to_owned()),
+            Value::Sequence(hashtags),
+        );
+    }
+
+    // keys keep the order they were first seen in
+    let mut keys: Vec<&str> = vec![];
+    for tag in tags {
+        if let Some(key) = tag.tag_name.strip_prefix(FRONTMATTER_TAG_PREFIX) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    for key in keys {
+        let values = tags
+            .iter()
+            .filter(|tag| tag.tag_name.strip_prefix(FRONTMATTER_TAG_PREFIX) == Some(key))
+            .map(|tag| field_value(&tag.tag_value))
+            .collect();
+        push_values(&mut mapping, key, values);
+    }
+
+    if mapping.is_empty() {
+        return Ok(None);
+    }
+    let yaml = serde_yaml::to_string(&mapping).map_err(|e| {
+        BackendError::GenericError(format!("Failed to serialize frontmatter: {}", e))
+    })?;
+    Ok(Some(format!("---\n{yaml}---\n")))
+}
+
+// replaces the frontmatter of the document with the current metadata and tags
+pub fn with_frontmatter(
+    content: &str,
+    metadata: Option<&ResourceMetadata>,
+    tags: &[ResourceTag],
+) -> BackendResult<String> {
+    let body = split_frontmatter(content)
+        .map(|(_, body)| body.trim_start_matches(['\r', '\n']))
+        .unwrap_or(content);
+    Ok(match render_frontmatter(metadata, tags)? {
+        Some(frontmatter) => format!("{frontmatter}\n{body}"),
+        None => body.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontmatter_to_tags_and_metadata() {
+        let content = "---\ntitle: Reading list\nsource: https://example.com/list\ncreated: 2024-03-01\ntags: [books, '#later']\nstatus: draft\npriority: 2\naliases:\n  - list\n  - books to read\nnested:\n  a: 1\n---\n\nA horizontal rule\n\n---\n\nis not frontmatter\n";
+        let (body, value) = parse_markdown_with_frontmatter(content).unwrap();
+        assert!(body.starts_with("A horizontal rule"));
+        assert!(body.ends_with("is not frontmatter"));
+
+        let frontmatter = resource_frontmatter(&value);
+        assert_eq!(frontmatter.title.as_deref(), Some("Reading list"));
+        assert_eq!(
+            frontmatter.source_uri.as_deref(),
+            Some("https://example.com/list")
+        );
+        assert_eq!(frontmatter.published_at.as_deref(), Some("2024-03-01"));
+        assert_eq!(
+            frontmatter.hashtags,
+            Some(vec!["books".to_owned(), "later".to_owned()])
+        );
+        assert_eq!(
+            frontmatter.fields,
+            vec![
+                ("status".to_owned(), "draft".to_owned()),
+                ("priority".to_owned(), "2".to_owned()),
+                ("aliases".to_owned(), "list".to_owned()),
+                ("aliases".to_owned(), "books to read".to_owned()),
+            ]
+        );
+
+        let (body, value) = parse_markdown_with_frontmatter("no --- frontmatter").unwrap();
+        assert_eq!(body, "no --- frontmatter");
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn test_frontmatter_round_trip() {
+        let content = "---\ntitle: Reading list\ntags:\n- books\npriority: 2\naliases:\n- list\n- books to read\n---\n\nbody\n";
+        let (_, value) = parse_markdown_with_frontmatter(content).unwrap();
+        let frontmatter = resource_frontmatter(&value);
+        let tags = frontmatter_tags(&frontmatter, "resource");
+        let metadata = ResourceMetadata {
+            id: String::new(),
+            resource_id: "resource".to_owned(),
+            name: frontmatter.title.clone().unwrap_or_default(),
+            source_uri: String::new(),
+            alt: String::new(),
+            user_context: String::new(),
+            byline: None,
+            published_at: None,
+            lead_image: None,
+            outline: None,
+        };
+
+        let exported = with_frontmatter("old body\n", Some(&metadata), &tags).unwrap();
+        assert_eq!(exported, content.replace("\nbody\n", "\nold body\n"));
+
+        let (_, reparsed) = parse_markdown_with_frontmatter(&exported).unwrap();
+        assert_eq!(resource_frontmatter(&reparsed), frontmatter);
+        assert_eq!(with_frontmatter(content, None, &[]).unwrap(), "body\n");
+    }
+}
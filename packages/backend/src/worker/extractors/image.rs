@@ -0,0 +1,441 @@
+use super::{
+    create_metadata_from_resource,
+    image_metadata::{perceptual_hash, read_image_metadata},
+    Extraction, Extractor, ExtractorContext,
+};
+use crate::{
+    store::models::{
+        random_uuid, BoundingBox, CompositeResource, OcrLine, OcrResult, OcrWord, ResourceTag,
+        ResourceTextContentType,
+    },
+    BackendError, BackendResult,
+};
+
+use ocrs::{ImageSource, OcrEngine};
+use rten::{ctc::CtcDecoder, Model};
+use rten_imageproc::RotatedRect;
+use rten_tensor::{prelude::*, NdTensor, NdTensorView, Tensor};
+use std::convert::TryInto;
+
+// the alphabet the ocrs models were trained with, ocrs keeps its copy private.
+// nb. the "E" before "ABCDE" stands for the euro sign, same as in ocrs
+const OCR_ALPHABET: &str = " 0123456789!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~EABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// words the recognition model is less sure about than this are mostly noise from icons,
+// borders or textures
+const MIN_WORD_CONFIDENCE: f32 = 0.5;
+
+// tags set from the image file itself
+pub const CAPTURED_AT_TAG: &str = "capturedAt";
+pub const CAMERA_TAG: &str = "camera";
+// `<latitude>,<longitude>` in decimal degrees
+pub const LOCATION_TAG: &str = "location";
+// `<width>x<height>` in pixels
+pub const DIMENSIONS_TAG: &str = "dimensions";
+
+// `OcrEngine::recognize_text` only returns the most likely characters, so lines are run
+// through a second copy of the recognition model to keep the probability of each one
+pub struct OcrModels {
+    pub engine: OcrEngine,
+    pub recognition_model: Model,
+}
+
+// a recognized character and the columns of the source image it covers
+struct RecognizedChar {
+    char: char,
+    left: u32,
+    right: u32,
+    probability: f32,
+}
+
+pub struct ImageOcrExtractor;
+
+impl Extractor for ImageOcrExtractor {
+    fn name(&self) -> &str {
+        "image_ocr"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec!["image/*".to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut extraction = Extraction::default();
+        let bytes = std::fs::read(&resource.resource.resource_path)
+            .map_err(|e| BackendError::GenericError(format!("failed to read image: {}", e)))?;
+
+        let metadata = read_image_metadata(&bytes);
+        let mut tags = vec![
+            (CAPTURED_AT_TAG, metadata.captured_at.clone()),
+            (CAMERA_TAG, metadata.camera()),
+            (
+                LOCATION_TAG,
+                metadata.gps.map(|(lat, lon)| format!("{lat:.6},{lon:.6}")),
+            ),
+        ];
+
+        // formats the image crate can't decode, e.g. svg, still get their metadata tags
+        let image = image::load_from_memory(&bytes);
+        if let Ok(image) = &image {
+            tags.push((
+                DIMENSIONS_TAG,
+                Some(format!("{}x{}", image.width(), image.height())),
+            ));
+            extraction.perceptual_hash = Some(perceptual_hash(image));
+        }
+        extraction.tags = tags
+            .into_iter()
+            .filter_map(|(name, value)| {
+                Some(ResourceTag {
+                    id: random_uuid(),
+                    resource_id: resource.resource.id.clone(),
+                    tag_name: name.to_owned(),
+                    tag_value: value?,
+                })
+            })
+            .collect();
+
+        // undecodable images keep the tags-only extraction instead of failing
+        if let (Some(ocr), Ok(image)) = (ctx.ocr, image) {
+            let image = image.into_rgb8();
+            let result = ocr_image(ocr, &image, ctx.ocr_language).map_err(|e| {
+                BackendError::GenericError(format!("image processing error: {}", e))
+            })?;
+            if !result.lines.is_empty() {
+                let mut metadata = create_metadata_from_resource(resource);
+                let text = result.text();
+                metadata.ocr = Some(result);
+                extraction.push(ResourceTextContentType::Image, text, metadata);
+            }
+        }
+        Ok(extraction)
+    }
+}
+
+pub(super) fn ocr_image(
+    ocr: &OcrModels,
+    img: &image::RgbImage,
+    language: Option<&str>,
+) -> Result<OcrResult, Box<dyn std::error::Error>> {
+    let img_source = ImageSource::from_bytes(img.as_raw(), img.dimensions())?;
+
+    let ocr_input = ocr.engine.prepare_input(img_source)?;
+    let word_rects = ocr.engine.detect_words(&ocr_input)?;
+    let line_rects = ocr.engine.find_text_lines(&ocr_input, &word_rects);
+
+    let mut lines = Vec::new();
+    for line_rect in line_rects.iter().filter(|rects| !rects.is_empty()) {
+        let line_image = ocr
+            .engine
+            .prepare_recognition_input(&ocr_input, line_rect)?;
+        let [height, width] = line_image.shape();
+        let input: Tensor<f32> = line_image.into_shape([1, 1, height, width]).into();
+        let mut output: NdTensor<f32, 3> = ocr
+            .recognition_model
+            .run_one((&input).into(), None)?
+            .try_into()
+            .map_err(|_| "unexpected output of the recognition model")?;
+        // [seq, batch, label] => [batch, seq, label]
+        output.permute([1, 0, 2]);
+
+        let bbox = rects_bounding_box(line_rect);
+        let chars = decode_line(output.slice([0]), width, &bbox);
+        if let Some(line) = build_line(split_words(&chars, &bbox)) {
+            lines.push(line);
+        }
+    }
+
+    Ok(OcrResult {
+        language: language.map(|language| language.to_owned()),
+        image_width: img.width(),
+        image_height: img.height(),
+        lines,
+    })
+}
+
+fn rects_bounding_box(rects: &[RotatedRect]) -> BoundingBox {
+    let corners = || rects.iter().flat_map(|rect| rect.corners());
+    let left = corners()
+        .map(|p| p.x)
+        .fold(f32::MAX, f32::min)
+        .floor()
+        .max(0.);
+    let top = corners()
+        .map(|p| p.y)
+        .fold(f32::MAX, f32::min)
+        .floor()
+        .max(0.);
+    let right = corners()
+        .map(|p| p.x)
+        .fold(f32::MIN, f32::max)
+        .ceil()
+        .max(left);
+    let bottom = corners()
+        .map(|p| p.y)
+        .fold(f32::MIN, f32::max)
+        .ceil()
+        .max(top);
+    BoundingBox {
+        x: left as u32,
+        y: top as u32,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    }
+}
+
+// greedy ctc decoding like `OcrEngine::recognize_text`, but keeping the probability of
+// every character. `log_probs` is the `[seq, label]` output for a line image that was
+// `input_width` pixels wide, characters are mapped back onto `line` the same way ocrs does
+fn decode_line(
+    log_probs: NdTensorView<f32, 2>,
+    input_width: usize,
+    line: &BoundingBox,
+) -> Vec<RecognizedChar> {
+    let [seq_len, _] = log_probs.shape();
+    if seq_len == 0 || input_width == 0 {
+        return Vec::new();
+    }
+    let hypothesis = CtcDecoder::new().decode_greedy(log_probs.view());
+    let steps = hypothesis.steps();
+
+    let downsample_factor = (input_width as f32 / seq_len as f32).round().max(1.);
+    let x_scale = line.width as f32 / input_width as f32;
+    let to_image_x = |pos: f32| line.x + (pos * x_scale) as u32;
+    let line_right = line.x + line.width;
+
+    steps
+        .iter()
+        .enumerate()
+        .filter_map(|(i, step)| {
+            let left = to_image_x(step.pos as f32 * downsample_factor);
+            let right = steps
+                .get(i + 1)
+                .map(|next| to_image_x(next.pos as f32 * downsample_factor))
+                .unwrap_or(line_right)
+                .min(line_right);
+            // the end of the model input can be padding outside of the line
+            if left >= line_right {
+                return None;
+            }
+            Some(RecognizedChar {
+                char: OCR_ALPHABET
+                    .chars()
+                    .nth(step.label as usize - 1)
+                    .unwrap_or('?'),
+                left,
+                right,
+                probability: log_probs[[step.pos as usize, step.label as usize]].exp(),
+            })
+        })
+        .collect()
+}
+
+// words are separated by recognized spaces, their confidence is the mean probability
+// of their characters
+fn split_words(chars: &[RecognizedChar], line: &BoundingBox) -> Vec<(String, BoundingBox, f32)> {
+    chars
+        .split(|c| c.char.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let text = word.iter().map(|c| c.char).collect();
+            let left = word[0].left;
+            let right = word[word.len() - 1].right.max(left);
+            let bbox = BoundingBox {
+                x: left,
+                y: line.y,
+                width: right - left,
+                height: line.height,
+            };
+            let confidence = word.iter().map(|c| c.probability).sum::<f32>() / word.len() as f32;
+            (text, bbox, confidence)
+        })
+        .collect()
+}
+
+// drops blank and low confidence words, a line without any words left is dropped entirely
+fn build_line(words: Vec<(String, BoundingBox, f32)>) -> Option<OcrLine> {
+    let words: Vec<OcrWord> = words
+        .into_iter()
+        .filter_map(|(text, bbox, confidence)| {
+            let text = text.trim().to_owned();
+            if text.is_empty() || confidence < MIN_WORD_CONFIDENCE {
+                return None;
+            }
+            Some(OcrWord {
+                text,
+                bbox,
+                confidence,
+            })
+        })
+        .collect();
+
+    let bbox = words
+        .iter()
+        .map(|word| word.bbox)
+        .reduce(|a, b| a.union(&b))?;
+    let confidence = words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32;
+    let text = words
+        .iter()
+        .map(|word| word.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(OcrLine {
+        text,
+        bbox,
+        confidence,
+        words,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rten::model_builder::{ModelBuilder, ModelFormat};
+
+    fn bbox(x: u32, y: u32, width: u32, height: u32) -> BoundingBox {
+        BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // a model without any operators, only for tests that never get to run it
+    fn empty_model() -> Model {
+        let mut builder = ModelBuilder::new(ModelFormat::V1);
+        let graph = builder.graph_builder().finish();
+        builder.set_graph(graph);
+        Model::load(builder.finish()).unwrap()
+    }
+
+    #[test]
+    fn test_undecodable_image_keeps_tags_with_ocr_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("icon.svg");
+        std::fs::write(
+            &path,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"></svg>"#,
+        )
+        .unwrap();
+
+        let now = crate::store::models::current_time();
+        let resource = CompositeResource {
+            resource: crate::store::models::Resource {
+                id: "icon".to_string(),
+                resource_path: path.to_str().unwrap().to_string(),
+                resource_type: "image/svg+xml".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            },
+            metadata: None,
+            text_content: None,
+            resource_tags: None,
+            resource_annotations: None,
+            post_processing_job: None,
+            space_ids: None,
+            thumbnail: None,
+        };
+        // an engine without models fails on any image it is asked to recognize
+        let ocr = OcrModels {
+            engine: OcrEngine::new(ocrs::OcrEngineParams::default()).unwrap(),
+            recognition_model: empty_model(),
+        };
+        let ctx = ExtractorContext {
+            ocr: Some(&ocr),
+            ocr_language: None,
+            language: None,
+            db_path: None,
+        };
+
+        let extraction = ImageOcrExtractor.extract(&resource, &ctx).unwrap();
+        assert!(extraction.contents.is_empty());
+        assert!(extraction.perceptual_hash.is_none());
+        assert!(extraction
+            .tags
+            .iter()
+            .all(|tag| tag.tag_name != DIMENSIONS_TAG));
+    }
+
+    #[test]
+    fn test_decode_line_keeps_character_probabilities() {
+        // 8 steps over 3 labels: blank, " " and "0", the model input was 32 pixels wide
+        let probs: [[f32; 3]; 8] = [
+            [0.9, 0.05, 0.05],
+            [0.1, 0.1, 0.8],
+            [0.2, 0.1, 0.7],
+            [0.9, 0.05, 0.05],
+            [0.1, 0.6, 0.3],
+            [0.1, 0.2, 0.7],
+            [0.9, 0.05, 0.05],
+            [0.9, 0.05, 0.05],
+        ];
+        let log_probs = NdTensor::from_data(
+            [8, 3],
+            probs.iter().flatten().map(|p| p.ln()).collect::<Vec<_>>(),
+        );
+        let chars = decode_line(log_probs.view(), 32, &bbox(100, 10, 64, 20));
+
+        let decoded: Vec<(char, u32, u32)> =
+            chars.iter().map(|c| (c.char, c.left, c.right)).collect();
+        assert_eq!(
+            decoded,
+            vec![('0', 108, 132), (' ', 132, 140), ('0', 140, 164)]
+        );
+        assert!((chars[0].probability - 0.8).abs() < 1e-5);
+        assert!((chars[1].probability - 0.6).abs() < 1e-5);
+
+        let words = split_words(&chars, &bbox(100, 10, 64, 20));
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].0, "0");
+        assert_eq!(words[0].1, bbox(108, 10, 24, 20));
+        assert!((words[1].2 - 0.7).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_build_line_filters_low_confidence_words_and_merges_boxes() {
+        let line = build_line(vec![
+            ("|".to_owned(), bbox(0, 10, 2, 20), 0.2),
+            ("C++".to_owned(), bbox(10, 10, 30, 20), 0.9),
+            (" ".to_owned(), bbox(40, 12, 5, 18), 0.9),
+            ("€".to_owned(), bbox(50, 12, 10, 22), 0.7),
+        ])
+        .unwrap();
+
+        assert_eq!(line.text, "C++ €");
+        assert_eq!(line.words.len(), 2);
+        assert_eq!(line.bbox, bbox(10, 10, 50, 24));
+        assert!((line.confidence - 0.8).abs() < 1e-5);
+
+        assert!(build_line(vec![("~~".to_owned(), bbox(0, 0, 5, 5), 0.1)]).is_none());
+    }
+
+    #[test]
+    fn test_ocr_result_metadata_round_trip() {
+        let result = OcrResult {
+            language: Some("en".to_owned()),
+            image_width: 200,
+            image_height: 100,
+            lines: vec![
+                build_line(vec![("Quarterly".to_owned(), bbox(5, 5, 80, 20), 0.9)]).unwrap(),
+                build_line(vec![
+                    ("revenue".to_owned(), bbox(5, 30, 70, 20), 0.8),
+                    ("grew".to_owned(), bbox(80, 30, 40, 20), 0.95),
+                ])
+                .unwrap(),
+            ],
+        };
+        assert_eq!(result.text(), "Quarterly\nrevenue grew");
+
+        let json = serde_json::to_string(&result).unwrap();
+        let parsed: OcrResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.lines[1].words[1].bbox, bbox(80, 30, 40, 20));
+        assert_eq!(parsed.language.as_deref(), Some("en"));
+    }
+}
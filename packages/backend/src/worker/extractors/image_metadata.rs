@@ -0,0 +1,608 @@
+use image::{imageops::FilterType, DynamicImage};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use std::convert::TryInto;
+
+// capture details read from the EXIF block and the XMP packet of an image file,
+// EXIF values win when both are present
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImageMetadata {
+    // local time of the camera, formatted as `YYYY-MM-DDTHH:MM:SS`
+    pub captured_at: Option<String>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    // latitude and longitude in decimal degrees
+    pub gps: Option<(f64, f64)>,
+}
+
+impl ImageMetadata {
+    // e.g. `Canon EOS R5`, the make is dropped when the model already starts with it
+    pub fn camera(&self) -> Option<String> {
+        match (&self.camera_make, &self.camera_model) {
+            (Some(make), Some(model)) => {
+                let first_word = make.split_whitespace().next().unwrap_or(make);
+                if model.to_lowercase().starts_with(&first_word.to_lowercase()) {
+                    Some(model.clone())
+                } else {
+                    Some(format!("{make} {model}"))
+                }
+            }
+            (None, Some(model)) => Some(model.clone()),
+            (Some(make), None) => Some(make.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+pub fn read_image_metadata(bytes: &[u8]) -> ImageMetadata {
+    let mut metadata = find_exif(bytes).and_then(parse_exif).unwrap_or_default();
+
+    if let Some(xmp) = find_xmp(bytes) {
+        let xmp = parse_xmp(&xmp);
+        metadata.captured_at = metadata.captured_at.or(xmp.captured_at);
+        metadata.camera_make = metadata.camera_make.or(xmp.camera_make);
+        metadata.camera_model = metadata.camera_model.or(xmp.camera_model);
+        metadata.gps = metadata.gps.or(xmp.gps);
+    }
+    metadata
+}
+
+// the TIFF structured EXIF payload of jpeg, png, webp and tiff files
+fn find_exif(bytes: &[u8]) -> Option<&[u8]> {
+    const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+    if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        return Some(bytes);
+    }
+
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        let mut pos = 2;
+        while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+            let marker = bytes[pos + 1];
+            // start of scan, the compressed image data follows
+            if marker == 0xDA {
+                break;
+            }
+            let length = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+            let segment = bytes.get(pos + 4..pos + 2 + length)?;
+            if marker == 0xE1 && segment.starts_with(EXIF_HEADER) {
+                return Some(&segment[EXIF_HEADER.len()..]);
+            }
+            pos += 2 + length;
+        }
+        return None;
+    }
+
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let mut pos = 8;
+        while pos + 8 <= bytes.len() {
+            let length = u32::from_be_bytes(bytes[pos..pos + 4].try_into().ok()?) as usize;
+            let chunk_type = &bytes[pos + 4..pos + 8];
+            let data = bytes.get(pos + 8..pos + 8 + length)?;
+            if chunk_type == b"eXIf" {
+                return Some(data);
+            }
+            // data is followed by a 4 byte crc
+            pos += 12 + length;
+        }
+        return None;
+    }
+
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let fourcc = &bytes[pos..pos + 4];
+            let length = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().ok()?) as usize;
+            let data = bytes.get(pos + 8..pos + 8 + length)?;
+            if fourcc == b"EXIF" {
+                return Some(data.strip_prefix(EXIF_HEADER).unwrap_or(data));
+            }
+            // chunks are padded to an even size
+            pos += 8 + length + (length & 1);
+        }
+    }
+    None
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+#[derive(Debug)]
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    // offset of the value inside the TIFF data, values of up to 4 bytes are stored inline
+    value_offset: usize,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn first_ifd(&self) -> Option<usize> {
+        self.u32(4).map(|offset| offset as usize)
+    }
+
+    fn entries(&self, ifd_offset: usize) -> Vec<IfdEntry> {
+        let count = match self.u16(ifd_offset) {
+            Some(count) => count as usize,
+            None => return vec![],
+        };
+        (0..count)
+            .filter_map(|i| {
+                let offset = ifd_offset + 2 + i * 12;
+                let tag = self.u16(offset)?;
+                let field_type = self.u16(offset + 2)?;
+                let count = self.u32(offset + 4)?;
+                let size = field_type_size(field_type)? * count as usize;
+                let value_offset = if size <= 4 {
+                    offset + 8
+                } else {
+                    self.u32(offset + 8)? as usize
+                };
+                Some(IfdEntry {
+                    tag,
+                    field_type,
+                    count,
+                    value_offset,
+                })
+            })
+            .collect()
+    }
+
+    fn ascii(&self, entry: &IfdEntry) -> Option<String> {
+        if entry.field_type != 2 {
+            return None;
+        }
+        let bytes = self
+            .data
+            .get(entry.value_offset..entry.value_offset + entry.count as usize)?;
+        let value = String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .trim()
+            .to_owned();
+        Some(value).filter(|value| !value.is_empty())
+    }
+
+    fn long(&self, entry: &IfdEntry) -> Option<u32> {
+        match entry.field_type {
+            3 => self.u16(entry.value_offset).map(|value| value as u32),
+            4 => self.u32(entry.value_offset),
+            _ => None,
+        }
+    }
+
+    fn rationals(&self, entry: &IfdEntry) -> Option<Vec<f64>> {
+        if entry.field_type != 5 {
+            return None;
+        }
+        (0..entry.count as usize)
+            .map(|i| {
+                let offset = entry.value_offset + i * 8;
+                let numerator = self.u32(offset)?;
+                let denominator = self.u32(offset + 4)?;
+                Some(if denominator == 0 {
+                    0.0
+                } else {
+                    numerator as f64 / denominator as f64
+                })
+            })
+            .collect()
+    }
+}
+
+fn field_type_size(field_type: u16) -> Option<usize> {
+    match field_type {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 => Some(4),
+        5 | 10 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_GPS_LATITUDE_REF: u16 = 0x0001;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE_REF: u16 = 0x0003;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+
+fn parse_exif(data: &[u8]) -> Option<ImageMetadata> {
+    let tiff = Tiff::new(data)?;
+    let mut metadata = ImageMetadata::default();
+    let mut modified_at = None;
+
+    for entry in tiff.entries(tiff.first_ifd()?) {
+        match entry.tag {
+            TAG_MAKE => metadata.camera_make = tiff.ascii(&entry),
+            TAG_MODEL => metadata.camera_model = tiff.ascii(&entry),
+            TAG_DATE_TIME => modified_at = tiff.ascii(&entry).and_then(|v| parse_exif_date(&v)),
+            TAG_EXIF_IFD => {
+                let exif_ifd = match tiff.long(&entry) {
+                    Some(offset) => offset as usize,
+                    None => continue,
+                };
+                for entry in tiff.entries(exif_ifd) {
+                    if entry.tag == TAG_DATE_TIME_ORIGINAL {
+                        metadata.captured_at = tiff.ascii(&entry).and_then(|v| parse_exif_date(&v));
+                    }
+                }
+            }
+            TAG_GPS_IFD => {
+                if let Some(offset) = tiff.long(&entry) {
+                    metadata.gps = parse_gps(&tiff, offset as usize);
+                }
+            }
+            _ => {}
+        }
+    }
+    // the file's modification date is the best guess when the original date is missing
+    metadata.captured_at = metadata.captured_at.or(modified_at);
+    Some(metadata)
+}
+
+fn parse_gps(tiff: &Tiff, ifd_offset: usize) -> Option<(f64, f64)> {
+    let (mut lat, mut lat_ref, mut lon, mut lon_ref) = (None, None, None, None);
+    for entry in tiff.entries(ifd_offset) {
+        match entry.tag {
+            TAG_GPS_LATITUDE_REF => lat_ref = tiff.ascii(&entry),
+            TAG_GPS_LATITUDE => lat = tiff.rationals(&entry),
+            TAG_GPS_LONGITUDE_REF => lon_ref = tiff.ascii(&entry),
+            TAG_GPS_LONGITUDE => lon = tiff.rationals(&entry),
+            _ => {}
+        }
+    }
+    let to_degrees = |parts: Vec<f64>, reference: Option<String>, negative: &str| {
+        let degrees = parts.first()?
+            + parts.get(1).unwrap_or(&0.0) / 60.0
+            + parts.get(2).unwrap_or(&0.0) / 3600.0;
+        Some(if reference.as_deref() == Some(negative) {
+            -degrees
+        } else {
+            degrees
+        })
+    };
+    let lat = to_degrees(lat?, lat_ref, "S")?;
+    let lon = to_degrees(lon?, lon_ref, "W")?;
+    valid_coordinates(lat, lon)
+}
+
+fn valid_coordinates(lat: f64, lon: f64) -> Option<(f64, f64)> {
+    // cameras without a fix write zeroes instead of leaving the tags out
+    if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon) && (lat, lon) != (0.0, 0.0)
+    {
+        Some((lat, lon))
+    } else {
+        None
+    }
+}
+
+// EXIF dates look like `2023:06:01 14:22:05`
+fn parse_exif_date(value: &str) -> Option<String> {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y:%m:%d %H:%M:%S")
+        .ok()
+        .map(|date| date.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+fn find_xmp(bytes: &[u8]) -> Option<String> {
+    const START: &[u8] = b"<x:xmpmeta";
+    const END: &[u8] = b"</x:xmpmeta>";
+
+    let start = bytes
+        .windows(START.len())
+        .position(|window| window == START)?;
+    let end = bytes[start..]
+        .windows(END.len())
+        .position(|window| window == END)?;
+    Some(String::from_utf8_lossy(&bytes[start..start + end + END.len()]).into_owned())
+}
+
+// XMP properties are written either as attributes or as elements of the description
+fn xmp_property(xmp: &str, name: &str) -> Option<String> {
+    let name = regex::escape(name);
+    let pattern = format!(r#"{name}\s*=\s*"([^"]*)"|<{name}>([^<]*)</{name}>"#);
+    let captures = Regex::new(&pattern).ok()?.captures(xmp)?;
+    let value = captures.get(1).or_else(|| captures.get(2))?.as_str().trim();
+    Some(html_escape::decode_html_entities(value).into_owned()).filter(|v| !v.is_empty())
+}
+
+fn parse_xmp(xmp: &str) -> ImageMetadata {
+    let captured_at = [
+        "exif:DateTimeOriginal",
+        "xmp:CreateDate",
+        "photoshop:DateCreated",
+    ]
+    .iter()
+    .find_map(|name| xmp_property(xmp, name).and_then(|v| parse_xmp_date(&v)));
+    let gps = match (
+        xmp_property(xmp, "exif:GPSLatitude").and_then(|v| parse_xmp_coordinate(&v)),
+        xmp_property(xmp, "exif:GPSLongitude").and_then(|v| parse_xmp_coordinate(&v)),
+    ) {
+        (Some(lat), Some(lon)) => valid_coordinates(lat, lon),
+        _ => None,
+    };
+
+    ImageMetadata {
+        captured_at,
+        camera_make: xmp_property(xmp, "tiff:Make"),
+        camera_model: xmp_property(xmp, "tiff:Model"),
+        gps,
+    }
+}
+
+// XMP dates are ISO 8601 and may be truncated or carry an offset, the offset is dropped
+// to match the local time EXIF dates
+fn parse_xmp_date(value: &str) -> Option<String> {
+    static DATE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(\d{4})-(\d{2})-(\d{2})(?:T(\d{2}):(\d{2})(?::(\d{2}))?)?").unwrap()
+    });
+    let captures = DATE.captures(value.trim())?;
+    let part = |i: usize| captures.get(i).map_or("00", |m| m.as_str());
+    let date = format!(
+        "{}-{}-{}T{}:{}:{}",
+        part(1),
+        part(2),
+        part(3),
+        part(4),
+        part(5),
+        part(6)
+    );
+    chrono::NaiveDateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .map(|date| date.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+// XMP coordinates look like `37,46.2855N` or `122,25,9.72W`
+fn parse_xmp_coordinate(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let direction = value.chars().last()?;
+    let negative = match direction.to_ascii_uppercase() {
+        'N' | 'E' => false,
+        'S' | 'W' => true,
+        _ => return None,
+    };
+    let mut degrees = 0.0;
+    for (i, part) in value[..value.len() - 1].split(',').enumerate() {
+        let part: f64 = part.trim().parse().ok()?;
+        degrees += part / 60f64.powi(i as i32);
+    }
+    Some(if negative { -degrees } else { degrees })
+}
+
+const HASH_IMAGE_SIZE: usize = 32;
+const HASH_SIZE: usize = 8;
+
+// 64 bit DCT based perceptual hash, images that look alike have hashes with a small
+// hamming distance even after resizing, recompression or small color changes
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let small = image
+        .grayscale()
+        .resize_exact(
+            HASH_IMAGE_SIZE as u32,
+            HASH_IMAGE_SIZE as u32,
+            FilterType::Triangle,
+        )
+        .into_luma8();
+    let pixels: Vec<f64> = small.pixels().map(|pixel| pixel.0[0] as f64).collect();
+
+    // only the lowest frequencies are needed, so the DCT is computed for those alone
+    let cosines: Vec<Vec<f64>> = (0..HASH_SIZE)
+        .map(|u| {
+            (0..HASH_IMAGE_SIZE)
+                .map(|x| {
+                    ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI
+                        / (2 * HASH_IMAGE_SIZE) as f64)
+                        .cos()
+                })
+                .collect()
+        })
+        .collect();
+    let mut coefficients = Vec::with_capacity(HASH_SIZE * HASH_SIZE);
+    for v in 0..HASH_SIZE {
+        for u in 0..HASH_SIZE {
+            let mut sum = 0.0;
+            for y in 0..HASH_IMAGE_SIZE {
+                for x in 0..HASH_IMAGE_SIZE {
+                    sum += pixels[y * HASH_IMAGE_SIZE + x] * cosines[u][x] * cosines[v][y];
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    // the DC coefficient is the average brightness and would skew the median
+    let mut sorted: Vec<f64> = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    coefficients
+        .iter()
+        .enumerate()
+        .fold(0u64, |hash, (i, coefficient)| {
+            if *coefficient > median {
+                hash | (1 << i)
+            } else {
+                hash
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a big endian TIFF block with make, model, the EXIF date and a GPS position
+    fn build_exif() -> Vec<u8> {
+        fn entry(out: &mut Vec<u8>, tag: u16, field_type: u16, count: u32, value: u32) {
+            out.extend_from_slice(&tag.to_be_bytes());
+            out.extend_from_slice(&field_type.to_be_bytes());
+            out.extend_from_slice(&count.to_be_bytes());
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+
+        let make = b"Canon\0";
+        let model = b"Canon EOS R5\0";
+        let date = b"2023:06:01 14:22:05\0";
+        // offsets of the out of line data, laid out after the three IFDs below
+        let ifd0 = 8;
+        let exif_ifd = ifd0 + 2 + 4 * 12 + 4;
+        let gps_ifd = exif_ifd + 2 + 12 + 4;
+        let data = gps_ifd + 2 + 4 * 12 + 4;
+        let make_offset = data;
+        let model_offset = make_offset + make.len();
+        let date_offset = model_offset + model.len();
+        let lat_offset = date_offset + date.len();
+        let lon_offset = lat_offset + 24;
+
+        let mut out = b"MM\0*".to_vec();
+        out.extend_from_slice(&(ifd0 as u32).to_be_bytes());
+
+        out.extend_from_slice(&4u16.to_be_bytes());
+        entry(&mut out, TAG_MAKE, 2, make.len() as u32, make_offset as u32);
+        entry(
+            &mut out,
+            TAG_MODEL,
+            2,
+            model.len() as u32,
+            model_offset as u32,
+        );
+        entry(&mut out, TAG_EXIF_IFD, 4, 1, exif_ifd as u32);
+        entry(&mut out, TAG_GPS_IFD, 4, 1, gps_ifd as u32);
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        out.extend_from_slice(&1u16.to_be_bytes());
+        entry(
+            &mut out,
+            TAG_DATE_TIME_ORIGINAL,
+            2,
+            date.len() as u32,
+            date_offset as u32,
+        );
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        out.extend_from_slice(&4u16.to_be_bytes());
+        // inline ascii values are left aligned in the value field
+        entry(
+            &mut out,
+            TAG_GPS_LATITUDE_REF,
+            2,
+            2,
+            u32::from_be_bytes(*b"N\0\0\0"),
+        );
+        entry(&mut out, TAG_GPS_LATITUDE, 5, 3, lat_offset as u32);
+        entry(
+            &mut out,
+            TAG_GPS_LONGITUDE_REF,
+            2,
+            2,
+            u32::from_be_bytes(*b"W\0\0\0"),
+        );
+        entry(&mut out, TAG_GPS_LONGITUDE, 5, 3, lon_offset as u32);
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        assert_eq!(out.len(), data);
+        out.extend_from_slice(make);
+        out.extend_from_slice(model);
+        out.extend_from_slice(date);
+        for (numerator, denominator) in [(37, 1), (46, 1), (3000, 100), (122, 1), (25, 1), (0, 1)] {
+            out.extend_from_slice(&(numerator as u32).to_be_bytes());
+            out.extend_from_slice(&(denominator as u32).to_be_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_exif_from_jpeg() {
+        let exif = build_exif();
+        let mut jpeg = vec![0xFF, 0xD8];
+        // an unrelated APP0 segment comes first in most files
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xE1]);
+        jpeg.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&exif);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+
+        let metadata = read_image_metadata(&jpeg);
+        assert_eq!(metadata.captured_at.as_deref(), Some("2023-06-01T14:22:05"));
+        assert_eq!(metadata.camera().as_deref(), Some("Canon EOS R5"));
+        let (lat, lon) = metadata.gps.unwrap();
+        assert!((lat - 37.775).abs() < 1e-9, "{}", lat);
+        assert!((lon + 122.416_666).abs() < 1e-5, "{}", lon);
+    }
+
+    #[test]
+    fn test_xmp_fills_in_missing_exif() {
+        let file = br#"GIF89a...<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF><rdf:Description
+            xmp:CreateDate="2021-03-04T05:06:07+02:00"
+            exif:GPSLatitude="48,51.5N" exif:GPSLongitude="2,21.05E">
+            <tiff:Model>Pixel 7</tiff:Model>
+            <tiff:Make>Google</tiff:Make>
+            </rdf:Description></rdf:RDF></x:xmpmeta>..."#;
+
+        let metadata = read_image_metadata(file);
+        assert_eq!(metadata.captured_at.as_deref(), Some("2021-03-04T05:06:07"));
+        assert_eq!(metadata.camera().as_deref(), Some("Google Pixel 7"));
+        let (lat, lon) = metadata.gps.unwrap();
+        assert!((lat - 48.858_333).abs() < 1e-5, "{}", lat);
+        assert!((lon - 2.350_833).abs() < 1e-5, "{}", lon);
+
+        assert_eq!(
+            read_image_metadata(b"no metadata here"),
+            ImageMetadata::default()
+        );
+    }
+
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(image::RgbImage::from_fn(width, height, |x, y| {
+            let value = ((x * 255 / width) + (y * 64 / height)).min(255) as u8;
+            image::Rgb([value, value / 2, 255 - value])
+        }))
+    }
+
+    #[test]
+    fn test_perceptual_hash_is_stable_across_sizes() {
+        let original = perceptual_hash(&gradient(400, 300));
+        let resized = perceptual_hash(&gradient(200, 150));
+        let flipped = perceptual_hash(&gradient(400, 300).fliph());
+
+        assert!((original ^ resized).count_ones() <= 4);
+        assert!((original ^ flipped).count_ones() > 20);
+    }
+}
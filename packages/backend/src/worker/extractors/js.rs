@@ -0,0 +1,74 @@
+use super::{create_metadata_from_resource, Extraction, Extractor, ExtractorContext};
+use crate::{
+    ai::brain::js_tools::JSToolRegistry,
+    store::models::{CompositeResource, ResourceTextContentMetadata, ResourceTextContentType},
+    BackendError, BackendResult,
+};
+
+use neon::{event::Channel, handle::Root, types::JsFunction};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct JsExtractedContent {
+    content_type: ResourceTextContentType,
+    content: String,
+    metadata: Option<ResourceTextContentMetadata>,
+}
+
+// extractor backed by a JS callback, the callback receives the resource as a JSON string
+// and returns (or resolves to) a JSON array of `{ content_type, content, metadata? }`
+pub struct JsExtractor {
+    name: String,
+    mime_types: Vec<String>,
+    callback: JSToolRegistry,
+}
+
+impl JsExtractor {
+    pub fn new(
+        name: String,
+        mime_types: Vec<String>,
+        callback: Root<JsFunction>,
+        channel: Channel,
+    ) -> BackendResult<Self> {
+        let registry = JSToolRegistry::new();
+        registry.add_tool(name.clone(), callback, channel)?;
+        Ok(Self {
+            name,
+            mime_types,
+            callback: registry,
+        })
+    }
+}
+
+impl Extractor for JsExtractor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        self.mime_types.clone()
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let resource_json = serde_json::to_string(resource)?;
+        let contents: Vec<JsExtractedContent> = self
+            .callback
+            .execute_tool(&self.name, Some(vec![resource_json]))
+            .map_err(|err| {
+                BackendError::GenericError(format!("extractor '{}' failed: {err}", self.name))
+            })?;
+
+        let mut extraction = Extraction::default();
+        for content in contents {
+            let metadata = content
+                .metadata
+                .unwrap_or_else(|| create_metadata_from_resource(resource));
+            extraction.push(content.content_type, content.content, metadata);
+        }
+        Ok(extraction)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    store::models::{ExtractedResourceLink, ResourceLinkKind, ResourceTag},
+    worker::vault::RESOURCE_URL_PREFIX,
+};
+
+use percent_encoding::percent_decode_str;
+use scraper::{ElementRef, Html, Selector};
+
+// tag of annotations with the id of the resource they were made on
+pub const ANNOTATES_TAG: &str = "annotates";
+const MAX_CONTEXT_LENGTH: usize = 300;
+
+fn selector(selector: &str) -> Selector {
+    Selector::parse(selector).expect("invalid selector")
+}
+
+fn collapse_whitespace(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(MAX_CONTEXT_LENGTH) {
+        Some((end, _)) => Some(format!("{}…", &text[..end])),
+        None => Some(text).filter(|text| !text.is_empty()),
+    }
+}
+
+// `surf://surf/resource/<id>`, images link to the raw data with `?raw`
+fn resource_id_from_url(url: &str) -> Option<String> {
+    let id = url.trim().strip_prefix(RESOURCE_URL_PREFIX)?;
+    let id = id.split(['?', '#', '/']).next().unwrap_or_default();
+    Some(id.to_owned()).filter(|id| !id.is_empty())
+}
+
+// the data of citations is url encoded json with the cited source
+fn cited_resource(element: &ElementRef) -> Option<(String, Option<String>)> {
+    let info = element.value().attr("data-info")?;
+    let info = percent_decode_str(info).decode_utf8().ok()?;
+    let info: serde_json::Value = serde_json::from_str(&info).ok()?;
+    let resource_id = info["source"]["resource_id"].as_str()?.to_owned();
+    let context = info["source"]["content"]
+        .as_str()
+        .or_else(|| info["text"].as_str())
+        .and_then(collapse_whitespace);
+    Some((resource_id, context))
+}
+
+// links, images, embedded resources and citations in the html of a note
+pub fn note_resource_links(html: &str) -> Vec<ExtractedResourceLink> {
+    let document = Html::parse_fragment(html);
+    let elements = selector("a[href], img[src], resource[id], surflet[data-resource-id], citation");
+
+    let mut links = vec![];
+    for element in document.select(&elements) {
+        let attr = |name: &str| element.value().attr(name);
+        let text = || collapse_whitespace(&element.text().collect::<String>());
+        let found = match element.value().name() {
+            "a" => attr("href")
+                .and_then(resource_id_from_url)
+                .map(|id| (id, text())),
+            "img" => attr("src")
+                .and_then(resource_id_from_url)
+                .map(|id| (id, attr("alt").and_then(collapse_whitespace))),
+            "resource" => attr("id").map(|id| (id.to_owned(), None)),
+            "surflet" => attr("data-resource-id").map(|id| (id.to_owned(), None)),
+            "citation" => cited_resource(&element),
+            _ => None,
+        };
+        if let Some((target_id, context)) = found {
+            links.push(ExtractedResourceLink {
+                target_id,
+                kind: ResourceLinkKind::LinksTo,
+                context,
+            });
+        }
+    }
+    links
+}
+
+// annotations are made on the resources in their `annotates` tags
+pub fn annotation_resource_links(
+    tags: &[ResourceTag],
+    context: Option<&str>,
+) -> Vec<ExtractedResourceLink> {
+    tags.iter()
+        .filter(|tag| tag.tag_name == ANNOTATES_TAG && !tag.tag_value.is_empty())
+        .map(|tag| ExtractedResourceLink {
+            target_id: tag.tag_value.clone(),
+            kind: ResourceLinkKind::Annotates,
+            context: context.and_then(collapse_whitespace),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_resource_links() {
+        let info = percent_encoding::utf8_percent_encode(
+            r#"{"id":"1","source":{"resource_id":"cited","content":"the  cited\nchunk"}}"#,
+            percent_encoding::NON_ALPHANUMERIC,
+        )
+        .to_string();
+        let html = format!(
+            r#"<p>See <a href="surf://surf/resource/page-1">the   <b>article</b></a> and
+            <a href="https://example.com">an external page</a>.</p>
+            <img src="surf://surf/resource/image-1?raw" alt="a chart">
+            <resource id="embedded" data-type="application/pdf"></resource>
+            <citation data-info="{info}">1</citation>"#
+        );
+
+        let links = note_resource_links(&html)
+            .into_iter()
+            .map(|link| (link.target_id, link.context))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                ("page-1".to_owned(), Some("the article".to_owned())),
+                ("image-1".to_owned(), Some("a chart".to_owned())),
+                ("embedded".to_owned(), None),
+                ("cited".to_owned(), Some("the cited chunk".to_owned())),
+            ]
+        );
+    }
+}
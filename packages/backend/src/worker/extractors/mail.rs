@@ -0,0 +1,742 @@
+use super::{
+    create_metadata_from_resource, readability::extract_article, Extraction, Extractor,
+    ExtractorContext,
+};
+use crate::{
+    store::models::{
+        random_uuid, CompositeResource, ExtractedResourceMetadata, ResourceTag,
+        ResourceTextContentType,
+    },
+    BackendResult,
+};
+
+use base64::engine::{general_purpose::GeneralPurpose, DecodePaddingMode, GeneralPurposeConfig};
+use base64::{alphabet, Engine};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+pub const MAIL_RESOURCE_TYPE: &str = "message/rfc822";
+
+// tags set from the message headers
+pub const EMAIL_FROM_TAG: &str = "emailFrom";
+// one tag per `To` and `Cc` address
+pub const EMAIL_TO_TAG: &str = "emailTo";
+// message id of the first message of the thread
+pub const EMAIL_THREAD_TAG: &str = "emailThread";
+pub const SOURCE_PUBLISHED_AT_TAG: &str = "sourcePublishedAt";
+// set on attachments, the value is the id of the message resource
+pub const ATTACHMENT_OF_TAG: &str = "attachmentOf";
+
+static ENCODED_WORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").unwrap());
+static ENCODED_WORD_GAP: Lazy<Regex> = Lazy::new(|| Regex::new(r"\?=\s+=\?").unwrap());
+static MESSAGE_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^<>\s]+)>").unwrap());
+static REPLY_PREFIX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*((re|fwd?|aw|wg|sv)\s*(\[\d+\])?\s*:\s*)+").unwrap());
+static DATE_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*\([^)]*\)\s*$").unwrap());
+
+// mail clients don't agree on padding and line lengths of base64 parts
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailAddress {
+    pub name: Option<String>,
+    pub address: String,
+}
+
+impl std::fmt::Display for MailAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name} <{}>", self.address),
+            None => write!(f, "{}", self.address),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct MailMessage {
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    pub subject: Option<String>,
+    pub from: Option<MailAddress>,
+    pub to: Vec<MailAddress>,
+    pub cc: Vec<MailAddress>,
+    pub date: Option<DateTime<Utc>>,
+    pub text_body: Option<String>,
+    pub html_body: Option<String>,
+    pub attachments: Vec<MailAttachment>,
+}
+
+impl MailMessage {
+    pub fn title(&self) -> String {
+        self.subject
+            .clone()
+            .filter(|subject| !subject.trim().is_empty())
+            .unwrap_or_else(|| "(no subject)".to_owned())
+    }
+
+    // the first reference is the root of the thread, replies from clients that
+    // don't keep references at least point to their parent
+    pub fn thread_id(&self) -> Option<&str> {
+        self.references
+            .first()
+            .or(self.in_reply_to.as_ref())
+            .or(self.message_id.as_ref())
+            .map(|id| id.as_str())
+    }
+
+    // subject without `Re:`/`Fwd:` prefixes, used to group messages without any ids
+    pub fn thread_subject(&self) -> String {
+        REPLY_PREFIX
+            .replace(&self.title(), "")
+            .trim()
+            .to_lowercase()
+    }
+
+    // `mid:` urls as in RFC 2392
+    pub fn source_uri(&self) -> Option<String> {
+        self.message_id.as_ref().map(|id| format!("mid:{id}"))
+    }
+
+    // plain text parts are used as they are, html only bodies go through the article
+    // extractor and fall back to all of the text for layouts it can't make sense of
+    pub fn body_text(&self) -> Option<String> {
+        if let Some(text) = self.text_body.as_ref().filter(|t| !t.trim().is_empty()) {
+            return Some(text.trim().to_owned());
+        }
+        let html = self.html_body.as_ref()?;
+        let article = extract_article(html, None);
+        let text = if article.content.trim().is_empty() {
+            html_to_text(html)
+        } else {
+            article.content
+        };
+        Some(text).filter(|text| !text.trim().is_empty())
+    }
+
+    pub fn metadata(&self) -> ExtractedResourceMetadata {
+        ExtractedResourceMetadata {
+            title: self.subject.clone(),
+            byline: self.from.as_ref().map(|from| from.to_string()),
+            published_at: self.date.map(|date| date.to_rfc3339()),
+            lead_image: None,
+            outline: None,
+        }
+    }
+
+    pub fn tags(&self, resource_id: &str) -> Vec<ResourceTag> {
+        let mut tags = vec![];
+        if let Some(from) = &self.from {
+            tags.push((EMAIL_FROM_TAG, from.address.to_lowercase()));
+        }
+        for recipient in self.to.iter().chain(self.cc.iter()) {
+            tags.push((EMAIL_TO_TAG, recipient.address.to_lowercase()));
+        }
+        if let Some(thread_id) = self.thread_id() {
+            tags.push((EMAIL_THREAD_TAG, thread_id.to_owned()));
+        }
+        if let Some(date) = self.date {
+            tags.push((SOURCE_PUBLISHED_AT_TAG, date.to_rfc3339()));
+        }
+        tags.into_iter()
+            .map(|(name, value)| ResourceTag {
+                id: random_uuid(),
+                resource_id: resource_id.to_owned(),
+                tag_name: name.to_owned(),
+                tag_value: value,
+            })
+            .collect()
+    }
+}
+
+pub struct MailExtractor;
+
+impl Extractor for MailExtractor {
+    fn name(&self) -> &str {
+        "mail"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![MAIL_RESOURCE_TYPE.to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let data = std::fs::read(&resource.resource.resource_path)?;
+        let message = parse_message(&data);
+
+        let mut extraction = Extraction::default();
+        if let Some(body) = message.body_text() {
+            extraction.push(
+                ResourceTextContentType::Email,
+                body,
+                create_metadata_from_resource(resource),
+            );
+        }
+        extraction.metadata = Some(message.metadata()).filter(|m| !m.is_empty());
+        extraction.tags = message.tags(&resource.resource.id);
+        Ok(extraction)
+    }
+}
+
+pub fn is_mbox(data: &[u8]) -> bool {
+    data.starts_with(b"From ")
+}
+
+// messages in an mbox start with a `From ` line after an empty line, `From ` at the
+// start of a body line is escaped as `>From ` and unescaped here
+pub fn split_mbox(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = vec![];
+    let mut current: Option<Vec<u8>> = None;
+    let mut previous_blank = true;
+
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            messages.extend(current.take());
+            current = Some(vec![]);
+            previous_blank = false;
+            continue;
+        }
+        previous_blank = trim_line_end(line).is_empty();
+
+        if let Some(message) = current.as_mut() {
+            let quotes = line.iter().take_while(|b| **b == b'>').count();
+            if quotes > 0 && line[quotes..].starts_with(b"From ") {
+                message.extend_from_slice(&line[1..]);
+            } else {
+                message.extend_from_slice(line);
+            }
+        }
+    }
+    messages.extend(current);
+    messages
+        .into_iter()
+        .filter(|message| !trim_line_end(message).is_empty())
+        .collect()
+}
+
+pub fn parse_message(raw: &[u8]) -> MailMessage {
+    let raw = normalize_line_endings(raw);
+    let (headers, body) = split_headers(&raw);
+
+    let mut message = MailMessage {
+        message_id: header(&headers, "message-id").and_then(|value| {
+            parse_message_ids(value)
+                .into_iter()
+                .next()
+                .or_else(|| Some(value.trim().to_owned()).filter(|id| !id.is_empty()))
+        }),
+        in_reply_to: header(&headers, "in-reply-to")
+            .and_then(|value| parse_message_ids(value).into_iter().next()),
+        references: header(&headers, "references")
+            .map(parse_message_ids)
+            .unwrap_or_default(),
+        subject: header(&headers, "subject").map(decode_encoded_words),
+        from: header(&headers, "from").and_then(|value| parse_addresses(value).into_iter().next()),
+        to: header(&headers, "to")
+            .map(parse_addresses)
+            .unwrap_or_default(),
+        cc: header(&headers, "cc")
+            .map(parse_addresses)
+            .unwrap_or_default(),
+        date: header(&headers, "date").and_then(parse_date),
+        ..Default::default()
+    };
+    walk_part(&mut message, &headers, body);
+    message
+}
+
+fn walk_part(message: &mut MailMessage, headers: &[(String, String)], body: &[u8]) {
+    let (mime, params) = header(headers, "content-type")
+        .map(parse_header_value)
+        .unwrap_or_else(|| ("text/plain".to_owned(), HashMap::new()));
+
+    if mime.starts_with("multipart/") {
+        if let Some(boundary) = params.get("boundary") {
+            for part in split_multipart(body, boundary) {
+                let (part_headers, part_body) = split_headers(part);
+                walk_part(message, &part_headers, part_body);
+            }
+            return;
+        }
+    }
+
+    let (disposition, disposition_params) = header(headers, "content-disposition")
+        .map(parse_header_value)
+        .unwrap_or_default();
+    let filename = disposition_params
+        .get("filename")
+        .or_else(|| params.get("name"))
+        .map(|name| decode_encoded_words(name));
+    let data = decode_transfer_encoding(body, header(headers, "content-transfer-encoding"));
+
+    let is_body = disposition != "attachment"
+        && (mime == "text/plain" || mime == "text/html")
+        && filename.is_none();
+    if is_body {
+        let charset = params.get("charset").map(|c| c.as_str());
+        let text = decode_charset(&data, charset);
+        let slot = if mime == "text/html" {
+            &mut message.html_body
+        } else {
+            &mut message.text_body
+        };
+        // alternatives of the same body, or a signature appended as its own part
+        match slot {
+            Some(existing) if mime == "text/plain" => {
+                existing.push_str("\n\n");
+                existing.push_str(&text);
+            }
+            Some(_) => {}
+            None => *slot = Some(text),
+        }
+        return;
+    }
+
+    let index = message.attachments.len() + 1;
+    let filename = filename.unwrap_or_else(|| match mime.as_str() {
+        MAIL_RESOURCE_TYPE => format!("message-{index}.eml"),
+        _ => format!("attachment-{index}"),
+    });
+    message.attachments.push(MailAttachment {
+        filename,
+        content_type: mime,
+        data,
+    });
+}
+
+fn normalize_line_endings(raw: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(raw.len());
+    let mut bytes = raw.iter().peekable();
+    while let Some(byte) = bytes.next() {
+        if *byte == b'\r' && bytes.peek() == Some(&&b'\n') {
+            continue;
+        }
+        normalized.push(*byte);
+    }
+    normalized
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let end = line
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    &line[..end]
+}
+
+// the header block ends at the first empty line, folded lines are joined
+fn split_headers(raw: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let (head, body) = if raw.starts_with(b"\n") {
+        (&raw[..0], &raw[1..])
+    } else {
+        match raw.windows(2).position(|w| w == b"\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None => (raw, &raw[raw.len()..]),
+        }
+    };
+
+    let mut headers: Vec<(String, String)> = vec![];
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        match line.split_once(':') {
+            // skips the `From ` line of mbox messages
+            Some((name, value)) if !name.contains(char::is_whitespace) => {
+                headers.push((name.to_lowercase(), value.trim().to_owned()))
+            }
+            _ => {}
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+// e.g. `text/plain; charset="utf-8"`, also handles RFC 2231 `filename*=utf-8''a%20b`
+fn parse_header_value(value: &str) -> (String, HashMap<String, String>) {
+    let mut segments = split_outside_quotes(value, ';').into_iter();
+    let kind = segments.next().unwrap_or_default().trim().to_lowercase();
+    let mut params = HashMap::new();
+    for segment in segments {
+        let (key, value) = match segment.split_once('=') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        let value = value.trim_matches('"').to_owned();
+        match key.strip_suffix('*') {
+            Some(key) => {
+                params.insert(key.to_owned(), decode_extended_param(&value));
+            }
+            None => {
+                params.entry(key).or_insert(value);
+            }
+        }
+    }
+    (kind, params)
+}
+
+fn decode_extended_param(value: &str) -> String {
+    let mut parts = value.splitn(3, '\'');
+    let (charset, encoded) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(charset), Some(_language), Some(encoded)) => (Some(charset), encoded),
+        _ => (None, value),
+    };
+    let bytes = percent_decode(encoded);
+    decode_charset(&bytes, charset)
+}
+
+fn percent_decode(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = hex_byte(bytes.get(i + 1..i + 3)) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
+
+fn hex_byte(digits: Option<&[u8]>) -> Option<u8> {
+    let digits = std::str::from_utf8(digits?).ok()?;
+    u8::from_str_radix(digits, 16).ok()
+}
+
+fn split_outside_quotes(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut in_angle = false;
+    for c in value.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_angle = true,
+            '>' if !in_quotes => in_angle = false,
+            c if c == separator && !in_quotes && !in_angle => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
+
+fn parse_addresses(value: &str) -> Vec<MailAddress> {
+    split_outside_quotes(value, ',')
+        .iter()
+        .filter_map(|entry| {
+            let entry = entry.trim();
+            let (name, address) = match (entry.find('<'), entry.rfind('>')) {
+                (Some(start), Some(end)) if start < end => {
+                    (&entry[..start], &entry[start + 1..end])
+                }
+                _ => ("", entry),
+            };
+            let address = address.trim();
+            if !address.contains('@') {
+                return None;
+            }
+            let name = decode_encoded_words(name.trim().trim_matches('"').trim());
+            Some(MailAddress {
+                name: Some(name).filter(|name| !name.is_empty()),
+                address: address.to_owned(),
+            })
+        })
+        .collect()
+}
+
+fn parse_message_ids(value: &str) -> Vec<String> {
+    MESSAGE_ID
+        .captures_iter(value)
+        .map(|captures| captures[1].to_owned())
+        .collect()
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = DATE_COMMENT.replace(value, "");
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+// RFC 2047 words like `=?utf-8?Q?caf=C3=A9?=`, whitespace between two words is dropped
+fn decode_encoded_words(value: &str) -> String {
+    let value = ENCODED_WORD_GAP.replace_all(value, "?==?");
+    ENCODED_WORD
+        .replace_all(&value, |captures: &regex::Captures| {
+            let bytes = match &captures[2] {
+                "B" | "b" => LENIENT_BASE64.decode(&captures[3]).ok(),
+                _ => Some(decode_quoted_printable(
+                    captures[3].replace('_', " ").as_bytes(),
+                )),
+            };
+            match bytes {
+                Some(bytes) => decode_charset(&bytes, Some(&captures[1])),
+                None => captures[0].to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+fn decode_transfer_encoding(body: &[u8], encoding: Option<&str>) -> Vec<u8> {
+    match encoding.map(|e| e.trim().to_lowercase()).as_deref() {
+        Some("base64") => {
+            let compact: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            LENIENT_BASE64
+                .decode(compact)
+                .unwrap_or_else(|_| body.to_vec())
+        }
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    }
+}
+
+fn decode_quoted_printable(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'=' {
+            // soft line break
+            if data.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            if let Some(byte) = hex_byte(data.get(i + 1..i + 3)) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(data[i]);
+        i += 1;
+    }
+    decoded
+}
+
+fn decode_charset(data: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|charset| encoding_rs::Encoding::for_label(charset.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(data).0.into_owned()
+}
+
+// the line break before a delimiter belongs to the delimiter
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = vec![];
+    let mut start: Option<usize> = None;
+    let mut offset: usize = 0;
+
+    for line in body.split_inclusive(|b| *b == b'\n') {
+        let trimmed = trim_line_end(line);
+        if let Some(rest) = trimmed.strip_prefix(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                parts.push(&body[start..offset.saturating_sub(1).max(start)]);
+            }
+            if rest.starts_with(b"--") {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    // unterminated multipart, keep what is there
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+fn html_to_text(html: &str) -> String {
+    let document = scraper::Html::parse_document(html);
+    document
+        .root_element()
+        .descendants()
+        .filter_map(|node| {
+            let text = node.value().as_text()?;
+            let parent = node.parent()?.value().as_element()?.name();
+            (!matches!(parent, "style" | "script" | "title")).then_some(&**text)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPART: &str = "From: =?utf-8?Q?Ren=C3=A9e?= Dupont <renee@example.com>\r\n\
+To: \"Smith, Alex\" <alex@example.com>, bob@example.com\r\n\
+Cc: Team <team@example.com>\r\n\
+Subject: =?utf-8?B?UXVhcnRlcmx5?=\r\n =?utf-8?Q?_r=C3=A9sultats?=\r\n\
+Date: Tue, 5 Mar 2024 14:30:00 +0100 (CET)\r\n\
+Message-ID: <root@example.com>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+preamble\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=inner\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=iso-8859-1\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Numbers are up, see the caf=E9 =\r\n\
+report.\r\n\
+--inner\r\n\
+Content-Type: text/html; charset=utf-8\r\n\
+\r\n\
+<p>Numbers are up</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: application/pdf; name=\"ignored.pdf\"\r\n\
+Content-Disposition: attachment; filename*=utf-8''r%C3%A9sum%C3%A9.pdf\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0x\r\n\
+LjQK\r\n\
+--outer--\r\n";
+
+    #[test]
+    fn test_parse_multipart_message() {
+        let message = parse_message(MULTIPART.as_bytes());
+
+        assert_eq!(message.subject.as_deref(), Some("Quarterly résultats"));
+        assert_eq!(
+            message
+                .from
+                .as_ref()
+                .map(|from| from.to_string())
+                .as_deref(),
+            Some("Renée Dupont <renee@example.com>")
+        );
+        assert_eq!(
+            message.to,
+            vec![
+                MailAddress {
+                    name: Some("Smith, Alex".to_owned()),
+                    address: "alex@example.com".to_owned()
+                },
+                MailAddress {
+                    name: None,
+                    address: "bob@example.com".to_owned()
+                },
+            ]
+        );
+        assert_eq!(message.cc[0].address, "team@example.com");
+        assert_eq!(
+            message.date.map(|date| date.to_rfc3339()).as_deref(),
+            Some("2024-03-05T13:30:00+00:00")
+        );
+        assert_eq!(message.message_id.as_deref(), Some("root@example.com"));
+        assert_eq!(
+            message.text_body.as_deref(),
+            Some("Numbers are up, see the café report.")
+        );
+        assert_eq!(message.html_body.as_deref(), Some("<p>Numbers are up</p>"));
+
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].filename, "résumé.pdf");
+        assert_eq!(message.attachments[0].content_type, "application/pdf");
+        assert_eq!(message.attachments[0].data, b"%PDF-1.4\n");
+
+        let tags: Vec<(String, String)> = message
+            .tags("resource1")
+            .into_iter()
+            .map(|tag| (tag.tag_name, tag.tag_value))
+            .collect();
+        assert!(tags.contains(&(EMAIL_FROM_TAG.to_owned(), "renee@example.com".to_owned())));
+        assert!(tags.contains(&(EMAIL_TO_TAG.to_owned(), "team@example.com".to_owned())));
+        assert!(tags.contains(&(EMAIL_THREAD_TAG.to_owned(), "root@example.com".to_owned())));
+    }
+
+    #[test]
+    fn test_split_mbox_and_threads() {
+        let mbox = "From alice@example.com Mon Mar  4 10:00:00 2024\n\
+Message-ID: <root@example.com>\n\
+Subject: Plans\n\
+\n\
+>From the start this was the plan.\n\
+\n\
+From bob@example.com Mon Mar  4 11:00:00 2024\n\
+Message-ID: <reply@example.com>\n\
+In-Reply-To: <root@example.com>\n\
+References: <root@example.com>\n\
+Subject: RE: Fwd: Plans\n\
+\n\
+Sounds good\n";
+        let messages = split_mbox(mbox.as_bytes());
+        assert!(is_mbox(mbox.as_bytes()));
+        assert_eq!(messages.len(), 2);
+
+        let first = parse_message(&messages[0]);
+        let reply = parse_message(&messages[1]);
+        assert_eq!(
+            first.body_text().as_deref(),
+            Some("From the start this was the plan.")
+        );
+        assert_eq!(first.thread_id(), reply.thread_id());
+        assert_eq!(reply.thread_subject(), "plans");
+        assert_eq!(reply.source_uri().as_deref(), Some("mid:reply@example.com"));
+    }
+
+    #[test]
+    fn test_html_only_body_text() {
+        let message = parse_message(
+            b"Subject: Newsletter\nContent-Type: text/html\n\n<table><tr><td>Hello</td><td>there</td></tr></table>",
+        );
+        // tables are kept as markdown by the article extractor
+        assert_eq!(message.body_text().as_deref(), Some("Hello | there"));
+        assert_eq!(
+            html_to_text("<head><style>p { margin: 0 }</style></head><p>Hello</p><b>there</b>"),
+            "Hello there"
+        );
+        assert_eq!(parse_message(b"Subject: \n\n").title(), "(no subject)");
+    }
+}
@@ -0,0 +1,266 @@
+pub mod code;
+pub mod epub;
+pub mod frontmatter;
+pub mod image;
+mod image_metadata;
+pub mod js;
+pub mod links;
+pub mod mail;
+pub mod office;
+pub mod pdf;
+pub mod readability;
+pub mod space_data;
+pub mod subtitles;
+pub mod youtube;
+
+use crate::{
+    store::models::{
+        CompositeResource, ExtractedResourceLink, ExtractedResourceMetadata, ResourceFrontmatter,
+        ResourceTag, ResourceTextContentMetadata, ResourceTextContentType,
+    },
+    BackendError, BackendResult,
+};
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+// shared state the processor hands to every extractor
+pub struct ExtractorContext<'a> {
+    pub ocr: Option<&'a image::OcrModels>,
+    // language of the ocr recognition model, if one was found for the language setting
+    pub ocr_language: Option<&'a str>,
+    pub language: Option<&'a str>,
+    // database used to cache fetched transcripts, if not set they are always fetched
+    pub db_path: Option<&'a str>,
+}
+
+#[derive(Debug, Default)]
+pub struct Extraction {
+    pub contents: HashMap<ResourceTextContentType, (Vec<String>, Vec<ResourceTextContentMetadata>)>,
+    // resource metadata found in the content itself, only fills in fields that are still blank
+    pub metadata: Option<ExtractedResourceMetadata>,
+    // tags derived from the content, replace existing tags with the same name
+    pub tags: Vec<ResourceTag>,
+    // only set for images, see `image_metadata::perceptual_hash`
+    pub perceptual_hash: Option<u64>,
+    // only set for notes and markdown files that start with a frontmatter block
+    pub frontmatter: Option<ResourceFrontmatter>,
+    // links to other resources found in notes and annotations, replace the resource's
+    // previously extracted links
+    pub links: Option<Vec<ExtractedResourceLink>>,
+}
+
+impl Extraction {
+    pub fn push(
+        &mut self,
+        content_type: ResourceTextContentType,
+        content: String,
+        metadata: ResourceTextContentMetadata,
+    ) {
+        let (contents, metadatas) = self.contents.entry(content_type).or_default();
+        contents.push(content);
+        metadatas.push(metadata);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents
+            .values()
+            .all(|(contents, _)| contents.is_empty())
+    }
+}
+
+pub trait Extractor: Send + Sync {
+    // unique name, registering an extractor with the same name replaces the previous one
+    fn name(&self) -> &str;
+
+    // either exact MIME types or prefixes ending in `*`, e.g. `image/*`
+    fn supported_mime_types(&self) -> Vec<String>;
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction>;
+}
+
+// how well a MIME pattern matches the resource type, `None` if it does not match at all
+fn match_specificity(pattern: &str, resource_type: &str) -> Option<usize> {
+    match pattern.strip_suffix('*') {
+        Some(prefix) if resource_type.starts_with(prefix) => Some(prefix.len()),
+        Some(_) => None,
+        None if pattern == resource_type => Some(usize::MAX),
+        None => None,
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ExtractorRegistry {
+    extractors: Arc<RwLock<Vec<Arc<dyn Extractor>>>>,
+}
+
+impl ExtractorRegistry {
+    pub fn with_builtin_extractors() -> Self {
+        let registry = Self::default();
+        let builtins: [Arc<dyn Extractor>; 11] = [
+            Arc::new(space_data::SpaceDataExtractor),
+            Arc::new(image::ImageOcrExtractor),
+            Arc::new(pdf::PdfExtractor),
+            Arc::new(youtube::YoutubeTranscriptExtractor),
+            Arc::new(office::DocxExtractor),
+            Arc::new(office::OdtExtractor),
+            Arc::new(epub::EpubExtractor),
+            Arc::new(readability::ReadabilityExtractor),
+            Arc::new(subtitles::SubtitleExtractor),
+            Arc::new(mail::MailExtractor),
+            // after space data so it wins for `text/*`
+            Arc::new(code::CodeExtractor),
+        ];
+        for extractor in builtins {
+            if let Err(err) = registry.register(extractor) {
+                tracing::error!("failed to register builtin extractor: {err}");
+            }
+        }
+        registry
+    }
+
+    pub fn register(&self, extractor: Arc<dyn Extractor>) -> BackendResult<()> {
+        let mut extractors = self.extractors.write().map_err(|_| {
+            BackendError::GenericError("Failed to acquire write lock on extractor registry".into())
+        })?;
+        extractors.retain(|e| e.name() != extractor.name());
+        extractors.push(extractor);
+        Ok(())
+    }
+
+    pub fn unregister(&self, name: &str) -> BackendResult<bool> {
+        let mut extractors = self.extractors.write().map_err(|_| {
+            BackendError::GenericError("Failed to acquire write lock on extractor registry".into())
+        })?;
+        let len = extractors.len();
+        extractors.retain(|e| e.name() != name);
+        Ok(extractors.len() != len)
+    }
+
+    // the most specific match wins, exact MIME types beat the longest prefix,
+    // ties go to the most recently registered extractor
+    pub fn find(&self, resource_type: &str) -> Option<Arc<dyn Extractor>> {
+        let extractors = self.extractors.read().ok()?;
+        let resource_type = resource_type.to_lowercase();
+
+        let mut best: Option<(usize, &Arc<dyn Extractor>)> = None;
+        for extractor in extractors.iter() {
+            let specificity = extractor
+                .supported_mime_types()
+                .iter()
+                .filter_map(|pattern| match_specificity(&pattern.to_lowercase(), &resource_type))
+                .max();
+            if let Some(specificity) = specificity {
+                if best.is_none_or(|(current, _)| specificity >= current) {
+                    best = Some((specificity, extractor));
+                }
+            }
+        }
+        best.map(|(_, extractor)| Arc::clone(extractor))
+    }
+}
+
+pub fn create_metadata_from_resource(resource: &CompositeResource) -> ResourceTextContentMetadata {
+    ResourceTextContentMetadata {
+        timestamp: None,
+        page: None,
+        url: resource.metadata.as_ref().map(|m| m.source_uri.clone()),
+        section: None,
+        ocr: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestExtractor {
+        name: &'static str,
+        mime_types: Vec<&'static str>,
+    }
+
+    impl Extractor for TestExtractor {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn supported_mime_types(&self) -> Vec<String> {
+            self.mime_types.iter().map(|m| m.to_string()).collect()
+        }
+
+        fn extract(
+            &self,
+            _resource: &CompositeResource,
+            _ctx: &ExtractorContext,
+        ) -> BackendResult<Extraction> {
+            Ok(Extraction::default())
+        }
+    }
+
+    fn test_extractor(name: &'static str, mime_types: Vec<&'static str>) -> Arc<dyn Extractor> {
+        Arc::new(TestExtractor { name, mime_types })
+    }
+
+    fn find_name(registry: &ExtractorRegistry, resource_type: &str) -> Option<String> {
+        registry
+            .find(resource_type)
+            .map(|extractor| extractor.name().to_string())
+    }
+
+    #[test]
+    fn test_most_specific_extractor_wins() {
+        let registry = ExtractorRegistry::default();
+        registry
+            .register(test_extractor("space", vec!["application/vnd.space.*"]))
+            .unwrap();
+        registry
+            .register(test_extractor(
+                "youtube",
+                vec!["application/vnd.space.post.youtube"],
+            ))
+            .unwrap();
+        registry
+            .register(test_extractor("post", vec!["application/vnd.space.post*"]))
+            .unwrap();
+
+        assert_eq!(
+            find_name(&registry, "application/vnd.space.post.youtube").as_deref(),
+            Some("youtube")
+        );
+        assert_eq!(
+            find_name(&registry, "application/vnd.space.post.reddit").as_deref(),
+            Some("post")
+        );
+        assert_eq!(
+            find_name(&registry, "application/vnd.space.link").as_deref(),
+            Some("space")
+        );
+        assert_eq!(find_name(&registry, "application/zip"), None);
+    }
+
+    #[test]
+    fn test_later_registrations_take_precedence() {
+        let registry = ExtractorRegistry::default();
+        registry
+            .register(test_extractor("builtin", vec!["application/pdf"]))
+            .unwrap();
+        registry
+            .register(test_extractor("custom", vec!["application/pdf"]))
+            .unwrap();
+        assert_eq!(
+            find_name(&registry, "application/pdf").as_deref(),
+            Some("custom")
+        );
+
+        assert!(registry.unregister("custom").unwrap());
+        assert_eq!(
+            find_name(&registry, "application/pdf").as_deref(),
+            Some("builtin")
+        );
+        assert!(!registry.unregister("custom").unwrap());
+    }
+}
@@ -0,0 +1,445 @@
+use super::{Extraction, Extractor, ExtractorContext};
+use crate::{
+    store::models::{CompositeResource, ResourceTextContentMetadata, ResourceTextContentType},
+    BackendError, BackendResult,
+};
+
+use quick_xml::{
+    events::{BytesStart, BytesText, Event},
+    Reader,
+};
+use std::io::{Read, Seek};
+
+const DOCX_MIME_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+const ODT_MIME_TYPE: &str = "application/vnd.oasis.opendocument.text";
+
+// upper bound for a single uncompressed archive entry so a crafted file can't exhaust memory
+const MAX_ARCHIVE_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+// a run of paragraphs under the same heading
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Section {
+    pub title: Option<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl Section {
+    pub fn to_content(&self) -> String {
+        self.title
+            .iter()
+            .chain(self.paragraphs.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[derive(Default)]
+pub(super) struct SectionBuilder {
+    sections: Vec<Section>,
+    current: Section,
+}
+
+impl SectionBuilder {
+    pub fn heading(&mut self, text: &str) {
+        let text = normalize_whitespace(text);
+        if text.is_empty() {
+            return;
+        }
+        self.finish_section();
+        self.current.title = Some(text);
+    }
+
+    pub fn paragraph(&mut self, text: &str) {
+        let text = normalize_whitespace(text);
+        if !text.is_empty() {
+            self.current.paragraphs.push(text);
+        }
+    }
+
+    fn finish_section(&mut self) {
+        let section = std::mem::take(&mut self.current);
+        if section.title.is_some() || !section.paragraphs.is_empty() {
+            self.sections.push(section);
+        }
+    }
+
+    pub fn build(mut self) -> Vec<Section> {
+        self.finish_section();
+        self.sections
+    }
+}
+
+// collapses runs of spaces but keeps explicit line breaks
+pub(super) fn normalize_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// text nodes can carry HTML entities (`&nbsp;` in XHTML) that the XML unescaper rejects
+pub(super) fn decode_text(text: &BytesText) -> String {
+    html_escape::decode_html_entities(&String::from_utf8_lossy(text)).into_owned()
+}
+
+pub(super) fn attribute_value(element: &BytesStart, local_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == local_name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+pub(super) fn read_archive_entry<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> BackendResult<String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| BackendError::GenericError(format!("failed to open {name}: {e}")))?;
+    if entry.size() > MAX_ARCHIVE_ENTRY_SIZE {
+        return Err(BackendError::GenericError(format!(
+            "{name} is too large to extract: {} bytes",
+            entry.size()
+        )));
+    }
+    let mut content = String::new();
+    entry
+        .take(MAX_ARCHIVE_ENTRY_SIZE)
+        .read_to_string(&mut content)?;
+    Ok(content)
+}
+
+pub(super) fn open_archive(path: &str) -> BackendResult<zip::ZipArchive<std::fs::File>> {
+    zip::ZipArchive::new(std::fs::File::open(path)?)
+        .map_err(|e| BackendError::GenericError(format!("failed to open archive: {e}")))
+}
+
+// documents have no fixed pages, `page` is left for formats where it matches what the
+// user sees in a viewer
+fn sections_to_extraction(resource: &CompositeResource, sections: Vec<Section>) -> Extraction {
+    let mut extraction = Extraction::default();
+    for section in sections {
+        let content = section.to_content();
+        if content.is_empty() {
+            continue;
+        }
+        extraction.push(
+            ResourceTextContentType::Document,
+            content,
+            ResourceTextContentMetadata {
+                url: resource.metadata.as_ref().map(|m| m.source_uri.clone()),
+                section: section.title,
+                ..Default::default()
+            },
+        );
+    }
+    extraction
+}
+
+pub struct DocxExtractor;
+
+impl Extractor for DocxExtractor {
+    fn name(&self) -> &str {
+        "docx"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![DOCX_MIME_TYPE.to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut archive = open_archive(&resource.resource.resource_path)?;
+        let document = read_archive_entry(&mut archive, "word/document.xml")?;
+        Ok(sections_to_extraction(
+            resource,
+            parse_docx_document(&document)?,
+        ))
+    }
+}
+
+pub struct OdtExtractor;
+
+impl Extractor for OdtExtractor {
+    fn name(&self) -> &str {
+        "odt"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![ODT_MIME_TYPE.to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut archive = open_archive(&resource.resource.resource_path)?;
+        let content = read_archive_entry(&mut archive, "content.xml")?;
+        Ok(sections_to_extraction(
+            resource,
+            parse_odt_content(&content)?,
+        ))
+    }
+}
+
+fn is_docx_heading_style(style: &str) -> bool {
+    let style = style.to_lowercase();
+    style == "title" || style.starts_with("heading")
+}
+
+// `word/document.xml`: paragraphs are `w:p`, text runs `w:t`, headings are paragraphs
+// with a `Heading*` or `Title` style
+fn parse_docx_document(xml: &str) -> BackendResult<Vec<Section>> {
+    let mut reader = Reader::from_str(xml);
+    let mut builder = SectionBuilder::default();
+    let mut buf = Vec::new();
+
+    let mut paragraph = String::new();
+    let mut is_heading = false;
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"p" => {
+                    paragraph.clear();
+                    is_heading = false;
+                }
+                b"t" => in_text = true,
+                b"pStyle" => {
+                    is_heading |= attribute_value(e, b"val")
+                        .is_some_and(|style| is_docx_heading_style(&style))
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"pStyle" => {
+                    is_heading |= attribute_value(e, b"val")
+                        .is_some_and(|style| is_docx_heading_style(&style))
+                }
+                b"tab" => paragraph.push('\t'),
+                b"br" | b"cr" => paragraph.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(ref e)) if in_text => paragraph.push_str(&decode_text(e)),
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" if is_heading => builder.heading(&paragraph),
+                b"p" => builder.paragraph(&paragraph),
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(BackendError::GenericError(format!(
+                    "failed to parse document.xml at position {}: {e}",
+                    reader.buffer_position()
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(builder.build())
+}
+
+// `content.xml`: paragraphs are `text:p`, headings `text:h`, whitespace is encoded
+// as `text:s`, `text:tab` and `text:line-break`
+fn parse_odt_content(xml: &str) -> BackendResult<Vec<Section>> {
+    let mut reader = Reader::from_str(xml);
+    let mut builder = SectionBuilder::default();
+    let mut buf = Vec::new();
+
+    let mut paragraph = String::new();
+    // paragraphs can nest through frames and notes, only the outermost one is emitted
+    let mut paragraph_depth = 0;
+    let mut is_heading = false;
+    // deleted tracked changes and comments are not part of the document text
+    let mut skip_depth = 0;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                _ if skip_depth > 0 => skip_depth += 1,
+                b"tracked-changes" | b"annotation" => skip_depth = 1,
+                name @ (b"p" | b"h") => {
+                    if paragraph_depth == 0 {
+                        paragraph.clear();
+                        is_heading = name == b"h";
+                    }
+                    paragraph_depth += 1;
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if skip_depth == 0 => match e.local_name().as_ref() {
+                b"s" => {
+                    let count = attribute_value(e, b"c")
+                        .and_then(|c| c.parse::<usize>().ok())
+                        .unwrap_or(1);
+                    paragraph.push_str(&" ".repeat(count));
+                }
+                b"tab" => paragraph.push('\t'),
+                b"line-break" => paragraph.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(ref e)) if skip_depth == 0 && paragraph_depth > 0 => {
+                paragraph.push_str(&decode_text(e))
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                _ if skip_depth > 0 => skip_depth -= 1,
+                b"p" | b"h" if paragraph_depth > 0 => {
+                    paragraph_depth -= 1;
+                    if paragraph_depth == 0 {
+                        if is_heading {
+                            builder.heading(&paragraph);
+                        } else {
+                            builder.paragraph(&paragraph);
+                        }
+                    } else {
+                        paragraph.push(' ');
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(BackendError::GenericError(format!(
+                    "failed to parse content.xml at position {}: {e}",
+                    reader.buffer_position()
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_docx_document_splits_sections_by_heading() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:r><w:t>Preamble &amp; notes</w:t></w:r></w:p>
+    <w:p>
+      <w:pPr><w:pStyle w:val="Heading1"/></w:pPr>
+      <w:r><w:t>Introduction</w:t></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Hello </w:t></w:r>
+      <w:r><w:rPr><w:b/></w:rPr><w:t>world</w:t></w:r>
+      <w:r><w:br/><w:t>next line</w:t></w:r>
+    </w:p>
+    <w:p><w:r><w:delText>removed</w:delText></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+        let sections = parse_docx_document(xml).unwrap();
+        assert_eq!(
+            sections,
+            vec![
+                Section {
+                    title: None,
+                    paragraphs: vec!["Preamble & notes".to_string()],
+                },
+                Section {
+                    title: Some("Introduction".to_string()),
+                    paragraphs: vec!["Hello world\nnext line".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            sections[1].to_content(),
+            "Introduction\n\nHello world\nnext line"
+        );
+    }
+
+    #[test]
+    fn test_parse_odt_content_handles_spacing_and_skipped_parts() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+  <office:body>
+    <office:text>
+      <text:tracked-changes>
+        <text:changed-region><text:deletion><text:p>deleted</text:p></text:deletion></text:changed-region>
+      </text:tracked-changes>
+      <text:h text:outline-level="1">Chapter <text:span>One</text:span></text:h>
+      <text:p>a<text:s text:c="3"/>b<text:tab/>c</text:p>
+      <text:p>commented<office:annotation><text:p>a comment</text:p></office:annotation></text:p>
+      <text:list><text:list-item><text:p>list item</text:p></text:list-item></text:list>
+    </office:text>
+  </office:body>
+</office:document-content>"#;
+
+        let sections = parse_odt_content(xml).unwrap();
+        assert_eq!(
+            sections,
+            vec![Section {
+                title: Some("Chapter One".to_string()),
+                paragraphs: vec![
+                    "a b c".to_string(),
+                    "commented".to_string(),
+                    "list item".to_string(),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sections_are_not_numbered_as_pages() {
+        let now = crate::store::models::current_time();
+        let resource = CompositeResource {
+            resource: crate::store::models::Resource {
+                id: "doc".to_string(),
+                resource_path: "doc.docx".to_string(),
+                resource_type: DOCX_MIME_TYPE.to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            },
+            metadata: None,
+            text_content: None,
+            resource_tags: None,
+            resource_annotations: None,
+            post_processing_job: None,
+            space_ids: None,
+            thumbnail: None,
+        };
+        let sections = vec![
+            Section {
+                title: Some("Introduction".to_string()),
+                paragraphs: vec!["first".to_string()],
+            },
+            Section {
+                title: Some("Usage".to_string()),
+                paragraphs: vec!["second".to_string()],
+            },
+        ];
+
+        let extraction = sections_to_extraction(&resource, sections);
+        let (contents, metadata) = &extraction.contents[&ResourceTextContentType::Document];
+        assert_eq!(contents.len(), 2);
+        assert_eq!(
+            metadata
+                .iter()
+                .map(|m| (m.page, m.section.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![(None, Some("Introduction")), (None, Some("Usage"))]
+        );
+    }
+}
@@ -0,0 +1,1549 @@
+use super::{
+    image::{ocr_image, OcrModels},
+    Extraction, Extractor, ExtractorContext,
+};
+use crate::{
+    store::models::{
+        CompositeResource, DocumentOutline, DocumentOutlineEntry, ExtractedResourceMetadata,
+        ResourceTextContentMetadata, ResourceTextContentType,
+    },
+    BackendError, BackendResult,
+};
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+// pages with fewer letters and digits than this are treated as scans without a text layer
+const MIN_TEXT_LAYER_CHARS: usize = 20;
+// guards against malformed or malicious outline trees
+const MAX_OUTLINE_ENTRIES: usize = 10_000;
+const MAX_OUTLINE_DEPTH: usize = 32;
+// form xobjects can nest, scanned pages rarely go deeper than one level
+const MAX_XOBJECT_DEPTH: usize = 3;
+
+pub struct PdfExtractor;
+
+impl Extractor for PdfExtractor {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec!["application/pdf".to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let doc = Document::load(&resource.resource.resource_path)
+            .map_err(|err| BackendError::GenericError(format!("failed to load pdf: {err}")))?;
+        let metadata = document_metadata(&doc);
+
+        let mut extraction = Extraction::default();
+        for (page_number, page_id) in doc.get_pages() {
+            let content = extract_page(&doc, page_number, page_id, ctx.ocr);
+            if content.trim().is_empty() {
+                continue;
+            }
+            extraction.push(
+                ResourceTextContentType::PDF,
+                content,
+                ResourceTextContentMetadata {
+                    url: resource.metadata.as_ref().map(|m| m.source_uri.clone()),
+                    page: Some(page_number),
+                    section: metadata
+                        .outline
+                        .as_ref()
+                        .and_then(|outline| section_for_page(outline, page_number)),
+                    ..Default::default()
+                },
+            );
+        }
+        extraction.metadata = Some(metadata).filter(|m| !m.is_empty());
+        Ok(extraction)
+    }
+}
+
+fn pdf_error(err: lopdf::Error) -> BackendError {
+    BackendError::GenericError(format!("pdf error: {err}"))
+}
+
+// a broken page is logged and skipped instead of failing the whole document
+fn extract_page(
+    doc: &Document,
+    page_number: u32,
+    page_id: ObjectId,
+    ocr: Option<&OcrModels>,
+) -> String {
+    let text = match extract_page_text(doc, page_id) {
+        Ok(text) => text,
+        Err(err) => {
+            tracing::warn!("failed to extract text from pdf page {page_number}: {err}");
+            String::new()
+        }
+    };
+    if has_text_layer(&text) {
+        return text;
+    }
+
+    let ocr = match ocr {
+        Some(ocr) => ocr,
+        None => return text,
+    };
+    match ocr_page(doc, page_id, ocr) {
+        Ok(Some(ocr_text)) if !ocr_text.is_empty() => ocr_text,
+        Ok(_) => text,
+        Err(err) => {
+            tracing::warn!("failed to ocr pdf page {page_number}: {err}");
+            text
+        }
+    }
+}
+
+fn has_text_layer(text: &str) -> bool {
+    text.chars().filter(|c| c.is_alphanumeric()).count() >= MIN_TEXT_LAYER_CHARS
+}
+
+fn deref<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
+
+fn dict_entry<'a>(doc: &'a Document, dict: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+    dict.get(key).ok().and_then(|object| deref(doc, object))
+}
+
+// `/Info` strings are either UTF-16BE with a BOM or PDFDocEncoding, which is close enough
+// to latin-1 for metadata
+fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// `D:YYYYMMDDHHmmSSOHH'mm'` where everything after the year is optional
+fn parse_pdf_date(date: &str) -> Option<String> {
+    let date = date.trim();
+    let date = date.strip_prefix("D:").unwrap_or(date);
+    let digits_len = date.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits_len < 4 {
+        return None;
+    }
+    let (digits, rest) = date.split_at(digits_len);
+    let field = |start: usize, len: usize, default: u32| {
+        digits
+            .get(start..start + len)
+            .and_then(|value| value.parse::<u32>().ok())
+            .unwrap_or(default)
+    };
+
+    let offset = match rest.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let mut parts = rest[1..].split('\'');
+            let hours = parts
+                .next()
+                .and_then(|h| h.parse::<u32>().ok())
+                .unwrap_or(0);
+            let minutes = parts
+                .next()
+                .and_then(|m| m.parse::<u32>().ok())
+                .unwrap_or(0);
+            format!("{sign}{hours:02}:{minutes:02}")
+        }
+        _ => "+00:00".to_owned(),
+    };
+    let rfc3339 = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+        field(0, 4, 0),
+        field(4, 2, 1),
+        field(6, 2, 1),
+        field(8, 2, 0),
+        field(10, 2, 0),
+        field(12, 2, 0),
+        offset
+    );
+    chrono::DateTime::parse_from_rfc3339(&rfc3339)
+        .ok()
+        .map(|date| date.to_rfc3339())
+}
+
+fn document_metadata(doc: &Document) -> ExtractedResourceMetadata {
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|info| deref(doc, info))
+        .and_then(|info| info.as_dict().ok());
+    let info_string = |key: &[u8]| {
+        info.and_then(|info| dict_entry(doc, info, key))
+            .and_then(|value| value.as_str().ok())
+            .map(|value| normalize_whitespace(&decode_text_string(value)))
+            .filter(|value| !value.is_empty())
+    };
+
+    ExtractedResourceMetadata {
+        title: info_string(b"Title"),
+        byline: info_string(b"Author"),
+        published_at: info_string(b"CreationDate").and_then(|date| parse_pdf_date(&date)),
+        lead_image: None,
+        outline: read_outline(doc),
+    }
+}
+
+struct OutlineReader<'a> {
+    doc: &'a Document,
+    page_numbers: HashMap<ObjectId, u32>,
+    named_destinations: Option<&'a Dictionary>,
+    visited: HashSet<ObjectId>,
+    entries: Vec<DocumentOutlineEntry>,
+}
+
+impl<'a> OutlineReader<'a> {
+    fn read_items(&mut self, first: &'a Object, level: usize) {
+        if level > MAX_OUTLINE_DEPTH {
+            return;
+        }
+        let mut next = Some(first);
+        while let Some(item) = next {
+            let id = match item.as_reference() {
+                Ok(id) => id,
+                Err(_) => break,
+            };
+            if !self.visited.insert(id) || self.entries.len() >= MAX_OUTLINE_ENTRIES {
+                break;
+            }
+            let item = match self.doc.get_dictionary(id) {
+                Ok(item) => item,
+                Err(_) => break,
+            };
+
+            let title = dict_entry(self.doc, item, b"Title")
+                .and_then(|title| title.as_str().ok())
+                .map(|title| normalize_whitespace(&decode_text_string(title)))
+                .filter(|title| !title.is_empty());
+            if let Some(title) = title {
+                let page = self.item_page(item);
+                self.entries
+                    .push(DocumentOutlineEntry { title, level, page });
+            }
+            if let Ok(children) = item.get(b"First") {
+                self.read_items(children, level + 1);
+            }
+            next = item.get(b"Next").ok();
+        }
+    }
+
+    fn item_page(&self, item: &Dictionary) -> Option<u32> {
+        if let Some(destination) = dict_entry(self.doc, item, b"Dest") {
+            return self.destination_page(destination, 0);
+        }
+        let action = dict_entry(self.doc, item, b"A")?.as_dict().ok()?;
+        if action.get(b"S").and_then(Object::as_name).ok()? != b"GoTo" {
+            return None;
+        }
+        self.destination_page(dict_entry(self.doc, action, b"D")?, 0)
+    }
+
+    fn destination_page(&self, destination: &Object, depth: usize) -> Option<u32> {
+        if depth > 3 {
+            return None;
+        }
+        match deref(self.doc, destination)? {
+            Object::Array(items) => items
+                .first()?
+                .as_reference()
+                .ok()
+                .and_then(|page_id| self.page_numbers.get(&page_id).copied()),
+            Object::Dictionary(dict) => {
+                self.destination_page(dict_entry(self.doc, dict, b"D")?, depth + 1)
+            }
+            // named destinations from the catalog's `/Dests`, the newer name tree is not resolved
+            Object::Name(name) | Object::String(name, _) => {
+                let destination = dict_entry(self.doc, self.named_destinations?, name)?;
+                self.destination_page(destination, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn read_outline(doc: &Document) -> Option<DocumentOutline> {
+    let catalog = doc.catalog().ok()?;
+    let outlines = dict_entry(doc, catalog, b"Outlines")?.as_dict().ok()?;
+    let first = outlines.get(b"First").ok()?;
+
+    let mut reader = OutlineReader {
+        doc,
+        page_numbers: doc
+            .get_pages()
+            .into_iter()
+            .map(|(number, id)| (id, number))
+            .collect(),
+        named_destinations: dict_entry(doc, catalog, b"Dests").and_then(|d| d.as_dict().ok()),
+        visited: HashSet::new(),
+        entries: Vec::new(),
+    };
+    reader.read_items(first, 0);
+    Some(DocumentOutline(reader.entries)).filter(|outline| !outline.0.is_empty())
+}
+
+// the closest outline entry at or before the page
+fn section_for_page(outline: &DocumentOutline, page_number: u32) -> Option<String> {
+    let mut section = None;
+    let mut section_page = 0;
+    for entry in &outline.0 {
+        if let Some(page) = entry
+            .page
+            .filter(|page| *page <= page_number && *page >= section_page)
+        {
+            section = Some(entry.title.clone());
+            section_page = page;
+        }
+    }
+    section
+}
+
+// code to unicode mapping from a font's `/ToUnicode` CMap
+fn parse_to_unicode(data: &[u8]) -> HashMap<u32, String> {
+    enum Token {
+        Hex(Vec<u8>),
+        ArrayStart,
+        ArrayEnd,
+        Word(String),
+    }
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'<' => {
+                let end = data[i..]
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .map_or(data.len(), |p| i + p);
+                let hex: Vec<u8> = data[i + 1..end]
+                    .iter()
+                    .filter(|b| b.is_ascii_hexdigit())
+                    .copied()
+                    .collect();
+                let bytes = hex
+                    .chunks(2)
+                    .filter_map(|pair| {
+                        let pair = std::str::from_utf8(pair).ok()?;
+                        // an odd trailing digit is padded with 0
+                        u8::from_str_radix(&format!("{pair:0<2}"), 16).ok()
+                    })
+                    .collect();
+                tokens.push(Token::Hex(bytes));
+                i = end + 1;
+            }
+            b'[' => {
+                tokens.push(Token::ArrayStart);
+                i += 1;
+            }
+            b']' => {
+                tokens.push(Token::ArrayEnd);
+                i += 1;
+            }
+            b'%' => {
+                while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                while i < data.len()
+                    && !data[i].is_ascii_whitespace()
+                    && !matches!(data[i], b'<' | b'[' | b']')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(
+                    String::from_utf8_lossy(&data[start..i]).into_owned(),
+                ));
+            }
+        }
+    }
+
+    fn code(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u32)
+    }
+    fn utf16(bytes: &[u8]) -> Vec<u16> {
+        bytes
+            .chunks(2)
+            .map(|pair| match pair {
+                [hi, lo] => u16::from_be_bytes([*hi, *lo]),
+                [single] => *single as u16,
+                _ => 0,
+            })
+            .collect()
+    }
+
+    let mut map = HashMap::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Word(word) if word == "beginbfchar" => {
+                while let (Some(Token::Hex(src)), Some(Token::Hex(dst))) =
+                    (tokens.next(), tokens.next())
+                {
+                    map.insert(code(&src), String::from_utf16_lossy(&utf16(&dst)));
+                }
+            }
+            Token::Word(word) if word == "beginbfrange" => {
+                while let (Some(Token::Hex(low)), Some(Token::Hex(high))) =
+                    (tokens.next(), tokens.next())
+                {
+                    let (low, high) = (code(&low), code(&high));
+                    // ranges over 64k codes are not something a real font needs
+                    if high < low || high - low > u16::MAX as u32 {
+                        break;
+                    }
+                    match tokens.next() {
+                        Some(Token::Hex(dst)) => {
+                            let base = utf16(&dst);
+                            for (offset, code) in (low..=high).enumerate() {
+                                let mut units = base.clone();
+                                if let Some(last) = units.last_mut() {
+                                    *last = last.wrapping_add(offset as u16);
+                                }
+                                map.insert(code, String::from_utf16_lossy(&units));
+                            }
+                        }
+                        Some(Token::ArrayStart) => {
+                            let mut code = low;
+                            while let Some(Token::Hex(dst)) = tokens.next() {
+                                if code <= high {
+                                    map.insert(code, String::from_utf16_lossy(&utf16(&dst)));
+                                }
+                                code += 1;
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    map
+}
+
+struct FontInfo {
+    encoding: Option<String>,
+    to_unicode: Option<HashMap<u32, String>>,
+    // composite fonts use two byte codes, which we can only map through `/ToUnicode`
+    composite: bool,
+    first_char: u32,
+    widths: Vec<f32>,
+}
+
+// width of glyphs the font doesn't describe, in thousandths of the font size
+const DEFAULT_GLYPH_WIDTH: f32 = 500.0;
+
+impl FontInfo {
+    fn new(doc: &Document, font: &Dictionary) -> Self {
+        let composite = font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0");
+        let encoding = dict_entry(doc, font, b"Encoding").and_then(|encoding| match encoding {
+            Object::Name(name) => Some(String::from_utf8_lossy(name).into_owned()),
+            Object::Dictionary(dict) => dict
+                .get(b"BaseEncoding")
+                .and_then(Object::as_name_str)
+                .ok()
+                .map(|name| name.to_owned()),
+            _ => None,
+        });
+        let to_unicode = dict_entry(doc, font, b"ToUnicode")
+            .and_then(|cmap| cmap.as_stream().ok())
+            .map(|cmap| {
+                parse_to_unicode(
+                    &cmap
+                        .decompressed_content()
+                        .unwrap_or_else(|_| cmap.content.clone()),
+                )
+            });
+        let first_char = font
+            .get(b"FirstChar")
+            .and_then(Object::as_i64)
+            .unwrap_or(0)
+            .max(0) as u32;
+        let widths = dict_entry(doc, font, b"Widths")
+            .and_then(|widths| widths.as_array().ok())
+            .map(|widths| {
+                widths
+                    .iter()
+                    .map(|w| deref(doc, w).and_then(|w| w.as_float().ok()).unwrap_or(0.0))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            encoding,
+            to_unicode,
+            composite,
+            first_char,
+            widths,
+        }
+    }
+
+    // decoded text and its advance in text space units, i.e. before scaling by the font size
+    fn decode(&self, bytes: &[u8]) -> (String, f32) {
+        let code_len = if self.composite { 2 } else { 1 };
+        let mut text = String::new();
+        let mut width = 0.0;
+        for chunk in bytes.chunks(code_len) {
+            let code = chunk.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            width += code
+                .checked_sub(self.first_char)
+                .and_then(|index| self.widths.get(index as usize))
+                .copied()
+                .filter(|w| *w > 0.0)
+                .unwrap_or(DEFAULT_GLYPH_WIDTH);
+
+            match self.to_unicode.as_ref().and_then(|map| map.get(&code)) {
+                Some(unicode) => text.push_str(unicode),
+                None if !self.composite => {
+                    text.push_str(&Document::decode_text(self.encoding.as_deref(), chunk))
+                }
+                None => {}
+            }
+        }
+        (text, width / 1000.0)
+    }
+}
+
+type Matrix = [f32; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    [
+        a[0] * b[0] + a[1] * b[2],
+        a[0] * b[1] + a[1] * b[3],
+        a[2] * b[0] + a[3] * b[2],
+        a[2] * b[1] + a[3] * b[3],
+        a[4] * b[0] + a[5] * b[2] + b[4],
+        a[4] * b[1] + a[5] * b[3] + b[5],
+    ]
+}
+
+fn translation(tx: f32, ty: f32) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+// a run of text shown by a single operator, positioned in page space
+#[derive(Debug, Clone)]
+struct TextSpan {
+    x: f32,
+    y: f32,
+    width: f32,
+    size: f32,
+    text: String,
+}
+
+struct TextState<'a> {
+    ctm: Matrix,
+    ctm_stack: Vec<Matrix>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+    font: Option<&'a FontInfo>,
+    font_size: f32,
+    leading: f32,
+}
+
+impl TextState<'_> {
+    fn next_line(&mut self, tx: f32, ty: f32) {
+        self.line_matrix = multiply(&translation(tx, ty), &self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    fn show(&mut self, operands: &[Object], spans: &mut Vec<TextSpan>) {
+        let font = match self.font {
+            Some(font) => font,
+            None => return,
+        };
+        let font_size = self.font_size;
+        let start = multiply(&self.text_matrix, &self.ctm);
+        let mut text = String::new();
+        let mut advance = 0.0;
+
+        let show_string = |bytes: &[u8], text: &mut String| {
+            let (decoded, width) = font.decode(bytes);
+            text.push_str(&decoded);
+            width * font_size
+        };
+        for operand in operands {
+            match operand {
+                Object::String(bytes, _) => advance += show_string(bytes, &mut text),
+                Object::Array(items) => {
+                    for item in items {
+                        match item {
+                            Object::String(bytes, _) => advance += show_string(bytes, &mut text),
+                            item => {
+                                let adjustment = item.as_float().unwrap_or(0.0);
+                                advance -= adjustment / 1000.0 * font_size;
+                                // large negative kerning is how many generators encode spaces
+                                if adjustment < -200.0 && !text.ends_with(' ') {
+                                    text.push(' ');
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.text_matrix = multiply(&translation(advance, 0.0), &self.text_matrix);
+
+        if !text.trim().is_empty() {
+            let scale_x = (start[0] * start[0] + start[1] * start[1]).sqrt();
+            let scale_y = (start[2] * start[2] + start[3] * start[3]).sqrt();
+            spans.push(TextSpan {
+                x: start[4],
+                y: start[5],
+                width: advance * scale_x,
+                size: (self.font_size * scale_y).abs().max(1.0),
+                text,
+            });
+        }
+    }
+}
+
+fn collect_spans(
+    operations: &[lopdf::content::Operation],
+    fonts: &HashMap<Vec<u8>, FontInfo>,
+) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    let mut state = TextState {
+        ctm: IDENTITY,
+        ctm_stack: Vec::new(),
+        text_matrix: IDENTITY,
+        line_matrix: IDENTITY,
+        font: None,
+        font_size: 0.0,
+        leading: 0.0,
+    };
+
+    for operation in operations {
+        let operands = &operation.operands;
+        let number = |index: usize| {
+            operands
+                .get(index)
+                .and_then(|operand| operand.as_float().ok())
+                .unwrap_or(0.0)
+        };
+        let matrix = || {
+            [
+                number(0),
+                number(1),
+                number(2),
+                number(3),
+                number(4),
+                number(5),
+            ]
+        };
+
+        match operation.operator.as_str() {
+            "q" => state.ctm_stack.push(state.ctm),
+            "Q" => state.ctm = state.ctm_stack.pop().unwrap_or(IDENTITY),
+            "cm" => state.ctm = multiply(&matrix(), &state.ctm),
+            "BT" => {
+                state.text_matrix = IDENTITY;
+                state.line_matrix = IDENTITY;
+            }
+            "Tf" => {
+                state.font = operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .and_then(|name| fonts.get(name));
+                state.font_size = number(1);
+            }
+            "TL" => state.leading = number(0),
+            "Td" => state.next_line(number(0), number(1)),
+            "TD" => {
+                state.leading = -number(1);
+                state.next_line(number(0), number(1));
+            }
+            "Tm" => {
+                state.line_matrix = matrix();
+                state.text_matrix = state.line_matrix;
+            }
+            "T*" => state.next_line(0.0, -state.leading),
+            "Tj" | "TJ" => state.show(operands, &mut spans),
+            "'" => {
+                state.next_line(0.0, -state.leading);
+                state.show(operands, &mut spans);
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+// a horizontal run of text on one line, lines of a multi-column page hold one segment per column
+#[derive(Debug, Clone)]
+struct Segment {
+    x0: f32,
+    x1: f32,
+    y: f32,
+    size: f32,
+    text: String,
+}
+
+fn build_segments(mut spans: Vec<TextSpan>) -> Vec<Vec<Segment>> {
+    // top to bottom, pdf y coordinates grow upwards
+    spans.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut lines: Vec<Vec<TextSpan>> = Vec::new();
+    for span in spans {
+        match lines.last_mut() {
+            Some(line) if (line[0].y - span.y).abs() <= line[0].size.min(span.size) * 0.5 => {
+                line.push(span)
+            }
+            _ => lines.push(vec![span]),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut segments: Vec<Segment> = Vec::new();
+            for span in line {
+                match segments.last_mut() {
+                    Some(segment) if span.x - segment.x1 <= span.size.max(segment.size) => {
+                        let gap = span.x - segment.x1;
+                        if gap > span.size * 0.15
+                            && !segment.text.ends_with(' ')
+                            && !span.text.starts_with(' ')
+                        {
+                            segment.text.push(' ');
+                        }
+                        segment.text.push_str(&span.text);
+                        segment.x1 = segment.x1.max(span.x + span.width);
+                    }
+                    _ => segments.push(Segment {
+                        x0: span.x,
+                        x1: span.x + span.width,
+                        y: span.y,
+                        size: span.size,
+                        text: span.text,
+                    }),
+                }
+            }
+            segments
+        })
+        .collect()
+}
+
+// the x position of the gap between two text columns, if the page has them
+fn find_gutter(segments: &[&Segment], page_left: f32, page_right: f32) -> Option<f32> {
+    let total = segments.len();
+    if total < 6 {
+        return None;
+    }
+    let width = page_right - page_left;
+
+    // the position in the middle of the page crossed by the fewest lines
+    let (crossing, gutter) = (30..=70)
+        .map(|percent| page_left + width * percent as f32 / 100.0)
+        .map(|x| (segments.iter().filter(|s| s.x0 < x && s.x1 > x).count(), x))
+        .min_by(|a, b| a.0.cmp(&b.0))?;
+    let left = segments.iter().filter(|s| s.x1 <= gutter).count();
+    let right = segments.iter().filter(|s| s.x0 >= gutter).count();
+
+    let is_columns = crossing * 5 <= total && left >= 3 && right >= 3;
+    is_columns.then_some(gutter)
+}
+
+// reading order: full-width lines stay in place, column text between them is read
+// left column first
+fn layout_text(spans: Vec<TextSpan>, page_left: f32, page_right: f32) -> String {
+    let lines = build_segments(spans);
+    let segments: Vec<&Segment> = lines.iter().flatten().collect();
+
+    let rows: Vec<(f32, f32, String)> = match find_gutter(&segments, page_left, page_right) {
+        Some(gutter) => {
+            let mut rows = Vec::new();
+            let mut left = Vec::new();
+            let mut right = Vec::new();
+            let flush = |left: &mut Vec<&Segment>, right: &mut Vec<&Segment>, rows: &mut Vec<_>| {
+                for segment in left.drain(..).chain(right.drain(..)) {
+                    rows.push((segment.y, segment.size, segment.text.clone()));
+                }
+            };
+            for segment in segments {
+                if segment.x1 <= gutter {
+                    left.push(segment);
+                } else if segment.x0 >= gutter {
+                    right.push(segment);
+                } else {
+                    flush(&mut left, &mut right, &mut rows);
+                    rows.push((segment.y, segment.size, segment.text.clone()));
+                }
+            }
+            flush(&mut left, &mut right, &mut rows);
+            rows
+        }
+        None => lines
+            .iter()
+            .map(|line| {
+                let text = line
+                    .iter()
+                    .map(|segment| segment.text.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (line[0].y, line[0].size, text)
+            })
+            .collect(),
+    };
+
+    let mut text = String::new();
+    let mut previous: Option<(f32, f32)> = None;
+    for (y, size, row) in rows {
+        let row = normalize_whitespace(&row);
+        if row.is_empty() {
+            continue;
+        }
+        if let Some((previous_y, previous_size)) = previous {
+            // a large gap or a jump back up to the next column starts a new paragraph
+            let gap = previous_y - y;
+            if gap < 0.0 || gap > previous_size.max(size) * 1.8 {
+                text.push_str("\n\n");
+            } else {
+                text.push('\n');
+            }
+        }
+        text.push_str(&row);
+        previous = Some((y, size));
+    }
+    text
+}
+
+fn inherited_page_entry<'a>(
+    doc: &'a Document,
+    page_id: ObjectId,
+    key: &[u8],
+) -> Option<&'a Object> {
+    let mut node = doc.get_dictionary(page_id).ok()?;
+    for _ in 0..32 {
+        if let Some(value) = dict_entry(doc, node, key) {
+            return Some(value);
+        }
+        node = dict_entry(doc, node, b"Parent")?.as_dict().ok()?;
+    }
+    None
+}
+
+fn page_horizontal_bounds(doc: &Document, page_id: ObjectId) -> (f32, f32) {
+    inherited_page_entry(doc, page_id, b"MediaBox")
+        .and_then(|media_box| media_box.as_array().ok())
+        .and_then(|media_box| {
+            let left = media_box.first()?.as_float().ok()?;
+            let right = media_box.get(2)?.as_float().ok()?;
+            Some((left.min(right), left.max(right)))
+        })
+        // US letter
+        .unwrap_or((0.0, 612.0))
+}
+
+fn extract_page_text(doc: &Document, page_id: ObjectId) -> BackendResult<String> {
+    let fonts: HashMap<Vec<u8>, FontInfo> = doc
+        .get_page_fonts(page_id)
+        .into_iter()
+        .map(|(name, font)| (name, FontInfo::new(doc, font)))
+        .collect();
+    let content = doc
+        .get_and_decode_page_content(page_id)
+        .map_err(pdf_error)?;
+    let spans = collect_spans(&content.operations, &fonts);
+    let (page_left, page_right) = page_horizontal_bounds(doc, page_id);
+    Ok(layout_text(spans, page_left, page_right))
+}
+
+// images in the order the page draws them, including the ones inside form xobjects
+fn page_images(doc: &Document, page_id: ObjectId) -> Vec<&Stream> {
+    let content = match doc.get_and_decode_page_content(page_id) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let (inline_resources, resource_ids) = doc.get_page_resources(page_id);
+    let resources: Vec<&Dictionary> = inline_resources
+        .into_iter()
+        .chain(
+            resource_ids
+                .into_iter()
+                .filter_map(|id| doc.get_dictionary(id).ok()),
+        )
+        .collect();
+    let mut images = Vec::new();
+    collect_drawn_images(doc, &content.operations, &resources, 0, &mut images);
+    images
+}
+
+fn collect_drawn_images<'a>(
+    doc: &'a Document,
+    operations: &[lopdf::content::Operation],
+    resources: &[&'a Dictionary],
+    depth: usize,
+    images: &mut Vec<&'a Stream>,
+) {
+    for operation in operations.iter().filter(|op| op.operator == "Do") {
+        let name = match operation.operands.first().and_then(|n| n.as_name().ok()) {
+            Some(name) => name,
+            None => continue,
+        };
+        let stream = resources.iter().find_map(|resources| {
+            let xobjects = dict_entry(doc, resources, b"XObject")?.as_dict().ok()?;
+            deref(doc, xobjects.get(name).ok()?)?.as_stream().ok()
+        });
+        let stream = match stream {
+            Some(stream) => stream,
+            None => continue,
+        };
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => {
+                let is_mask = stream
+                    .dict
+                    .get(b"ImageMask")
+                    .and_then(Object::as_bool)
+                    .unwrap_or(false);
+                if !is_mask {
+                    images.push(stream);
+                }
+            }
+            Ok(b"Form") if depth < MAX_XOBJECT_DEPTH => {
+                let content = stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone());
+                let operations = match lopdf::content::Content::decode(&content) {
+                    Ok(content) => content.operations,
+                    Err(_) => continue,
+                };
+                // forms without their own resources use the page's
+                match dict_entry(doc, &stream.dict, b"Resources").and_then(|r| r.as_dict().ok()) {
+                    Some(form_resources) => {
+                        collect_drawn_images(doc, &operations, &[form_resources], depth + 1, images)
+                    }
+                    None => collect_drawn_images(doc, &operations, resources, depth + 1, images),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn image_dimension(stream: &Stream, key: &[u8]) -> u32 {
+    stream
+        .dict
+        .get(key)
+        .and_then(Object::as_i64)
+        .unwrap_or(0)
+        .clamp(0, u32::MAX as i64) as u32
+}
+
+// some scanners store a page as horizontal strips drawn top to bottom, consecutive images
+// of the same width are stacked back together so lines cut by a strip boundary stay whole
+fn page_scan_images(doc: &Document, page_id: ObjectId) -> Vec<image::RgbImage> {
+    let mut scans: Vec<image::RgbImage> = Vec::new();
+    for stream in page_images(doc, page_id) {
+        let image = match decode_image(doc, stream) {
+            Ok(image) => image,
+            Err(err) => {
+                tracing::warn!("failed to decode pdf page image: {err}");
+                continue;
+            }
+        };
+        match scans.last_mut() {
+            Some(last) if last.width() == image.width() => {
+                let mut stacked =
+                    image::RgbImage::new(last.width(), last.height() + image.height());
+                image::imageops::replace(&mut stacked, last, 0, 0);
+                image::imageops::replace(&mut stacked, &image, 0, last.height() as i64);
+                *last = stacked;
+            }
+            _ => scans.push(image),
+        }
+    }
+    scans
+}
+
+fn inflate(data: &[u8]) -> BackendResult<Vec<u8>> {
+    let mut output = Vec::new();
+    flate2::read::ZlibDecoder::new(data)
+        .read_to_end(&mut output)
+        .map_err(|err| BackendError::GenericError(format!("failed to inflate image: {err}")))?;
+    Ok(output)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// undoes the png row filters flate streams can use when `/Predictor` is 10 or higher
+fn png_unpredict(data: &[u8], bytes_per_pixel: usize, row_len: usize) -> BackendResult<Vec<u8>> {
+    let mut output = Vec::with_capacity(data.len());
+    let mut previous = vec![0u8; row_len];
+    for row in data.chunks_exact(row_len + 1) {
+        let filter = row[0];
+        let mut current = row[1..].to_vec();
+        for i in 0..row_len {
+            let left = if i >= bytes_per_pixel {
+                current[i - bytes_per_pixel]
+            } else {
+                0
+            };
+            let up = previous[i];
+            let up_left = if i >= bytes_per_pixel {
+                previous[i - bytes_per_pixel]
+            } else {
+                0
+            };
+            current[i] = match filter {
+                0 => current[i],
+                1 => current[i].wrapping_add(left),
+                2 => current[i].wrapping_add(up),
+                3 => current[i].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => current[i].wrapping_add(paeth(left, up, up_left)),
+                filter => {
+                    return Err(BackendError::GenericError(format!(
+                        "unknown png filter {filter}"
+                    )))
+                }
+            };
+        }
+        output.extend_from_slice(&current);
+        previous = current;
+    }
+    Ok(output)
+}
+
+fn color_components(doc: &Document, stream: &Stream) -> BackendResult<usize> {
+    let color_space = dict_entry(doc, &stream.dict, b"ColorSpace");
+    let name = match color_space {
+        Some(Object::Name(name)) => name.as_slice(),
+        Some(Object::Array(items)) => match items.first().and_then(|i| i.as_name().ok()) {
+            Some(b"ICCBased") => {
+                return items
+                    .get(1)
+                    .and_then(|profile| deref(doc, profile))
+                    .and_then(|profile| profile.as_stream().ok())
+                    .and_then(|profile| profile.dict.get(b"N").and_then(Object::as_i64).ok())
+                    .map(|n| n as usize)
+                    .ok_or_else(|| {
+                        BackendError::GenericError("invalid ICCBased color space".to_owned())
+                    })
+            }
+            Some(name) => name,
+            None => b"",
+        },
+        // grayscale is the safest guess for scans
+        _ => b"DeviceGray",
+    };
+    match name {
+        b"DeviceGray" | b"CalGray" | b"G" => Ok(1),
+        b"DeviceRGB" | b"CalRGB" | b"RGB" => Ok(3),
+        b"DeviceCMYK" | b"CMYK" => Ok(4),
+        other => Err(BackendError::GenericError(format!(
+            "unsupported image color space {}",
+            String::from_utf8_lossy(other)
+        ))),
+    }
+}
+
+// decodes the image formats scanners commonly produce, CCITT, JBIG2 and JPEG 2000 are not
+// supported by the image crate and are reported as errors
+fn decode_image(doc: &Document, stream: &Stream) -> BackendResult<image::RgbImage> {
+    let width = image_dimension(stream, b"Width");
+    let height = image_dimension(stream, b"Height");
+    if width == 0 || height == 0 {
+        return Err(BackendError::GenericError(
+            "image has no dimensions".to_owned(),
+        ));
+    }
+
+    let mut data = stream.content.clone();
+    for filter in stream.filters().unwrap_or_default() {
+        match filter.as_str() {
+            "FlateDecode" | "Fl" => data = inflate(&data)?,
+            "DCTDecode" | "DCT" => {
+                return image::load_from_memory_with_format(&data, image::ImageFormat::Jpeg)
+                    .map(|image| image.into_rgb8())
+                    .map_err(|err| {
+                        BackendError::GenericError(format!("failed to decode jpeg: {err}"))
+                    })
+            }
+            other => {
+                return Err(BackendError::GenericError(format!(
+                    "unsupported image filter {other}"
+                )))
+            }
+        }
+    }
+
+    let components = color_components(doc, stream)?;
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .unwrap_or(8) as usize;
+    let row_len = match bits {
+        8 => width as usize * components,
+        1 if components == 1 => (width as usize).div_ceil(8),
+        bits => {
+            return Err(BackendError::GenericError(format!(
+                "unsupported image bit depth {bits}"
+            )))
+        }
+    };
+
+    let predictor = dict_entry(doc, &stream.dict, b"DecodeParms")
+        .and_then(|params| match params {
+            Object::Array(params) => params.first().and_then(|p| deref(doc, p)),
+            params => Some(params),
+        })
+        .and_then(|params| params.as_dict().ok())
+        .and_then(|params| params.get(b"Predictor").and_then(Object::as_i64).ok())
+        .unwrap_or(1);
+    if predictor >= 10 {
+        data = png_unpredict(&data, components.max(bits * components / 8), row_len)?;
+    }
+    if data.len() < row_len * height as usize {
+        return Err(BackendError::GenericError(
+            "image data is truncated".to_owned(),
+        ));
+    }
+
+    let pixel = |x: usize, y: usize| -> [u8; 3] {
+        let row = &data[y * row_len..(y + 1) * row_len];
+        match (bits, components) {
+            (1, _) => {
+                let bit = row[x / 8] >> (7 - x % 8) & 1;
+                let value = if bit == 1 { 255 } else { 0 };
+                [value, value, value]
+            }
+            (_, 1) => [row[x], row[x], row[x]],
+            (_, 3) => [row[x * 3], row[x * 3 + 1], row[x * 3 + 2]],
+            _ => {
+                let cmyk = &row[x * components..x * components + 4];
+                let channel = |c: u8| ((255 - c as u16) * (255 - cmyk[3] as u16) / 255) as u8;
+                [channel(cmyk[0]), channel(cmyk[1]), channel(cmyk[2])]
+            }
+        }
+    };
+    Ok(image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb(pixel(x as usize, y as usize))
+    }))
+}
+
+// pages aren't rasterized, so the preview of a pdf is the biggest image on its first page,
+// which covers scans and most slide decks but not text only documents
+pub(crate) fn first_page_image(path: &str) -> BackendResult<Option<image::RgbImage>> {
+    let doc = Document::load(path)
+        .map_err(|err| BackendError::GenericError(format!("failed to load pdf: {err}")))?;
+    let page_id = match doc.get_pages().into_values().next() {
+        Some(page_id) => page_id,
+        None => return Ok(None),
+    };
+    Ok(page_scan_images(&doc, page_id)
+        .into_iter()
+        .max_by_key(|image| image.width() as u64 * image.height() as u64))
+}
+
+// only the images embedded in the page are read, text drawn as vector paths is not found
+fn ocr_page(doc: &Document, page_id: ObjectId, ocr: &OcrModels) -> BackendResult<Option<String>> {
+    let scans = page_scan_images(doc, page_id);
+    if scans.is_empty() {
+        return Ok(None);
+    }
+    let mut texts = Vec::new();
+    for scan in scans {
+        let result = ocr_image(ocr, &scan, None)
+            .map_err(|err| BackendError::GenericError(format!("ocr failed: {err}")))?;
+        texts.push(result.text());
+    }
+    texts.retain(|text| !text.trim().is_empty());
+    Ok(Some(texts.join("\n\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{
+        content::{Content, Operation},
+        dictionary,
+    };
+
+    // a document whose pages are given as raw content streams, all using Helvetica as /F1
+    fn build_document(pages: Vec<Vec<u8>>) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let mut kids = Vec::new();
+        for content in pages {
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+        let count = kids.len() as i64;
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => count,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    fn text_content(lines: &[(f32, f32, &str)]) -> Vec<u8> {
+        let mut operations = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 10.into()]),
+        ];
+        for (x, y, text) in lines {
+            operations.push(Operation::new(
+                "Tm",
+                vec![
+                    1.into(),
+                    0.into(),
+                    0.into(),
+                    1.into(),
+                    (*x).into(),
+                    (*y).into(),
+                ],
+            ));
+            operations.push(Operation::new("Tj", vec![Object::string_literal(*text)]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+        Content { operations }.encode().unwrap()
+    }
+
+    #[test]
+    fn test_two_columns_are_read_left_column_first() {
+        // generators often emit multi-column text row by row across both columns
+        let left = [
+            "The left column starts the article",
+            "and continues on this second line",
+            "before it ends on the third line.",
+        ];
+        let right = [
+            "The right column picks up the story",
+            "and carries it through to the end",
+            "of the page with this final line.",
+        ];
+        let mut lines = vec![(
+            72.0,
+            740.0,
+            "A Heading Spanning The Whole Page Width For Both Columns",
+        )];
+        for (i, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            let y = 700.0 - i as f32 * 12.0;
+            lines.push((72.0, y, l));
+            lines.push((320.0, y, r));
+        }
+        let doc = build_document(vec![text_content(&lines)]);
+        let page_id = doc.get_pages()[&1];
+
+        let text = extract_page_text(&doc, page_id).unwrap();
+        let expected = format!(
+            "A Heading Spanning The Whole Page Width For Both Columns\n\n{}\n\n{}",
+            left.join("\n"),
+            right.join("\n")
+        );
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_broken_page_does_not_fail_the_document() {
+        let doc = build_document(vec![
+            text_content(&[(
+                72.0,
+                700.0,
+                "First page with enough text to count as a text layer",
+            )]),
+            // a `Tf` without operands used to fail text extraction for the whole document
+            b"BT Tf (orphaned text) Tj ET".to_vec(),
+            text_content(&[(
+                72.0,
+                700.0,
+                "Third page is fine as well and has plenty of text",
+            )]),
+        ]);
+        assert!(doc.extract_text(&[2]).is_err());
+
+        let pages: Vec<String> = doc
+            .get_pages()
+            .into_iter()
+            .map(|(number, id)| extract_page(&doc, number, id, None))
+            .collect();
+        assert_eq!(
+            pages,
+            vec![
+                "First page with enough text to count as a text layer".to_string(),
+                String::new(),
+                "Third page is fine as well and has plenty of text".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_unicode_cmap() {
+        let cmap = b"/CIDInit /ProcSet findresource begin
+            1 begincodespacerange <0000> <FFFF> endcodespacerange
+            2 beginbfchar
+            <0003> <0020>
+            <0011> <00660069>
+            endbfchar
+            2 beginbfrange
+            <0024> <0026> <0041>
+            <0030> <0031> [<0078> <0079>]
+            endbfrange
+            endcmap";
+        let map = parse_to_unicode(cmap);
+        assert_eq!(map.get(&0x03).map(String::as_str), Some(" "));
+        assert_eq!(map.get(&0x11).map(String::as_str), Some("fi"));
+        assert_eq!(map.get(&0x26).map(String::as_str), Some("C"));
+        assert_eq!(map.get(&0x31).map(String::as_str), Some("y"));
+
+        let font = FontInfo {
+            encoding: None,
+            to_unicode: Some(map),
+            composite: true,
+            first_char: 0,
+            widths: vec![],
+        };
+        assert_eq!(font.decode(&[0x00, 0x24, 0x00, 0x03, 0x00, 0x11]).0, "A fi");
+    }
+
+    #[test]
+    fn test_document_metadata() {
+        let mut doc = build_document(vec![
+            text_content(&[(72.0, 700.0, "one")]),
+            text_content(&[(72.0, 700.0, "two")]),
+            text_content(&[(72.0, 700.0, "three")]),
+        ]);
+        let pages = doc.get_pages();
+
+        let mut utf16_title = vec![0xfe, 0xff];
+        utf16_title.extend(
+            "Annual Report – 2023"
+                .encode_utf16()
+                .flat_map(u16::to_be_bytes),
+        );
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::String(utf16_title, lopdf::StringFormat::Hexadecimal),
+            "Author" => Object::string_literal("Jane Doe"),
+            "CreationDate" => Object::string_literal("D:20230104120000+01'00'"),
+        });
+        doc.trailer.set("Info", info_id);
+
+        let outlines_id = doc.new_object_id();
+        let results_id = doc.new_object_id();
+        let details_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Details"),
+            "Parent" => results_id,
+            "Dest" => vec![pages[&3].into(), "Fit".into()],
+        });
+        let intro_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Introduction"),
+            "Parent" => outlines_id,
+            "Next" => results_id,
+            "A" => dictionary! { "S" => "GoTo", "D" => vec![pages[&1].into(), "Fit".into()] },
+        });
+        doc.objects.insert(
+            results_id,
+            Object::Dictionary(dictionary! {
+                "Title" => Object::string_literal("Results"),
+                "Parent" => outlines_id,
+                "Prev" => intro_id,
+                "First" => details_id,
+                "Dest" => vec![pages[&2].into(), "Fit".into()],
+            }),
+        );
+        doc.objects.insert(
+            outlines_id,
+            Object::Dictionary(dictionary! { "First" => intro_id }),
+        );
+        let catalog_id = doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        doc.get_dictionary_mut(catalog_id)
+            .unwrap()
+            .set("Outlines", outlines_id);
+
+        let metadata = document_metadata(&doc);
+        assert_eq!(metadata.title.as_deref(), Some("Annual Report – 2023"));
+        assert_eq!(metadata.byline.as_deref(), Some("Jane Doe"));
+        assert_eq!(
+            metadata.published_at.as_deref(),
+            Some("2023-01-04T12:00:00+01:00")
+        );
+
+        let outline = metadata.outline.unwrap();
+        let entries: Vec<(&str, usize, Option<u32>)> = outline
+            .0
+            .iter()
+            .map(|e| (e.title.as_str(), e.level, e.page))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("Introduction", 0, Some(1)),
+                ("Results", 0, Some(2)),
+                ("Details", 1, Some(3)),
+            ]
+        );
+        assert_eq!(section_for_page(&outline, 3).as_deref(), Some("Details"));
+        assert_eq!(section_for_page(&outline, 2).as_deref(), Some("Results"));
+    }
+
+    #[test]
+    fn test_parse_pdf_date() {
+        assert_eq!(
+            parse_pdf_date("D:20240229083000Z").as_deref(),
+            Some("2024-02-29T08:30:00+00:00")
+        );
+        assert_eq!(
+            parse_pdf_date("D:199812231952-08'00'").as_deref(),
+            Some("1998-12-23T19:52:00-08:00")
+        );
+        assert_eq!(
+            parse_pdf_date("2021").as_deref(),
+            Some("2021-01-01T00:00:00+00:00")
+        );
+        assert_eq!(parse_pdf_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_scanned_page_image_is_decoded() {
+        let mut doc = build_document(vec![b"q 612 0 0 792 0 0 cm /Im0 Do Q".to_vec()]);
+        let page_id = doc.get_pages()[&1];
+
+        // 4x2 grayscale with png "up" prediction, as scanners often write it
+        let rows: [[u8; 5]; 2] = [[0, 0, 64, 128, 255], [2, 10, 10, 10, 0]];
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &rows.concat()).unwrap();
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 4,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "Filter" => "FlateDecode",
+                "DecodeParms" => dictionary! { "Predictor" => 15, "Columns" => 4 },
+            },
+            encoder.finish().unwrap(),
+        ));
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Resources",
+            dictionary! { "XObject" => dictionary! { "Im0" => image_id } },
+        );
+
+        assert!(!has_text_layer(&extract_page_text(&doc, page_id).unwrap()));
+        let images = page_scan_images(&doc, page_id);
+        assert_eq!(images.len(), 1);
+        let image = &images[0];
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(2, 0).0, [128, 128, 128]);
+        assert_eq!(image.get_pixel(2, 1).0, [138, 138, 138]);
+        assert_eq!(image.get_pixel(3, 1).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_scanned_page_strips_are_stacked_in_drawing_order() {
+        // the bottom strip comes first in the resources, the content draws the top one first
+        let mut doc = build_document(vec![b"q 612 0 0 396 0 396 cm /Top Do Q \
+            q 612 0 0 396 0 0 cm /Bottom Do Q \
+            q 50 0 0 50 10 10 cm /Logo Do Q"
+            .to_vec()]);
+        let page_id = doc.get_pages()[&1];
+        let mut gray_image = |width: i64, value: u8| {
+            doc.add_object(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => width,
+                    "Height" => 2,
+                    "ColorSpace" => "DeviceGray",
+                    "BitsPerComponent" => 8,
+                },
+                vec![value; width as usize * 2],
+            ))
+        };
+        let bottom_id = gray_image(4, 200);
+        let top_id = gray_image(4, 50);
+        let logo_id = gray_image(2, 0);
+        // the logo is drawn through a form xobject, which uses the page resources
+        let form_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Form" },
+            b"/LogoImage Do".to_vec(),
+        ));
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Resources",
+            dictionary! {
+                "XObject" => dictionary! {
+                    "Bottom" => bottom_id,
+                    "Top" => top_id,
+                    "Logo" => form_id,
+                    "LogoImage" => logo_id,
+                },
+            },
+        );
+
+        let images = page_scan_images(&doc, page_id);
+        let dimensions: Vec<(u32, u32)> = images.iter().map(|i| i.dimensions()).collect();
+        assert_eq!(dimensions, vec![(4, 4), (2, 2)]);
+        assert_eq!(images[0].get_pixel(0, 1).0, [50, 50, 50]);
+        assert_eq!(images[0].get_pixel(0, 2).0, [200, 200, 200]);
+    }
+}
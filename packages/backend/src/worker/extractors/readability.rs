@@ -0,0 +1,827 @@
+use super::{create_metadata_from_resource, Extraction, Extractor, ExtractorContext};
+use crate::{
+    store::models::{CompositeResource, ExtractedResourceMetadata, ResourceTextContentType},
+    BackendResult,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+// class/id patterns, loosely following Mozilla's Readability
+static UNLIKELY_CANDIDATES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)-ad-|ai2html|banner|breadcrumbs|combx|comment|community|consent|cookie|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|newsletter|pager|pagination|popup|promo|related|remark|replies|rss|share|shoutbox|sidebar|skyscraper|social|sponsor|subscribe|supplemental|yom-remote",
+    )
+    .unwrap()
+});
+static MAYBE_CANDIDATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)and|article|body|column|content|main|shadow").unwrap());
+static POSITIVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story")
+        .unwrap()
+});
+static NEGATIVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)-ad-|hidden|banner|combx|comment|com-|consent|contact|cookie|foot|footer|footnote|gdpr|masthead|media|meta|newsletter|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget",
+    )
+    .unwrap()
+});
+static LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| selector("a"));
+static IMAGE_SELECTOR: Lazy<Selector> = Lazy::new(|| selector("img[src]"));
+static TITLE_SEPARATOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+[|\-–—»:]\s+").unwrap());
+
+// paragraphs shorter than this don't say anything about where the content is
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub published_at: Option<String>,
+    pub lead_image: Option<String>,
+    // main content as markdown, headings, lists, quotes and code blocks are kept
+    pub content: String,
+}
+
+impl Article {
+    pub fn metadata(&self) -> ExtractedResourceMetadata {
+        ExtractedResourceMetadata {
+            title: self.title.clone(),
+            byline: self.byline.clone(),
+            published_at: self.published_at.clone(),
+            lead_image: self.lead_image.clone(),
+            outline: None,
+        }
+    }
+}
+
+// main-content extractor for saved web pages
+pub struct ReadabilityExtractor;
+
+impl Extractor for ReadabilityExtractor {
+    fn name(&self) -> &str {
+        "readability"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec!["text/html".to_owned(), "application/xhtml+xml".to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let html = std::fs::read_to_string(&resource.resource.resource_path)?;
+        let source_uri = resource.metadata.as_ref().map(|m| m.source_uri.as_str());
+        let article = extract_article(&html, source_uri);
+
+        let mut extraction = Extraction::default();
+        if !article.content.is_empty() {
+            extraction.push(
+                ResourceTextContentType::Article,
+                article.content.clone(),
+                create_metadata_from_resource(resource),
+            );
+        }
+        extraction.metadata = Some(article.metadata()).filter(|m| !m.is_empty());
+        Ok(extraction)
+    }
+}
+
+pub fn extract_article(html: &str, url: Option<&str>) -> Article {
+    let document = Html::parse_document(html);
+    let json_ld = parse_json_ld(&document);
+    let base_url = url.and_then(|url| url::Url::parse(url).ok());
+
+    let content_elements = find_content(&document);
+    let mut blocks = Vec::new();
+    for element in &content_elements {
+        render_blocks(*element, &mut blocks);
+    }
+    let content = blocks.join("\n\n");
+
+    let lead_image = meta_content(
+        &document,
+        &["og:image", "twitter:image", "twitter:image:src"],
+    )
+    .or_else(|| json_ld.as_ref().and_then(json_ld_image))
+    .or_else(|| {
+        content_elements
+            .iter()
+            .flat_map(|element| element.select(&IMAGE_SELECTOR))
+            .find_map(|img| img.value().attr("src"))
+            .map(|src| src.to_string())
+    })
+    .map(|src| resolve_url(base_url.as_ref(), &src));
+
+    Article {
+        title: extract_title(&document, json_ld.as_ref()),
+        byline: extract_byline(&document, json_ld.as_ref()),
+        published_at: extract_published_at(&document, json_ld.as_ref()),
+        lead_image,
+        content,
+    }
+}
+
+fn selector(selector: &str) -> Selector {
+    Selector::parse(selector).expect("invalid selector")
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn element_text(element: ElementRef) -> String {
+    normalize_text(&element.text().collect::<String>())
+}
+
+fn non_empty(text: String) -> Option<String> {
+    Some(text).filter(|t| !t.is_empty())
+}
+
+fn resolve_url(base_url: Option<&url::Url>, src: &str) -> String {
+    base_url
+        .and_then(|base| base.join(src).ok())
+        .map(|url| url.to_string())
+        .unwrap_or_else(|| src.to_string())
+}
+
+fn meta_content(document: &Html, names: &[&str]) -> Option<String> {
+    for name in names {
+        let query = format!(r#"meta[property="{name}"], meta[name="{name}"]"#);
+        let found = document
+            .select(&selector(&query))
+            .filter_map(|meta| meta.value().attr("content"))
+            .map(normalize_text)
+            .find(|content| !content.is_empty());
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+// the first schema.org article-like object in the page's JSON-LD blocks
+fn parse_json_ld(document: &Html) -> Option<serde_json::Value> {
+    fn find_article(value: serde_json::Value) -> Option<serde_json::Value> {
+        match value {
+            serde_json::Value::Array(items) => items.into_iter().find_map(find_article),
+            serde_json::Value::Object(mut object) => {
+                if let Some(graph) = object.remove("@graph") {
+                    if let Some(article) = find_article(graph) {
+                        return Some(article);
+                    }
+                }
+                let is_article = match object.get("@type") {
+                    Some(serde_json::Value::String(t)) => {
+                        t.ends_with("Article") || t == "BlogPosting"
+                    }
+                    Some(serde_json::Value::Array(types)) => types.iter().any(|t| {
+                        t.as_str()
+                            .is_some_and(|t| t.ends_with("Article") || t == "BlogPosting")
+                    }),
+                    _ => false,
+                };
+                is_article.then_some(serde_json::Value::Object(object))
+            }
+            _ => None,
+        }
+    }
+
+    document
+        .select(&selector(r#"script[type="application/ld+json"]"#))
+        .filter_map(|script| {
+            serde_json::from_str::<serde_json::Value>(&script.text().collect::<String>()).ok()
+        })
+        .find_map(find_article)
+}
+
+fn json_ld_string(json_ld: &serde_json::Value, key: &str) -> Option<String> {
+    json_ld
+        .get(key)
+        .and_then(|value| value.as_str())
+        .map(normalize_text)
+        .and_then(non_empty)
+}
+
+fn json_ld_author(json_ld: &serde_json::Value) -> Option<String> {
+    let names: Vec<String> = match json_ld.get("author")? {
+        serde_json::Value::Array(authors) => authors
+            .iter()
+            .filter_map(|author| match author {
+                serde_json::Value::String(name) => Some(name.clone()),
+                author => author.get("name")?.as_str().map(|n| n.to_string()),
+            })
+            .collect(),
+        serde_json::Value::String(name) => vec![name.clone()],
+        author => vec![author.get("name")?.as_str()?.to_string()],
+    };
+    non_empty(normalize_text(&names.join(", ")))
+}
+
+fn json_ld_image(json_ld: &serde_json::Value) -> Option<String> {
+    let mut image = json_ld.get("image")?;
+    if let serde_json::Value::Array(images) = image {
+        image = images.first()?;
+    }
+    match image {
+        serde_json::Value::String(url) => Some(url.clone()),
+        image => image.get("url")?.as_str().map(|url| url.to_string()),
+    }
+}
+
+fn extract_title(document: &Html, json_ld: Option<&serde_json::Value>) -> Option<String> {
+    if let Some(title) = meta_content(document, &["og:title", "twitter:title"])
+        .or_else(|| json_ld.and_then(|ld| json_ld_string(ld, "headline")))
+    {
+        return Some(title);
+    }
+
+    let document_title = document
+        .select(&selector("title"))
+        .next()
+        .map(element_text)
+        .and_then(non_empty);
+
+    // a single h1 is usually the article title without the site name
+    let headings: Vec<String> = document
+        .select(&selector("h1"))
+        .map(element_text)
+        .filter(|h| !h.is_empty())
+        .collect();
+    if headings.len() == 1 {
+        return headings.into_iter().next();
+    }
+
+    // "Article title | Site name", unless that leaves too little of the title
+    document_title.map(|title| {
+        let stripped = TITLE_SEPARATOR
+            .find_iter(&title)
+            .last()
+            .map(|separator| title[..separator.start()].trim().to_string());
+        match stripped {
+            Some(stripped) if stripped.split_whitespace().count() >= 3 => stripped,
+            _ => title,
+        }
+    })
+}
+
+fn extract_byline(document: &Html, json_ld: Option<&serde_json::Value>) -> Option<String> {
+    if let Some(byline) = json_ld.and_then(json_ld_author) {
+        return Some(byline);
+    }
+    // `article:author` is often a profile URL, which is not a byline
+    if let Some(byline) = meta_content(document, &["author", "article:author", "parsely-author"])
+        .filter(|author| !author.starts_with("http"))
+    {
+        return Some(byline);
+    }
+    document
+        .select(&selector(
+            r#"[rel="author"], [itemprop="author"], .byline, .author, .post-author"#,
+        ))
+        .map(element_text)
+        .map(|text| {
+            text.trim_start_matches("By ")
+                .trim_start_matches("by ")
+                .to_string()
+        })
+        // long matches are author bio boxes, not bylines
+        .find(|text| !text.is_empty() && text.len() <= 100)
+}
+
+fn extract_published_at(document: &Html, json_ld: Option<&serde_json::Value>) -> Option<String> {
+    json_ld
+        .and_then(|ld| json_ld_string(ld, "datePublished"))
+        .or_else(|| {
+            meta_content(
+                document,
+                &[
+                    "article:published_time",
+                    "datePublished",
+                    "date",
+                    "pubdate",
+                    "publishdate",
+                    "dc.date.issued",
+                    "parsely-pub-date",
+                ],
+            )
+        })
+        .or_else(|| {
+            document
+                .select(&selector(r#"[itemprop="datePublished"], time[datetime]"#))
+                .filter_map(|element| {
+                    element
+                        .value()
+                        .attr("datetime")
+                        .or_else(|| element.value().attr("content"))
+                })
+                .map(normalize_text)
+                .find(|date| !date.is_empty())
+        })
+}
+
+fn class_and_id(element: ElementRef) -> String {
+    format!(
+        "{} {}",
+        element.value().attr("class").unwrap_or_default(),
+        element.value().attr("id").unwrap_or_default()
+    )
+}
+
+// elements that never contain the main content
+fn is_boilerplate(element: ElementRef) -> bool {
+    let value = element.value();
+    let tag = value.name();
+    if matches!(
+        tag,
+        "script"
+            | "style"
+            | "noscript"
+            | "template"
+            | "nav"
+            | "header"
+            | "footer"
+            | "aside"
+            | "form"
+            | "iframe"
+            | "svg"
+            | "button"
+            | "input"
+            | "select"
+            | "textarea"
+            | "dialog"
+    ) {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if let Some(role) = value.attr("role") {
+        if matches!(
+            role,
+            "navigation"
+                | "banner"
+                | "complementary"
+                | "contentinfo"
+                | "dialog"
+                | "alertdialog"
+                | "menu"
+                | "menubar"
+                | "search"
+        ) {
+            return true;
+        }
+    }
+    if matches!(tag, "html" | "body" | "article" | "main" | "a") {
+        return false;
+    }
+    let class_and_id = class_and_id(element);
+    UNLIKELY_CANDIDATES.is_match(&class_and_id) && !MAYBE_CANDIDATE.is_match(&class_and_id)
+}
+
+fn class_weight(element: ElementRef) -> f64 {
+    let mut weight = 0.0;
+    for attr in ["class", "id"] {
+        if let Some(value) = element.value().attr(attr) {
+            if NEGATIVE.is_match(value) {
+                weight -= 25.0;
+            }
+            if POSITIVE.is_match(value) {
+                weight += 25.0;
+            }
+        }
+    }
+    weight
+}
+
+fn initial_score(element: ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "article" | "main" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(element)
+}
+
+fn is_block_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "address"
+            | "article"
+            | "blockquote"
+            | "dd"
+            | "div"
+            | "dl"
+            | "dt"
+            | "figure"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "li"
+            | "main"
+            | "ol"
+            | "p"
+            | "pre"
+            | "section"
+            | "table"
+            | "ul"
+    )
+}
+
+fn text_length(element: ElementRef) -> usize {
+    element_text(element).chars().count()
+}
+
+fn link_density(element: ElementRef) -> f64 {
+    let length = text_length(element);
+    if length == 0 {
+        return 0.0;
+    }
+    let link_length: usize = element.select(&LINK_SELECTOR).map(text_length).sum();
+    link_length as f64 / length as f64
+}
+
+// paragraph-like elements whose text is scored: paragraphs, cells and divs used as paragraphs
+fn is_scorable(element: ElementRef) -> bool {
+    match element.value().name() {
+        "p" | "pre" | "td" => true,
+        "div" | "section" => !element
+            .child_elements()
+            .any(|child| is_block_tag(child.value().name())),
+        _ => false,
+    }
+}
+
+fn collect_scorable<'a>(element: ElementRef<'a>, paragraphs: &mut Vec<ElementRef<'a>>) {
+    for child in element.child_elements() {
+        if is_boilerplate(child) {
+            continue;
+        }
+        if is_scorable(child) {
+            paragraphs.push(child);
+        }
+        collect_scorable(child, paragraphs);
+    }
+}
+
+// the best scoring element plus the siblings that look like they belong to the same content,
+// in document order
+fn find_content(document: &Html) -> Vec<ElementRef<'_>> {
+    let body = match document.select(&selector("body")).next() {
+        Some(body) => body,
+        None => return vec![],
+    };
+
+    let mut paragraphs = Vec::new();
+    collect_scorable(body, &mut paragraphs);
+
+    let mut scores = HashMap::new();
+    let mut candidates = Vec::new();
+    for paragraph in paragraphs {
+        let text = element_text(paragraph);
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (length as f64 / 100.0).min(3.0);
+
+        let ancestors = paragraph.ancestors().filter_map(ElementRef::wrap).take(3);
+        for (level, ancestor) in ancestors.enumerate() {
+            let entry = scores.entry(ancestor.id()).or_insert_with(|| {
+                candidates.push(ancestor);
+                initial_score(ancestor)
+            });
+            *entry += match level {
+                0 => score,
+                1 => score / 2.0,
+                level => score / (level as f64 * 3.0),
+            };
+        }
+    }
+
+    let final_score = |element: ElementRef| {
+        scores.get(&element.id()).copied().unwrap_or_default() * (1.0 - link_density(element))
+    };
+    let top = candidates
+        .iter()
+        .copied()
+        .max_by(|a, b| final_score(*a).total_cmp(&final_score(*b)));
+    let top = match top {
+        Some(top) => top,
+        None => return vec![body],
+    };
+    let top_score = final_score(top);
+
+    // merge in siblings that scored well or are long, mostly unlinked paragraphs
+    let threshold = (top_score * 0.2).max(10.0);
+    let parent = match top.parent().and_then(ElementRef::wrap) {
+        Some(parent) => parent,
+        None => return vec![top],
+    };
+    let mut content = Vec::new();
+    for sibling in parent.child_elements() {
+        if sibling.id() == top.id() {
+            content.push(top);
+            continue;
+        }
+        if is_boilerplate(sibling) {
+            continue;
+        }
+        let belongs = final_score(sibling) >= threshold
+            || (sibling.value().name() == "p"
+                && text_length(sibling) > 80
+                && link_density(sibling) < 0.25);
+        if belongs {
+            content.push(sibling);
+        }
+    }
+    content
+}
+
+#[derive(Default)]
+struct InlineText {
+    text: String,
+}
+
+impl InlineText {
+    fn push_text(&mut self, text: &str) {
+        let mut last_is_space =
+            self.text.is_empty() || self.text.ends_with(' ') || self.text.ends_with('\n');
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !last_is_space {
+                    self.text.push(' ');
+                    last_is_space = true;
+                }
+            } else {
+                self.text.push(c);
+                last_is_space = false;
+            }
+        }
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.text.trim_end_matches(' ').len();
+        self.text.truncate(trimmed);
+        self.text.push('\n');
+    }
+
+    fn take(&mut self) -> Option<String> {
+        let text = std::mem::take(&mut self.text);
+        let text = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        non_empty(text)
+    }
+}
+
+fn render_blocks(element: ElementRef, blocks: &mut Vec<String>) {
+    let mut inline = InlineText::default();
+    render_children(element, blocks, &mut inline);
+    blocks.extend(inline.take());
+}
+
+fn render_children(element: ElementRef, blocks: &mut Vec<String>, inline: &mut InlineText) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => inline.push_text(text),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    render_element(child, blocks, inline);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_element(element: ElementRef, blocks: &mut Vec<String>, inline: &mut InlineText) {
+    if is_boilerplate(element) {
+        return;
+    }
+    // link lists inside the content are usually "read more" or tag clouds
+    let tag = element.value().name();
+    if matches!(tag, "ul" | "ol" | "div" | "section")
+        && text_length(element) < 200
+        && link_density(element) > 0.5
+    {
+        return;
+    }
+
+    match tag {
+        "br" => inline.line_break(),
+        "img" | "picture" | "video" | "audio" | "canvas" | "hr" => {}
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            blocks.extend(inline.take());
+            let level = tag[1..].parse::<usize>().unwrap_or(1);
+            if let Some(text) = non_empty(element_text(element)) {
+                blocks.push(format!("{} {}", "#".repeat(level), text));
+            }
+        }
+        "ul" | "ol" => {
+            blocks.extend(inline.take());
+            let mut lines = Vec::new();
+            render_list(element, 0, &mut lines);
+            if !lines.is_empty() {
+                blocks.push(lines.join("\n"));
+            }
+        }
+        "blockquote" => {
+            blocks.extend(inline.take());
+            let mut quoted = Vec::new();
+            render_blocks(element, &mut quoted);
+            if !quoted.is_empty() {
+                let quote = quoted
+                    .join("\n\n")
+                    .lines()
+                    .map(|line| format!("> {line}").trim_end().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                blocks.push(quote);
+            }
+        }
+        "pre" => {
+            blocks.extend(inline.take());
+            let code = element.text().collect::<String>();
+            let code = code.trim_matches('\n');
+            if !code.trim().is_empty() {
+                blocks.push(format!("```\n{code}\n```"));
+            }
+        }
+        "table" => {
+            blocks.extend(inline.take());
+            let rows: Vec<String> = element
+                .select(&selector("tr"))
+                .map(|row| {
+                    row.child_elements()
+                        .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                        .map(element_text)
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                })
+                .filter(|row| !row.trim().is_empty())
+                .collect();
+            if !rows.is_empty() {
+                blocks.push(rows.join("\n"));
+            }
+        }
+        tag if is_block_tag(tag) => {
+            blocks.extend(inline.take());
+            render_children(element, blocks, inline);
+            blocks.extend(inline.take());
+        }
+        _ => render_children(element, blocks, inline),
+    }
+}
+
+fn render_list(list: ElementRef, depth: usize, lines: &mut Vec<String>) {
+    let ordered = list.value().name() == "ol";
+    let indent = "  ".repeat(depth);
+    let items = list
+        .child_elements()
+        .filter(|child| child.value().name() == "li");
+    for (index, item) in items.enumerate() {
+        let marker = if ordered {
+            format!("{}.", index + 1)
+        } else {
+            "-".to_string()
+        };
+
+        let mut inline = InlineText::default();
+        let mut nested = Vec::new();
+        for child in item.children() {
+            match child.value() {
+                Node::Text(text) => inline.push_text(text),
+                Node::Element(_) => match ElementRef::wrap(child) {
+                    Some(child) if matches!(child.value().name(), "ul" | "ol") => {
+                        nested.push(child)
+                    }
+                    Some(child) if !is_boilerplate(child) => {
+                        inline.push_text(&child.text().collect::<String>())
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        if let Some(text) = inline.take() {
+            lines.push(format!("{indent}{marker} {}", text.replace('\n', " ")));
+        }
+        for list in nested {
+            render_list(list, depth + 1, lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWS_ARTICLE: &str = include_str!("fixtures/news_article.html");
+    const BLOG_POST: &str = include_str!("fixtures/blog_post.html");
+
+    #[test]
+    fn test_news_article_drops_boilerplate() {
+        let article = extract_article(
+            NEWS_ARTICLE,
+            Some("https://news.example.com/science/2024/03/ocean-heat"),
+        );
+
+        assert_eq!(
+            article.title.as_deref(),
+            Some("Oceans absorbed record heat last year, researchers say")
+        );
+        assert_eq!(article.byline.as_deref(), Some("Maria Lopez, Tom Becker"));
+        assert_eq!(
+            article.published_at.as_deref(),
+            Some("2024-03-12T08:30:00+00:00")
+        );
+        assert_eq!(
+            article.lead_image.as_deref(),
+            Some("https://news.example.com/images/ocean-heat.jpg")
+        );
+
+        let content = &article.content;
+        assert!(content.starts_with("The world's oceans absorbed more heat"));
+        assert!(content.contains("## What the data shows"));
+        assert!(content.contains("- Surface temperatures rose in every basin"));
+        assert!(content.contains("> We are seeing changes"));
+        for boilerplate in [
+            "Accept all cookies",
+            "Subscribe to our newsletter",
+            "Most read",
+            "Share on",
+            "All rights reserved",
+            "window.dataLayer",
+            "Sections",
+        ] {
+            assert!(
+                !content.contains(boilerplate),
+                "content should not contain {:?}:\n{}",
+                boilerplate,
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_blog_post_keeps_structure() {
+        let article = extract_article(BLOG_POST, Some("https://blog.example.org/posts/rust-tips"));
+
+        assert_eq!(
+            article.title.as_deref(),
+            Some("Five small Rust habits that pay off")
+        );
+        assert_eq!(article.byline.as_deref(), Some("Sam Carter"));
+        assert_eq!(article.published_at.as_deref(), Some("2023-11-04"));
+        assert_eq!(
+            article.lead_image.as_deref(),
+            Some("https://blog.example.org/static/cover.png")
+        );
+
+        let content = &article.content;
+        assert!(content.contains("## 1. Prefer iterators"));
+        assert!(content.contains("1. Write the test first\n2. Make it pass"));
+        assert!(content.contains("  - nested point"));
+        assert!(content.contains("```\nfn main() {\n    println!(\"hi\");\n}\n```"));
+        for boilerplate in [
+            "Leave a comment",
+            "Great post!",
+            "Recent posts",
+            "Home",
+            "Powered by",
+        ] {
+            assert!(
+                !content.contains(boilerplate),
+                "content should not contain {:?}:\n{}",
+                boilerplate,
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_title_falls_back_to_document_title() {
+        let html = "<html><head><title>Release notes for version 2 - Example Project</title></head>
+            <body><h2>One</h2><h2>Two</h2><p>Short.</p></body></html>";
+        let article = extract_article(html, None);
+        assert_eq!(
+            article.title.as_deref(),
+            Some("Release notes for version 2")
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use super::{
+    create_metadata_from_resource,
+    frontmatter::{parse_markdown_with_frontmatter, resource_frontmatter, split_frontmatter},
+    links::{annotation_resource_links, note_resource_links},
+    readability::{extract_article, Article},
+    Extraction, Extractor, ExtractorContext,
+};
+use crate::{
+    store::models::{
+        CompositeResource, ExtractedResourceLink, ExtractedResourceMetadata, ResourceLinkKind,
+        ResourceTextContentType,
+    },
+    BackendError, BackendResult,
+};
+
+use serde::{Deserialize, Serialize};
+
+mod resource_types {
+    pub const POST: &str = "application/vnd.space.post";
+    pub const ARTICLE: &str = "application/vnd.space.article";
+    pub const LINK: &str = "application/vnd.space.link";
+}
+
+// the JSON (or markdown with frontmatter) data files the app stores for its own resource types,
+// plus plain text files
+pub struct SpaceDataExtractor;
+
+impl Extractor for SpaceDataExtractor {
+    fn name(&self) -> &str {
+        "space_data"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec!["application/vnd.space.*".to_owned(), "text/*".to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut extraction = Extraction::default();
+        let mut resource_data = std::fs::read_to_string(&resource.resource.resource_path)?;
+
+        // the frontmatter of notes and markdown files is written by the user, the app's own
+        // markdown resource types keep their data in it instead
+        if !is_markdown_resource_type(&resource.resource.resource_type)
+            && is_markdown_file(&resource.resource.resource_path)
+        {
+            let parsed = split_frontmatter(&resource_data).and_then(|(yaml, body)| {
+                let frontmatter = serde_yaml::from_str(yaml).ok()?;
+                Some((resource_frontmatter(&frontmatter), body.trim().to_owned()))
+            });
+            if let Some((frontmatter, body)) = parsed {
+                extraction.frontmatter = Some(frontmatter);
+                resource_data = body;
+            }
+        }
+
+        if let Some((content_type, content)) =
+            process_resource_data(resource, &resource_data, &mut extraction.metadata)?
+        {
+            extraction.push(
+                content_type,
+                content,
+                create_metadata_from_resource(resource),
+            );
+        }
+        extraction.links = resource_links(resource, &resource_data);
+        Ok(extraction)
+    }
+}
+
+fn is_markdown_resource_type(resource_type: &str) -> bool {
+    matches!(
+        resource_type,
+        resource_types::POST | resource_types::ARTICLE | resource_types::LINK
+    )
+}
+
+fn is_markdown_file(file_name: &str) -> bool {
+    file_name.ends_with(".md")
+}
+
+fn process_resource_data(
+    resource: &CompositeResource,
+    resource_data: &str,
+    metadata: &mut Option<ExtractedResourceMetadata>,
+) -> BackendResult<Option<(ResourceTextContentType, String)>> {
+    let resource_text_content_type =
+        ResourceTextContentType::from_resource_type(&resource.resource.resource_type)
+            .ok_or_else(|| BackendError::GenericError("invalid resource type".to_string()))?;
+
+    match resource_text_content_type {
+        ResourceTextContentType::Note => Ok(Some((
+            resource_text_content_type,
+            resource_data.to_string(),
+        ))),
+
+        ResourceTextContentType::Post => process_file_data::<PostData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |post_data| {
+                let title = post_data.title.as_deref().unwrap_or_default();
+                let excerpt = post_data.excerpt.as_deref().unwrap_or_default();
+                let content = post_data.content_plain.as_deref().unwrap_or_default();
+                let author = post_data.author.as_deref().unwrap_or_default();
+                let site = post_data.site_name.as_deref().unwrap_or_default();
+                format!("{title} {excerpt} {content} {author} {site}")
+            },
+        ),
+
+        ResourceTextContentType::ChatMessage => process_file_data::<ChatMessageData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |msg| {
+                let author = msg.author.as_deref().unwrap_or_default();
+                let content = msg.content_plain.as_deref().unwrap_or_default();
+                let platform = msg.platform_name.as_deref().unwrap_or_default();
+                format!("{author} {content} {platform}")
+            },
+        ),
+
+        ResourceTextContentType::Document => process_file_data::<DocumentData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |doc| {
+                let author = doc.author.as_deref().unwrap_or_default();
+                let content = doc.content_plain.as_deref().unwrap_or_default();
+                let editor = doc.editor_name.as_deref().unwrap_or_default();
+                format!("{author} {content} {editor}")
+            },
+        ),
+
+        ResourceTextContentType::Article => process_file_data::<ArticleData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |article| {
+                // the saved html keeps headings and lists, content_plain is a flat fallback
+                let parsed = article
+                    .content_html
+                    .as_deref()
+                    .map(|html| extract_article(html, article.url.as_deref()));
+                *metadata =
+                    Some(article_metadata(article, parsed.as_ref())).filter(|m| !m.is_empty());
+
+                let title = article.title.as_deref().unwrap_or_default();
+                let excerpt = article.excerpt.as_deref().unwrap_or_default();
+                let content = parsed
+                    .map(|parsed| parsed.content)
+                    .filter(|content| !content.is_empty())
+                    .or_else(|| article.content_plain.clone())
+                    .unwrap_or_default();
+                format!("{title} {excerpt}\n{content}")
+            },
+        ),
+
+        ResourceTextContentType::Link => process_file_data::<LinkData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |link| {
+                let title = link.title.as_deref().unwrap_or_default();
+                let desc = link.description.as_deref().unwrap_or_default();
+                let url = link.url.as_deref().unwrap_or_default();
+                let content = link.content_plain.as_deref().unwrap_or_default();
+                format!("{title} {desc} {url}\n{content}")
+            },
+        ),
+
+        ResourceTextContentType::ChatThread => process_file_data::<ChatThreadData>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |thread| {
+                let messages_content = thread
+                    .messages
+                    .as_deref()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|msg| msg.content_plain.as_deref().unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let title = thread.title.as_deref().unwrap_or_default();
+                format!("{title} {messages_content}")
+            },
+        ),
+        ResourceTextContentType::Annotation => process_file_data::<ResourceDataAnnotation>(
+            resource_data,
+            resource_text_content_type,
+            resource,
+            |ann| {
+                let content = match &ann.data {
+                    AnnotationData::Comment(comment) => Some(comment.content_plain.clone()),
+                    _ => None,
+                };
+
+                let content_plain = match &ann.anchor {
+                    Some(AnnotationAnchor {
+                        data: AnnotationAnchorData::Range(range),
+                        ..
+                    }) => range.content_plain.as_deref(),
+                    _ => None,
+                };
+
+                format!(
+                    "{} {}",
+                    content_plain.unwrap_or_default(),
+                    content.unwrap_or_default()
+                )
+            },
+        ),
+        ResourceTextContentType::GenericText => Ok(Some((
+            resource_text_content_type,
+            resource_data.to_string(),
+        ))),
+        _ => Ok(None),
+    }
+}
+
+// `None` for resource types that can't link to other resources
+fn resource_links(
+    resource: &CompositeResource,
+    resource_data: &str,
+) -> Option<Vec<ExtractedResourceLink>> {
+    match ResourceTextContentType::from_resource_type(&resource.resource.resource_type)? {
+        ResourceTextContentType::Note => Some(note_resource_links(resource_data)),
+        ResourceTextContentType::Annotation => {
+            let annotation = serde_json::from_str::<ResourceDataAnnotation>(resource_data).ok();
+            let context = annotation.as_ref().and_then(annotation_context);
+            let tags = resource.resource_tags.as_deref().unwrap_or_default();
+            let mut links = annotation_resource_links(tags, context);
+
+            if let Some(AnnotationData::Link(AnnotationLinkData {
+                target_type: AnnotationLinkTargetType::Resource,
+                resource_id: Some(resource_id),
+                ..
+            })) = annotation.as_ref().map(|annotation| &annotation.data)
+            {
+                links.push(ExtractedResourceLink {
+                    target_id: resource_id.clone(),
+                    kind: ResourceLinkKind::LinksTo,
+                    context: context.map(|context| context.to_owned()),
+                });
+            }
+            Some(links)
+        }
+        _ => None,
+    }
+}
+
+// the annotated text, or the comment for annotations without a text selection
+fn annotation_context(annotation: &ResourceDataAnnotation) -> Option<&str> {
+    match (&annotation.anchor, &annotation.data) {
+        (
+            Some(AnnotationAnchor {
+                data: AnnotationAnchorData::Range(range),
+                ..
+            }),
+            _,
+        ) if range.content_plain.is_some() => range.content_plain.as_deref(),
+        (_, AnnotationData::Comment(comment)) => Some(comment.content_plain.as_str()),
+        _ => None,
+    }
+}
+
+// fields the app saved win over what we find in the page html
+fn article_metadata(article: &ArticleData, parsed: Option<&Article>) -> ExtractedResourceMetadata {
+    let saved = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+    ExtractedResourceMetadata {
+        title: saved(&article.title).or_else(|| parsed.and_then(|p| p.title.clone())),
+        byline: saved(&article.author).or_else(|| parsed.and_then(|p| p.byline.clone())),
+        published_at: saved(&article.date_published)
+            .or_else(|| parsed.and_then(|p| p.published_at.clone())),
+        lead_image: parsed.and_then(|p| p.lead_image.clone()),
+        outline: None,
+    }
+}
+
+fn process_file_data<T>(
+    data: &str,
+    content_type: ResourceTextContentType,
+    resource: &CompositeResource,
+    formatter: impl FnOnce(&T) -> String,
+) -> BackendResult<Option<(ResourceTextContentType, String)>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // Check if this is a markdown file for supported resource types
+    if is_markdown_resource_type(&resource.resource.resource_type)
+        && is_markdown_file(&resource.resource.resource_path)
+    {
+        // Parse markdown with frontmatter
+        let (content, frontmatter) = parse_markdown_with_frontmatter(data)?;
+
+        // Try to deserialize the frontmatter into our expected type
+        match serde_yaml::from_value::<T>(frontmatter) {
+            Ok(parsed_data) => {
+                // Format the data and combine with content
+                let formatted = formatter(&parsed_data);
+                Ok(Some((content_type, format!("{}\n{}", formatted, content))))
+            }
+            Err(_) => {
+                // If frontmatter parsing fails, just use the content
+                Ok(Some((content_type, content)))
+            }
+        }
+    } else {
+        // Regular JSON processing
+        serde_json::from_str::<T>(data)
+            .map(|parsed_data| Some((content_type, formatter(&parsed_data))))
+            .map_err(|err| {
+                let preview = if data.len() > 200 {
+                    format!(
+                        "{}... (truncated, total {} bytes)",
+                        &data[..200],
+                        data.len()
+                    )
+                } else {
+                    data.to_string()
+                };
+                BackendError::GenericError(format!(
+                    "failed to deserialize data: {err}\nResource path: {}\nData preview: {preview}",
+                    resource.resource.resource_path
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PostData {
+    author: Option<String>,
+    author_fullname: Option<String>,
+    author_image: Option<String>,
+    author_url: Option<String>,
+    content_html: Option<String>,
+    content_plain: Option<String>,
+    date_edited: Option<String>,
+    date_published: Option<String>,
+    edited: Option<bool>,
+    excerpt: Option<String>,
+    images: Option<Vec<String>>,
+    lang: Option<String>,
+    links: Option<Vec<String>>,
+    parent_title: Option<String>,
+    parent_url: Option<String>,
+    post_id: Option<String>,
+    site_icon: Option<String>,
+    site_name: Option<String>,
+    stats: Option<PostStats>,
+    title: Option<String>,
+    url: Option<String>,
+    video: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PostStats {
+    comments: Option<i32>,
+    down_votes: Option<i32>,
+    up_votes: Option<i32>,
+    views: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChatMessageData {
+    author: Option<String>,
+    author_image: Option<String>,
+    author_url: Option<String>,
+    content_html: Option<String>,
+    content_plain: Option<String>,
+    date_edited: Option<String>,
+    date_sent: Option<String>,
+    images: Option<Vec<String>>,
+    in_reply_to: Option<String>,
+    #[serde(rename = "messageId")]
+    message_id: Option<String>,
+    parent_title: Option<String>,
+    parent_url: Option<String>,
+    platform_icon: Option<String>,
+    platform_name: Option<String>,
+    url: Option<String>,
+    video: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DocumentData {
+    author: Option<String>,
+    author_fullname: Option<String>,
+    author_image: Option<String>,
+    author_url: Option<String>,
+    content_html: Option<String>,
+    content_plain: Option<String>,
+    date_created: Option<String>,
+    date_edited: Option<String>,
+    editor_icon: Option<String>,
+    editor_name: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArticleData {
+    author: Option<String>,
+    author_image: Option<String>,
+    author_url: Option<String>,
+    category_name: Option<String>,
+    category_url: Option<String>,
+    content_html: Option<String>,
+    content_plain: Option<String>,
+    date_published: Option<String>,
+    date_updated: Option<String>,
+    direction: Option<String>,
+    excerpt: Option<String>,
+    //images: Vec<String>,
+    lang: Option<String>,
+    site_icon: Option<String>,
+    site_name: Option<String>,
+    //stats: Option<HashMap<String, Option<i32>>>,
+    title: Option<String>,
+    url: Option<String>,
+    word_count: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LinkData {
+    author: Option<String>,
+    date_modified: Option<String>,
+    date_published: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    image: Option<String>,
+    keywords: Option<Vec<String>>,
+    language: Option<String>,
+    provider: Option<String>,
+    title: Option<String>,
+    url: Option<String>,
+    content_plain: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChatThreadData {
+    content_plain: Option<String>,
+    creator: Option<String>,
+    creator_image: Option<String>,
+    creator_url: Option<String>,
+    messages: Option<Vec<ChatMessageData>>,
+    platform_icon: Option<String>,
+    platform_name: Option<String>,
+    title: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResourceDataAnnotation {
+    #[serde(rename = "type")]
+    type_: AnnotationType,
+    data: AnnotationData,
+    anchor: Option<AnnotationAnchor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AnnotationType {
+    Highlight,
+    Comment,
+    Link,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AnnotationAnchorType {
+    Range,
+    Element,
+    Area,
+}
+
+// untagged variants are tried in order, highlights have no required fields and match anything
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum AnnotationData {
+    Comment(AnnotationCommentData),
+    Link(AnnotationLinkData),
+    Highlight(AnnotationHighlightData),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationHighlightData {
+    url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationCommentData {
+    url: Option<String>,
+    content_plain: String,
+    content_html: Option<String>,
+    source: AnnotationCommentSource,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AnnotationCommentSource {
+    User,
+    InlineAi,
+    ChatAi,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationLinkData {
+    target_type: AnnotationLinkTargetType,
+    url: Option<String>,
+    resource_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AnnotationLinkTargetType {
+    External,
+    Resource,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum AnnotationAnchorData {
+    Range(AnnotationRangeData),
+    Element(AnnotationElementData),
+    Area(AnnotationAreaData),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationAnchor {
+    #[serde(rename = "type")]
+    type_: AnnotationAnchorType,
+    data: AnnotationAnchorData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationRangeData {
+    content_plain: Option<String>,
+    content_html: Option<String>,
+    start_offset: i32,
+    end_offset: i32,
+    start_xpath: String,
+    end_xpath: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationElementData {
+    xpath: String,
+    query_selector: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct AnnotationAreaData {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
@@ -0,0 +1,236 @@
+use super::{Extraction, Extractor, ExtractorContext};
+use crate::{
+    ai::{embeddings::chunking::ContentChunker, youtube::YoutubeTranscriptPiece},
+    store::models::{CompositeResource, ResourceTextContentMetadata, ResourceTextContentType},
+    BackendError, BackendResult,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+// transcript chunks cover at least this many seconds so citations point to a useful window
+const TRANSCRIPT_CHUNK_SECONDS: f64 = 20.0;
+const SUBTITLE_EXTENSIONS: [&str; 2] = ["vtt", "srt"];
+
+static TIMING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*((?:\d+:)?\d{1,2}:\d{2}[.,]\d{1,3})\s*-->\s*((?:\d+:)?\d{1,2}:\d{2}[.,]\d{1,3})",
+    )
+    .unwrap()
+});
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>|\{\\[^}]*\}").unwrap());
+
+pub struct SubtitleExtractor;
+
+impl Extractor for SubtitleExtractor {
+    fn name(&self) -> &str {
+        "subtitles"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![
+            "text/vtt".to_owned(),
+            "application/x-subrip".to_owned(),
+            "text/srt".to_owned(),
+            "video/*".to_owned(),
+        ]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        _ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut extraction = Extraction::default();
+        let path = if resource.resource.resource_type.starts_with("video/") {
+            match find_sidecar_subtitles(resource) {
+                Some(path) => path,
+                None => return Ok(extraction),
+            }
+        } else {
+            PathBuf::from(&resource.resource.resource_path)
+        };
+
+        let data = std::fs::read(&path)?;
+        let pieces = parse_subtitles(&String::from_utf8_lossy(&data));
+        if pieces.is_empty() {
+            return Err(BackendError::GenericError(format!(
+                "no subtitle cues found in {path:?}"
+            )));
+        }
+
+        let url = resource.metadata.as_ref().map(|m| m.source_uri.as_str());
+        let (contents, metadatas) = chunk_transcript(&pieces, url);
+        extraction
+            .contents
+            .insert(ResourceTextContentType::Transcript, (contents, metadatas));
+        Ok(extraction)
+    }
+}
+
+// subtitles attached to a video are stored next to it, or next to the original file
+// when the video was imported from disk, with the same name and a subtitle extension
+fn find_sidecar_subtitles(resource: &CompositeResource) -> Option<PathBuf> {
+    let mut candidates = vec![PathBuf::from(&resource.resource.resource_path)];
+    if let Some(metadata) = &resource.metadata {
+        let source = metadata
+            .source_uri
+            .strip_prefix("file://")
+            .unwrap_or(&metadata.source_uri);
+        if Path::new(source).is_absolute() {
+            candidates.push(PathBuf::from(source));
+        }
+    }
+
+    candidates.iter().find_map(|video| {
+        SUBTITLE_EXTENSIONS
+            .iter()
+            .map(|extension| video.with_extension(extension))
+            .find(|path| path.is_file())
+    })
+}
+
+// e.g. `01:02:03,450` in srt files or `02:03.450` in vtt files
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let value = value.replace(',', ".");
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+fn clean_cue_text(line: &str) -> String {
+    let text = TAG.replace_all(line, "");
+    html_escape::decode_html_entities(&text).trim().to_owned()
+}
+
+// parses both srt and webvtt, the cue layout is the same apart from the header, the
+// optional cue identifiers and the decimal separator of the timestamps
+pub fn parse_subtitles(data: &str) -> Vec<YoutubeTranscriptPiece> {
+    let data = data.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut pieces: Vec<YoutubeTranscriptPiece> = vec![];
+    let mut previous_lines: Vec<String> = vec![];
+
+    for block in data.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !TIMING.is_match(line));
+        let timing = match lines.next().and_then(|line| TIMING.captures(line)) {
+            Some(timing) => timing,
+            // the WEBVTT header, NOTE and STYLE blocks have no timing line
+            None => continue,
+        };
+        let (start, end) = match (parse_timestamp(&timing[1]), parse_timestamp(&timing[2])) {
+            (Some(start), Some(end)) => (start, end),
+            _ => continue,
+        };
+
+        let cue_lines: Vec<String> = lines
+            .map(clean_cue_text)
+            .filter(|line| !line.is_empty())
+            .collect();
+        // auto generated captions roll up, every cue repeats the lines of the previous one
+        let new_lines: Vec<String> = cue_lines
+            .iter()
+            .filter(|line| !previous_lines.contains(line))
+            .cloned()
+            .collect();
+        previous_lines = cue_lines;
+        if new_lines.is_empty() {
+            continue;
+        }
+
+        pieces.push(YoutubeTranscriptPiece {
+            text: new_lines.join(" "),
+            start,
+            duration: (end - start).max(0.0),
+        });
+    }
+    pieces.sort_by(|a, b| a.start.total_cmp(&b.start));
+    pieces
+}
+
+// every chunk is timestamped with the start of its first piece
+pub fn chunk_transcript(
+    pieces: &[YoutubeTranscriptPiece],
+    url: Option<&str>,
+) -> (Vec<String>, Vec<ResourceTextContentMetadata>) {
+    let mut contents: Vec<String> = vec![];
+    let mut metadatas: Vec<ResourceTextContentMetadata> = vec![];
+    let mut chunk_start = None;
+    let mut chunk: Vec<&str> = vec![];
+
+    for (i, piece) in pieces.iter().enumerate() {
+        let start = *chunk_start.get_or_insert(piece.start);
+        chunk.push(&piece.text);
+
+        let is_last = i == pieces.len() - 1;
+        let next_start = pieces.get(i + 1).map(|next| next.start);
+        if is_last || next_start.is_some_and(|next| next - start >= TRANSCRIPT_CHUNK_SECONDS) {
+            contents.push(ContentChunker::normalize(&chunk.join(" ")));
+            metadatas.push(ResourceTextContentMetadata {
+                timestamp: Some(start as f32),
+                url: url.map(|url| url.to_owned()),
+                page: None,
+                section: None,
+                ocr: None,
+            });
+            chunk_start = None;
+            chunk.clear();
+        }
+    }
+    (contents, metadatas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:04,500\r\nHello <i>there</i>,\r\nwelcome back.\r\n\r\n2\r\n00:00:05,000 --> 00:00:07,000\r\nTom &amp; Jerry\r\n";
+        let pieces = parse_subtitles(srt);
+
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].text, "Hello there, welcome back.");
+        assert_eq!(pieces[0].start, 1.0);
+        assert_eq!(pieces[0].duration, 3.5);
+        assert_eq!(pieces[1].text, "Tom & Jerry");
+    }
+
+    #[test]
+    fn test_parse_vtt_with_rolling_captions() {
+        let vtt = "WEBVTT\nKind: captions\n\nNOTE generated\n\nintro\n00:05.000 --> 00:08.000 align:start\n<v Alice>first line\n\n01:00:08.000 --> 01:00:10.000\nfirst line\nsecond <00:00:09.000><c>line</c>\n";
+        let pieces = parse_subtitles(vtt);
+
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].text, "first line");
+        assert_eq!(pieces[0].start, 5.0);
+        assert_eq!(pieces[1].text, "second line");
+        assert_eq!(pieces[1].start, 3608.0);
+    }
+
+    #[test]
+    fn test_chunk_transcript_by_time_window() {
+        let pieces: Vec<YoutubeTranscriptPiece> = (0..10)
+            .map(|i| YoutubeTranscriptPiece {
+                text: format!("piece{i}"),
+                start: i as f64 * 6.0,
+                duration: 6.0,
+            })
+            .collect();
+        let (contents, metadatas) = chunk_transcript(&pieces, Some("https://example.com/v"));
+
+        assert_eq!(
+            contents,
+            vec![
+                "piece0 piece1 piece2 piece3",
+                "piece4 piece5 piece6 piece7",
+                "piece8 piece9"
+            ]
+        );
+        let timestamps: Vec<Option<f32>> = metadatas.iter().map(|m| m.timestamp).collect();
+        assert_eq!(timestamps, vec![Some(0.0), Some(24.0), Some(48.0)]);
+        assert_eq!(metadatas[0].url.as_deref(), Some("https://example.com/v"));
+    }
+}
@@ -0,0 +1,56 @@
+use super::{subtitles::chunk_transcript, Extraction, Extractor, ExtractorContext};
+use crate::{
+    ai::youtube::{fetch_transcript, fetch_transcript_cached},
+    store::{
+        db::Database,
+        models::{CompositeResource, ResourceTextContentMetadata, ResourceTextContentType},
+    },
+    BackendResult,
+};
+
+pub struct YoutubeTranscriptExtractor;
+
+impl Extractor for YoutubeTranscriptExtractor {
+    fn name(&self) -> &str {
+        "youtube_transcript"
+    }
+
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec!["application/vnd.space.post.youtube".to_owned()]
+    }
+
+    fn extract(
+        &self,
+        resource: &CompositeResource,
+        ctx: &ExtractorContext,
+    ) -> BackendResult<Extraction> {
+        let mut extraction = Extraction::default();
+        if let Some(metadata) = &resource.metadata {
+            let (contents, metadatas) =
+                get_youtube_contents_metadatas(&metadata.source_uri, ctx.language, ctx.db_path)?;
+            extraction.contents.insert(
+                ResourceTextContentType::YoutubeTranscript,
+                (contents, metadatas),
+            );
+        }
+        Ok(extraction)
+    }
+}
+
+pub fn get_youtube_contents_metadatas(
+    source_uri: &str,
+    language: Option<&str>,
+    db_path: Option<&str>,
+) -> BackendResult<(Vec<String>, Vec<ResourceTextContentMetadata>)> {
+    let transcript = match db_path {
+        Some(db_path) => {
+            let mut db = Database::new(db_path, false)?;
+            fetch_transcript_cached(&mut db, source_uri, language)?
+        }
+        None => fetch_transcript(source_uri, language)?,
+    };
+    Ok(chunk_transcript(
+        &transcript.metadata.transcript_pieces,
+        Some(source_uri),
+    ))
+}
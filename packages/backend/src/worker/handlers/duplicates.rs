@@ -0,0 +1,221 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{
+            random_uuid, DuplicateGroup, DuplicateReason, DuplicateReport,
+            InternalResourceTagNames, ProcessingPriority, ResourceMergeResult, ResourceTag,
+        },
+    },
+    utils::content_hash,
+    worker::{
+        duplicates::{group_pairs, normalize_source_uri, similar_pairs},
+        extractors::{
+            frontmatter::{FRONTMATTER_TAG_PREFIX, HASHTAG_TAG},
+            links::ANNOTATES_TAG,
+        },
+        Worker,
+    },
+    BackendError, BackendResult,
+};
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tracing::instrument;
+
+// the most recent resources compared by their text, every pair of them is compared
+const MAX_SIMILARITY_CANDIDATES: usize = 2000;
+const EXCERPT_LENGTH: usize = 2000;
+const ENCODE_BATCH_SIZE: usize = 64;
+// reposts and slightly edited copies, different articles on one topic stay below this
+const SIMILAR_TEXT_THRESHOLD: f32 = 0.95;
+
+impl Worker {
+    #[instrument(level = "trace", skip(self))]
+    pub fn find_duplicate_resources(
+        &mut self,
+        include_similar: bool,
+    ) -> BackendResult<DuplicateReport> {
+        let mut report = DuplicateReport::default();
+
+        let mut uri_keys: Vec<String> = vec![];
+        let mut by_uri: HashMap<String, Vec<String>> = HashMap::new();
+        for (resource_id, source_uri) in self.db.list_resource_source_uris()? {
+            if let Some(key) = normalize_source_uri(&source_uri) {
+                let ids = by_uri.entry(key.clone()).or_insert_with(|| {
+                    uri_keys.push(key);
+                    vec![]
+                });
+                ids.push(resource_id);
+            }
+        }
+        for key in uri_keys {
+            let resource_ids = by_uri.remove(&key).unwrap_or_default();
+            if resource_ids.len() > 1 {
+                report.groups.push(DuplicateGroup {
+                    reason: DuplicateReason::SourceUri,
+                    key: Some(key),
+                    resource_ids,
+                    similarity: None,
+                });
+            }
+        }
+
+        // empty notes are not copies of each other
+        let empty_hash = content_hash(&[]);
+        for (hash, resource_ids) in self.db.list_duplicate_content_hashes()? {
+            if hash != empty_hash {
+                report.groups.push(DuplicateGroup {
+                    reason: DuplicateReason::ContentHash,
+                    key: Some(hash),
+                    resource_ids,
+                    similarity: None,
+                });
+            }
+        }
+
+        if include_similar {
+            // the embedding model may not be running, the exact matches are still useful
+            match self.similar_resource_groups() {
+                Ok(groups) => {
+                    let exact: HashSet<Vec<String>> = report
+                        .groups
+                        .iter()
+                        .map(|group| sorted(&group.resource_ids))
+                        .collect();
+                    report.groups.extend(
+                        groups
+                            .into_iter()
+                            .filter(|group| !exact.contains(&sorted(&group.resource_ids))),
+                    );
+                }
+                Err(e) => tracing::warn!("failed to compare the text of resources: {e}"),
+            }
+        }
+        Ok(report)
+    }
+
+    fn similar_resource_groups(&self) -> BackendResult<Vec<DuplicateGroup>> {
+        let excerpts = self
+            .db
+            .list_resource_text_excerpts(MAX_SIMILARITY_CANDIDATES, EXCERPT_LENGTH)?;
+        let mut embeddings = Vec::with_capacity(excerpts.len());
+        for batch in excerpts.chunks(ENCODE_BATCH_SIZE) {
+            let texts = batch.iter().map(|(_, text)| text.clone()).collect();
+            embeddings.extend(self.ai.encode_sentences(&texts)?);
+        }
+        if embeddings.len() != excerpts.len() {
+            return Err(BackendError::GenericError(
+                "number of embeddings does not match the number of texts".to_owned(),
+            ));
+        }
+
+        let pairs = similar_pairs(&embeddings, SIMILAR_TEXT_THRESHOLD);
+        Ok(group_pairs(excerpts.len(), &pairs)
+            .into_iter()
+            .map(|(members, similarity)| DuplicateGroup {
+                reason: DuplicateReason::SimilarText,
+                key: None,
+                // the excerpts are newest first
+                resource_ids: members
+                    .into_iter()
+                    .rev()
+                    .map(|i| excerpts[i].0.clone())
+                    .collect(),
+                similarity: Some(similarity),
+            })
+            .collect())
+    }
+
+    // the tags, spaces, annotations and links of the other resources are added to the target,
+    // the other resources go to the trash
+    #[instrument(level = "trace", skip(self))]
+    pub fn merge_resources(
+        &mut self,
+        target_id: String,
+        source_ids: Vec<String>,
+    ) -> BackendResult<ResourceMergeResult> {
+        match self.db.get_resource(&target_id)? {
+            Some(target) if target.deleted == 0 => {}
+            _ => {
+                return Err(BackendError::GenericError(
+                    "resource to merge into not found".to_owned(),
+                ))
+            }
+        }
+
+        let mut present: HashSet<(String, String)> = self
+            .db
+            .list_resource_tags(&target_id)?
+            .into_iter()
+            .map(|tag| (tag.tag_name, tag.tag_value))
+            .collect();
+        let mut merged_ids = vec![];
+        let mut tags = vec![];
+        for source_id in source_ids {
+            if source_id == target_id || merged_ids.contains(&source_id) {
+                continue;
+            }
+            if self.db.get_resource(&source_id)?.is_none() {
+                return Err(BackendError::GenericError(format!(
+                    "resource {source_id} does not exist"
+                )));
+            }
+            for tag in self.db.list_resource_tags(&source_id)? {
+                if is_mergeable_tag(&tag)
+                    && present.insert((tag.tag_name.clone(), tag.tag_value.clone()))
+                {
+                    tags.push(ResourceTag {
+                        id: random_uuid(),
+                        resource_id: target_id.clone(),
+                        ..tag
+                    });
+                }
+            }
+            merged_ids.push(source_id);
+        }
+
+        let mut annotation_ids = vec![];
+        let mut tx = self.db.begin()?;
+        for tag in &tags {
+            Database::create_resource_tag_tx(&mut tx, tag)?;
+        }
+        for source_id in &merged_ids {
+            annotation_ids.extend(Database::merge_resource_into_tx(
+                &mut tx, &target_id, source_id,
+            )?);
+            Database::update_resource_deleted_tx(&mut tx, source_id, 1)?;
+            Database::update_resource_tag_by_name_tx(
+                &mut tx,
+                &ResourceTag::new_deleted(source_id, true),
+            )?;
+        }
+        tx.commit()?;
+
+        // their links are extracted from their tags
+        if !annotation_ids.is_empty() {
+            self.batch_post_processing_job(
+                annotation_ids.clone(),
+                ProcessingPriority::UserInitiated,
+            )?;
+        }
+        Ok(ResourceMergeResult {
+            resource_id: target_id,
+            merged_ids,
+            annotation_ids,
+        })
+    }
+}
+
+// tags extracted from the content or describing the resource itself stay with it
+fn is_mergeable_tag(tag: &ResourceTag) -> bool {
+    InternalResourceTagNames::from_str(&tag.tag_name).is_err()
+        && tag.tag_name != ANNOTATES_TAG
+        && tag.tag_name != HASHTAG_TAG
+        && !tag.tag_name.starts_with(FRONTMATTER_TAG_PREFIX)
+}
+
+fn sorted(ids: &[String]) -> Vec<String> {
+    let mut ids = ids.to_vec();
+    ids.sort();
+    ids
+}
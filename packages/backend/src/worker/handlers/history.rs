@@ -7,7 +7,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
     api::message::{HistoryMessage, TunnelOneshot},
-    store::models::{HistoryEntry, HistoryEntryType},
+    store::models::{HistoryEntry, HistoryEntryType, Page, PageRequest},
     worker::{send_worker_response, Worker},
     BackendError, BackendResult,
 };
@@ -44,6 +44,13 @@ impl Worker {
         self.db.get_all_history_entries(limit)
     }
 
+    pub fn get_history_entries_page(
+        &mut self,
+        page: PageRequest,
+    ) -> BackendResult<Page<HistoryEntry>> {
+        self.db.list_history_entries_page(&page)
+    }
+
     pub fn remove_all_history_entries(&mut self) -> BackendResult<()> {
         self.db.remove_all_history_entries()
     }
@@ -456,6 +463,10 @@ pub fn handle_history_message(
             let result = worker.get_all_history_entries(limit);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
+        HistoryMessage::GetHistoryEntriesPage(page) => {
+            let result = worker.get_history_entries_page(page);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
         HistoryMessage::GetHistoryEntry(id) => {
             let result = worker.get_history_entry(id);
             send_worker_response(&mut worker.channel, oneshot, result);
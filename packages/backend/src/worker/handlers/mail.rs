@@ -0,0 +1,185 @@
+use crate::{
+    api::message::SpaceEntryInput,
+    store::models::{
+        MailImportResult, ProcessingPriority, ResourceMetadata, ResourceTag, SpaceEntryType,
+    },
+    utils::content_hash,
+    worker::{
+        extractors::mail::{
+            is_mbox, parse_message, split_mbox, MailMessage, ATTACHMENT_OF_TAG, MAIL_RESOURCE_TYPE,
+        },
+        Worker,
+    },
+    BackendResult,
+};
+
+use std::collections::HashMap;
+use tracing::instrument;
+
+const SAVED_WITH_ACTION_TAG: &str = "savedWithAction";
+
+impl Worker {
+    // every message becomes a resource with its attachments as separate resources,
+    // threads with more than one message are collected in a space of their own
+    #[instrument(level = "trace", skip(self))]
+    pub fn import_mail(&mut self, path: String) -> BackendResult<MailImportResult> {
+        let data = std::fs::read(&path)?;
+        let raw_messages = if is_mbox(&data) {
+            split_mbox(&data)
+        } else {
+            vec![data]
+        };
+        std::fs::create_dir_all(&self.resources_path)?;
+
+        let mut result = MailImportResult::default();
+        // title of the first message, number of messages and all resource ids of a thread
+        let mut threads: Vec<(String, usize, Vec<String>)> = vec![];
+        let mut thread_index: HashMap<String, usize> = HashMap::new();
+
+        for raw in raw_messages {
+            let message = parse_message(&raw);
+            let (resource_id, attachment_ids) = self.import_mail_message(&message, &raw, &path)?;
+
+            let key = message
+                .thread_id()
+                .map(|id| id.to_owned())
+                .unwrap_or_else(|| format!("subject:{}", message.thread_subject()));
+            let index = *thread_index.entry(key).or_insert_with(|| {
+                threads.push((message.title(), 0, vec![]));
+                threads.len() - 1
+            });
+            let (_, message_count, resource_ids) = &mut threads[index];
+            *message_count += 1;
+            resource_ids.push(resource_id.clone());
+            resource_ids.extend(attachment_ids.iter().cloned());
+
+            result.resource_ids.push(resource_id);
+            result.attachment_ids.extend(attachment_ids);
+        }
+
+        for (title, message_count, resource_ids) in threads {
+            if message_count < 2 {
+                continue;
+            }
+            let space = self.create_space(&imported_space_name(&title))?;
+            let entries = resource_ids
+                .into_iter()
+                .map(|entry_id| SpaceEntryInput {
+                    entry_type: SpaceEntryType::Resource,
+                    entry_id,
+                    manually_added: 1,
+                })
+                .collect();
+            self.create_space_entries(space.id.clone(), entries)?;
+            result.space_ids.push(space.id);
+        }
+
+        let all_ids: Vec<String> = result
+            .resource_ids
+            .iter()
+            .chain(result.attachment_ids.iter())
+            .cloned()
+            .collect();
+        if !all_ids.is_empty() {
+            let progress =
+                self.batch_post_processing_job(all_ids, ProcessingPriority::BackgroundImport)?;
+            result.batch_id = Some(progress.batch_id);
+        }
+        Ok(result)
+    }
+
+    fn import_mail_message(
+        &mut self,
+        message: &MailMessage,
+        raw: &[u8],
+        source_path: &str,
+    ) -> BackendResult<(String, Vec<String>)> {
+        let source_uri = message
+            .source_uri()
+            .unwrap_or_else(|| format!("file://{source_path}"));
+        let mail_metadata = message.metadata();
+        let metadata = ResourceMetadata {
+            id: String::new(),
+            resource_id: String::new(),
+            name: message.title(),
+            source_uri: source_uri.clone(),
+            alt: String::new(),
+            user_context: String::new(),
+            byline: mail_metadata.byline,
+            published_at: mail_metadata.published_at,
+            lead_image: None,
+            outline: None,
+        };
+        let mut tags = message.tags("");
+        tags.push(imported_tag());
+
+        let resource_id = self.create_imported_resource(MAIL_RESOURCE_TYPE, tags, metadata, raw)?;
+
+        let mut attachment_ids = vec![];
+        for attachment in &message.attachments {
+            let metadata = ResourceMetadata {
+                id: String::new(),
+                resource_id: String::new(),
+                name: attachment.filename.clone(),
+                source_uri: source_uri.clone(),
+                alt: String::new(),
+                user_context: String::new(),
+                byline: None,
+                published_at: None,
+                lead_image: None,
+                outline: None,
+            };
+            let tags = vec![import_tag(ATTACHMENT_OF_TAG, &resource_id), imported_tag()];
+            attachment_ids.push(self.create_imported_resource(
+                &attachment.content_type,
+                tags,
+                metadata,
+                &attachment.data,
+            )?);
+        }
+        Ok((resource_id, attachment_ids))
+    }
+
+    // writes the data to the resource file and records its hash so it can be processed
+    pub(super) fn create_imported_resource(
+        &mut self,
+        resource_type: &str,
+        tags: Vec<ResourceTag>,
+        metadata: ResourceMetadata,
+        data: &[u8],
+    ) -> BackendResult<String> {
+        let resource =
+            self.create_resource(resource_type.to_owned(), Some(tags), Some(metadata))?;
+        std::fs::write(&resource.resource.resource_path, data)?;
+        self.upsert_resource_hash(resource.resource.id.clone(), content_hash(data))?;
+        Ok(resource.resource.id)
+    }
+}
+
+// ids are filled in when the resource is created
+pub(super) fn import_tag(name: &str, value: &str) -> ResourceTag {
+    ResourceTag {
+        id: String::new(),
+        resource_id: String::new(),
+        tag_name: name.to_owned(),
+        tag_value: value.to_owned(),
+    }
+}
+
+pub(super) fn imported_tag() -> ResourceTag {
+    import_tag(SAVED_WITH_ACTION_TAG, "import")
+}
+
+// spaces are named with their serialized `SpaceData`
+pub(super) fn imported_space_name(title: &str) -> String {
+    serde_json::json!({
+        "folderName": title,
+        "showInSidebar": false,
+        "liveModeEnabled": false,
+        "smartFilterQuery": null,
+        "sql_query": null,
+        "embedding_query": null,
+        "imported": true,
+    })
+    .to_string()
+}
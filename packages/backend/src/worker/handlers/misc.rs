@@ -21,11 +21,11 @@ use crate::{
             ResourceTextContent,
         },
     },
-    worker::{send_worker_response, Worker},
+    worker::{extractors::js::JsExtractor, send_worker_response, Worker},
     BackendError, BackendResult,
 };
 use neon::prelude::*;
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 use uuid::Uuid;
 
 impl Worker {
@@ -50,6 +50,27 @@ impl Worker {
         Ok(removed.to_string())
     }
 
+    pub fn register_extractor(
+        &self,
+        name: String,
+        mime_types: Vec<String>,
+        callback: Root<JsFunction>,
+    ) -> BackendResult<String> {
+        if mime_types.is_empty() {
+            return Err(BackendError::GenericError(
+                "extractor needs at least one MIME type".to_string(),
+            ));
+        }
+        let extractor = JsExtractor::new(name, mime_types, callback, self.channel.clone())?;
+        self.extractor_registry.register(Arc::new(extractor))?;
+        Ok("true".to_string())
+    }
+
+    pub fn unregister_extractor(&self, name: String) -> BackendResult<String> {
+        let removed = self.extractor_registry.unregister(&name)?;
+        Ok(removed.to_string())
+    }
+
     pub fn get_ai_chat_message(&mut self, id: String) -> BackendResult<AIChatSessionHistory> {
         let session = self
             .db
@@ -622,10 +643,13 @@ impl Worker {
         Ok(())
     }
 
-    pub fn get_youtube_transcript(&self, video_url: String) -> BackendResult<YoutubeTranscript> {
+    pub fn get_youtube_transcript(
+        &mut self,
+        video_url: String,
+    ) -> BackendResult<YoutubeTranscript> {
         // use english as default language
         let lang = Some("en");
-        crate::ai::youtube::fetch_transcript(&video_url, lang)
+        crate::ai::youtube::fetch_transcript_cached(&mut self.db, &video_url, lang)
     }
 
     pub fn query_sffs_resources(
@@ -1039,5 +1063,17 @@ pub fn handle_misc_message(
             let result = worker.unregister_tool(tool_id);
             send_worker_response(&mut worker.channel, oneshot, result)
         }
+        MiscMessage::RegisterExtractor {
+            name,
+            mime_types,
+            callback,
+        } => {
+            let result = worker.register_extractor(name, mime_types, callback);
+            send_worker_response(&mut worker.channel, oneshot, result)
+        }
+        MiscMessage::UnregisterExtractor { name } => {
+            let result = worker.unregister_extractor(name);
+            send_worker_response(&mut worker.channel, oneshot, result)
+        }
     }
 }
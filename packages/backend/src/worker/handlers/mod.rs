@@ -1,9 +1,15 @@
 pub mod app;
+pub mod duplicates;
 pub mod history;
 pub mod kv;
+pub mod mail;
 pub mod misc;
 pub mod resource;
+pub mod resource_links;
+pub mod resource_versions;
 pub mod space;
+pub mod vault;
+pub mod watched_folders;
 
 pub use app::handle_app_message;
 pub use history::handle_history_message;
@@ -1,25 +1,45 @@
 use std::collections::HashSet;
 
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
+use super::resource_versions::remove_resource_snapshots;
 use crate::{
-    api::message::{ProcessorMessage, ResourceMessage, ResourceTagMessage, TunnelOneshot},
+    api::message::{
+        EventBusMessage, ProcessorMessage, ResourceMessage, ResourceTagMessage, TunnelOneshot,
+    },
     store::{
         db::Database,
         models::{
             current_time, random_uuid, CompositeResource, EmbeddingResource, EmbeddingType,
-            InternalResourceTagNames, PostProcessingJob, Resource, ResourceMetadata,
-            ResourceOrSpace, ResourceProcessingState, ResourceTag, ResourceTagFilter,
-            ResourceTextContentMetadata, ResourceTextContentType, SearchEngine,
-            SearchResourcesParams, SearchResult, SearchResultItem, SearchResultSimple,
-            SearchResultSpaceItem, SpaceEntryExtended, SpaceEntryType,
+            ExtractedResourceMetadata, InternalResourceTagNames, PageRequest,
+            PostProcessingBatchProgress, PostProcessingJob, ProcessingPriority, Resource,
+            ResourceFacets, ResourceFilter, ResourceFrontmatter, ResourceMetadata, ResourceOrSpace,
+            ResourceProcessingState, ResourceTag, ResourceTextContentMetadata,
+            ResourceTextContentType, ResourceThumbnail, SearchEngine, SearchLanguage,
+            SearchQueryError, SearchQueryErrorKind, SearchResourcesParams, SearchResult,
+            SearchResultItem, SearchResultSimple, SearchResultSpaceItem, SimilarImageResource,
+            SpaceEntryExtended, SpaceEntryType,
+        },
+        post_processing_jobs::post_processing_backoff,
+        search::DEFAULT_SNIPPET_LENGTH,
+    },
+    worker::{
+        extractors::frontmatter::{
+            frontmatter_tags, with_frontmatter, FRONTMATTER_TAG_PREFIX, HASHTAG_TAG,
         },
+        queue::EnqueueError,
+        search_query::parse_search_query,
+        send_worker_response, Worker,
     },
-    worker::{send_worker_response, Worker},
     BackendError, BackendResult,
 };
 use std::{path::Path, str::FromStr};
 
+const POST_PROCESSING_DISPATCH_BATCH_SIZE: usize = 64;
+const PROCESSING_LANE_FULL_DELAY_SECS: i64 = 5;
+// out of 64 bits, up to about 10 differing bits the images still look alike
+const DEFAULT_SIMILAR_IMAGE_DISTANCE: u32 = 10;
+
 impl Worker {
     #[instrument(level = "trace", skip(self, tags, metadata))]
     pub fn create_resource(
@@ -131,6 +151,7 @@ impl Worker {
             resource_annotations: None,
             post_processing_job: None,
             space_ids: None,
+            thumbnail: None,
         })
     }
 
@@ -149,6 +170,7 @@ impl Worker {
         let space_ids = self.db.list_space_ids_by_resource_id(&resource.id)?;
         let resource_tags = self.db.list_resource_tags(&resource.id)?;
         let resource_tags = (!resource_tags.is_empty()).then_some(resource_tags);
+        let thumbnail = self.db.get_resource_thumbnail(&resource.id)?;
         let mut resource_annotations = None;
         if include_annotations {
             let annotations = self.db.list_resource_annotations(&[id])?;
@@ -165,6 +187,7 @@ impl Worker {
             resource_annotations,
             post_processing_job: processing_state,
             space_ids: Some(space_ids),
+            thumbnail,
         }))
     }
 
@@ -192,7 +215,7 @@ impl Worker {
         }
         let mut tx = self.db.begin()?;
 
-        Database::remove_resources_tx(&mut tx, &ids)?;
+        let files_to_remove = Database::remove_resources_tx(&mut tx, &ids)?;
         self.ai
             .upsert_embeddings(all_embedding_keys, vec![], vec![])?;
         for resource in resources_to_remove {
@@ -209,12 +232,18 @@ impl Worker {
         }
 
         tx.commit()?;
+        for path in files_to_remove {
+            remove_resource_file(&path);
+        }
+        for id in &ids {
+            remove_resource_snapshots(&self.resources_path, id);
+        }
         Ok(())
     }
 
     #[instrument(level = "trace", skip(self))]
-    pub fn remove_resources_by_tags(&mut self, tags: Vec<ResourceTagFilter>) -> BackendResult<()> {
-        let ids = self.db.list_resource_ids_by_tags(&tags)?;
+    pub fn remove_resources_by_tags(&mut self, filter: ResourceFilter) -> BackendResult<()> {
+        let ids = self.db.list_resource_ids_by_tags(&filter)?;
         self.remove_resources(ids)
     }
 
@@ -231,30 +260,65 @@ impl Worker {
     // Only return resource ids
     pub fn list_resources_by_tags(
         &mut self,
-        tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
+        include_facets: bool,
+        page: Option<PageRequest>,
     ) -> BackendResult<SearchResultSimple> {
-        self.db.list_resources_by_tags(tags)
+        let page = match page {
+            Some(page) => page,
+            None => {
+                let mut result = self.db.list_resources_by_tags(filter)?;
+                if include_facets {
+                    result.facets = Some(self.count_resource_facets(&result.items)?);
+                }
+                return Ok(result);
+            }
+        };
+        let mut result = self.db.list_resources_by_tags_page(&filter, false, &page)?;
+        // the facets count every match and not just the ones on this page
+        if include_facets {
+            let resource_ids = self.db.list_resource_ids_by_tags(&filter)?;
+            result.facets = Some(self.count_resource_facets(&resource_ids)?);
+        }
+        Ok(result)
+    }
+
+    // the hashtags are the tags the user gave the resources
+    fn count_resource_facets(&self, resource_ids: &[String]) -> BackendResult<ResourceFacets> {
+        self.db.count_resource_facets(resource_ids, HASHTAG_TAG)
     }
 
     #[instrument(level = "trace", skip(self))]
     pub fn list_all_resources_and_spaces(
         &mut self,
-        tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
     ) -> BackendResult<Vec<ResourceOrSpace>> {
-        self.db.list_all_resources_and_spaces(tags)
+        self.db.list_all_resources_and_spaces(filter)
     }
 
     // Only return resource ids
     pub fn list_resources_by_tags_no_space(
         &mut self,
-        tags: Vec<ResourceTagFilter>,
+        filter: ResourceFilter,
+        page: Option<PageRequest>,
     ) -> BackendResult<SearchResultSimple> {
-        self.db.list_resources_by_tags_no_space(tags)
+        match page {
+            Some(page) => self.db.list_resources_by_tags_page(&filter, true, &page),
+            None => self.db.list_resources_by_tags_no_space(filter),
+        }
+    }
+
+    pub fn get_search_language(&mut self) -> BackendResult<SearchLanguage> {
+        self.db.get_search_language()
+    }
+
+    pub fn set_search_language(&mut self, language: SearchLanguage) -> BackendResult<()> {
+        self.db.set_search_language(language)
     }
 
     fn get_filtered_ids_for_search(
         &mut self,
-        resource_tag_filters: Option<Vec<ResourceTagFilter>>,
+        resource_tag_filters: Option<ResourceFilter>,
         space_id: Option<String>,
     ) -> BackendResult<Option<Vec<String>>> {
         if let Some(resource_tag_filters) = resource_tag_filters {
@@ -280,16 +344,6 @@ impl Worker {
         &mut self,
         params: SearchResourcesParams,
     ) -> BackendResult<SearchResult> {
-        if let Some(resource_tag_filters) = &params.resource_tag_filters {
-            // we use an `INTERSECT` for each resouce tag filter
-            // so limiting the number of filters
-            if resource_tag_filters.len() > 20 {
-                return Err(BackendError::GenericError(format!(
-                    "Max {} filters allowed",
-                    20
-                )));
-            }
-        }
         let keyword_limit = params.keyword_limit.unwrap_or(100);
         let include_annotations = params.include_annotations.unwrap_or(false);
 
@@ -298,17 +352,66 @@ impl Worker {
         let embeddings_distance_threshold = params.embeddings_distance_threshold.unwrap_or(0.4);
         let embeddings_limit = params.embeddings_limit.unwrap_or(100);
 
+        let query = parse_search_query(&params.query);
+        let mut query_errors = query.errors.clone();
+        let mut space_id = params.space_id.clone();
+        if let Some(space) = &query.space {
+            let mut space_ids = self.db.list_space_ids_by_name(&space.name)?;
+            let error = match space_ids.len() {
+                1 => {
+                    space_id = space_ids.pop();
+                    None
+                }
+                0 => Some((
+                    SearchQueryErrorKind::UnknownSpace,
+                    format!("there is no space named `{}`", space.name),
+                )),
+                n => Some((
+                    SearchQueryErrorKind::AmbiguousSpace,
+                    format!("{} spaces are named `{}`", n, space.name),
+                )),
+            };
+            if let Some((kind, message)) = error {
+                query_errors.push(SearchQueryError {
+                    kind,
+                    message,
+                    start: space.start,
+                    end: space.end,
+                });
+            }
+        }
+        if !query_errors.is_empty() {
+            return Ok(SearchResult {
+                items: vec![],
+                spaces: vec![],
+                total: 0,
+                space_entries: None,
+                query_errors,
+                facets: None,
+            });
+        }
+        // the operators only narrow down the keyword search, the other searches use the words
+        let text = query.text();
+        let resource_filter = match (params.resource_tag_filters, query.filter()) {
+            (Some(filter), Some(query_filter)) => Some(ResourceFilter::And {
+                filters: vec![filter, query_filter],
+            }),
+            (filter, query_filter) => filter.or(query_filter),
+        };
+
         let mut seen_keys: HashSet<String> = HashSet::new();
         let mut results: Vec<SearchResultItem> = vec![];
 
         let filtered_resource_ids =
-            self.get_filtered_ids_for_search(params.resource_tag_filters, params.space_id.clone())?;
+            self.get_filtered_ids_for_search(resource_filter, space_id.clone())?;
 
-        let db_results = self.db.search_resources(
-            &params.query,
+        let db_results = self.db.search_resources_by_match(
+            query.fts_match().as_deref(),
+            &query.search_indexes(),
             &filtered_resource_ids,
             include_annotations,
             Some(keyword_limit),
+            params.snippet_length.unwrap_or(DEFAULT_SNIPPET_LENGTH),
         )?;
 
         for result in db_results.items {
@@ -322,11 +425,11 @@ impl Worker {
             results.push(result)
         }
 
-        if semantic_search_enabled {
+        if semantic_search_enabled && !text.is_empty() {
             self.wait_for_backend();
             let vector_search_results = self.ai.vector_search(
                 &self.db,
-                params.query.clone(),
+                text.clone(),
                 embeddings_limit as usize,
                 filtered_resource_ids,
                 true,
@@ -343,14 +446,15 @@ impl Worker {
                 results.push(SearchResultItem {
                     resource: result,
                     engine: SearchEngine::Embeddings,
+                    snippets: vec![],
                 });
             }
         }
         let spaces: Vec<SearchResultSpaceItem>;
         let mut space_entries: Option<Vec<SpaceEntryExtended>> = None;
-        match params.space_id {
+        match space_id {
             Some(space_id) => {
-                spaces = self.db.search_sub_space_entries(&space_id, &params.query)?;
+                spaces = self.db.search_sub_space_entries(&space_id, &text)?;
                 let resource_ids = results
                     .iter()
                     .map(|r| r.resource.resource.id.clone())
@@ -391,15 +495,30 @@ impl Worker {
                 });
                 space_entries = Some(entries);
             }
+            // a query of only operators is about resources
+            None if text.is_empty() && query.has_operators() => {
+                spaces = vec![];
+            }
             None => {
-                spaces = self.db.search_spaces(&params.query)?;
+                spaces = self.db.search_spaces(&text)?;
             }
         }
+        let facets = if params.include_facets.unwrap_or(false) {
+            let resource_ids = results
+                .iter()
+                .map(|result| result.resource.resource.id.clone())
+                .collect::<Vec<_>>();
+            Some(self.count_resource_facets(&resource_ids)?)
+        } else {
+            None
+        };
         Ok(SearchResult {
             total: results.len() as i64 + spaces.len() as i64,
             items: results,
             spaces,
             space_entries,
+            query_errors: vec![],
+            facets,
         })
     }
 
@@ -412,7 +531,46 @@ impl Worker {
     }
 
     #[instrument(level = "trace", skip(self))]
-    pub fn post_processing_job(&mut self, resource_id: String) -> BackendResult<PostProcessingJob> {
+    pub fn post_processing_job(
+        &mut self,
+        resource_id: String,
+        priority: ProcessingPriority,
+    ) -> BackendResult<PostProcessingJob> {
+        self.create_post_processing_job(resource_id, priority, None)
+    }
+
+    // queues post processing for many resources at once, progress of the batch
+    // is reported on the event bus as its jobs complete
+    #[instrument(level = "trace", skip(self, resource_ids))]
+    pub fn batch_post_processing_job(
+        &mut self,
+        resource_ids: Vec<String>,
+        priority: ProcessingPriority,
+    ) -> BackendResult<PostProcessingBatchProgress> {
+        let batch_id = random_uuid();
+        let mut errors = vec![];
+        for resource_id in resource_ids {
+            if let Err(err) =
+                self.create_post_processing_job(resource_id, priority, Some(batch_id.clone()))
+            {
+                errors.push(err);
+            }
+        }
+        if !errors.is_empty() {
+            tracing::error!(
+                "failed to create {} jobs of post processing batch {batch_id}: {errors:?}",
+                errors.len()
+            );
+        }
+        self.db.get_post_processing_batch_progress(&batch_id)
+    }
+
+    fn create_post_processing_job(
+        &mut self,
+        resource_id: String,
+        priority: ProcessingPriority,
+        batch_id: Option<String>,
+    ) -> BackendResult<PostProcessingJob> {
         let resource = self
             .read_resource(resource_id.as_str(), false)?
             // mb this should be a `DatabaseError`?
@@ -426,23 +584,15 @@ impl Worker {
                     "resource content hash does not exist".to_owned(),
                 ))?;
 
-        let job = PostProcessingJob {
-            id: random_uuid(),
-            created_at: current_time(),
-            updated_at: current_time(),
-            resource_id,
-            content_hash,
-            state: ResourceProcessingState::Pending,
-        };
+        let mut job = PostProcessingJob::new(resource_id, content_hash).with_priority(priority);
+        if let Some(batch_id) = batch_id {
+            job = job.with_batch_id(batch_id);
+        }
         self.db.create_processing_job_entry(&job)?;
 
-        self.tqueue_tx
-            .send(ProcessorMessage::ProcessResource(
-                job.clone(),
-                Box::new(resource.clone()),
-            ))
+        self.send_to_processing_queue(job.clone(), resource)
             .map_err(|err| {
-                let mut errors = vec![BackendError::GenericError(err.to_string())];
+                let mut errors = vec![err];
                 if let Err(err) = self.db.remove_processing_job_entry(job.id.clone()) {
                     errors.push(err)
                 }
@@ -533,7 +683,12 @@ impl Worker {
         let mut metadatas: Vec<ResourceTextContentMetadata> = vec![];
 
         for (c, m) in content.iter().zip(metadata.iter()) {
-            let embedding_chunks = self.ai.chunker.chunk(c);
+            // code is already split at its definitions, sentence chunking would mangle it
+            let embedding_chunks = if content_type == ResourceTextContentType::Code {
+                vec![c.clone()]
+            } else {
+                self.ai.chunker.chunk(c)
+            };
             // same metadata for each chunk
             metadatas.extend(std::iter::repeat_n(m.clone(), embedding_chunks.len()));
             chunks.extend(embedding_chunks);
@@ -674,9 +829,135 @@ impl Worker {
         let mut tx = self.db.begin()?;
         Database::upsert_resource_hash_tx(&mut tx, &resource_id, &hash)?;
         tx.commit()?;
+        // the content is saved either way, a missing version is not worth failing the save
+        if let Err(e) = self.snapshot_resource_version(&resource_id, &hash) {
+            tracing::warn!("failed to snapshot version of {resource_id}: {e}");
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn apply_extracted_resource_metadata(
+        &mut self,
+        resource_id: String,
+        metadata: ExtractedResourceMetadata,
+    ) -> BackendResult<()> {
+        let mut tx = self.db.begin()?;
+        Database::apply_extracted_resource_metadata_tx(&mut tx, &resource_id, &metadata)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_extracted_resource_tags(
+        &mut self,
+        resource_id: String,
+        tags: Vec<ResourceTag>,
+    ) -> BackendResult<()> {
+        let mut tx = self.db.begin()?;
+        // names can repeat, e.g. one tag per recipient, so remove all of them before adding any
+        let mut tag_names: Vec<&str> = tags.iter().map(|tag| tag.tag_name.as_str()).collect();
+        tag_names.sort_unstable();
+        tag_names.dedup();
+        for tag_name in tag_names {
+            Database::remove_resource_tag_by_tag_name_tx(&mut tx, &resource_id, tag_name)?;
+        }
+        for tag in tags.iter() {
+            Database::create_resource_tag_tx(
+                &mut tx,
+                &ResourceTag {
+                    id: random_uuid(),
+                    resource_id: resource_id.clone(),
+                    tag_name: tag.tag_name.clone(),
+                    tag_value: tag.tag_value.clone(),
+                },
+            )?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
+    // tags from an earlier version of the frontmatter are removed, hashtags only when the
+    // frontmatter lists its own
+    #[instrument(level = "trace", skip(self))]
+    pub fn apply_resource_frontmatter(
+        &mut self,
+        resource_id: String,
+        frontmatter: ResourceFrontmatter,
+    ) -> BackendResult<()> {
+        let mut tx = self.db.begin()?;
+        Database::apply_resource_frontmatter_tx(&mut tx, &resource_id, &frontmatter)?;
+        Database::remove_resource_tags_by_tag_name_prefix_tx(
+            &mut tx,
+            &resource_id,
+            FRONTMATTER_TAG_PREFIX,
+        )?;
+        if frontmatter.hashtags.is_some() {
+            Database::remove_resource_tag_by_tag_name_tx(&mut tx, &resource_id, HASHTAG_TAG)?;
+        }
+        for tag in frontmatter_tags(&frontmatter, &resource_id) {
+            Database::create_resource_tag_tx(
+                &mut tx,
+                &ResourceTag {
+                    id: random_uuid(),
+                    ..tag
+                },
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // the resource data with its metadata and tags written into the frontmatter
+    #[instrument(level = "trace", skip(self))]
+    pub fn export_resource_markdown(&mut self, resource_id: String) -> BackendResult<String> {
+        let resource = self
+            .db
+            .get_resource(&resource_id)?
+            .ok_or_else(|| BackendError::GenericError("resource not found".to_owned()))?;
+        let metadata = self.db.get_resource_metadata_by_resource_id(&resource_id)?;
+        let tags = self.db.list_resource_tags(&resource_id)?;
+        let content = std::fs::read_to_string(&resource.resource_path)?;
+        with_frontmatter(&content, metadata.as_ref(), &tags)
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_resource_thumbnail(&mut self, thumbnail: ResourceThumbnail) -> BackendResult<()> {
+        let previous = self.db.get_resource_thumbnail(&thumbnail.resource_id)?;
+        let mut tx = self.db.begin()?;
+        Database::upsert_resource_thumbnail_tx(&mut tx, &thumbnail)?;
+        tx.commit()?;
+
+        if let Some(previous) = previous.filter(|previous| previous.path != thumbnail.path) {
+            remove_resource_file(&previous.path);
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn upsert_resource_perceptual_hash(
+        &mut self,
+        resource_id: String,
+        hash: u64,
+    ) -> BackendResult<()> {
+        let mut tx = self.db.begin()?;
+        Database::upsert_resource_perceptual_hash_tx(&mut tx, &resource_id, hash)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn list_similar_image_resources(
+        &mut self,
+        resource_id: String,
+        max_distance: Option<u32>,
+    ) -> BackendResult<Vec<SimilarImageResource>> {
+        self.db.list_similar_image_resources(
+            &resource_id,
+            max_distance.unwrap_or(DEFAULT_SIMILAR_IMAGE_DISTANCE),
+        )
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub fn get_resource_hash(&mut self, resource_id: String) -> BackendResult<Option<String>> {
         self.db.get_resource_hash(&resource_id)
@@ -690,9 +971,144 @@ impl Worker {
         Ok(())
     }
 
+    // a job whose lane is full stays pending in the database and is picked up
+    // again by the scheduler once the lane had time to drain
+    fn send_to_processing_queue(
+        &mut self,
+        job: PostProcessingJob,
+        resource: CompositeResource,
+    ) -> BackendResult<()> {
+        let job_id = job.id.clone();
+        let priority = job.priority;
+        match self.tqueue_tx.try_send(
+            priority,
+            ProcessorMessage::ProcessResource(job, Box::new(resource)),
+        ) {
+            Ok(_) => Ok(()),
+            Err(EnqueueError::Full(_)) => {
+                debug!("{priority} processing lane is full, deferring job {job_id}");
+                self.db.schedule_post_processing_job(
+                    &job_id,
+                    &(current_time() + chrono::Duration::seconds(PROCESSING_LANE_FULL_DELAY_SECS)),
+                )
+            }
+            Err(EnqueueError::Disconnected(_)) => Err(BackendError::GenericError(
+                "processing queue is disconnected".to_owned(),
+            )),
+        }
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn send_post_processing_batch_progress(&mut self, batch_id: String) -> BackendResult<()> {
+        let progress = self.db.get_post_processing_batch_progress(&batch_id)?;
+        self.send_event_bus_message(EventBusMessage::PostProcessingBatchProgress(progress));
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn record_post_processing_job_failure(
+        &mut self,
+        job_id: String,
+        error: String,
+    ) -> BackendResult<ResourceProcessingState> {
+        self.db.record_post_processing_job_failure(&job_id, &error)
+    }
+
     #[instrument(level = "trace", skip(self))]
-    pub fn fail_active_post_processing_jobs(&mut self) -> BackendResult<()> {
-        self.db.fail_active_post_processing_jobs(&self.created_at)
+    pub fn list_post_processing_jobs(
+        &mut self,
+        state: Option<String>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<PostProcessingJob>> {
+        self.db.list_post_processing_jobs(state.as_deref(), limit)
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn retry_post_processing_job(
+        &mut self,
+        job_id: String,
+    ) -> BackendResult<PostProcessingJob> {
+        let job = self
+            .db
+            .get_post_processing_job(&job_id)?
+            .ok_or(BackendError::GenericError(
+                "post processing job does not exist".to_owned(),
+            ))?;
+        let resource =
+            self.read_resource(&job.resource_id, false)?
+                .ok_or(BackendError::GenericError(
+                    "resource does not exist".to_owned(),
+                ))?;
+
+        if !self.db.reset_post_processing_job(&job.id)? {
+            return Err(BackendError::GenericError(
+                "post processing job was superseded by a newer one".to_owned(),
+            ));
+        }
+        let job = self
+            .db
+            .get_post_processing_job(&job.id)?
+            .ok_or(BackendError::GenericError(
+                "post processing job does not exist".to_owned(),
+            ))?;
+        self.send_to_processing_queue(job.clone(), resource)?;
+
+        Ok(job)
+    }
+
+    // hands the jobs whose retry time has come over to the processor threads
+    #[instrument(level = "trace", skip(self))]
+    pub fn dispatch_due_post_processing_jobs(&mut self) -> BackendResult<usize> {
+        let jobs = self
+            .db
+            .list_due_post_processing_jobs(&current_time(), POST_PROCESSING_DISPATCH_BATCH_SIZE)?;
+
+        let mut dispatched = 0;
+        for job in jobs {
+            // another worker thread might have picked up the same job
+            if !self.db.claim_post_processing_job(&job.id)? {
+                continue;
+            }
+            // a claimed job has no `next_run_at` anymore, so it is scheduled again when it
+            // can't be handed over, otherwise the scheduler would never pick it up
+            let job_id = job.id.clone();
+            let retry_at = current_time() + post_processing_backoff(job.attempts + 1);
+            match self.dispatch_claimed_post_processing_job(job) {
+                Ok(true) => dispatched += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("failed to dispatch post processing job {job_id}, retrying later: {e}");
+                    self.db.schedule_post_processing_job(&job_id, &retry_at)?;
+                }
+            }
+        }
+
+        if dispatched > 0 {
+            debug!("dispatched {dispatched} scheduled post processing jobs");
+        }
+        Ok(dispatched)
+    }
+
+    // returns false when the resource is gone and the job was removed instead
+    fn dispatch_claimed_post_processing_job(
+        &mut self,
+        job: PostProcessingJob,
+    ) -> BackendResult<bool> {
+        match self.read_resource(&job.resource_id, false)? {
+            Some(resource) => {
+                self.send_to_processing_queue(job, resource)?;
+                Ok(true)
+            }
+            None => {
+                self.db.remove_processing_job_entry(job.id)?;
+                Ok(false)
+            }
+        }
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn resume_post_processing_jobs(&mut self) -> BackendResult<()> {
+        self.db.resume_post_processing_jobs(&self.created_at)
     }
 }
 
@@ -756,16 +1172,20 @@ pub fn handle_resource_message(
             let result = worker.remove_resources_by_tags(tags);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
-        ResourceMessage::ListResourcesByTags(tags) => {
-            let result = worker.list_resources_by_tags(tags);
+        ResourceMessage::ListResourcesByTags {
+            filter,
+            include_facets,
+            page,
+        } => {
+            let result = worker.list_resources_by_tags(filter, include_facets, page);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
         ResourceMessage::ListAllResourcesAndSpaces(tags) => {
             let result = worker.list_all_resources_and_spaces(tags);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
-        ResourceMessage::ListResourcesByTagsNoSpace(tags) => {
-            let result = worker.list_resources_by_tags_no_space(tags);
+        ResourceMessage::ListResourcesByTagsNoSpace { filter, page } => {
+            let result = worker.list_resources_by_tags_no_space(filter, page);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
         ResourceMessage::SearchResources(search_params) => {
@@ -780,8 +1200,22 @@ pub fn handle_resource_message(
             let result = worker.update_resource_metadata(metadata);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
-        ResourceMessage::PostProcessJob(id) => {
-            let result = worker.post_processing_job(id);
+        ResourceMessage::PostProcessJob {
+            resource_id,
+            priority,
+        } => {
+            let result = worker.post_processing_job(resource_id, priority);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::BatchPostProcessJob {
+            resource_ids,
+            priority,
+        } => {
+            let result = worker.batch_post_processing_job(resource_ids, priority);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SendPostProcessingBatchProgress(batch_id) => {
+            let result = worker.send_post_processing_batch_progress(batch_id);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
         ResourceMessage::BatchUpsertResourceTextContent {
@@ -810,13 +1244,170 @@ pub fn handle_resource_message(
             let result = worker.delete_resource_hash(resource_id);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
+        ResourceMessage::ApplyExtractedResourceMetadata {
+            resource_id,
+            metadata,
+        } => {
+            let result = worker.apply_extracted_resource_metadata(resource_id, metadata);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SetExtractedResourceTags { resource_id, tags } => {
+            let result = worker.set_extracted_resource_tags(resource_id, tags);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ApplyResourceFrontmatter {
+            resource_id,
+            frontmatter,
+        } => {
+            let result = worker.apply_resource_frontmatter(resource_id, frontmatter);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ExportResourceMarkdown(resource_id) => {
+            let result = worker.export_resource_markdown(resource_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ImportMarkdownVault(path) => {
+            let result = worker.import_markdown_vault(path);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::AddWatchedFolder { path, space_id } => {
+            let result = worker.add_watched_folder(path, space_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::RemoveWatchedFolder(folder_id) => {
+            let result = worker.remove_watched_folder(folder_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListWatchedFolders => {
+            let result = worker.list_watched_folders();
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SyncWatchedFolder { folder_id, paths } => {
+            let result = worker.sync_watched_folder(folder_id, paths);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ResumeWatchedFolders => {
+            let result = worker.resume_watched_folders();
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SetExtractedResourceLinks { resource_id, links } => {
+            let result = worker.set_extracted_resource_links(resource_id, links);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::CreateResourceLink {
+            source_id,
+            target_id,
+            context,
+        } => {
+            let result = worker.create_resource_link(source_id, target_id, context);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::RemoveResourceLink(link_id) => {
+            let result = worker.remove_resource_link(link_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListOutgoingResourceLinks(resource_id) => {
+            let result = worker.list_outgoing_resource_links(resource_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListResourceBacklinks(resource_id) => {
+            let result = worker.list_resource_backlinks(resource_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::GetResourceGraph { resource_id, hops } => {
+            let result = worker.get_resource_graph(resource_id, hops);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListResourceVersions(resource_id) => {
+            let result = worker.list_resource_versions(resource_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::GetResourceVersionContent(version_id) => {
+            let result = worker.get_resource_version_content(version_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::DiffResourceVersions {
+            from_version_id,
+            to_version_id,
+        } => {
+            let result = worker.diff_resource_versions(from_version_id, to_version_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::RestoreResourceVersion(version_id) => {
+            let result = worker.restore_resource_version(version_id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::FindDuplicateResources { include_similar } => {
+            let result = worker.find_duplicate_resources(include_similar);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::MergeResources {
+            target_id,
+            source_ids,
+        } => {
+            let result = worker.merge_resources(target_id, source_ids);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::GetSearchLanguage => {
+            let result = worker.get_search_language();
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SetSearchLanguage(language) => {
+            let result = worker.set_search_language(language);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::SetResourceThumbnail(thumbnail) => {
+            let result = worker.set_resource_thumbnail(thumbnail);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::UpsertResourcePerceptualHash { resource_id, hash } => {
+            let result = worker.upsert_resource_perceptual_hash(resource_id, hash);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListSimilarImageResources {
+            resource_id,
+            max_distance,
+        } => {
+            let result = worker.list_similar_image_resources(resource_id, max_distance);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ImportMail(path) => {
+            let result = worker.import_mail(path);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
         ResourceMessage::SetPostProcessingState { id, state } => {
             let result = worker.set_post_processing_job_state(id, state);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
-        ResourceMessage::FailActivePostProcessingJobs => {
-            let result = worker.fail_active_post_processing_jobs();
+        ResourceMessage::RecordPostProcessingFailure { id, error } => {
+            let result = worker.record_post_processing_job_failure(id, error);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ListPostProcessingJobs { state, limit } => {
+            let result = worker.list_post_processing_jobs(state, limit);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::RetryPostProcessingJob(id) => {
+            let result = worker.retry_post_processing_job(id);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::DispatchDuePostProcessingJobs => {
+            let result = worker.dispatch_due_post_processing_jobs();
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
+        ResourceMessage::ResumePostProcessingJobs => {
+            let result = worker.resume_post_processing_jobs();
             send_worker_response(&mut worker.channel, oneshot, result);
         }
     }
 }
+
+// thumbnails and snapshots of a resource only take up space, so failing to remove one is
+// only logged
+fn remove_resource_file(path: &str) {
+    match std::fs::remove_file(path) {
+        Ok(_) => {}
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("failed to remove {path}: {e}"),
+    }
+}
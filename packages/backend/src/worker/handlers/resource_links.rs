@@ -0,0 +1,111 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{
+            current_time, random_uuid, ExtractedResourceLink, ResourceGraph, ResourceLink,
+            ResourceLinkKind,
+        },
+    },
+    worker::Worker,
+    BackendError, BackendResult,
+};
+
+use tracing::instrument;
+
+// graphs get too large to render long before these are reached
+const MAX_GRAPH_HOPS: usize = 4;
+const MAX_GRAPH_RESOURCES: usize = 500;
+
+impl Worker {
+    #[instrument(level = "trace", skip(self, links))]
+    pub fn set_extracted_resource_links(
+        &mut self,
+        resource_id: String,
+        links: Vec<ExtractedResourceLink>,
+    ) -> BackendResult<()> {
+        let mut tx = self.db.begin()?;
+        Database::set_extracted_resource_links_tx(&mut tx, &resource_id, &links)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn create_resource_link(
+        &mut self,
+        source_id: String,
+        target_id: String,
+        context: Option<String>,
+    ) -> BackendResult<ResourceLink> {
+        if source_id == target_id {
+            return Err(BackendError::GenericError(
+                "a resource can't be linked to itself".to_owned(),
+            ));
+        }
+        for id in [&source_id, &target_id] {
+            if self.db.get_resource(id)?.is_none() {
+                return Err(BackendError::GenericError(format!(
+                    "resource {id} does not exist"
+                )));
+            }
+        }
+
+        let link = ResourceLink {
+            id: random_uuid(),
+            source_id,
+            target_id,
+            kind: ResourceLinkKind::User,
+            context,
+            created_at: current_time(),
+        };
+        let mut tx = self.db.begin()?;
+        Database::create_resource_link_tx(&mut tx, &link)?;
+        tx.commit()?;
+        Ok(link)
+    }
+
+    // extracted links come back the next time their source is processed, so only the user's
+    // own links can be removed
+    #[instrument(level = "trace", skip(self))]
+    pub fn remove_resource_link(&mut self, link_id: String) -> BackendResult<()> {
+        let link = match self.db.get_resource_link(&link_id)? {
+            Some(link) => link,
+            None => return Ok(()),
+        };
+        if link.kind != ResourceLinkKind::User {
+            return Err(BackendError::GenericError(format!(
+                "{} links are managed by post processing",
+                link.kind
+            )));
+        }
+        let mut tx = self.db.begin()?;
+        Database::remove_resource_link_tx(&mut tx, &link_id)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn list_outgoing_resource_links(
+        &mut self,
+        resource_id: String,
+    ) -> BackendResult<Vec<ResourceLink>> {
+        self.db.list_outgoing_resource_links(&resource_id)
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn list_resource_backlinks(
+        &mut self,
+        resource_id: String,
+    ) -> BackendResult<Vec<ResourceLink>> {
+        self.db.list_resource_backlinks(&resource_id)
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_resource_graph(
+        &mut self,
+        resource_id: String,
+        hops: usize,
+    ) -> BackendResult<ResourceGraph> {
+        self.db
+            .get_resource_graph(&resource_id, hops.min(MAX_GRAPH_HOPS), MAX_GRAPH_RESOURCES)
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    store::{
+        db::Database,
+        models::{
+            current_time, random_uuid, DiffLine, DiffLineKind, ProcessingPriority, Resource,
+            ResourceVersion, ResourceVersionContent, ResourceVersionDiff,
+        },
+    },
+    utils::content_hash,
+    worker::{
+        versions::{
+            is_versioned_resource_type, text_diff, version_path, version_text, versions_dir,
+        },
+        Worker,
+    },
+    BackendError, BackendResult,
+};
+
+use tracing::instrument;
+
+// versions past this are removed, oldest first
+const MAX_RESOURCE_VERSIONS: usize = 50;
+
+impl Worker {
+    // called whenever the content hash of a resource changes, `None` for resources without
+    // versions and when the content is the one of the latest version
+    pub fn snapshot_resource_version(
+        &mut self,
+        resource_id: &str,
+        hash: &str,
+    ) -> BackendResult<Option<ResourceVersion>> {
+        let resource = match self.db.get_resource(resource_id)? {
+            Some(resource) if is_versioned_resource_type(&resource.resource_type) => resource,
+            _ => return Ok(None),
+        };
+        let latest = self.db.get_latest_resource_version(resource_id)?;
+        if latest.is_some_and(|latest| latest.content_hash == hash) {
+            return Ok(None);
+        }
+
+        let data = std::fs::read(&resource.resource_path)?;
+        let id = random_uuid();
+        let path = version_path(&versions_dir(&self.resources_path), resource_id, &id);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, &data)?;
+
+        let version = ResourceVersion {
+            id,
+            resource_id: resource_id.to_owned(),
+            content_hash: hash.to_owned(),
+            snapshot_path: path.to_string_lossy().into_owned(),
+            size: data.len() as i64,
+            created_at: current_time(),
+        };
+        let expired = self
+            .db
+            .list_expired_resource_versions(resource_id, MAX_RESOURCE_VERSIONS - 1)?;
+        let mut tx = self.db.begin()?;
+        Database::create_resource_version_tx(&mut tx, &version)?;
+        for expired in &expired {
+            Database::remove_resource_version_tx(&mut tx, &expired.id)?;
+        }
+        tx.commit()?;
+
+        for expired in expired {
+            remove_snapshot_file(&expired.snapshot_path);
+        }
+        Ok(Some(version))
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn list_resource_versions(
+        &mut self,
+        resource_id: String,
+    ) -> BackendResult<Vec<ResourceVersion>> {
+        self.db.list_resource_versions(&resource_id)
+    }
+
+    fn read_resource_version(
+        &self,
+        version_id: &str,
+    ) -> BackendResult<(ResourceVersion, Resource, Vec<u8>)> {
+        let version = self
+            .db
+            .get_resource_version(version_id)?
+            .ok_or_else(|| BackendError::GenericError("resource version not found".to_owned()))?;
+        let resource = self
+            .db
+            .get_resource(&version.resource_id)?
+            .ok_or_else(|| BackendError::GenericError("resource not found".to_owned()))?;
+        let data = std::fs::read(&version.snapshot_path)?;
+        Ok((version, resource, data))
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_resource_version_content(
+        &mut self,
+        version_id: String,
+    ) -> BackendResult<ResourceVersionContent> {
+        let (version, _, data) = self.read_resource_version(&version_id)?;
+        Ok(ResourceVersionContent {
+            version,
+            content: String::from_utf8_lossy(&data).into_owned(),
+        })
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn diff_resource_versions(
+        &mut self,
+        from_version_id: String,
+        to_version_id: String,
+    ) -> BackendResult<ResourceVersionDiff> {
+        let (from, resource, from_data) = self.read_resource_version(&from_version_id)?;
+        let (to, _, to_data) = self.read_resource_version(&to_version_id)?;
+        if from.resource_id != to.resource_id {
+            return Err(BackendError::GenericError(
+                "versions of different resources can't be compared".to_owned(),
+            ));
+        }
+
+        let lines = text_diff(
+            &version_text(&resource.resource_type, &from_data),
+            &version_text(&resource.resource_type, &to_data),
+        );
+        Ok(ResourceVersionDiff {
+            from_version_id,
+            to_version_id,
+            insertions: count_lines(&lines, DiffLineKind::Insert),
+            deletions: count_lines(&lines, DiffLineKind::Delete),
+            lines,
+        })
+    }
+
+    // the restored content becomes the newest version, so restoring can be undone like any
+    // other change
+    #[instrument(level = "trace", skip(self))]
+    pub fn restore_resource_version(
+        &mut self,
+        version_id: String,
+    ) -> BackendResult<ResourceVersion> {
+        let (version, resource, data) = self.read_resource_version(&version_id)?;
+        std::fs::write(&resource.resource_path, &data)?;
+
+        let mut tx = self.db.begin()?;
+        Database::update_resource_tx(
+            &mut tx,
+            &Resource {
+                updated_at: current_time(),
+                ..resource.clone()
+            },
+        )?;
+        tx.commit()?;
+        self.upsert_resource_hash(resource.id.clone(), content_hash(&data))?;
+        self.post_processing_job(resource.id.clone(), ProcessingPriority::UserInitiated)?;
+
+        Ok(self
+            .db
+            .get_latest_resource_version(&resource.id)?
+            .unwrap_or(version))
+    }
+}
+
+fn count_lines(lines: &[DiffLine], kind: DiffLineKind) -> usize {
+    lines.iter().filter(|line| line.kind == kind).count()
+}
+
+// the snapshots of removed resources, their rows are removed with the resource
+pub(super) fn remove_resource_snapshots(resources_path: &str, resource_id: &str) {
+    let dir = versions_dir(resources_path).join(resource_id);
+    match std::fs::remove_dir_all(&dir) {
+        Ok(_) => {}
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("failed to remove version snapshots {dir:?}: {e}"),
+    }
+}
+
+// a snapshot that can't be removed only takes up space
+fn remove_snapshot_file(path: &str) {
+    match std::fs::remove_file(path) {
+        Ok(_) => {}
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("failed to remove version snapshot {path}: {e}"),
+    }
+}
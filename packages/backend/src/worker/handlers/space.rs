@@ -3,7 +3,7 @@ use crate::{
     store::{
         db::Database,
         models::{
-            current_time, random_uuid, SearchResultSpaceItem, Space, SpaceEntry,
+            current_time, random_uuid, Page, PageRequest, SearchResultSpaceItem, Space, SpaceEntry,
             SpaceEntryExtended, SpaceEntryType, SpaceExtended, SubSpaceEntry,
         },
     },
@@ -123,6 +123,17 @@ impl Worker {
             .list_space_entries(space_id, sort_by, order_by, limit)
     }
 
+    pub fn get_space_entries_page(
+        &self,
+        space_id: &str,
+        sort_by: Option<&str>,
+        order_by: Option<&str>,
+        page: &PageRequest,
+    ) -> BackendResult<Page<SpaceEntryExtended>> {
+        self.db
+            .list_space_entries_page(space_id, sort_by, order_by, page)
+    }
+
     pub fn delete_space_entries(
         &mut self,
         entries: Vec<DeleteSpaceEntryInput>,
@@ -218,10 +229,28 @@ pub fn handle_space_message(
                 worker.get_space_entries(&space_id, sort_by.as_deref(), order_by.as_deref(), limit);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
+        SpaceMessage::GetSpaceEntriesPage {
+            space_id,
+            sort_by,
+            order_by,
+            page,
+        } => {
+            let result = worker.get_space_entries_page(
+                &space_id,
+                sort_by.as_deref(),
+                order_by.as_deref(),
+                &page,
+            );
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
         SpaceMessage::DeleteSpaceEntries(entries) => {
             let result = worker.delete_space_entries(entries);
             send_worker_response(&mut worker.channel, oneshot, result);
         }
+        SpaceMessage::ExportMarkdownVault { space_id, path } => {
+            let result = worker.export_space_markdown_vault(space_id, path);
+            send_worker_response(&mut worker.channel, oneshot, result);
+        }
         SpaceMessage::MoveSpace {
             space_id,
             new_parent_space_id,
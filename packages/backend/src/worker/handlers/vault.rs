@@ -0,0 +1,309 @@
+use super::mail::{imported_space_name, imported_tag};
+use crate::{
+    api::message::SpaceEntryInput,
+    store::models::{
+        ExtractedResourceLink, ProcessingPriority, ResourceFrontmatter, ResourceLinkKind,
+        ResourceMetadata, SpaceEntryType, VaultExportResult, VaultImportResult,
+    },
+    utils::content_hash,
+    worker::{
+        extractors::frontmatter::{
+            frontmatter_tags, resource_frontmatter, split_frontmatter, with_frontmatter,
+        },
+        vault::{
+            html_to_markdown, is_markdown_path, markdown_to_html, relativize_resource_links,
+            rewrite_vault_links, scan_vault, unique_file_name, LinkResolver, NOTE_RESOURCE_TYPE,
+        },
+        Worker,
+    },
+    BackendError, BackendResult,
+};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::instrument;
+
+// a note waiting for its links to be resolved
+struct ImportedNote {
+    path: PathBuf,
+    resource_id: String,
+    resource_path: String,
+    body: String,
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn folder_name(root: &Path, folder: &Path) -> String {
+    folder
+        .file_name()
+        .or_else(|| root.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Vault".to_owned())
+}
+
+fn space_folder_name(space_name: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(space_name)
+        .ok()
+        .and_then(|data| data["folderName"].as_str().map(|name| name.to_owned()))
+        .unwrap_or_else(|| space_name.to_owned())
+}
+
+pub(super) fn imported_metadata(name: String, source_uri: String) -> ResourceMetadata {
+    ResourceMetadata {
+        id: String::new(),
+        resource_id: String::new(),
+        name,
+        source_uri,
+        alt: String::new(),
+        user_context: String::new(),
+        byline: None,
+        published_at: None,
+        lead_image: None,
+        outline: None,
+    }
+}
+
+impl Worker {
+    // notes are created for markdown files and resources for everything else, folders become
+    // nested spaces, links between files become links between the resources
+    #[instrument(level = "trace", skip(self))]
+    pub fn import_markdown_vault(&mut self, path: String) -> BackendResult<VaultImportResult> {
+        let root = PathBuf::from(&path);
+        let tree = scan_vault(&root)?;
+        std::fs::create_dir_all(&self.resources_path)?;
+        let mut result = VaultImportResult::default();
+
+        let mut folder_spaces: HashMap<PathBuf, String> = HashMap::new();
+        for folder in &tree.folders {
+            let space = self.create_space(&imported_space_name(&folder_name(&root, folder)))?;
+            if let Some(parent_id) = folder.parent().and_then(|parent| folder_spaces.get(parent)) {
+                let entry = SpaceEntryInput {
+                    entry_type: SpaceEntryType::Space,
+                    entry_id: space.id.clone(),
+                    manually_added: 1,
+                };
+                self.create_space_entries(parent_id.clone(), vec![entry])?;
+            }
+            folder_spaces.insert(folder.clone(), space.id.clone());
+            result.space_ids.push(space.id);
+        }
+        result.root_space_id = folder_spaces.get(Path::new("")).cloned();
+
+        let mut resolver = LinkResolver::default();
+        let mut notes: Vec<ImportedNote> = vec![];
+        let mut space_entries: BTreeMap<String, Vec<SpaceEntryInput>> = BTreeMap::new();
+        for file in &tree.files {
+            let absolute_path = root.join(file);
+            let source_uri = format!("file://{}", absolute_path.to_string_lossy());
+            let resource_id = if is_markdown_path(file) {
+                let content = String::from_utf8_lossy(&std::fs::read(&absolute_path)?).into_owned();
+                let (frontmatter, body) = match split_frontmatter(&content) {
+                    Some((yaml, body)) => (
+                        serde_yaml::from_str(yaml)
+                            .map(|value| resource_frontmatter(&value))
+                            .unwrap_or_default(),
+                        body.to_owned(),
+                    ),
+                    None => (ResourceFrontmatter::default(), content.clone()),
+                };
+
+                let mut metadata = imported_metadata(
+                    frontmatter.title.clone().unwrap_or_else(|| file_stem(file)),
+                    frontmatter.source_uri.clone().unwrap_or(source_uri),
+                );
+                metadata.byline = frontmatter.byline.clone();
+                metadata.published_at = frontmatter.published_at.clone();
+                let mut tags = frontmatter_tags(&frontmatter, "");
+                tags.push(imported_tag());
+
+                let resource = self.create_resource(
+                    NOTE_RESOURCE_TYPE.to_owned(),
+                    Some(tags),
+                    Some(metadata),
+                )?;
+                notes.push(ImportedNote {
+                    path: file.clone(),
+                    resource_id: resource.resource.id.clone(),
+                    resource_path: resource.resource.resource_path,
+                    body,
+                });
+                result.resource_ids.push(resource.resource.id.clone());
+                resource.resource.id
+            } else {
+                let data = std::fs::read(&absolute_path)?;
+                let resource_type = mime_guess::from_path(file)
+                    .first_or_octet_stream()
+                    .to_string();
+                let file_name = file
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let resource_id = self.create_imported_resource(
+                    &resource_type,
+                    vec![imported_tag()],
+                    imported_metadata(file_name, source_uri),
+                    &data,
+                )?;
+                result.attachment_ids.push(resource_id.clone());
+                resource_id
+            };
+
+            resolver.insert(file, &resource_id);
+            let folder = file.parent().unwrap_or_else(|| Path::new(""));
+            if let Some(space_id) = folder_spaces.get(folder) {
+                space_entries
+                    .entry(space_id.clone())
+                    .or_default()
+                    .push(SpaceEntryInput {
+                        entry_type: SpaceEntryType::Resource,
+                        entry_id: resource_id,
+                        manually_added: 1,
+                    });
+            }
+        }
+        for (space_id, entries) in space_entries {
+            self.create_space_entries(space_id, entries)?;
+        }
+
+        // links can point at any file of the vault, so the notes are written once all exist
+        let note_ids: HashSet<String> = result.resource_ids.iter().cloned().collect();
+        let is_note = |resource_id: &str| note_ids.contains(resource_id);
+        for note in notes {
+            let (markdown, linked_ids) =
+                rewrite_vault_links(&note.body, &note.path, &resolver, &is_note);
+            let html = markdown_to_html(&markdown);
+            std::fs::write(&note.resource_path, &html)?;
+            self.upsert_resource_hash(note.resource_id.clone(), content_hash(html.as_bytes()))?;
+
+            let links = linked_ids
+                .into_iter()
+                .map(|target_id| ExtractedResourceLink {
+                    target_id,
+                    kind: ResourceLinkKind::LinksTo,
+                    context: None,
+                })
+                .collect();
+            self.set_extracted_resource_links(note.resource_id, links)?;
+        }
+
+        let all_ids: Vec<String> = result
+            .resource_ids
+            .iter()
+            .chain(result.attachment_ids.iter())
+            .cloned()
+            .collect();
+        if !all_ids.is_empty() {
+            let progress =
+                self.batch_post_processing_job(all_ids, ProcessingPriority::BackgroundImport)?;
+            result.batch_id = Some(progress.batch_id);
+        }
+        Ok(result)
+    }
+
+    // the space becomes the vault root and its nested spaces its folders, notes are written
+    // as markdown with frontmatter and all other resources are copied as they are
+    #[instrument(level = "trace", skip(self))]
+    pub fn export_space_markdown_vault(
+        &mut self,
+        space_id: String,
+        path: String,
+    ) -> BackendResult<VaultExportResult> {
+        let root = PathBuf::from(&path);
+
+        let mut folders: Vec<(String, PathBuf)> = vec![];
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut pending = vec![(space_id, PathBuf::new())];
+        while let Some((space_id, folder)) = pending.pop() {
+            if !visited.insert(space_id.clone()) {
+                continue;
+            }
+            let space = self
+                .db
+                .get_space(&space_id)?
+                .ok_or_else(|| BackendError::GenericError("space not found".to_owned()))?;
+            let mut taken = HashSet::new();
+            for child_id in space.child_space_ids {
+                let name = match self.db.get_space(&child_id)? {
+                    Some(child) => space_folder_name(&child.name),
+                    None => continue,
+                };
+                let child_folder = folder.join(unique_file_name(&name, "", &mut taken));
+                pending.push((child_id, child_folder));
+            }
+            folders.push((space_id, folder));
+        }
+
+        // a resource in several spaces is only written to the first folder it is found in
+        let mut exported: HashMap<String, PathBuf> = HashMap::new();
+        let mut resources = vec![];
+        for (space_id, folder) in &folders {
+            let mut taken = HashSet::new();
+            let entries = self.db.list_space_entries(space_id, None, None, None)?;
+            for entry in entries {
+                if entry.entry_type != SpaceEntryType::Resource
+                    || exported.contains_key(&entry.entry_id)
+                {
+                    continue;
+                }
+                let resource = match self.db.get_resource(&entry.entry_id)? {
+                    Some(resource) if resource.deleted == 0 => resource,
+                    _ => continue,
+                };
+                let metadata = self.db.get_resource_metadata_by_resource_id(&resource.id)?;
+                let name = metadata
+                    .as_ref()
+                    .map(|metadata| metadata.name.clone())
+                    .unwrap_or_default();
+                let is_note = resource.resource_type.starts_with(NOTE_RESOURCE_TYPE);
+                let extension = if is_note {
+                    "md".to_owned()
+                } else {
+                    Path::new(&resource.resource_path)
+                        .extension()
+                        .map(|extension| extension.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                };
+                // attachment names usually already end in their extension
+                let stem = match extension.as_str() {
+                    "" => name,
+                    extension => name
+                        .strip_suffix(&format!(".{extension}"))
+                        .map(|stem| stem.to_owned())
+                        .unwrap_or(name),
+                };
+                let file = folder.join(unique_file_name(&stem, &extension, &mut taken));
+                exported.insert(resource.id.clone(), file.clone());
+                resources.push((resource, metadata, is_note, file));
+            }
+        }
+
+        for (_, folder) in &folders {
+            std::fs::create_dir_all(root.join(folder))?;
+        }
+        let mut result = VaultExportResult::default();
+        for (resource, metadata, is_note, file) in resources {
+            let target = root.join(&file);
+            if is_note {
+                let html = std::fs::read_to_string(&resource.resource_path)?;
+                let markdown =
+                    relativize_resource_links(&html_to_markdown(&html), &file, &exported);
+                let tags = self.db.list_resource_tags(&resource.id)?;
+                let markdown = with_frontmatter(&markdown, metadata.as_ref(), &tags)?;
+                std::fs::write(&target, markdown)?;
+                result
+                    .note_paths
+                    .push(target.to_string_lossy().into_owned());
+            } else {
+                std::fs::copy(&resource.resource_path, &target)?;
+                result
+                    .attachment_paths
+                    .push(target.to_string_lossy().into_owned());
+            }
+        }
+        Ok(result)
+    }
+}
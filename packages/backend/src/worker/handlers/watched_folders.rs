@@ -0,0 +1,270 @@
+use super::{
+    mail::{imported_space_name, imported_tag},
+    vault::imported_metadata,
+};
+use crate::{
+    api::message::SpaceEntryInput,
+    store::{
+        db::Database,
+        models::{
+            current_time, random_uuid, ProcessingPriority, Resource, ResourceTag, SpaceEntryType,
+            WatchedFile, WatchedFolder, WatchedFolderSyncResult,
+        },
+    },
+    utils::content_hash,
+    worker::{watcher::changed_files, Worker},
+    BackendError, BackendResult,
+};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::instrument;
+
+enum FileChange {
+    Created(String),
+    Updated(String),
+    Deleted(String),
+    Unchanged,
+}
+
+impl Worker {
+    // the files of the folder are synced into the space, a new space named after the folder
+    // is created without one
+    #[instrument(level = "trace", skip(self))]
+    pub fn add_watched_folder(
+        &mut self,
+        path: String,
+        space_id: Option<String>,
+    ) -> BackendResult<WatchedFolder> {
+        let root = std::fs::canonicalize(&path)?;
+        if !root.is_dir() {
+            return Err(BackendError::GenericError(format!(
+                "{path} is not a folder"
+            )));
+        }
+        let path = root.to_string_lossy().into_owned();
+        if let Some(folder) = self.db.get_watched_folder_by_path(&path)? {
+            return Ok(folder);
+        }
+
+        let space_id = match space_id {
+            Some(space_id) => {
+                self.db
+                    .get_space(&space_id)?
+                    .ok_or_else(|| BackendError::GenericError("space not found".to_owned()))?;
+                space_id
+            }
+            None => {
+                let name = root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.clone());
+                self.create_space(&imported_space_name(&name))?.id
+            }
+        };
+
+        let folder = WatchedFolder {
+            id: random_uuid(),
+            path,
+            space_id,
+            created_at: current_time(),
+        };
+        self.db.create_watched_folder(&folder)?;
+        self.sync_watched_folder(folder.id.clone(), vec![String::new()])?;
+        if let Some(watcher) = &self.folder_watcher {
+            watcher.watch(&folder.id, &folder.path);
+        }
+        Ok(folder)
+    }
+
+    // the resources of the files stay where they are
+    #[instrument(level = "trace", skip(self))]
+    pub fn remove_watched_folder(&mut self, folder_id: String) -> BackendResult<()> {
+        if let Some(watcher) = &self.folder_watcher {
+            watcher.unwatch(&folder_id);
+        }
+        let mut tx = self.db.begin()?;
+        Database::remove_watched_folder_tx(&mut tx, &folder_id)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    pub fn list_watched_folders(&mut self) -> BackendResult<Vec<WatchedFolder>> {
+        self.db.list_watched_folders()
+    }
+
+    // `paths` are relative to the folder, the empty path syncs the whole folder
+    #[instrument(level = "trace", skip(self))]
+    pub fn sync_watched_folder(
+        &mut self,
+        folder_id: String,
+        paths: Vec<String>,
+    ) -> BackendResult<WatchedFolderSyncResult> {
+        let folder = self
+            .db
+            .get_watched_folder(&folder_id)?
+            .ok_or_else(|| BackendError::GenericError("watched folder not found".to_owned()))?;
+        let root = PathBuf::from(&folder.path);
+        let mut result = WatchedFolderSyncResult::default();
+        // an unmounted drive is not the same as all files being deleted
+        if !root.is_dir() {
+            tracing::warn!("watched folder {} is not available", folder.path);
+            return Ok(result);
+        }
+
+        let tracked: HashMap<String, String> = self
+            .db
+            .list_watched_files(&folder.id)?
+            .into_iter()
+            .map(|file| (file.path, file.resource_id))
+            .collect();
+        let tracked_paths = tracked.keys().cloned().collect::<Vec<_>>();
+        std::fs::create_dir_all(&self.resources_path)?;
+
+        let mut space_entries = vec![];
+        for file in changed_files(&root, &paths, &tracked_paths)? {
+            // a file that can't be read right now is picked up by its next change
+            match self.sync_watched_file(&folder, &root, &file, tracked.get(&file)) {
+                Ok(FileChange::Created(resource_id)) => {
+                    space_entries.push(SpaceEntryInput {
+                        entry_type: SpaceEntryType::Resource,
+                        entry_id: resource_id.clone(),
+                        manually_added: 1,
+                    });
+                    result.created_ids.push(resource_id);
+                }
+                Ok(FileChange::Updated(resource_id)) => result.updated_ids.push(resource_id),
+                Ok(FileChange::Deleted(resource_id)) => result.deleted_ids.push(resource_id),
+                Ok(FileChange::Unchanged) => {}
+                Err(e) => tracing::warn!("failed to sync {file} of {}: {e}", folder.path),
+            }
+        }
+        if !space_entries.is_empty() {
+            self.create_space_entries(folder.space_id.clone(), space_entries)?;
+        }
+
+        let changed_ids: Vec<String> = result
+            .created_ids
+            .iter()
+            .chain(result.updated_ids.iter())
+            .cloned()
+            .collect();
+        if !changed_ids.is_empty() {
+            let progress =
+                self.batch_post_processing_job(changed_ids, ProcessingPriority::BackgroundImport)?;
+            result.batch_id = Some(progress.batch_id);
+        }
+        Ok(result)
+    }
+
+    fn sync_watched_file(
+        &mut self,
+        folder: &WatchedFolder,
+        root: &Path,
+        file: &str,
+        resource_id: Option<&String>,
+    ) -> BackendResult<FileChange> {
+        let absolute_path = root.join(file);
+        let resource = match resource_id {
+            Some(resource_id) => self.db.get_resource(resource_id)?,
+            None => None,
+        };
+
+        if !absolute_path.is_file() {
+            return match resource {
+                Some(resource) if resource.deleted == 0 => {
+                    let mut tx = self.db.begin()?;
+                    Database::update_resource_deleted_tx(&mut tx, &resource.id, 1)?;
+                    Database::update_resource_tag_by_name_tx(
+                        &mut tx,
+                        &ResourceTag::new_deleted(&resource.id, true),
+                    )?;
+                    tx.commit()?;
+                    Ok(FileChange::Deleted(resource.id))
+                }
+                _ => Ok(FileChange::Unchanged),
+            };
+        }
+
+        let data = std::fs::read(&absolute_path)?;
+        let hash = content_hash(&data);
+        let resource = match resource {
+            Some(resource) => resource,
+            // new files and files whose resource was removed for good
+            None => {
+                let resource_type = mime_guess::from_path(&absolute_path)
+                    .first_or_octet_stream()
+                    .to_string();
+                let file_name = absolute_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let source_uri = format!("file://{}", absolute_path.to_string_lossy());
+                let resource_id = self.create_imported_resource(
+                    &resource_type,
+                    vec![imported_tag()],
+                    imported_metadata(file_name, source_uri),
+                    &data,
+                )?;
+
+                let mut tx = self.db.begin()?;
+                let watched_file = WatchedFile {
+                    folder_id: folder.id.clone(),
+                    path: file.to_owned(),
+                    resource_id: resource_id.clone(),
+                };
+                Database::upsert_watched_file_tx(&mut tx, &watched_file)?;
+                tx.commit()?;
+                return Ok(FileChange::Created(resource_id));
+            }
+        };
+
+        let restored = resource.deleted != 0;
+        let changed = self.db.get_resource_hash(&resource.id)?.as_deref() != Some(hash.as_str());
+        if !restored && !changed {
+            return Ok(FileChange::Unchanged);
+        }
+        if changed {
+            std::fs::write(&resource.resource_path, &data)?;
+        }
+
+        let mut tx = self.db.begin()?;
+        Database::update_resource_tx(
+            &mut tx,
+            &Resource {
+                updated_at: current_time(),
+                deleted: 0,
+                ..resource.clone()
+            },
+        )?;
+        if restored {
+            Database::update_resource_tag_by_name_tx(
+                &mut tx,
+                &ResourceTag::new_deleted(&resource.id, false),
+            )?;
+        }
+        Database::upsert_resource_hash_tx(&mut tx, &resource.id, &hash)?;
+        tx.commit()?;
+        if changed {
+            if let Err(e) = self.snapshot_resource_version(&resource.id, &hash) {
+                tracing::warn!("failed to snapshot version of {}: {e}", resource.id);
+            }
+        }
+        Ok(FileChange::Updated(resource.id))
+    }
+
+    // catches up on the changes made while the app wasn't running, then keeps watching
+    #[instrument(level = "trace", skip(self))]
+    pub fn resume_watched_folders(&mut self) -> BackendResult<()> {
+        for folder in self.db.list_watched_folders()? {
+            if let Err(e) = self.sync_watched_folder(folder.id.clone(), vec![String::new()]) {
+                tracing::error!("failed to sync watched folder {}: {e}", folder.path);
+            }
+            if let Some(watcher) = &self.folder_watcher {
+                watcher.watch(&folder.id, &folder.path);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
+pub mod duplicates;
+pub mod extractors;
 pub mod handlers;
 pub mod processor;
+pub mod queue;
+pub mod search_query;
+pub mod thumbnails;
 pub mod tunnel;
+pub mod vault;
+pub mod versions;
+pub mod watcher;
 
 const _MODULE_PREFIX: &str = "backend";
 
@@ -12,8 +20,11 @@ use crate::{
     store::{db::Database, kv::KeyValueStore, models::current_time},
     BackendError, BackendResult,
 };
+use extractors::ExtractorRegistry;
 use handlers::*;
+use queue::ProcessingQueueSender;
 use tunnel::SurfBackendHealth;
+use watcher::FolderWatcher;
 
 use chrono::{DateTime, Utc};
 use crossbeam_channel as crossbeam;
@@ -78,7 +89,7 @@ impl AIConfig {
 }
 
 pub struct ChannelConfig {
-    pub tqueue_tx: crossbeam::Sender<ProcessorMessage>,
+    pub tqueue_tx: ProcessingQueueSender<ProcessorMessage>,
     pub aiqueue_tx: crossbeam::Sender<AIMessage>,
     pub channel: Channel,
     pub event_bus_rx: Arc<Root<JsFunction>>,
@@ -92,6 +103,8 @@ pub struct WorkerConfig {
     pub run_migrations: bool,
     pub surf_backend_health: SurfBackendHealth,
     pub claude_agent_runner: ClaudeAgentRunnerHandle,
+    pub extractor_registry: ExtractorRegistry,
+    pub folder_watcher: Option<FolderWatcher>,
 }
 
 pub struct Worker {
@@ -100,7 +113,7 @@ pub struct Worker {
     pub ai: AI,
     pub channel: Channel,
     pub event_bus_rx: Arc<Root<JsFunction>>,
-    pub tqueue_tx: crossbeam::Sender<ProcessorMessage>,
+    pub tqueue_tx: ProcessingQueueSender<ProcessorMessage>,
     pub aiqueue_tx: crossbeam::Sender<AIMessage>,
     pub app_path: String,
     pub backend_root_path: String,
@@ -109,6 +122,8 @@ pub struct Worker {
     pub language_setting: String,
     pub async_runtime: tokio::runtime::Runtime,
     pub surf_backend_health: SurfBackendHealth,
+    pub extractor_registry: ExtractorRegistry,
+    pub folder_watcher: Option<FolderWatcher>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -141,6 +156,8 @@ impl Worker {
             language_setting: config.language_setting,
             async_runtime: tokio::runtime::Runtime::new()?,
             surf_backend_health: config.surf_backend_health,
+            extractor_registry: config.extractor_registry,
+            folder_watcher: config.folder_watcher,
             created_at: current_time(),
         })
     }
@@ -0,0 +1,334 @@
+use crate::store::models::ProcessingPriority;
+
+use crossbeam_channel as crossbeam;
+use serde::Deserialize;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Weak,
+};
+use std::time::Duration;
+
+const LANE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LaneConfig {
+    // number of jobs that can wait in the lane before senders get pushed back
+    pub capacity: usize,
+    // number of jobs from the lane that can be processed at the same time
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProcessingQueueConfig {
+    pub user_initiated: LaneConfig,
+    pub background_import: LaneConfig,
+    pub reindex: LaneConfig,
+}
+
+impl Default for ProcessingQueueConfig {
+    fn default() -> Self {
+        Self {
+            user_initiated: LaneConfig {
+                capacity: 256,
+                concurrency: 12,
+            },
+            background_import: LaneConfig {
+                capacity: 128,
+                concurrency: 6,
+            },
+            reindex: LaneConfig {
+                capacity: 64,
+                concurrency: 2,
+            },
+        }
+    }
+}
+
+impl ProcessingQueueConfig {
+    fn lane(&self, priority: ProcessingPriority) -> LaneConfig {
+        match priority {
+            ProcessingPriority::UserInitiated => self.user_initiated,
+            ProcessingPriority::BackgroundImport => self.background_import,
+            ProcessingPriority::Reindex => self.reindex,
+        }
+    }
+}
+
+fn lane_index(priority: ProcessingPriority) -> usize {
+    ProcessingPriority::ALL
+        .iter()
+        .position(|p| *p == priority)
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+pub enum EnqueueError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+pub struct ProcessingQueueSender<T> {
+    lanes: Vec<crossbeam::Sender<T>>,
+    // shared by all clones, the receivers watch it to know when the senders are gone
+    alive: Arc<()>,
+}
+
+impl<T> Clone for ProcessingQueueSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lanes: self.lanes.clone(),
+            alive: Arc::clone(&self.alive),
+        }
+    }
+}
+
+impl<T> ProcessingQueueSender<T> {
+    // never blocks, a full lane hands the message back so the caller can defer it
+    pub fn try_send(
+        &self,
+        priority: ProcessingPriority,
+        message: T,
+    ) -> Result<(), EnqueueError<T>> {
+        self.lanes[lane_index(priority)]
+            .try_send(message)
+            .map_err(|err| match err {
+                crossbeam::TrySendError::Full(message) => EnqueueError::Full(message),
+                crossbeam::TrySendError::Disconnected(message) => {
+                    EnqueueError::Disconnected(message)
+                }
+            })
+    }
+}
+
+struct Lane<T> {
+    rx: crossbeam::Receiver<T>,
+    in_flight: Arc<AtomicUsize>,
+    concurrency: usize,
+}
+
+impl<T> Lane<T> {
+    fn has_free_slot(&self) -> bool {
+        self.in_flight.load(Ordering::Acquire) < self.concurrency
+    }
+
+    fn try_acquire(&self) -> Option<LaneSlot> {
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.concurrency).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| LaneSlot {
+                in_flight: Arc::clone(&self.in_flight),
+            })
+    }
+}
+
+impl<T> Clone for Lane<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rx: self.rx.clone(),
+            in_flight: Arc::clone(&self.in_flight),
+            concurrency: self.concurrency,
+        }
+    }
+}
+
+// held while a job is being processed, frees the slot in its lane when dropped
+pub struct LaneSlot {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for LaneSlot {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pub struct ProcessingQueueReceiver<T> {
+    lanes: Vec<Lane<T>>,
+    senders: Weak<()>,
+}
+
+impl<T> Clone for ProcessingQueueReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lanes: self.lanes.clone(),
+            senders: Weak::clone(&self.senders),
+        }
+    }
+}
+
+impl<T> ProcessingQueueReceiver<T> {
+    // blocks until a lane with a free slot has a message, higher priority lanes are drained first,
+    // returns `None` once every sender is gone and there is nothing left this receiver can take
+    pub fn recv(&self) -> Option<(T, LaneSlot)> {
+        loop {
+            // checked before the lanes are, nothing new can arrive after the senders are gone
+            let disconnected = self.senders.strong_count() == 0;
+            for lane in self.lanes.iter() {
+                let slot = match lane.try_acquire() {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+                if let Ok(message) = lane.rx.try_recv() {
+                    return Some((message, slot));
+                }
+            }
+            // what is left in saturated lanes is taken by the receivers holding their slots
+            if disconnected {
+                return None;
+            }
+
+            // only wait for readiness here, the message is taken on the next pass so that
+            // the priority order is kept
+            let mut select = crossbeam::Select::new();
+            let mut watched = 0;
+            for lane in self.lanes.iter().filter(|lane| lane.has_free_slot()) {
+                select.recv(&lane.rx);
+                watched += 1;
+            }
+            if watched == 0 {
+                std::thread::sleep(LANE_POLL_INTERVAL);
+                continue;
+            }
+            let _ = select.ready_timeout(LANE_POLL_INTERVAL);
+        }
+    }
+}
+
+pub fn processing_queue<T>(
+    config: &ProcessingQueueConfig,
+) -> (ProcessingQueueSender<T>, ProcessingQueueReceiver<T>) {
+    let (senders, lanes) = ProcessingPriority::ALL
+        .iter()
+        .map(|priority| {
+            let lane_config = config.lane(*priority);
+            let (tx, rx) = crossbeam::bounded(lane_config.capacity.max(1));
+            let lane = Lane {
+                rx,
+                in_flight: Arc::new(AtomicUsize::new(0)),
+                concurrency: lane_config.concurrency.max(1),
+            };
+            (tx, lane)
+        })
+        .unzip();
+
+    let alive = Arc::new(());
+    let receiver = ProcessingQueueReceiver {
+        lanes,
+        senders: Arc::downgrade(&alive),
+    };
+    (
+        ProcessingQueueSender {
+            lanes: senders,
+            alive,
+        },
+        receiver,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> ProcessingQueueConfig {
+        ProcessingQueueConfig {
+            user_initiated: LaneConfig {
+                capacity: 4,
+                concurrency: 2,
+            },
+            background_import: LaneConfig {
+                capacity: 2,
+                concurrency: 1,
+            },
+            reindex: LaneConfig {
+                capacity: 2,
+                concurrency: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_higher_priority_lanes_are_drained_first() {
+        let (tx, rx) = processing_queue(&test_config());
+        tx.try_send(ProcessingPriority::Reindex, "reindex").unwrap();
+        tx.try_send(ProcessingPriority::BackgroundImport, "import")
+            .unwrap();
+        tx.try_send(ProcessingPriority::UserInitiated, "user")
+            .unwrap();
+
+        let mut received = vec![];
+        for _ in 0..3 {
+            let (message, _slot) = rx.recv().unwrap();
+            received.push(message);
+        }
+        assert_eq!(received, vec!["user", "import", "reindex"]);
+    }
+
+    #[test]
+    fn test_full_lane_pushes_back() {
+        let (tx, _rx) = processing_queue(&test_config());
+        tx.try_send(ProcessingPriority::BackgroundImport, 1)
+            .unwrap();
+        tx.try_send(ProcessingPriority::BackgroundImport, 2)
+            .unwrap();
+
+        match tx.try_send(ProcessingPriority::BackgroundImport, 3) {
+            Err(EnqueueError::Full(message)) => assert_eq!(message, 3),
+            other => panic!("expected a full lane, got {:?}", other),
+        }
+        assert!(tx.try_send(ProcessingPriority::UserInitiated, 4).is_ok());
+    }
+
+    #[test]
+    fn test_lane_concurrency_is_bounded() {
+        let (tx, rx) = processing_queue(&test_config());
+        tx.try_send(ProcessingPriority::BackgroundImport, "import-1")
+            .unwrap();
+        tx.try_send(ProcessingPriority::BackgroundImport, "import-2")
+            .unwrap();
+
+        let (first, slot) = rx.recv().unwrap();
+        assert_eq!(first, "import-1");
+
+        // the lane only allows one job at a time, the second one waits for the slot
+        tx.try_send(ProcessingPriority::UserInitiated, "user")
+            .unwrap();
+        let (next, _user_slot) = rx.recv().unwrap();
+        assert_eq!(next, "user");
+
+        drop(slot);
+        let (second, _slot) = rx.recv().unwrap();
+        assert_eq!(second, "import-2");
+    }
+
+    #[test]
+    fn test_recv_returns_none_when_senders_are_gone() {
+        let (tx, rx) = processing_queue::<u32>(&test_config());
+        drop(tx);
+        assert!(rx.recv().is_none());
+    }
+
+    #[test]
+    fn test_recv_returns_none_when_senders_are_gone_with_a_saturated_lane() {
+        let (tx, rx) = processing_queue(&test_config());
+        tx.try_send(ProcessingPriority::BackgroundImport, "import-1")
+            .unwrap();
+        tx.try_send(ProcessingPriority::BackgroundImport, "import-2")
+            .unwrap();
+        let (_, slot) = rx.recv().unwrap();
+        drop(tx);
+
+        // the lane is at full concurrency, another receiver must not wait for it forever
+        let other = rx.clone();
+        let handle = std::thread::spawn(move || other.recv().map(|(message, _)| message));
+        assert_eq!(handle.join().unwrap(), None);
+
+        // the receiver holding the slot still gets what is left in the lane
+        drop(slot);
+        let (message, _slot) = rx.recv().unwrap();
+        assert_eq!(message, "import-2");
+        assert!(rx.recv().is_none());
+    }
+}
@@ -0,0 +1,452 @@
+use crate::{
+    store::{
+        models::{
+            ResourceFilter, ResourceTagFilter, ResourceTagFilterOp, SearchQueryError,
+            SearchQueryErrorKind,
+        },
+        search::{search_indexes_for_terms, SearchIndex},
+    },
+    worker::extractors::frontmatter::HASHTAG_TAG,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+const CANONICAL_URL_TAG: &str = "canonicalUrl";
+
+// short names for `type:`, anything with a `/` is taken as the start of a mime type
+const TYPE_ALIASES: &[(&str, &str, bool)] = &[
+    ("note", "application/vnd.space.document.space-note", false),
+    ("document", "application/vnd.space.document", true),
+    ("link", "application/vnd.space.link", true),
+    ("article", "application/vnd.space.article", true),
+    ("post", "application/vnd.space.post", true),
+    ("annotation", "application/vnd.space.annotation", true),
+    ("chat", "application/vnd.space.chat-thread", true),
+    ("pdf", "application/pdf", false),
+    ("email", "message/rfc822", false),
+    ("mail", "message/rfc822", false),
+    ("image", "image/", true),
+    ("video", "video/", true),
+    ("audio", "audio/", true),
+    ("text", "text/", true),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuerySpace {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+// the parts of a query like `rust "error handling" site:docs.rs -draft type:article`
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    // words and quoted phrases that have to be in the resource
+    pub terms: Vec<String>,
+    // words and phrases after a `-` that must not be
+    pub excluded: Vec<String>,
+    pub filters: Vec<ResourceFilter>,
+    // the name of the space from `space:`, resolved by the caller
+    pub space: Option<SearchQuerySpace>,
+    pub errors: Vec<SearchQueryError>,
+}
+
+struct Token {
+    negated: bool,
+    field: Option<String>,
+    value: String,
+    quoted: bool,
+    // the token as typed, without the `-`
+    raw: String,
+    start: usize,
+    end: usize,
+}
+
+fn query_error(
+    kind: SearchQueryErrorKind,
+    message: String,
+    start: usize,
+    end: usize,
+) -> SearchQueryError {
+    SearchQueryError {
+        kind,
+        message,
+        start,
+        end,
+    }
+}
+
+// reads up to the closing quote, the opening one is already consumed
+fn read_quoted(chars: &[char], pos: &mut usize) -> (String, bool) {
+    let mut value = String::new();
+    while *pos < chars.len() {
+        let c = chars[*pos];
+        *pos += 1;
+        if c == '"' {
+            return (value, true);
+        }
+        value.push(c);
+    }
+    (value, false)
+}
+
+fn tokenize(query: &str, errors: &mut Vec<SearchQueryError>) -> Vec<Token> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < chars.len() {
+        if chars[pos].is_whitespace() {
+            pos += 1;
+            continue;
+        }
+        let start = pos;
+        // a lone `-` or one inside a word is just text
+        let negated = chars[pos] == '-'
+            && chars
+                .get(pos + 1)
+                .is_some_and(|c| !c.is_whitespace() && *c != '-');
+        if negated {
+            pos += 1;
+        }
+
+        let raw_start = pos;
+        let mut field = None;
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut terminated = true;
+        if chars[pos] == '"' {
+            pos += 1;
+            quoted = true;
+            (value, terminated) = read_quoted(&chars, &mut pos);
+        } else {
+            while pos < chars.len() && !chars[pos].is_whitespace() {
+                let c = chars[pos];
+                pos += 1;
+                if c == ':' && field.is_none() && !value.is_empty() {
+                    field = Some(std::mem::take(&mut value));
+                    if chars.get(pos) == Some(&'"') {
+                        pos += 1;
+                        quoted = true;
+                        (value, terminated) = read_quoted(&chars, &mut pos);
+                        break;
+                    }
+                    continue;
+                }
+                value.push(c);
+            }
+        }
+        if !terminated {
+            errors.push(query_error(
+                SearchQueryErrorKind::UnterminatedQuote,
+                "quote is not closed".to_owned(),
+                start,
+                pos,
+            ));
+        }
+        tokens.push(Token {
+            negated,
+            field,
+            value,
+            quoted,
+            raw: chars[raw_start..pos].iter().collect(),
+            start,
+            end: pos,
+        });
+    }
+    tokens
+}
+
+fn tag_filter(tag_name: &str, tag_value: &str, op: ResourceTagFilterOp) -> ResourceFilter {
+    ResourceFilter::Tag(ResourceTagFilter {
+        tag_name: tag_name.to_owned(),
+        tag_value: tag_value.to_owned(),
+        op,
+    })
+}
+
+// pages of the site and of its `www.` host, over http and https
+fn site_filter(site: &str) -> ResourceFilter {
+    let site = site.to_lowercase();
+    let site = site
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    let site = site.strip_prefix("www.").unwrap_or(site);
+    let mut filters = vec![];
+    for scheme in ["https", "http"] {
+        for host in [site.to_owned(), format!("www.{site}")] {
+            let url = format!("{scheme}://{host}");
+            filters.push(tag_filter(CANONICAL_URL_TAG, &url, ResourceTagFilterOp::Eq));
+            filters.push(tag_filter(
+                CANONICAL_URL_TAG,
+                &format!("{url}/"),
+                ResourceTagFilterOp::Prefix,
+            ));
+        }
+    }
+    ResourceFilter::Or { filters }
+}
+
+fn type_filter(value: &str) -> Option<ResourceFilter> {
+    let value = value.to_lowercase();
+    let (resource_type, prefix) = match TYPE_ALIASES.iter().find(|(alias, _, _)| *alias == value) {
+        Some((_, resource_type, prefix)) => (resource_type.to_string(), *prefix),
+        None if value.contains('/') => (value, true),
+        None => return None,
+    };
+    Some(ResourceFilter::ResourceType {
+        resource_type,
+        prefix,
+    })
+}
+
+// `tag:rust` is a hashtag, `tag:name=value` any other tag
+fn tag_value_filter(value: &str) -> ResourceFilter {
+    match value.split_once('=') {
+        Some((name, value)) => tag_filter(name, value, ResourceTagFilterOp::Eq),
+        None => tag_filter(
+            HASHTAG_TAG,
+            value.trim_start_matches('#'),
+            ResourceTagFilterOp::Eq,
+        ),
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+// fts5 strings are quoted with doubled quotes inside
+fn fts_string(text: &str) -> String {
+    format!(r#""{}""#, text.replace('"', r#""""#))
+}
+
+pub fn parse_search_query(query: &str) -> SearchQuery {
+    let mut result = SearchQuery::default();
+    let tokens = tokenize(query, &mut result.errors);
+    for token in tokens {
+        let field = token.field.as_deref().map(|field| field.to_lowercase());
+        let filter = match field.as_deref() {
+            Some(field @ ("site" | "type" | "tag" | "space" | "before" | "after"))
+                if token.value.trim().is_empty() =>
+            {
+                result.errors.push(query_error(
+                    SearchQueryErrorKind::EmptyValue,
+                    format!("`{field}:` needs a value"),
+                    token.start,
+                    token.end,
+                ));
+                continue;
+            }
+            Some("site") => site_filter(&token.value),
+            Some("type") => match type_filter(&token.value) {
+                Some(filter) => filter,
+                None => {
+                    result.errors.push(query_error(
+                        SearchQueryErrorKind::UnknownType,
+                        format!("unknown resource type `{}`", token.value),
+                        token.start,
+                        token.end,
+                    ));
+                    continue;
+                }
+            },
+            Some("tag") => tag_value_filter(&token.value),
+            Some("space") => {
+                if token.negated {
+                    result.errors.push(query_error(
+                        SearchQueryErrorKind::UnsupportedExclusion,
+                        "a space can't be excluded".to_owned(),
+                        token.start,
+                        token.end,
+                    ));
+                } else if result.space.is_some() {
+                    result.errors.push(query_error(
+                        SearchQueryErrorKind::MultipleSpaces,
+                        "only one space can be searched".to_owned(),
+                        token.start,
+                        token.end,
+                    ));
+                } else {
+                    result.space = Some(SearchQuerySpace {
+                        name: token.value.clone(),
+                        start: token.start,
+                        end: token.end,
+                    });
+                }
+                continue;
+            }
+            Some(field @ ("before" | "after")) => match parse_date(&token.value) {
+                Some(date) if field == "before" => ResourceFilter::CreatedAt {
+                    after: None,
+                    before: Some(date),
+                },
+                Some(date) => ResourceFilter::CreatedAt {
+                    after: Some(date),
+                    before: None,
+                },
+                None => {
+                    result.errors.push(query_error(
+                        SearchQueryErrorKind::InvalidDate,
+                        format!("`{}` is not a date like 2024-01-31", token.value),
+                        token.start,
+                        token.end,
+                    ));
+                    continue;
+                }
+            },
+            // not a field we know, like the `https:` of a link
+            _ => {
+                let text = if token.quoted && token.field.is_none() {
+                    token.value
+                } else {
+                    token.raw
+                };
+                if !text.chars().any(|c| c.is_alphanumeric()) {
+                    continue;
+                }
+                if token.negated {
+                    result.excluded.push(text);
+                } else {
+                    result.terms.push(text);
+                }
+                continue;
+            }
+        };
+        result.filters.push(if token.negated {
+            ResourceFilter::Not {
+                filter: Box::new(filter),
+            }
+        } else {
+            filter
+        });
+    }
+
+    if result.terms.is_empty() && !result.excluded.is_empty() {
+        result.errors.push(query_error(
+            SearchQueryErrorKind::OnlyExclusions,
+            "excluded words need words to search for".to_owned(),
+            0,
+            query.chars().count(),
+        ));
+    }
+    result
+}
+
+impl SearchQuery {
+    // the words and phrases searched for, for the searches that don't take operators
+    pub fn text(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    pub fn has_operators(&self) -> bool {
+        !self.excluded.is_empty() || !self.filters.is_empty() || self.space.is_some()
+    }
+
+    pub fn filter(&self) -> Option<ResourceFilter> {
+        match self.filters.len() {
+            0 => None,
+            _ => Some(ResourceFilter::And {
+                filters: self.filters.clone(),
+            }),
+        }
+    }
+
+    // every term has to match and none of the excluded ones, `None` without terms
+    pub fn fts_match(&self) -> Option<String> {
+        if self.terms.is_empty() {
+            return None;
+        }
+        let mut expression = format!(
+            "({})",
+            self.terms
+                .iter()
+                .map(|term| fts_string(term))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        for excluded in &self.excluded {
+            expression.push_str(&format!(" NOT {}", fts_string(excluded)));
+        }
+        Some(expression)
+    }
+
+    pub fn search_indexes(&self) -> Vec<SearchIndex> {
+        search_indexes_for_terms(self.terms.iter().chain(&self.excluded).map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_query() {
+        let query = parse_search_query(
+            r#"rust "error handling" -draft site:www.Docs.rs type:pdf -tag:#old after:2024-01-31 space:"Work Projects" https://x.org"#,
+        );
+        assert!(query.errors.is_empty(), "{:?}", query.errors);
+        assert_eq!(query.terms, vec!["rust", "error handling", "https://x.org"]);
+        assert_eq!(query.excluded, vec!["draft"]);
+        assert_eq!(
+            query.fts_match().unwrap(),
+            r#"("rust" "error handling" "https://x.org") NOT "draft""#
+        );
+        assert_eq!(query.space.unwrap().name, "Work Projects");
+
+        assert_eq!(query.filters.len(), 4);
+        match &query.filters[0] {
+            ResourceFilter::Or { filters } => assert_eq!(filters.len(), 8),
+            filter => panic!("unexpected filter {:?}", filter),
+        }
+        assert!(matches!(
+            &query.filters[1],
+            ResourceFilter::ResourceType { resource_type, prefix: false } if resource_type == "application/pdf"
+        ));
+        match &query.filters[2] {
+            ResourceFilter::Not { filter } => assert!(matches!(
+                filter.as_ref(),
+                ResourceFilter::Tag(tag) if tag.tag_name == "hashtag" && tag.tag_value == "old"
+            )),
+            filter => panic!("unexpected filter {:?}", filter),
+        }
+        assert!(matches!(
+            &query.filters[3],
+            ResourceFilter::CreatedAt { after: Some(after), before: None }
+                if after.to_rfc3339() == "2024-01-31T00:00:00+00:00"
+        ));
+    }
+
+    #[test]
+    fn test_parse_search_query_errors() {
+        let kinds = |query: &str| {
+            parse_search_query(query)
+                .errors
+                .into_iter()
+                .map(|error| (error.kind, error.start, error.end))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            kinds(r#"rust "open"#),
+            vec![(SearchQueryErrorKind::UnterminatedQuote, 5, 10)]
+        );
+        assert_eq!(
+            kinds("a type:movie before:yesterday site:"),
+            vec![
+                (SearchQueryErrorKind::UnknownType, 2, 12),
+                (SearchQueryErrorKind::InvalidDate, 13, 29),
+                (SearchQueryErrorKind::EmptyValue, 30, 35),
+            ]
+        );
+        assert_eq!(
+            kinds("-draft"),
+            vec![(SearchQueryErrorKind::OnlyExclusions, 0, 6)]
+        );
+        assert!(kinds("type:pdf - re:things").is_empty());
+    }
+}
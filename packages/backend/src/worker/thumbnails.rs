@@ -0,0 +1,185 @@
+use crate::{
+    ai::youtube::extract_youtube_video_id,
+    store::models::{current_time, CompositeResource, ResourceThumbnail},
+    worker::extractors::pdf::first_page_image,
+    BackendError, BackendResult,
+};
+
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, GenericImageView};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// longest side in pixels, thumbnails keep the aspect ratio of the source
+const THUMBNAIL_SIZE: u32 = 512;
+const THUMBNAIL_QUALITY: u8 = 80;
+const YOUTUBE_POSTER_URL: &str = "https://i.ytimg.com/vi";
+
+pub fn thumbnails_dir(resources_path: &str) -> PathBuf {
+    Path::new(resources_path).join("thumbnails")
+}
+
+// the name only depends on the resource and its content, so a thumbnail generated
+// for the same content is found again and a changed content gets a new file
+pub fn thumbnail_path(dir: &Path, resource_id: &str, content_hash: &str) -> PathBuf {
+    let hash: String = content_hash
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(16)
+        .collect();
+    dir.join(format!("{resource_id}-{hash}.jpg"))
+}
+
+// `Ok(None)` for resources that have nothing to preview
+pub fn generate_thumbnail(
+    resource: &CompositeResource,
+    content_hash: &str,
+    dir: &Path,
+) -> BackendResult<Option<ResourceThumbnail>> {
+    let path = thumbnail_path(dir, &resource.resource.id, content_hash);
+    let (width, height) = if path.is_file() {
+        image::image_dimensions(&path).map_err(|e| {
+            BackendError::GenericError(format!("failed to read thumbnail {path:?}: {e}"))
+        })?
+    } else {
+        let source = match load_source_image(resource)? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+        let thumbnail = source.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+        std::fs::create_dir_all(dir)?;
+        write_jpeg(&thumbnail, &path)?;
+        thumbnail.dimensions()
+    };
+
+    Ok(Some(ResourceThumbnail {
+        resource_id: resource.resource.id.clone(),
+        path: path.to_string_lossy().to_string(),
+        content_hash: content_hash.to_owned(),
+        width,
+        height,
+        created_at: current_time(),
+    }))
+}
+
+fn load_source_image(resource: &CompositeResource) -> BackendResult<Option<DynamicImage>> {
+    let resource_type = resource.resource.resource_type.as_str();
+    if resource_type.starts_with("image/") {
+        // svg and other formats the image crate can't decode get no thumbnail
+        return Ok(image::open(&resource.resource.resource_path).ok());
+    }
+
+    match resource_type {
+        "application/pdf" => {
+            Ok(first_page_image(&resource.resource.resource_path)?.map(DynamicImage::ImageRgb8))
+        }
+        "application/vnd.space.post.youtube" => {
+            let video_id = resource
+                .metadata
+                .as_ref()
+                .and_then(|metadata| extract_youtube_video_id(&metadata.source_uri));
+            match video_id {
+                Some(video_id) => fetch_youtube_poster(&video_id).map(Some),
+                None => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+fn fetch_youtube_poster(video_id: &str) -> BackendResult<DynamicImage> {
+    let url = format!("{YOUTUBE_POSTER_URL}/{video_id}/hqdefault.jpg");
+    let bytes = reqwest::blocking::get(url)?.error_for_status()?.bytes()?;
+    image::load_from_memory(&bytes)
+        .map_err(|e| BackendError::GenericError(format!("failed to decode youtube poster: {e}")))
+}
+
+// written to a temporary file first so that readers never see a partial thumbnail
+fn write_jpeg(image: &DynamicImage, path: &Path) -> BackendResult<()> {
+    let tmp_path = path.with_extension("jpg.tmp");
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+    JpegEncoder::new_with_quality(&mut writer, THUMBNAIL_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| BackendError::GenericError(format!("failed to encode thumbnail: {e}")))?;
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::models::Resource;
+    use tempfile::tempdir;
+
+    fn image_resource(path: &Path) -> CompositeResource {
+        let now = current_time();
+        CompositeResource {
+            resource: Resource {
+                id: "resource1".to_string(),
+                resource_path: path.to_string_lossy().to_string(),
+                resource_type: "image/png".to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted: 0,
+            },
+            metadata: None,
+            text_content: None,
+            resource_tags: None,
+            resource_annotations: None,
+            post_processing_job: None,
+            space_ids: None,
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_thumbnail_is_generated_once_per_content_hash() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        image::RgbImage::from_pixel(2048, 1024, image::Rgb([200, 40, 40]))
+            .save(&source)
+            .unwrap();
+        let resource = image_resource(&source);
+        let thumbnails = thumbnails_dir(&dir.path().to_string_lossy());
+
+        let first = generate_thumbnail(&resource, "abc123", &thumbnails)
+            .unwrap()
+            .unwrap();
+        assert_eq!((first.width, first.height), (512, 256));
+        assert_eq!(
+            Path::new(&first.path),
+            thumbnails.join("resource1-abc123.jpg")
+        );
+        let modified = std::fs::metadata(&first.path).unwrap().modified().unwrap();
+
+        let again = generate_thumbnail(&resource, "abc123", &thumbnails)
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.path, first.path);
+        assert_eq!(
+            std::fs::metadata(&again.path).unwrap().modified().unwrap(),
+            modified
+        );
+
+        let changed = generate_thumbnail(&resource, "def456", &thumbnails)
+            .unwrap()
+            .unwrap();
+        assert_ne!(changed.path, first.path);
+        assert!(Path::new(&changed.path).is_file());
+    }
+
+    #[test]
+    fn test_no_thumbnail_for_unsupported_resources() {
+        let dir = tempdir().unwrap();
+        let mut resource = image_resource(&dir.path().join("missing.png"));
+        assert!(generate_thumbnail(&resource, "abc", dir.path())
+            .unwrap()
+            .is_none());
+
+        resource.resource.resource_type = "text/plain".to_string();
+        assert!(generate_thumbnail(&resource, "abc", dir.path())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,6 +1,11 @@
 use super::{
-    processor::processor_thread_entry_point, worker_thread_entry_point, AIConfig, ChannelConfig,
-    PathConfig, WorkerConfig,
+    extractors::ExtractorRegistry,
+    processor::processor_thread_entry_point,
+    queue::{
+        processing_queue, ProcessingQueueConfig, ProcessingQueueReceiver, ProcessingQueueSender,
+    },
+    watcher::FolderWatcher,
+    worker_thread_entry_point, AIConfig, ChannelConfig, PathConfig, WorkerConfig,
 };
 use crate::{
     ai::claude_agent::ClaudeAgentRunnerHandle,
@@ -19,16 +24,20 @@ use std::panic;
 
 const NUM_WORKER_THREADS: usize = 12;
 const NUM_PROCESSOR_THREADS: usize = 12;
+const POST_PROCESSING_SCHEDULER_INTERVAL: Duration = Duration::from_secs(10);
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct WorkerTunnel {
     pub worker_tx: crossbeam::Sender<TunnelMessage>,
-    pub tqueue_rx: crossbeam::Receiver<ProcessorMessage>,
+    pub tqueue_rx: ProcessingQueueReceiver<ProcessorMessage>,
     pub aiqueue_rx: crossbeam::Receiver<AIMessage>,
     pub event_bus_rx_callback: Arc<Root<JsFunction>>,
     pub surf_backend_health: SurfBackendHealth,
     pub claude_agent_runner: ClaudeAgentRunnerHandle,
+    pub extractor_registry: ExtractorRegistry,
+    pub folder_watcher: Option<FolderWatcher>,
 }
 
 pub struct SurfBackendHealth(Arc<(Mutex<bool>, Condvar)>);
@@ -78,6 +87,7 @@ pub struct TunnelConfig {
     pub language_setting: String,
     pub num_worker_threads: Option<usize>,
     pub num_processor_threads: Option<usize>,
+    pub processing_queue: ProcessingQueueConfig,
 }
 
 impl Finalize for WorkerTunnel {}
@@ -92,11 +102,12 @@ impl WorkerTunnel {
         C: Context<'a>,
     {
         let (worker_tx, worker_rx) = crossbeam::unbounded();
-        let (tqueue_tx, tqueue_rx) = crossbeam::unbounded();
+        let (tqueue_tx, tqueue_rx) = processing_queue(&config.processing_queue);
         let (aiqueue_tx, aiqueue_rx) = crossbeam::unbounded();
         let surf_backend_health = SurfBackendHealth::new(Some(false));
         let event_bus_rx_callback = Arc::new(event_bus_rx_callback);
         let claude_agent_runner = Arc::new(Mutex::new(None));
+        let folder_watcher = Self::spawn_folder_watcher(worker_tx.clone());
         let tunnel = Self {
             worker_tx,
             tqueue_rx,
@@ -104,6 +115,8 @@ impl WorkerTunnel {
             event_bus_rx_callback: event_bus_rx_callback.clone(),
             surf_backend_health: surf_backend_health.clone(),
             claude_agent_runner: claude_agent_runner.clone(),
+            extractor_registry: ExtractorRegistry::with_builtin_extractors(),
+            folder_watcher,
         };
 
         Self::spawn_threads(cx, config, worker_rx, tqueue_tx, aiqueue_tx, &tunnel);
@@ -116,34 +129,24 @@ impl WorkerTunnel {
         cx: &mut C,
         config: TunnelConfig,
         worker_rx: crossbeam::Receiver<TunnelMessage>,
-        tqueue_tx: crossbeam::Sender<ProcessorMessage>,
+        tqueue_tx: ProcessingQueueSender<ProcessorMessage>,
         aiqueue_tx: crossbeam::Sender<AIMessage>,
         tunnel: &WorkerTunnel,
     ) where
         C: Context<'a>,
     {
-        Self::spawn_worker_threads(
-            cx,
-            &config,
-            worker_rx,
-            tqueue_tx,
-            aiqueue_tx,
-            Arc::clone(&tunnel.event_bus_rx_callback),
-            tunnel.surf_backend_health.clone(),
-            tunnel.claude_agent_runner.clone(),
-        );
+        Self::spawn_worker_threads(cx, &config, worker_rx, tqueue_tx, aiqueue_tx, tunnel);
         Self::spawn_processor_threads(tunnel, &config);
+        Self::spawn_post_processing_scheduler(tunnel.worker_tx.clone());
     }
 
     fn spawn_worker_threads<'a, C>(
         cx: &mut C,
         config: &TunnelConfig,
         worker_rx: crossbeam::Receiver<TunnelMessage>,
-        tqueue_tx: crossbeam::Sender<ProcessorMessage>,
+        tqueue_tx: ProcessingQueueSender<ProcessorMessage>,
         aiqueue_tx: crossbeam::Sender<AIMessage>,
-        event_bus_rx_callback: Arc<Root<JsFunction>>,
-        surf_backend_health: SurfBackendHealth,
-        claude_agent_runner: ClaudeAgentRunnerHandle,
+        tunnel: &WorkerTunnel,
     ) where
         C: Context<'a>,
     {
@@ -155,8 +158,8 @@ impl WorkerTunnel {
             let worker_rx = worker_rx.clone();
             let tqueue_tx = tqueue_tx.clone();
             let aiqueue_tx = aiqueue_tx.clone();
-            let callback = Arc::clone(&event_bus_rx_callback);
-            let surf_backend_health = surf_backend_health.clone();
+            let callback = Arc::clone(&tunnel.event_bus_rx_callback);
+            let surf_backend_health = tunnel.surf_backend_health.clone();
             let libuv_ch = libuv_ch.clone();
             let thread_name = format!("W{n}");
 
@@ -167,7 +170,9 @@ impl WorkerTunnel {
             let backend_root_path = config.backend_root_path.clone();
             let local_ai_mode = config.local_ai_mode;
             let language_setting = config.language_setting.clone();
-            let runner_handle = claude_agent_runner.clone();
+            let runner_handle = tunnel.claude_agent_runner.clone();
+            let extractor_registry = tunnel.extractor_registry.clone();
+            let folder_watcher = tunnel.folder_watcher.clone();
 
             std::thread::Builder::new()
             .name(thread_name.clone())
@@ -197,6 +202,8 @@ impl WorkerTunnel {
                         run_migrations: _run_migrations,
                         surf_backend_health: surf_backend_health.clone(),
                         claude_agent_runner: runner_handle.clone(),
+                        extractor_registry: extractor_registry.clone(),
+                        folder_watcher: folder_watcher.clone(),
                     };
 
                     worker_thread_entry_point(worker_rx.clone(), worker_config)
@@ -212,6 +219,10 @@ impl WorkerTunnel {
 
     fn spawn_processor_threads(tunnel: &WorkerTunnel, config: &TunnelConfig) {
         let language = Some(config.language_setting.clone()).filter(|lang| lang == "en");
+        let path_config =
+            PathConfig::new(config.app_path.clone(), config.backend_root_path.clone());
+        let resources_path = path_config.resources_path();
+        let db_path = path_config.db_path();
         let num_processor_threads = config
             .num_processor_threads
             .unwrap_or(NUM_PROCESSOR_THREADS);
@@ -220,6 +231,8 @@ impl WorkerTunnel {
             let tunnel = tunnel.clone();
             let config = config.clone();
             let language = language.clone();
+            let resources_path = resources_path.clone();
+            let db_path = db_path.clone();
             let thread_name = format!("P{n}");
 
             std::thread::Builder::new()
@@ -229,7 +242,10 @@ impl WorkerTunnel {
                         processor_thread_entry_point(
                             tunnel.clone(),
                             config.app_path.clone(),
+                            resources_path.clone(),
+                            db_path.clone(),
                             language.clone(),
+                            config.language_setting.clone(),
                         )
                     }));
 
@@ -241,17 +257,58 @@ impl WorkerTunnel {
         }
     }
 
+    // periodically asks the workers to dispatch post processing jobs that are due for a retry,
+    // stops once the workers are gone and the channel is disconnected
+    fn spawn_post_processing_scheduler(worker_tx: crossbeam::Sender<TunnelMessage>) {
+        std::thread::Builder::new()
+            .name("PS".to_owned())
+            .spawn(move || loop {
+                std::thread::sleep(POST_PROCESSING_SCHEDULER_INTERVAL);
+                let message =
+                    WorkerMessage::ResourceMessage(ResourceMessage::DispatchDuePostProcessingJobs);
+                if worker_tx.send(TunnelMessage(message, None)).is_err() {
+                    tracing::info!(
+                        "worker channel disconnected, stopping post processing scheduler"
+                    );
+                    break;
+                }
+            })
+            .expect("failed to spawn post processing scheduler thread");
+    }
+
+    // changes in watched folders are synced by the workers, without a watcher the folders
+    // are still synced on startup
+    fn spawn_folder_watcher(worker_tx: crossbeam::Sender<TunnelMessage>) -> Option<FolderWatcher> {
+        FolderWatcher::spawn(move |folder_id, paths| {
+            let message = WorkerMessage::ResourceMessage(ResourceMessage::SyncWatchedFolder {
+                folder_id,
+                paths,
+            });
+            if worker_tx.send(TunnelMessage(message, None)).is_err() {
+                tracing::error!("failed to send watched folder changes to the workers");
+            }
+        })
+        .map_err(|e| tracing::error!("failed to start folder watcher: {e}"))
+        .ok()
+    }
+
     fn initiate_worker_startup_jobs(&self) {
         let (tx, rx) = crossbeam_channel::bounded(1);
 
         self.worker_send_rust(
-            WorkerMessage::ResourceMessage(ResourceMessage::FailActivePostProcessingJobs),
+            WorkerMessage::ResourceMessage(ResourceMessage::ResumePostProcessingJobs),
             Some(tx),
         );
 
         rx.recv()
             .map_err(|e| tracing::error!("failed to initiate worker jobs: {e}"))
             .ok();
+
+        // syncing the folders can take a while, startup doesn't wait for it
+        self.worker_send_rust(
+            WorkerMessage::ResourceMessage(ResourceMessage::ResumeWatchedFolders),
+            None,
+        );
     }
 
     pub fn register_claude_agent_runner(&self, runner: Root<JsFunction>) {
@@ -0,0 +1,395 @@
+use crate::{utils::sanitize_filename, BackendResult};
+
+use once_cell::sync::Lazy;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+pub const NOTE_RESOURCE_TYPE: &str = "application/vnd.space.document.space-note";
+pub const RESOURCE_URL_PREFIX: &str = "surf://surf/resource/";
+const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+// spaces, parentheses and the rest that would end a markdown link destination early
+const LINK_PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'(')
+    .add(b')')
+    .add(b'<')
+    .add(b'>')
+    .add(b'[')
+    .add(b']')
+    .add(b'#')
+    .add(b'?')
+    .add(b'%');
+
+// `[[target]]`, `[[target#heading|alias]]` and embeds like `![[image.png]]`
+static WIKILINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(!?)\[\[([^\[\]|]+?)(?:\|([^\[\]]*))?\]\]").unwrap());
+// `[text](destination "title")` and `![alt](<destination with spaces>)`
+static MARKDOWN_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(!?)\[([^\]]*)\]\((?:<([^>]*)>|([^)\s]*))((?:\s+"[^"]*")?)\)"#).unwrap()
+});
+static RESOURCE_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\]\(<?surf://surf/resource/([0-9A-Za-z-]+)(?:\?raw)?>?((?:\s+[^)]*)?)\)").unwrap()
+});
+
+#[derive(Debug, Default, PartialEq)]
+pub struct VaultTree {
+    // relative to the vault root, parents come before their children and the root is the
+    // empty path
+    pub folders: Vec<PathBuf>,
+    pub files: Vec<PathBuf>,
+}
+
+// hidden files and folders like `.obsidian` or `.git` belong to tools, not to the notes
+pub fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+pub fn scan_vault(root: &Path) -> BackendResult<VaultTree> {
+    let mut tree = VaultTree::default();
+    scan_folder(root, PathBuf::new(), &mut tree)?;
+    Ok(tree)
+}
+
+fn scan_folder(root: &Path, folder: PathBuf, tree: &mut VaultTree) -> BackendResult<()> {
+    let mut entries = std::fs::read_dir(root.join(&folder))?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    tree.folders.push(folder.clone());
+
+    let mut subfolders = vec![];
+    for entry in entries {
+        let name = entry.file_name();
+        if is_hidden(&name.to_string_lossy()) {
+            continue;
+        }
+        let path = folder.join(&name);
+        if entry.file_type()?.is_dir() {
+            subfolders.push(path);
+        } else {
+            tree.files.push(path);
+        }
+    }
+    for subfolder in subfolders {
+        scan_folder(root, subfolder, tree)?;
+    }
+    Ok(())
+}
+
+pub fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| MARKDOWN_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+// vault paths always use `/` and notes are linked to without their extension
+fn link_key(path: &str) -> String {
+    let path = path.trim().replace('\\', "/").to_lowercase();
+    MARKDOWN_EXTENSIONS
+        .iter()
+        .find_map(|extension| path.strip_suffix(&format!(".{extension}")))
+        .map(|path| path.to_owned())
+        .unwrap_or(path)
+}
+
+// drops `.` and resolves `..`, `None` if the path leaves the vault
+fn normalize_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(normalized)
+}
+
+// finds link targets the way vault apps do, by their path in the vault or by their name alone
+#[derive(Debug, Default)]
+pub struct LinkResolver {
+    by_path: HashMap<String, String>,
+    by_name: HashMap<String, String>,
+}
+
+impl LinkResolver {
+    pub fn insert(&mut self, path: &Path, resource_id: &str) {
+        let key = link_key(&path.to_string_lossy());
+        self.by_path.insert(key.clone(), resource_id.to_owned());
+        // with several files of the same name the first one wins
+        let name = key.rsplit('/').next().unwrap_or(&key).to_owned();
+        self.by_name
+            .entry(name)
+            .or_insert_with(|| resource_id.to_owned());
+    }
+
+    pub fn resolve_wikilink(&self, target: &str) -> Option<&str> {
+        let key = link_key(target);
+        self.by_path
+            .get(&key)
+            .or_else(|| self.by_name.get(&key))
+            .map(|id| id.as_str())
+    }
+
+    // relative to the folder of the linking note, urls are not resolved
+    pub fn resolve_relative(&self, note_path: &Path, destination: &str) -> Option<&str> {
+        if destination.is_empty() || destination.starts_with('#') || destination.contains(':') {
+            return None;
+        }
+        let destination = destination.split(['#', '?']).next()?;
+        let destination = percent_decode_str(destination).decode_utf8().ok()?;
+        let folder = note_path.parent().unwrap_or_else(|| Path::new(""));
+        let path = normalize_path(&folder.join(destination.as_ref()))?;
+        self.by_path
+            .get(&link_key(&path.to_string_lossy()))
+            .map(|id| id.as_str())
+    }
+}
+
+fn resource_url(resource_id: &str, raw: bool) -> String {
+    let query = if raw { "?raw" } else { "" };
+    format!("{RESOURCE_URL_PREFIX}{resource_id}{query}")
+}
+
+fn rewrite_line(
+    line: &str,
+    note_path: &Path,
+    resolver: &LinkResolver,
+    is_note: &dyn Fn(&str) -> bool,
+    linked_ids: &mut Vec<String>,
+) -> String {
+    let mut link_to = |resource_id: &str| {
+        if !linked_ids.iter().any(|id| id == resource_id) {
+            linked_ids.push(resource_id.to_owned());
+        }
+    };
+
+    let line = WIKILINK.replace_all(line, |caps: &Captures| {
+        let target = &caps[2];
+        let path = target.split(['#', '^']).next().unwrap_or_default();
+        let display = caps
+            .get(3)
+            .map(|alias| alias.as_str())
+            .unwrap_or(target)
+            .trim();
+        match resolver.resolve_wikilink(path) {
+            Some(resource_id) => {
+                link_to(resource_id);
+                let embed = !caps[1].is_empty() && !is_note(resource_id);
+                let marker = if embed { "!" } else { "" };
+                format!("{marker}[{display}]({})", resource_url(resource_id, embed))
+            }
+            None => caps[0].to_owned(),
+        }
+    });
+
+    MARKDOWN_LINK
+        .replace_all(&line, |caps: &Captures| {
+            let destination = caps
+                .get(3)
+                .or_else(|| caps.get(4))
+                .map_or("", |m| m.as_str());
+            match resolver.resolve_relative(note_path, destination) {
+                Some(resource_id) => {
+                    link_to(resource_id);
+                    let embed = !caps[1].is_empty() && !is_note(resource_id);
+                    format!(
+                        "{}[{}]({}{})",
+                        if embed { "!" } else { "" },
+                        &caps[2],
+                        resource_url(resource_id, embed),
+                        &caps[5]
+                    )
+                }
+                None => caps[0].to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+// points wikilinks and relative links at the imported resources, code blocks are left alone,
+// returns the markdown and the ids of all linked resources
+pub fn rewrite_vault_links(
+    markdown: &str,
+    note_path: &Path,
+    resolver: &LinkResolver,
+    is_note: &dyn Fn(&str) -> bool,
+) -> (String, Vec<String>) {
+    let mut linked_ids = vec![];
+    let mut in_code_block = false;
+    let lines: Vec<String> = markdown
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code_block = !in_code_block;
+                return line.to_owned();
+            }
+            if in_code_block {
+                return line.to_owned();
+            }
+            rewrite_line(line, note_path, resolver, is_note, &mut linked_ids)
+        })
+        .collect();
+    (lines.join("\n"), linked_ids)
+}
+
+pub fn markdown_to_html(markdown: &str) -> String {
+    let options = pulldown_cmark::Options::ENABLE_TABLES
+        | pulldown_cmark::Options::ENABLE_STRIKETHROUGH
+        | pulldown_cmark::Options::ENABLE_TASKLISTS;
+    let parser = pulldown_cmark::Parser::new_ext(markdown, options);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html
+}
+
+pub fn html_to_markdown(html: &str) -> String {
+    html2md::parse_html(html).trim().to_owned()
+}
+
+// `to` relative to the folder of `from`, both relative to the vault root
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<_> = from
+        .parent()
+        .map(|folder| folder.components().collect())
+        .unwrap_or_default();
+    let to: Vec<_> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts: Vec<String> = vec!["..".to_owned(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts
+        .iter()
+        .map(|part| utf8_percent_encode(part, LINK_PATH).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// the inverse of `rewrite_vault_links`, links to resources outside of the export are kept
+pub fn relativize_resource_links(
+    markdown: &str,
+    note_path: &Path,
+    exported: &HashMap<String, PathBuf>,
+) -> String {
+    RESOURCE_LINK
+        .replace_all(markdown, |caps: &Captures| match exported.get(&caps[1]) {
+            Some(path) => format!("]({}{})", relative_path(note_path, path), &caps[2]),
+            None => caps[0].to_owned(),
+        })
+        .into_owned()
+}
+
+// a file name that is not taken yet in its folder, compared case insensitively since most
+// file systems the vault ends up on are
+pub fn unique_file_name(name: &str, extension: &str, taken: &mut HashSet<String>) -> String {
+    let name = sanitize_filename(&name.replace(['/', '\\'], "-"));
+    let name = match name.trim() {
+        "" => "Untitled",
+        name => name,
+    };
+    let with_extension = |name: &str| match extension {
+        "" => name.to_owned(),
+        extension => format!("{name}.{extension}"),
+    };
+    let mut file_name = with_extension(name);
+    let mut counter = 1;
+    while !taken.insert(file_name.to_lowercase()) {
+        counter += 1;
+        file_name = with_extension(&format!("{name} {counter}"));
+    }
+    file_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_vault_skips_hidden_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".obsidian")).unwrap();
+        std::fs::create_dir_all(root.join("projects/2024")).unwrap();
+        std::fs::write(root.join(".obsidian/app.json"), "{}").unwrap();
+        std::fs::write(root.join("index.md"), "# index").unwrap();
+        std::fs::write(root.join("projects/plan.md"), "plan").unwrap();
+        std::fs::write(root.join("projects/2024/q1.md"), "q1").unwrap();
+
+        let tree = scan_vault(root).unwrap();
+        assert_eq!(
+            tree.folders,
+            vec![
+                PathBuf::new(),
+                PathBuf::from("projects"),
+                PathBuf::from("projects/2024")
+            ]
+        );
+        assert_eq!(
+            tree.files,
+            vec![
+                PathBuf::from("index.md"),
+                PathBuf::from("projects/plan.md"),
+                PathBuf::from("projects/2024/q1.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_vault_links() {
+        let mut resolver = LinkResolver::default();
+        resolver.insert(Path::new("projects/Plan.md"), "plan");
+        resolver.insert(Path::new("assets/diagram one.png"), "diagram");
+        resolver.insert(Path::new("index.md"), "index");
+        let is_note = |id: &str| id != "diagram";
+
+        let markdown = "See [[plan|the plan]] and [[Missing]].\n![[diagram one.png]]\n[back](../index.md#top) [site](https://example.com)\n```\n[[plan]]\n```\n![d](<../assets/diagram one.png>)";
+        let (rewritten, linked_ids) =
+            rewrite_vault_links(markdown, Path::new("projects/Plan.md"), &resolver, &is_note);
+        assert_eq!(
+            rewritten,
+            "See [the plan](surf://surf/resource/plan) and [[Missing]].\n![diagram one.png](surf://surf/resource/diagram?raw)\n[back](surf://surf/resource/index) [site](https://example.com)\n```\n[[plan]]\n```\n![d](surf://surf/resource/diagram?raw)"
+        );
+        assert_eq!(linked_ids, vec!["plan", "diagram", "index"]);
+
+        let html = markdown_to_html(&rewritten);
+        assert!(html.contains(r#"<a href="surf://surf/resource/plan">the plan</a>"#));
+        assert!(html.contains(r#"<img src="surf://surf/resource/diagram?raw""#));
+    }
+
+    #[test]
+    fn test_relativize_resource_links() {
+        let exported = HashMap::from([
+            ("plan".to_owned(), PathBuf::from("projects/Plan.md")),
+            (
+                "diagram".to_owned(),
+                PathBuf::from("assets/diagram one.png"),
+            ),
+        ]);
+        let markdown = html_to_markdown(
+            r#"<p><a href="surf://surf/resource/plan">the plan</a> <img src="surf://surf/resource/diagram?raw" alt="d"> <a href="surf://surf/resource/other">other</a></p>"#,
+        );
+        let relative = relativize_resource_links(&markdown, Path::new("notes/Today.md"), &exported);
+        assert!(relative.contains("[the plan](../projects/Plan.md)"));
+        assert!(relative.contains("![d](../assets/diagram%20one.png)"));
+        assert!(relative.contains("[other](surf://surf/resource/other)"));
+
+        let mut taken = HashSet::new();
+        assert_eq!(unique_file_name("a/b", "md", &mut taken), "a-b.md");
+        assert_eq!(unique_file_name("A-B", "md", &mut taken), "A-B 2.md");
+        assert_eq!(unique_file_name("  ", "", &mut taken), "Untitled");
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    store::models::{DiffLine, DiffLineKind},
+    worker::vault::{html_to_markdown, NOTE_RESOURCE_TYPE},
+};
+
+use std::path::{Path, PathBuf};
+
+// past this the line by line comparison takes too much memory, the changed part is then
+// shown as removed and added as a whole
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+pub fn versions_dir(resources_path: &str) -> PathBuf {
+    Path::new(resources_path).join("versions")
+}
+
+pub fn version_path(dir: &Path, resource_id: &str, version_id: &str) -> PathBuf {
+    dir.join(resource_id).join(version_id)
+}
+
+// notes and documents edited in the app and text files synced from disk, other resources
+// don't change once saved
+pub fn is_versioned_resource_type(resource_type: &str) -> bool {
+    resource_type.starts_with("application/vnd.space.document")
+        || resource_type.starts_with("text/")
+}
+
+// notes are compared as markdown, their html changes with every edit of the formatting
+pub fn version_text(resource_type: &str, data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    if resource_type == NOTE_RESOURCE_TYPE {
+        html_to_markdown(&text)
+    } else {
+        text.into_owned()
+    }
+}
+
+fn diff_line(kind: DiffLineKind, text: &str) -> DiffLine {
+    DiffLine {
+        kind,
+        text: text.to_owned(),
+    }
+}
+
+// line based diff from the longest common subsequence of the lines that differ
+pub fn text_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_changed = &old[prefix..old.len() - suffix];
+    let new_changed = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| diff_line(DiffLineKind::Equal, line))
+        .collect();
+
+    let (n, m) = (old_changed.len(), new_changed.len());
+    if n * m > MAX_DIFF_CELLS {
+        lines.extend(
+            old_changed
+                .iter()
+                .map(|line| diff_line(DiffLineKind::Delete, line)),
+        );
+        lines.extend(
+            new_changed
+                .iter()
+                .map(|line| diff_line(DiffLineKind::Insert, line)),
+        );
+    } else {
+        // lengths of the common subsequences of the remaining lines
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[at(i, j)] = if old_changed[i] == new_changed[j] {
+                    lcs[at(i + 1, j + 1)] + 1
+                } else {
+                    lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_changed[i] == new_changed[j] {
+                lines.push(diff_line(DiffLineKind::Equal, old_changed[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+                lines.push(diff_line(DiffLineKind::Delete, old_changed[i]));
+                i += 1;
+            } else {
+                lines.push(diff_line(DiffLineKind::Insert, new_changed[j]));
+                j += 1;
+            }
+        }
+    }
+
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| diff_line(DiffLineKind::Equal, line)),
+    );
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                let sign = match line.kind {
+                    DiffLineKind::Equal => ' ',
+                    DiffLineKind::Insert => '+',
+                    DiffLineKind::Delete => '-',
+                };
+                format!("{sign}{}", line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_diff() {
+        let old = "title\none\ntwo\nthree\nend";
+        let new = "title\none\n2\nthree\nfour\nend";
+        assert_eq!(
+            render(&text_diff(old, new)),
+            vec![" title", " one", "-two", "+2", " three", "+four", " end"]
+        );
+
+        assert_eq!(render(&text_diff("", "a\nb")), vec!["+a", "+b"]);
+        assert_eq!(render(&text_diff("a\nb", "")), vec!["-a", "-b"]);
+        assert!(text_diff("same\ntext", "same\ntext")
+            .iter()
+            .all(|line| line.kind == DiffLineKind::Equal));
+    }
+}
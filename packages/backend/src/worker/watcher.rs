@@ -0,0 +1,287 @@
+use crate::{
+    worker::vault::{is_hidden, scan_vault},
+    BackendError, BackendResult,
+};
+
+use crossbeam_channel as crossbeam;
+use notify::{
+    event::{AccessKind, AccessMode},
+    EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// editors write files in several steps, changes are collected until the folder is quiet
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+enum WatcherCommand {
+    Watch { folder_id: String, path: PathBuf },
+    Unwatch(String),
+}
+
+// handle to the thread that watches the folders, inotify on linux
+#[derive(Clone)]
+pub struct FolderWatcher {
+    command_tx: crossbeam::Sender<WatcherCommand>,
+}
+
+impl FolderWatcher {
+    // `on_change` is called with the folder id and the changed paths relative to the folder,
+    // the empty path stands for the whole folder
+    pub fn spawn<F>(on_change: F) -> BackendResult<Self>
+    where
+        F: Fn(String, Vec<String>) + Send + 'static,
+    {
+        let (command_tx, command_rx) = crossbeam::unbounded();
+        let (event_tx, event_rx) = crossbeam::unbounded();
+        let watcher = notify::recommended_watcher(event_tx).map_err(|e| {
+            BackendError::GenericError(format!("Failed to create folder watcher: {}", e))
+        })?;
+
+        std::thread::Builder::new()
+            .name("FW".to_owned())
+            .spawn(move || watcher_thread(watcher, command_rx, event_rx, on_change))?;
+        Ok(Self { command_tx })
+    }
+
+    pub fn watch(&self, folder_id: &str, path: &str) {
+        self.send(WatcherCommand::Watch {
+            folder_id: folder_id.to_owned(),
+            path: PathBuf::from(path),
+        });
+    }
+
+    pub fn unwatch(&self, folder_id: &str) {
+        self.send(WatcherCommand::Unwatch(folder_id.to_owned()));
+    }
+
+    fn send(&self, command: WatcherCommand) {
+        if self.command_tx.send(command).is_err() {
+            tracing::error!("folder watcher thread is not running");
+        }
+    }
+}
+
+fn watcher_thread<F>(
+    mut watcher: RecommendedWatcher,
+    command_rx: crossbeam::Receiver<WatcherCommand>,
+    event_rx: crossbeam::Receiver<notify::Result<notify::Event>>,
+    on_change: F,
+) where
+    F: Fn(String, Vec<String>),
+{
+    let mut folders: HashMap<String, PathBuf> = HashMap::new();
+    // changed paths of each folder and when the last change came in
+    let mut pending: HashMap<String, (BTreeSet<String>, Instant)> = HashMap::new();
+
+    loop {
+        let timeout = pending
+            .values()
+            .map(|(_, last_change)| {
+                (*last_change + DEBOUNCE_INTERVAL).saturating_duration_since(Instant::now())
+            })
+            .min()
+            .unwrap_or(IDLE_INTERVAL);
+
+        crossbeam::select! {
+            recv(command_rx) -> command => match command {
+                Ok(WatcherCommand::Watch { folder_id, path }) => {
+                    match watcher.watch(&path, RecursiveMode::Recursive) {
+                        Ok(_) => {
+                            folders.insert(folder_id, path);
+                        }
+                        Err(e) => tracing::error!("failed to watch folder {path:?}: {e}"),
+                    }
+                }
+                Ok(WatcherCommand::Unwatch(folder_id)) => {
+                    if let Some(path) = folders.remove(&folder_id) {
+                        if let Err(e) = watcher.unwatch(&path) {
+                            tracing::warn!("failed to unwatch folder {path:?}: {e}");
+                        }
+                    }
+                    pending.remove(&folder_id);
+                }
+                // all handles are gone
+                Err(_) => return,
+            },
+            recv(event_rx) -> event => match event {
+                Ok(Ok(event)) if !is_ignored_event(&event.kind) => {
+                    // the events were dropped by the os, the whole folder has to be checked
+                    let paths = if event.need_rescan() {
+                        folders.values().cloned().collect()
+                    } else {
+                        event.paths
+                    };
+                    for path in paths {
+                        if let Some((folder_id, relative_path)) = watched_path(&folders, &path) {
+                            let (changed, last_change) = pending
+                                .entry(folder_id)
+                                .or_insert_with(|| (BTreeSet::new(), Instant::now()));
+                            changed.insert(relative_path);
+                            *last_change = Instant::now();
+                        }
+                    }
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("folder watcher error: {e}"),
+                Err(_) => return,
+            },
+            default(timeout) => {}
+        }
+
+        let now = Instant::now();
+        let quiet = pending
+            .iter()
+            .filter(|(_, (_, last_change))| now.duration_since(*last_change) >= DEBOUNCE_INTERVAL)
+            .map(|(folder_id, _)| folder_id.clone())
+            .collect::<Vec<_>>();
+        for folder_id in quiet {
+            if let Some((changed, _)) = pending.remove(&folder_id) {
+                on_change(folder_id, changed.into_iter().collect());
+            }
+        }
+    }
+}
+
+// opening and reading files doesn't change them
+fn is_ignored_event(kind: &EventKind) -> bool {
+    matches!(kind, EventKind::Access(access) if *access != AccessKind::Close(AccessMode::Write))
+}
+
+// the folder containing the path and the path relative to it, paths in hidden folders like
+// `.git` are skipped
+fn watched_path(folders: &HashMap<String, PathBuf>, path: &Path) -> Option<(String, String)> {
+    folders
+        .iter()
+        .filter_map(|(folder_id, root)| {
+            let relative_path = path.strip_prefix(root).ok()?;
+            Some((folder_id, root, relative_path))
+        })
+        // nested watched folders, the innermost one wins
+        .max_by_key(|(_, root, _)| root.components().count())
+        .and_then(|(folder_id, _, relative_path)| {
+            relative_path_key(relative_path).map(|key| (folder_id.clone(), key))
+        })
+}
+
+// watched files are keyed by their relative path joined with `/`
+pub fn relative_path_key(path: &Path) -> Option<String> {
+    let mut parts = vec![];
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => {
+                let part = part.to_string_lossy();
+                if is_hidden(&part) {
+                    return None;
+                }
+                parts.push(part.into_owned());
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+// the files to check for the changed paths, the ones on disk below them and the tracked ones
+// below them that may have vanished
+pub fn changed_files(
+    root: &Path,
+    changed: &[String],
+    tracked: &[String],
+) -> BackendResult<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    for changed_path in changed {
+        let path = root.join(changed_path);
+        if path.is_dir() {
+            for file in scan_vault(&path)?.files {
+                if let Some(key) = relative_path_key(&Path::new(changed_path).join(file)) {
+                    files.insert(key);
+                }
+            }
+        } else if path.is_file() {
+            files.insert(changed_path.clone());
+        }
+
+        let prefix = format!("{changed_path}/");
+        files.extend(
+            tracked
+                .iter()
+                .filter(|file| {
+                    changed_path.is_empty() || *file == changed_path || file.starts_with(&prefix)
+                })
+                .cloned(),
+        );
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_changed_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("projects/.git")).unwrap();
+        std::fs::write(root.join("inbox.md"), "inbox").unwrap();
+        std::fs::write(root.join("projects/plan.md"), "plan").unwrap();
+        std::fs::write(root.join("projects/.git/HEAD"), "ref").unwrap();
+
+        let tracked = vec![
+            "inbox.md".to_owned(),
+            "projects/old.md".to_owned(),
+            "projects-archive/old.md".to_owned(),
+        ];
+        let files = |changed: &[&str]| {
+            let changed = changed
+                .iter()
+                .map(|path| path.to_string())
+                .collect::<Vec<_>>();
+            changed_files(root, &changed, &tracked)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            files(&["projects"]),
+            vec!["projects/old.md", "projects/plan.md"]
+        );
+        assert_eq!(files(&["inbox.md", "gone.md"]), vec!["inbox.md"]);
+        assert_eq!(
+            files(&[""]),
+            vec![
+                "inbox.md",
+                "projects-archive/old.md",
+                "projects/old.md",
+                "projects/plan.md"
+            ]
+        );
+
+        let folders = HashMap::from([
+            ("notes".to_owned(), root.to_path_buf()),
+            ("projects".to_owned(), root.join("projects")),
+        ]);
+        assert_eq!(
+            watched_path(&folders, &root.join("projects/plan.md")),
+            Some(("projects".to_owned(), "plan.md".to_owned()))
+        );
+        assert_eq!(
+            watched_path(&folders, &root.join("inbox.md")),
+            Some(("notes".to_owned(), "inbox.md".to_owned()))
+        );
+        assert_eq!(
+            watched_path(&folders, &root.join("projects/.git/HEAD")),
+            None
+        );
+        assert_eq!(
+            watched_path(&folders, Path::new("/elsewhere/file.md")),
+            None
+        );
+    }
+}
@@ -1,5 +1,6 @@
 import {
   BROWSER_TYPE_DATA,
+  ProcessingPriority,
   ResourceTagDataStateValue,
   SpaceEntryOrigin,
   type BrowserType
@@ -72,7 +73,8 @@ export class Importer {
                 name: item.title,
                 sourceURI: item.url
               },
-              [ResourceTag.import(), ResourceTag.dataState(ResourceTagDataStateValue.PARTIAL)]
+              [ResourceTag.import(), ResourceTag.dataState(ResourceTagDataStateValue.PARTIAL)],
+              { deferPostProcessing: true }
             )
 
             resources.push(resource)
@@ -88,6 +90,15 @@ export class Importer {
       })
     )
 
+    // queued as one batch in the import lane so the import doesn't hold up what the user saves
+    // in the meantime
+    if (importedResources.length > 0) {
+      await this.resourceManager.sffs.batchPostProcessResources(
+        importedResources.map((resource) => resource.id),
+        ProcessingPriority.BackgroundImport
+      )
+    }
+
     this.log.debug('imported resources', importedResources)
     return importedResources
   }
@@ -56,6 +56,7 @@ import {
   ResourceProcessingStateType,
   ResourceTagDataStateValue,
   NotebookDefaults,
+  ProcessingPriority,
   type DetectedResource,
   type EventBusMessage,
   type ResourceData,
@@ -367,6 +368,7 @@ export class Resource extends EventEmitterBase<ResourceEventHandlers> {
       [ResourceProcessingStateType.Pending]: 'running',
       [ResourceProcessingStateType.Started]: 'running',
       [ResourceProcessingStateType.Failed]: 'error',
+      [ResourceProcessingStateType.DeadLetter]: 'error',
       [ResourceProcessingStateType.Finished]: 'idle'
     }
     this.extractionState = writable('idle')
@@ -442,7 +444,7 @@ export class Resource extends EventEmitterBase<ResourceEventHandlers> {
     return this.readDataPromise
   }
 
-  async writeData() {
+  async writeData(deferPostProcessing = false) {
     if (this.dummy) {
       this.log.debug('skipping writing resource data for dummy resource')
       return
@@ -455,7 +457,7 @@ export class Resource extends EventEmitterBase<ResourceEventHandlers> {
       return
     }
 
-    await this.sffs.writeDataFile(this.id, this.type, this.path, this.rawData)
+    await this.sffs.writeDataFile(this.id, this.type, this.path, this.rawData, deferPostProcessing)
   }
 
   updateData(data: Blob, write = true) {
@@ -843,7 +845,10 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
       resource.updatePostProcessingState('running')
     } else if (status === ResourceProcessingStateType.Finished) {
       resource.updatePostProcessingState('idle')
-    } else if (status === ResourceProcessingStateType.Failed) {
+    } else if (
+      status === ResourceProcessingStateType.Failed ||
+      status === ResourceProcessingStateType.DeadLetter
+    ) {
       resource.updatePostProcessingState('error')
     }
   }
@@ -903,7 +908,8 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
     type: string,
     data?: Blob,
     metadata?: Partial<SFFSResourceMetadata>,
-    tags?: SFFSResourceTag[]
+    tags?: SFFSResourceTag[],
+    opts?: { deferPostProcessing?: boolean }
   ) {
     this.log.debug('creating resource', type, data, metadata, tags)
     const parsedMetadata = Object.assign(
@@ -952,7 +958,7 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
     // store the data in the resource and write it to sffs
     if (data) {
       resource.rawData = data
-      await resource.writeData()
+      await resource.writeData(opts?.deferPostProcessing)
     }
 
     this.emit(ResourceManagerEvents.Created, resource)
@@ -1563,7 +1569,8 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
   async createResourceLink(
     data: Partial<ResourceDataLink>,
     metadata?: Partial<SFFSResourceMetadata>,
-    tags?: SFFSResourceTag[]
+    tags?: SFFSResourceTag[],
+    opts?: { deferPostProcessing?: boolean }
   ) {
     const blob = await this.createFormattedResourceBlob(ResourceTypes.LINK, data as ResourceData)
 
@@ -1593,7 +1600,8 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
       ResourceTypes.LINK,
       blob,
       fullMetadata,
-      allTags
+      allTags,
+      opts
     ) as Promise<ResourceLink>
   }
 
@@ -1883,6 +1891,13 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
     }
   }
 
+  // runs the post processing of existing resources again in the re-index lane so it doesn't
+  // hold up newly saved resources
+  async reprocessResources(resourceIds: string[]) {
+    this.log.debug('re-running post processing', resourceIds)
+    return this.sffs.batchPostProcessResources(resourceIds, ProcessingPriority.Reindex)
+  }
+
   async refreshResourceData(resourceOrId: ResourceObject | string) {
     const resource =
       typeof resourceOrId === 'string' ? await this.getResource(resourceOrId) : resourceOrId
@@ -1907,7 +1922,7 @@ export class ResourceManager extends EventEmitterBase<ResourceManagerEventHandle
           resource.id,
           resource.type
         )
-        await this.sffs.backend.js__store_resource_post_process(resource.id)
+        await this.reprocessResources([resource.id])
 
         if ((resource.tags ?? []).find((x) => x.name === ResourceTagsBuiltInKeys.DATA_STATE)) {
           await this.updateResourceTag(
@@ -1,4 +1,5 @@
 import { isDev, useLogScope, type ScopedLogger } from '@deta/utils'
+import { ProcessingPriority } from '@deta/types'
 import type {
   BookmarkFolder,
   BrowserType,
@@ -31,6 +32,42 @@ import type {
   AIChatRaw,
   SpaceEntrySearchOptions,
   SFFSRawBookmarkFolder,
+  SFFSRawMailImportResult,
+  MailImportResult,
+  SFFSRawVaultImportResult,
+  SFFSRawVaultExportResult,
+  VaultImportResult,
+  VaultExportResult,
+  SFFSRawWatchedFolder,
+  SFFSRawWatchedFolderSyncResult,
+  SFFSRawPostProcessingJob,
+  PostProcessingJob,
+  PostProcessingBatchProgress,
+  ResourceProcessingStateType,
+  WatchedFolder,
+  WatchedFolderSyncResult,
+  SFFSRawResourceLink,
+  SFFSRawResourceGraph,
+  ResourceLink,
+  ResourceGraph,
+  SFFSRawResourceVersion,
+  SFFSRawResourceVersionContent,
+  SFFSRawResourceVersionDiff,
+  ResourceVersion,
+  ResourceVersionContent,
+  ResourceVersionDiff,
+  SFFSRawDuplicateReport,
+  SFFSRawResourceMergeResult,
+  DuplicateGroup,
+  ResourceMergeResult,
+  ResourceFilter,
+  SFFSRawResourceFilter,
+  SearchSnippet,
+  SFFSRawSearchSnippet,
+  SearchLanguage,
+  ResourceFacets,
+  SFFSPage,
+  SFFSExtractedContent,
   AIChatData,
   AIChatMessage,
   AIChatMessageSource,
@@ -121,6 +158,36 @@ export class SFFS {
     this.registeredTools.delete(toolId)
   }
 
+  async registerExtractor(
+    name: string,
+    mimeTypes: string[],
+    handler: (
+      resource: SFFSRawCompositeResource
+    ) => SFFSExtractedContent[] | Promise<SFFSExtractedContent[]>
+  ): Promise<void> {
+    const wrappedHandler = async (resourceJson: string) => {
+      const result = await handler(JSON.parse(resourceJson))
+      return JSON.stringify(result ?? [])
+    }
+
+    await this.withErrorHandling(
+      this.backend,
+      this.backend.js__backend_register_extractor,
+      name,
+      JSON.stringify(mimeTypes),
+      wrappedHandler
+    )
+  }
+
+  async unregisterExtractor(name: string): Promise<boolean> {
+    const removed = await this.withErrorHandling(
+      this.backend,
+      this.backend.js__backend_unregister_extractor,
+      name
+    )
+    return removed === 'true'
+  }
+
   convertCompositeResourceToResource(composite: SFFSRawCompositeResource): SFFSResource {
     return {
       id: composite.resource.id,
@@ -133,7 +200,15 @@ export class SFFS {
         name: composite.metadata?.name ?? '',
         sourceURI: composite.metadata?.source_uri ?? '',
         alt: composite.metadata?.alt ?? '',
-        userContext: composite.metadata?.user_context ?? ''
+        userContext: composite.metadata?.user_context ?? '',
+        byline: composite.metadata?.byline ?? undefined,
+        publishedAt: composite.metadata?.published_at ?? undefined,
+        leadImage: composite.metadata?.lead_image ?? undefined,
+        outline: composite.metadata?.outline?.map((entry) => ({
+          title: entry.title,
+          level: entry.level,
+          page: entry.page ?? undefined
+        }))
       },
       tags: (composite.resource_tags || []).map((tag) =>
         this.convertRawResourceTagToResourceTag(tag)
@@ -150,7 +225,14 @@ export class SFFS {
         }
       }),
       postProcessingState: composite.post_processing_job?.state,
-      spaceIds: composite.space_ids ?? []
+      spaceIds: composite.space_ids ?? [],
+      thumbnail: composite.thumbnail
+        ? {
+            path: composite.thumbnail.path,
+            width: composite.thumbnail.width,
+            height: composite.thumbnail.height
+          }
+        : undefined
     }
   }
 
@@ -223,6 +305,113 @@ export class SFFS {
     }
   }
 
+  convertRawPostProcessingJobToPostProcessingJob(
+    raw: SFFSRawPostProcessingJob
+  ): PostProcessingJob {
+    return {
+      id: raw.id,
+      resourceId: raw.resource_id,
+      state: raw.state,
+      priority: raw.priority,
+      batchId: raw.batch_id,
+      attempts: raw.attempts,
+      maxAttempts: raw.max_attempts,
+      nextRunAt: raw.next_run_at,
+      lastError: raw.last_error,
+      createdAt: raw.created_at,
+      updatedAt: raw.updated_at
+    }
+  }
+
+  convertRawWatchedFolderToWatchedFolder(raw: SFFSRawWatchedFolder): WatchedFolder {
+    return {
+      id: raw.id,
+      path: raw.path,
+      spaceId: raw.space_id,
+      createdAt: raw.created_at
+    }
+  }
+
+  convertRawResourceLinkToResourceLink(raw: SFFSRawResourceLink): ResourceLink {
+    return {
+      id: raw.id,
+      sourceId: raw.source_id,
+      targetId: raw.target_id,
+      kind: raw.kind,
+      context: raw.context ?? undefined,
+      createdAt: raw.created_at
+    }
+  }
+
+  convertRawResourceVersionToResourceVersion(raw: SFFSRawResourceVersion): ResourceVersion {
+    return {
+      id: raw.id,
+      resourceId: raw.resource_id,
+      contentHash: raw.content_hash,
+      size: raw.size,
+      createdAt: raw.created_at
+    }
+  }
+
+  convertResourceFilterToRaw(filter: ResourceFilter): SFFSRawResourceFilter {
+    switch (filter.type) {
+      case 'and':
+      case 'or':
+        return {
+          type: filter.type,
+          filters: filter.filters.map((filter) => this.convertResourceFilterToRaw(filter))
+        }
+      case 'not':
+        return { type: 'not', filter: this.convertResourceFilterToRaw(filter.filter) }
+      case 'tag':
+        return {
+          type: 'tag',
+          tag_name: filter.name ?? '',
+          tag_value: filter.value ?? '',
+          op: filter.op ?? 'eq'
+        }
+      case 'resource_type':
+        return {
+          type: 'resource_type',
+          resource_type: filter.resourceType,
+          prefix: filter.prefix ?? false
+        }
+      default:
+        return { type: filter.type, after: filter.after, before: filter.before }
+    }
+  }
+
+  // plain lists of tags are sent in the format the backend always accepted
+  stringifyResourceFilter(filter: SFFSResourceTag[] | ResourceFilter): string {
+    if (!Array.isArray(filter)) {
+      return JSON.stringify(this.convertResourceFilterToRaw(filter))
+    }
+    return JSON.stringify(
+      filter.map(
+        (tag) =>
+          ({
+            id: '',
+            resource_id: '',
+            tag_name: tag.name ?? '',
+            tag_value: tag.value ?? '',
+            op: tag.op ?? 'eq'
+          }) as SFFSRawResourceTag
+      )
+    )
+  }
+
+  convertRawSearchSnippetToSearchSnippet(raw: SFFSRawSearchSnippet): SearchSnippet {
+    return {
+      contentId: raw.content_id,
+      contentType: raw.content_type,
+      text: raw.text,
+      highlights: raw.highlights,
+      page: raw.page,
+      timestamp: raw.timestamp,
+      section: raw.section
+    }
+  }
+
   convertRawSpaceToSpace(raw: any): Space {
     const parsedName = this.parseData<SpaceData>(raw.name)
     const nameData =
@@ -398,7 +587,11 @@ export class SFFS {
       name: metadata.name,
       source_uri: metadata.sourceURI,
       alt: metadata.alt,
-      user_context: metadata.userContext
+      user_context: metadata.userContext,
+      // the backend stores these as given, leaving them out would clear them
+      byline: metadata.byline ?? null,
+      published_at: metadata.publishedAt ?? null,
+      lead_image: metadata.leadImage ?? null
     } as SFFSRawResourceMetadata)
 
     const result = this.backend.js__store_update_resource_metadata(stringified)
@@ -456,9 +649,9 @@ export class SFFS {
     await this.backend.js__store_remove_resources(ids)
   }
 
-  async deleteResourcesByTags(tags: SFFSResourceTag[]): Promise<void> {
+  async deleteResourcesByTags(tags: SFFSResourceTag[] | ResourceFilter): Promise<void> {
     this.log.debug('deleting resources by tags', tags)
-    await this.backend.js__store_remove_resources_by_tags(tags)
+    await this.backend.js__store_remove_resources_by_tags(this.stringifyResourceFilter(tags))
   }
 
   async recoverResource(id: string): Promise<void> {
@@ -466,26 +659,60 @@ export class SFFS {
     await this.backend.js__store_recover_resource(id)
   }
 
+  async postProcessResource(
+    resourceId: string,
+    priority: ProcessingPriority = ProcessingPriority.UserInitiated
+  ): Promise<PostProcessingJob | null> {
+    this.log.debug('post processing resource', resourceId, priority)
+    const raw = await this.backend.js__store_resource_post_process(resourceId, priority)
+    const job = this.parseData<SFFSRawPostProcessingJob>(raw)
+    return job ? this.convertRawPostProcessingJobToPostProcessingJob(job) : null
+  }
+
+  // the progress of the batch is reported on the event bus under the returned `batch_id`
+  async batchPostProcessResources(
+    resourceIds: string[],
+    priority: ProcessingPriority
+  ): Promise<PostProcessingBatchProgress | null> {
+    this.log.debug('post processing resources', resourceIds.length, priority)
+    const raw = await this.backend.js__store_batch_resource_post_process(
+      JSON.stringify(resourceIds),
+      priority
+    )
+    return this.parseData<PostProcessingBatchProgress>(raw)
+  }
+
+  // e.g. the dead-lettered jobs that ran out of attempts, most recently updated first
+  async listPostProcessingJobs(
+    state?: ResourceProcessingStateType,
+    limit?: number
+  ): Promise<PostProcessingJob[]> {
+    this.log.debug('listing post processing jobs', state, limit)
+    const raw = await this.backend.js__store_list_post_processing_jobs(state, limit)
+    const jobs = this.parseData<SFFSRawPostProcessingJob[]>(raw) ?? []
+    return jobs.map((job) => this.convertRawPostProcessingJobToPostProcessingJob(job))
+  }
+
+  // queues the job again with all of its attempts, fails for jobs superseded by a newer one
+  async retryPostProcessingJob(jobId: string): Promise<PostProcessingJob | null> {
+    this.log.debug('retrying post processing job', jobId)
+    const raw = await this.backend.js__store_retry_post_processing_job(jobId)
+    const job = this.parseData<SFFSRawPostProcessingJob>(raw)
+    return job ? this.convertRawPostProcessingJobToPostProcessingJob(job) : null
+  }
+
   async readResources(): Promise<SFFSResource[]> {
     this.log.debug('reading all resources')
     const items = await this.backend.js__store_get_resources()
     return items.map(this.convertCompositeResourceToResource)
   }
 
-  async listResourceIDsByTags(tags: SFFSResourceTag[], excludeWithinSpaces: boolean = false) {
+  async listResourceIDsByTags(
+    tags: SFFSResourceTag[] | ResourceFilter,
+    excludeWithinSpaces: boolean = false
+  ) {
     this.log.debug('listing resources by tags', tags, excludeWithinSpaces)
-    const tagsData = JSON.stringify(
-      tags.map(
-        (tag) =>
-          ({
-            id: '',
-            resource_id: '',
-            tag_name: tag.name,
-            tag_value: tag.value,
-            op: tag.op ?? 'eq'
-          }) as SFFSRawResourceTag
-      )
-    )
+    const tagsData = this.stringifyResourceFilter(tags)
 
     let raw: string
     if (excludeWithinSpaces) {
@@ -498,20 +725,57 @@ export class SFFS {
     return parsed?.items ?? []
   }
 
-  async listAllResourcesAndSpaces(tags: SFFSResourceTag[]) {
-    this.log.debug('listing all resources and spaces by tags', tags)
-    const tagsData = JSON.stringify(
-      tags.map(
-        (tag) =>
-          ({
-            id: '',
-            resource_id: '',
-            tag_name: tag.name,
-            tag_value: tag.value,
-            op: tag.op ?? 'eq'
-          }) as SFFSRawResourceTag
-      )
+  // the ids with the counts of the resources by type, hostname, space, creation date and hashtag
+  async listResourceIDsWithFacetsByTags(
+    tags: SFFSResourceTag[] | ResourceFilter
+  ): Promise<{ items: string[]; facets: ResourceFacets | null }> {
+    this.log.debug('listing resources with facets by tags', tags)
+    const raw = await this.backend.js__store_list_resources_by_tags(
+      this.stringifyResourceFilter(tags),
+      true
     )
+    const parsed = this.parseData<{ items: string[]; facets: ResourceFacets | null }>(raw)
+    return {
+      items: parsed?.items ?? [],
+      facets: parsed?.facets ?? null
+    }
+  }
+
+  // newest first, the total counts the resources of all pages
+  async listResourceIDsPageByTags(
+    tags: SFFSResourceTag[] | ResourceFilter,
+    opts?: { cursor?: string | null; limit?: number; excludeWithinSpaces?: boolean }
+  ): Promise<SFFSPage<string> & { total: number }> {
+    this.log.debug('listing page of resources by tags', tags, opts)
+    const tagsData = this.stringifyResourceFilter(tags)
+
+    let raw: string
+    if (opts?.excludeWithinSpaces) {
+      raw = await this.backend.js__store_list_resources_by_tags_no_space(
+        tagsData,
+        opts?.cursor ?? '',
+        opts?.limit
+      )
+    } else {
+      raw = await this.backend.js__store_list_resources_by_tags(
+        tagsData,
+        false,
+        opts?.cursor ?? '',
+        opts?.limit
+      )
+    }
+
+    const parsed = this.parseData<SFFSPage<string> & { total: number }>(raw)
+    return {
+      items: parsed?.items ?? [],
+      total: parsed?.total ?? 0,
+      next_cursor: parsed?.next_cursor ?? null
+    }
+  }
+
+  async listAllResourcesAndSpaces(tags: SFFSResourceTag[] | ResourceFilter) {
+    this.log.debug('listing all resources and spaces by tags', tags)
+    const tagsData = this.stringifyResourceFilter(tags)
     const raw = await this.backend.js__store_list_all_resources_and_spaces(tagsData)
     const parsed = this.parseData<SFFSResourceOrSpace[]>(raw)
     return parsed ?? []
@@ -519,7 +783,7 @@ export class SFFS {
 
   async searchResources(
     query: string,
-    tags?: SFFSResourceTag[],
+    tags?: SFFSResourceTag[] | ResourceFilter,
     parameters?: SFFSSearchParameters
   ): Promise<SFFSSearchResult> {
     this.log.debug(
@@ -530,18 +794,7 @@ export class SFFS {
       'and parameters',
       parameters
     )
-    const tagsData = JSON.stringify(
-      (tags ?? []).map(
-        (tag) =>
-          ({
-            id: '',
-            resource_id: '',
-            tag_name: tag.name ?? '',
-            tag_value: tag.value ?? '',
-            op: tag.op ?? 'eq'
-          }) as SFFSRawResourceTag
-      )
-    )
+    const tagsData = this.stringifyResourceFilter(tags ?? [])
     const raw = await this.backend.js__store_search_resources(
       query,
       tagsData,
@@ -550,7 +803,9 @@ export class SFFS {
       parameters?.semanticLimit,
       parameters?.includeAnnotations,
       parameters?.spaceId,
-      parameters?.keywordLimit
+      parameters?.keywordLimit,
+      parameters?.snippetLength,
+      parameters?.includeFacets
     )
     const parsed = this.parseData<SFFSSearchResult>(raw)
     const parsedItems = parsed?.items ?? []
@@ -559,7 +814,10 @@ export class SFFS {
     const items = parsedItems.map((item) => ({
       ...item,
       engine: item.engine.toLowerCase() as SFFSSearchResultEngine,
-      resource: this.convertCompositeResourceToResource(item.resource)
+      resource: this.convertCompositeResourceToResource(item.resource),
+      snippets: (item.snippets ?? []).map((snippet) =>
+        this.convertRawSearchSnippetToSearchSnippet(snippet)
+      )
     }))
 
     const spaces = parsedSpaces.map((space) => ({
@@ -569,7 +827,9 @@ export class SFFS {
     return {
       items,
       spaces,
-      space_entries: parsed?.space_entries
+      space_entries: parsed?.space_entries,
+      query_errors: parsed?.query_errors,
+      facets: parsed?.facets
     }
   }
 
@@ -708,6 +968,25 @@ export class SFFS {
     return entries
   }
 
+  async getSpaceContentsPage(
+    space_id: string,
+    opts?: Omit<SpaceEntrySearchOptions, 'search_query'> & { cursor?: string | null }
+  ): Promise<SFFSPage<SpaceEntry>> {
+    this.log.debug('getting page of space entries for space with id', space_id, opts)
+    const raw = await this.backend.js__store_get_space_entries_page(
+      space_id,
+      opts?.sort_by,
+      opts?.order,
+      opts?.cursor ?? '',
+      opts?.limit
+    )
+    const parsed = this.parseData<SFFSPage<SpaceEntry>>(raw)
+    return {
+      items: parsed?.items ?? [],
+      next_cursor: parsed?.next_cursor ?? null
+    }
+  }
+
   // NOTE: the ids here are the ids of the entries themselves and NOT THE RESOURCE/SPACE IDS
   async deleteSpaceEntries(ids: string[], isResourceType = true): Promise<void> {
     this.log.debug('deleting space entries with ids', ids)
@@ -770,15 +1049,17 @@ export class SFFS {
     return uInt8
   }
 
+  // `deferPostProcessing` is for callers that queue the post processing themselves
   async writeDataFile(
     resourceId: string,
     resourceType: string,
     resourcePath: string,
-    data: Blob
+    data: Blob,
+    deferPostProcessing = false
   ): Promise<void> {
     this.log.debug('writing data file', data)
 
-    await this.fs.openResource(resourceId, resourceType, resourcePath, 'w')
+    await this.fs.openResource(resourceId, resourceType, resourcePath, 'w', deferPostProcessing)
 
     const buffer = await data.arrayBuffer()
 
@@ -821,6 +1102,20 @@ export class SFFS {
     return entries.map((e) => this.convertRawHistoryEntryToHistoryEntry(e))
   }
 
+  // newest first
+  async getHistoryEntriesPage(
+    cursor?: string | null,
+    limit?: number
+  ): Promise<SFFSPage<HistoryEntry>> {
+    this.log.debug('getting page of history entries', cursor, limit)
+    const raw = await this.backend.js__store_get_history_entries_page(cursor ?? '', limit)
+    const parsed = this.parseData<SFFSPage<SFFSRawHistoryEntry>>(raw)
+    return {
+      items: (parsed?.items ?? []).map((e) => this.convertRawHistoryEntryToHistoryEntry(e)),
+      next_cursor: parsed?.next_cursor ?? null
+    }
+  }
+
   async updateHistoryEntry(data: Partial<HistoryEntry>): Promise<void> {
     this.log.debug('updating history entry', data)
     const rawEntry = this.convertHistoryEntryToRawHistoryEntry(data as HistoryEntry)
@@ -860,6 +1155,241 @@ export class SFFS {
     return entries.map((e) => this.convertRawBookmarkFolderToBookmarkFolder(e))
   }
 
+  // imports an `.eml` or `.mbox` file, messages and attachments are post processed in a batch
+  async importMail(path: string): Promise<MailImportResult | null> {
+    this.log.debug('importing mail from', path)
+    const raw = await this.backend.js__store_import_mail(path)
+    const result = this.parseData<SFFSRawMailImportResult>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      resourceIds: result.resource_ids,
+      attachmentIds: result.attachment_ids,
+      spaceIds: result.space_ids,
+      batchId: result.batch_id ?? undefined
+    }
+  }
+
+  // folders become nested spaces, markdown files notes and every other file a resource
+  async importMarkdownVault(path: string): Promise<VaultImportResult | null> {
+    this.log.debug('importing markdown vault from', path)
+    const raw = await this.backend.js__store_import_markdown_vault(path)
+    const result = this.parseData<SFFSRawVaultImportResult>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      resourceIds: result.resource_ids,
+      attachmentIds: result.attachment_ids,
+      spaceIds: result.space_ids,
+      rootSpaceId: result.root_space_id ?? undefined,
+      batchId: result.batch_id ?? undefined
+    }
+  }
+
+  async exportSpaceMarkdownVault(spaceId: string, path: string): Promise<VaultExportResult | null> {
+    this.log.debug('exporting space as markdown vault', spaceId, path)
+    const raw = await this.backend.js__store_export_space_markdown_vault(spaceId, path)
+    const result = this.parseData<SFFSRawVaultExportResult>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      notePaths: result.note_paths,
+      attachmentPaths: result.attachment_paths
+    }
+  }
+
+  // files of the folder are kept in sync with resources in the space, a space named after the
+  // folder is created without one
+  async addWatchedFolder(path: string, spaceId?: string): Promise<WatchedFolder | null> {
+    this.log.debug('adding watched folder', path, spaceId)
+    const raw = await this.backend.js__store_add_watched_folder(path, spaceId)
+    const folder = this.parseData<SFFSRawWatchedFolder>(raw)
+    return folder ? this.convertRawWatchedFolderToWatchedFolder(folder) : null
+  }
+
+  async removeWatchedFolder(folderId: string): Promise<void> {
+    this.log.debug('removing watched folder', folderId)
+    await this.backend.js__store_remove_watched_folder(folderId)
+  }
+
+  async listWatchedFolders(): Promise<WatchedFolder[]> {
+    this.log.debug('listing watched folders')
+    const raw = await this.backend.js__store_list_watched_folders()
+    const folders = this.parseData<SFFSRawWatchedFolder[]>(raw) ?? []
+    return folders.map((folder) => this.convertRawWatchedFolderToWatchedFolder(folder))
+  }
+
+  async syncWatchedFolder(folderId: string): Promise<WatchedFolderSyncResult | null> {
+    this.log.debug('syncing watched folder', folderId)
+    const raw = await this.backend.js__store_sync_watched_folder(folderId)
+    const result = this.parseData<SFFSRawWatchedFolderSyncResult>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      createdIds: result.created_ids,
+      updatedIds: result.updated_ids,
+      deletedIds: result.deleted_ids,
+      batchId: result.batch_id ?? undefined
+    }
+  }
+
+  async createResourceLink(
+    sourceId: string,
+    targetId: string,
+    context?: string
+  ): Promise<ResourceLink | null> {
+    this.log.debug('creating resource link', sourceId, targetId)
+    const raw = await this.backend.js__store_create_resource_link(sourceId, targetId, context)
+    const link = this.parseData<SFFSRawResourceLink>(raw)
+    return link ? this.convertRawResourceLinkToResourceLink(link) : null
+  }
+
+  // only links created by the user can be removed, the others come from the resource content
+  async removeResourceLink(linkId: string): Promise<void> {
+    this.log.debug('removing resource link', linkId)
+    await this.backend.js__store_remove_resource_link(linkId)
+  }
+
+  async listResourceLinks(resourceId: string): Promise<ResourceLink[]> {
+    this.log.debug('listing resource links', resourceId)
+    const raw = await this.backend.js__store_list_resource_links(resourceId)
+    const links = this.parseData<SFFSRawResourceLink[]>(raw) ?? []
+    return links.map((link) => this.convertRawResourceLinkToResourceLink(link))
+  }
+
+  async listResourceBacklinks(resourceId: string): Promise<ResourceLink[]> {
+    this.log.debug('listing resource backlinks', resourceId)
+    const raw = await this.backend.js__store_list_resource_backlinks(resourceId)
+    const links = this.parseData<SFFSRawResourceLink[]>(raw) ?? []
+    return links.map((link) => this.convertRawResourceLinkToResourceLink(link))
+  }
+
+  // the resources within `hops` links of the resource in either direction
+  async getResourceGraph(resourceId: string, hops = 1): Promise<ResourceGraph | null> {
+    this.log.debug('getting resource graph', resourceId, hops)
+    const raw = await this.backend.js__store_get_resource_graph(resourceId, hops)
+    const graph = this.parseData<SFFSRawResourceGraph>(raw)
+    if (!graph) {
+      return null
+    }
+
+    return {
+      resourceIds: graph.resource_ids,
+      links: graph.links.map((link) => this.convertRawResourceLinkToResourceLink(link))
+    }
+  }
+
+  // newest first, a version is kept for each change of the content up to a limit
+  async listResourceVersions(resourceId: string): Promise<ResourceVersion[]> {
+    this.log.debug('listing resource versions', resourceId)
+    const raw = await this.backend.js__store_list_resource_versions(resourceId)
+    const versions = this.parseData<SFFSRawResourceVersion[]>(raw) ?? []
+    return versions.map((version) => this.convertRawResourceVersionToResourceVersion(version))
+  }
+
+  async getResourceVersion(versionId: string): Promise<ResourceVersionContent | null> {
+    this.log.debug('getting resource version', versionId)
+    const raw = await this.backend.js__store_get_resource_version(versionId)
+    const result = this.parseData<SFFSRawResourceVersionContent>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      version: this.convertRawResourceVersionToResourceVersion(result.version),
+      content: result.content
+    }
+  }
+
+  async diffResourceVersions(
+    fromVersionId: string,
+    toVersionId: string
+  ): Promise<ResourceVersionDiff | null> {
+    this.log.debug('diffing resource versions', fromVersionId, toVersionId)
+    const raw = await this.backend.js__store_diff_resource_versions(fromVersionId, toVersionId)
+    const diff = this.parseData<SFFSRawResourceVersionDiff>(raw)
+    if (!diff) {
+      return null
+    }
+
+    return {
+      fromVersionId: diff.from_version_id,
+      toVersionId: diff.to_version_id,
+      insertions: diff.insertions,
+      deletions: diff.deletions,
+      lines: diff.lines
+    }
+  }
+
+  // the restored content becomes the newest version
+  async restoreResourceVersion(versionId: string): Promise<ResourceVersion | null> {
+    this.log.debug('restoring resource version', versionId)
+    const raw = await this.backend.js__store_restore_resource_version(versionId)
+    const version = this.parseData<SFFSRawResourceVersion>(raw)
+    return version ? this.convertRawResourceVersionToResourceVersion(version) : null
+  }
+
+  // resources saved more than once by source url or content, `includeSimilar` also compares
+  // their text with the embedding model which takes longer
+  async findDuplicateResources(includeSimilar = false): Promise<DuplicateGroup[]> {
+    this.log.debug('finding duplicate resources', includeSimilar)
+    const raw = await this.backend.js__store_find_duplicate_resources(includeSimilar)
+    const report = this.parseData<SFFSRawDuplicateReport>(raw)
+    return (report?.groups ?? []).map((group) => ({
+      reason: group.reason,
+      key: group.key ?? undefined,
+      resourceIds: group.resource_ids,
+      similarity: group.similarity ?? undefined
+    }))
+  }
+
+  // the tags, spaces, annotations and links of the other resources are added to the target,
+  // the other resources are moved to the trash
+  async mergeResources(
+    targetId: string,
+    sourceIds: string[]
+  ): Promise<ResourceMergeResult | null> {
+    this.log.debug('merging resources', targetId, sourceIds)
+    const raw = await this.backend.js__store_merge_resources(targetId, sourceIds)
+    const result = this.parseData<SFFSRawResourceMergeResult>(raw)
+    if (!result) {
+      return null
+    }
+
+    return {
+      resourceId: result.resource_id,
+      mergedIds: result.merged_ids,
+      annotationIds: result.annotation_ids
+    }
+  }
+
+  async getSearchLanguage(): Promise<SearchLanguage | null> {
+    const raw = await this.backend.js__store_get_search_language()
+    return this.parseData<SearchLanguage>(raw)
+  }
+
+  // rebuilds the word search index, this takes a while with many resources
+  async setSearchLanguage(language: SearchLanguage): Promise<void> {
+    this.log.debug('setting search language', language)
+    await this.backend.js__store_set_search_language(language)
+  }
+
+  // the resource data with its title, source, hashtags and other frontmatter tags written
+  // into its frontmatter, importing the markdown again restores them
+  async exportResourceMarkdown(resourceId: string): Promise<string | null> {
+    this.log.debug('exporting resource as markdown', resourceId)
+    const raw = await this.backend.js__store_export_resource_markdown(resourceId)
+    return this.parseData<string>(raw)
+  }
+
   // returns a list of unique hostnames
   async searchHistoryEntriesByHostnamePrefix(
     prefix: string,
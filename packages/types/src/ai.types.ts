@@ -127,10 +127,14 @@ export type YoutubeTranscriptPiece = {
   duration: number
 }
 
+// which step of the language fallback chain the transcript came from
+export type YoutubeTranscriptSource = 'preferred' | 'original' | 'auto_generated' | 'translated'
+
 export type YoutubeTranscript = {
   transcript: string
   metadata: {
-    source: string
+    source?: YoutubeTranscriptSource | null
+    language?: string | null
     transcript_pieces: YoutubeTranscriptPiece[]
   }
 }
@@ -2,21 +2,40 @@ export enum ResourceProcessingStateType {
   Pending = 'pending',
   Started = 'started',
   Failed = 'failed',
+  DeadLetter = 'dead_letter',
   Finished = 'finished'
 }
 
 export enum EventBusMessageType {
-  ResourceProcessingMessage = 'ResourceProcessingMessage'
+  ResourceProcessingMessage = 'ResourceProcessingMessage',
+  PostProcessingBatchProgress = 'PostProcessingBatchProgress'
 }
 
 export type ResourceProcessingState =
   | { type: ResourceProcessingStateType.Pending }
   | { type: ResourceProcessingStateType.Started }
   | { type: ResourceProcessingStateType.Failed; message: string }
+  | { type: ResourceProcessingStateType.DeadLetter; message: string }
   | { type: ResourceProcessingStateType.Finished }
 
-export type EventBusMessage = {
+export type ResourceProcessingMessage = {
   type: EventBusMessageType.ResourceProcessingMessage
   resource_id: string
   status: ResourceProcessingState
 }
+
+export type PostProcessingBatchProgress = {
+  batch_id: string
+  total: number
+  pending: number
+  started: number
+  finished: number
+  // includes jobs that ended up in the dead-letter state
+  failed: number
+}
+
+export type PostProcessingBatchProgressMessage = {
+  type: EventBusMessageType.PostProcessingBatchProgress
+} & PostProcessingBatchProgress
+
+export type EventBusMessage = ResourceProcessingMessage | PostProcessingBatchProgressMessage
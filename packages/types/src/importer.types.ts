@@ -178,3 +178,46 @@ export type BookmarkFolder = {
   lastUsedAt: string
   children: BookmarkItem[]
 }
+
+export type MailImportResult = {
+  // one resource per message, attachments are resources of their own
+  resourceIds: string[]
+  attachmentIds: string[]
+  // spaces created for threads with more than one message
+  spaceIds: string[]
+  // post processing batch of all imported resources
+  batchId?: string
+}
+
+export type VaultImportResult = {
+  // one note per markdown file, every other file becomes an attachment resource
+  resourceIds: string[]
+  attachmentIds: string[]
+  // one space per folder, nested like the folders
+  spaceIds: string[]
+  rootSpaceId?: string
+  // post processing batch of all imported resources
+  batchId?: string
+}
+
+export type VaultExportResult = {
+  notePaths: string[]
+  attachmentPaths: string[]
+}
+
+// local folder whose files are kept in sync with resources in a space
+export type WatchedFolder = {
+  id: string
+  path: string
+  spaceId: string
+  createdAt: string
+}
+
+export type WatchedFolderSyncResult = {
+  createdIds: string[]
+  updatedIds: string[]
+  // soft deleted, they are recovered if their file comes back
+  deletedIds: string[]
+  // post processing batch of the created and updated resources
+  batchId?: string
+}
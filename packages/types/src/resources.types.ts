@@ -19,6 +19,18 @@ export interface SFFSResourceMetadata {
   sourceURI: string
   alt: string
   userContext: string
+  // filled in by the backend from the page itself, e.g. by the article extractor
+  byline?: string
+  publishedAt?: string
+  leadImage?: string
+  outline?: SFFSResourceOutlineEntry[]
+}
+
+export interface SFFSResourceOutlineEntry {
+  title: string
+  // nesting depth, top level entries are 0
+  level: number
+  page?: number
 }
 
 export interface SFFSResourceTag {
@@ -28,6 +40,16 @@ export interface SFFSResourceTag {
   op?: 'eq' | 'ne' | 'prefix' | 'suffix' | 'notexists' | 'neprefix' | 'nesuffix'
 }
 
+// groups of filters combined with and/or/not, a list of tags is the same as an `and` of them
+export type ResourceFilter =
+  | { type: 'and'; filters: ResourceFilter[] }
+  | { type: 'or'; filters: ResourceFilter[] }
+  | { type: 'not'; filter: ResourceFilter }
+  | ({ type: 'tag' } & SFFSResourceTag)
+  // ISO dates, `after` is inclusive and `before` exclusive
+  | { type: 'created_at' | 'updated_at'; after?: string; before?: string }
+  | { type: 'resource_type'; resourceType: string; prefix?: boolean }
+
 export enum ResourceTagsBuiltInKeys {
   SAVED_WITH_ACTION = 'savedWithAction',
   TYPE = 'type',
@@ -51,7 +73,12 @@ export enum ResourceTagsBuiltInKeys {
   PRELOADED_RESOURCE = 'preloadedResource', // resource was preloaded (e.g. from a web clipper)
   EMPTY_RESOURCE = 'emptyResource', // resource was created as an empty resource (e.g. new note)
   ONBOARDING = 'onboarding', // resource is part of the onboarding flow
-  CAPTION = 'caption' // caption for image resources
+  CAPTION = 'caption', // caption for image resources
+  EMAIL_FROM = 'emailFrom', // sender address of imported mail
+  EMAIL_TO = 'emailTo', // one tag per recipient address of imported mail
+  EMAIL_THREAD = 'emailThread', // message id of the first message of the mail thread
+  ATTACHMENT_OF = 'attachmentOf', // resource id of the mail the attachment belongs to
+  CODE_LANGUAGE = 'codeLanguage' // language of source code resources, e.g. 'rust' or 'python'
 }
 
 export interface ResourceTagsBuiltIn {
@@ -82,6 +109,11 @@ export interface ResourceTagsBuiltIn {
   [ResourceTagsBuiltInKeys.EMPTY_RESOURCE]: boolean
   [ResourceTagsBuiltInKeys.ONBOARDING]: boolean
   [ResourceTagsBuiltInKeys.CAPTION]: string
+  [ResourceTagsBuiltInKeys.EMAIL_FROM]: string
+  [ResourceTagsBuiltInKeys.EMAIL_TO]: string
+  [ResourceTagsBuiltInKeys.EMAIL_THREAD]: string
+  [ResourceTagsBuiltInKeys.ATTACHMENT_OF]: string
+  [ResourceTagsBuiltInKeys.CODE_LANGUAGE]: string
 }
 
 export type UserViewPrefsTagValue = {
@@ -107,6 +139,13 @@ export interface SFFSResource {
   annotations?: SFFSResource[]
   postProcessingState?: ResourceProcessingState
   spaceIds?: string[]
+  thumbnail?: SFFSResourceThumbnail
+}
+
+export interface SFFSResourceThumbnail {
+  path: string
+  width: number
+  height: number
 }
 
 export interface SFFSSpace {
@@ -127,6 +166,8 @@ export interface SFFSSearchGeneralParameters {
   includeAnnotations?: boolean
   spaceId?: string
   keywordLimit?: number // Limit for keyword-based search results
+  snippetLength?: number // characters around the first match, 200 by default
+  includeFacets?: boolean // counts by type, hostname, space, creation date and hashtag
 }
 
 export interface SFFSSearchSemanticParameters {
@@ -137,9 +178,22 @@ export interface SFFSSearchSemanticParameters {
 
 export type SFFSSearchParameters = SFFSSearchGeneralParameters & SFFSSearchSemanticParameters
 
+export interface SearchSnippet {
+  contentId: string
+  contentType: string
+  text: string
+  // character ranges of the matches in `text`
+  highlights: [number, number][]
+  page: number | null
+  timestamp: number | null
+  section: string | null
+}
+
 export interface SFFSSearchResultItem {
   resource: SFFSResource
   engine: SFFSSearchResultEngine
+  // excerpts of the matched text contents, best match first
+  snippets: SearchSnippet[]
 }
 
 export interface SFFSSearchResultItemSpace {
@@ -152,6 +206,7 @@ export enum ResourceTypes {
   HTML = 'text/html',
   JAVASCRIPT = 'text/javascript',
   IMAGE = 'image',
+  MAIL = 'message/rfc822',
 
   SPACE = 'application/vnd.space',
 
@@ -267,6 +322,29 @@ export type DetectedResource<T = ResourceData> = {
 export type ResourceStateCombined = 'idle' | 'extracting' | 'post-processing' | 'error'
 export type ResourceState = 'idle' | 'running' | 'error'
 
+// the processing lane a job waits in, lower lanes don't hold up the ones above them
+export enum ProcessingPriority {
+  UserInitiated = 'user_initiated',
+  BackgroundImport = 'background_import',
+  Reindex = 'reindex'
+}
+
+export type PostProcessingJob = {
+  id: string
+  resourceId: string
+  state: ResourceProcessingState
+  priority: ProcessingPriority
+  // shared by the jobs that were queued together, see `PostProcessingBatchProgress`
+  batchId: string | null
+  attempts: number
+  maxAttempts: number
+  // set while the job waits for its next attempt
+  nextRunAt: string | null
+  lastError: string | null
+  createdAt: string
+  updatedAt: string
+}
+
 export const MARKDOWN_RESOURCE_TYPES = [
   ResourceTypes.LINK,
   ResourceTypes.ARTICLE,
@@ -278,4 +356,68 @@ export const isMarkdownResourceType = (type: string) => {
   return MARKDOWN_RESOURCE_TYPES.findIndex((x) => type.startsWith(x)) !== -1
 }
 
+// links_to and annotates links are extracted from notes and annotations during post processing
+export type ResourceLinkKind = 'links_to' | 'annotates' | 'user'
+
+export type ResourceLink = {
+  id: string
+  sourceId: string
+  targetId: string
+  kind: ResourceLinkKind
+  // text around the link in the source
+  context?: string
+  createdAt: string
+}
+
+export type ResourceGraph = {
+  // the requested resource comes first
+  resourceIds: string[]
+  links: ResourceLink[]
+}
+
+// snapshot of the content of a note or text resource, taken whenever its content changes
+export type ResourceVersion = {
+  id: string
+  resourceId: string
+  contentHash: string
+  size: number
+  createdAt: string
+}
+
+export type ResourceVersionContent = {
+  version: ResourceVersion
+  content: string
+}
+
+export type DiffLineKind = 'equal' | 'insert' | 'delete'
+
+export type ResourceVersionDiff = {
+  fromVersionId: string
+  toVersionId: string
+  insertions: number
+  deletions: number
+  // notes are compared as markdown
+  lines: { kind: DiffLineKind; text: string }[]
+}
+
+export type DuplicateReason = 'source_uri' | 'content_hash' | 'similar_text'
+
+export type DuplicateGroup = {
+  reason: DuplicateReason
+  // the normalized source uri or the content hash
+  key?: string
+  // oldest first
+  resourceIds: string[]
+  // lowest text similarity within the group
+  similarity?: number
+}
+
+export type ResourceMergeResult = {
+  resourceId: string
+  // moved to the trash
+  mergedIds: string[]
+  // annotations of the merged resources, now made on the surviving one
+  annotationIds: string[]
+}
+
 export * from './resources/index.types'
@@ -1,4 +1,10 @@
-import type { ResourceProcessingState } from '@deta/types'
+import type {
+  DiffLineKind,
+  DuplicateReason,
+  ProcessingPriority,
+  ResourceLinkKind,
+  ResourceProcessingState
+} from '@deta/types'
 import { type SpaceEntry } from './spaces.types'
 import { type Optional } from './utils.types'
 import type { AIChatMessageRole, AIChatMessageSource } from './ai.types'
@@ -8,6 +14,17 @@ export type SFFSSearchResultEngineRaw = 'Keyword' | 'Proximity' | 'Semantic'
 export interface SFFSSearchResultRawItem {
   resource: SFFSRawCompositeResource
   engine: SFFSSearchResultEngineRaw
+  snippets?: SFFSRawSearchSnippet[]
+}
+
+export type SFFSRawSearchSnippet = {
+  content_id: string
+  content_type: string
+  text: string
+  highlights: [number, number][]
+  page: number | null
+  timestamp: number | null
+  section: string | null
 }
 
 export interface SFFSSearchResultRawItemSpace {
@@ -20,6 +37,57 @@ export interface SFFSSearchResult {
   spaces: SFFSSearchResultRawItemSpace[]
   total: number
   space_entries?: SpaceEntry[]
+  // set when the query has invalid operators, nothing is searched then
+  query_errors?: SearchQueryError[]
+  // only when asked for with `includeFacets`
+  facets?: ResourceFacets | null
+}
+
+export interface FacetCount {
+  value: string
+  // the display name for values that are ids, like the name of a space
+  label: string | null
+  count: number
+}
+
+// how many resources of a result share a property, the largest counts first
+export interface ResourceFacets {
+  resource_types: FacetCount[]
+  hostnames: FacetCount[]
+  spaces: FacetCount[]
+  // 'past_day', 'past_week', 'past_month', 'past_year' and 'older', in that order
+  created_at: FacetCount[]
+  // hashtags
+  tags: FacetCount[]
+}
+
+// one page of a listing, `next_cursor` is passed back to get the page after it and is null on
+// the last page
+export interface SFFSPage<T> {
+  items: T[]
+  next_cursor: string | null
+}
+
+export type SearchQueryErrorKind =
+  | 'unterminated_quote'
+  | 'empty_value'
+  | 'invalid_date'
+  | 'unknown_type'
+  | 'unknown_space'
+  | 'ambiguous_space'
+  | 'multiple_spaces'
+  | 'unsupported_exclusion'
+  | 'only_exclusions'
+
+// stemming of the word search index, `any` matches whole words of any language
+export type SearchLanguage = 'english' | 'any'
+
+// `start` and `end` are character offsets into the query
+export interface SearchQueryError {
+  kind: SearchQueryErrorKind
+  message: string
+  start: number
+  end: number
 }
 
 /*
@@ -49,6 +117,16 @@ export type SFFSRawResourceMetadata = {
   source_uri: string
   alt: string
   user_context: string
+  byline?: string | null
+  published_at?: string | null
+  lead_image?: string | null
+  outline?: SFFSRawResourceOutlineEntry[] | null
+}
+
+export type SFFSRawResourceOutlineEntry = {
+  title: string
+  level: number
+  page: number | null
 }
 
 export type SFFSRawResourceTag = {
@@ -59,6 +137,18 @@ export type SFFSRawResourceTag = {
   op?: 'eq' | 'ne' | 'prefix' | 'suffix' | 'neprefix' | 'nesuffix'
 }
 
+export type SFFSRawResourceFilter =
+  | { type: 'and' | 'or'; filters: SFFSRawResourceFilter[] }
+  | { type: 'not'; filter: SFFSRawResourceFilter }
+  | {
+      type: 'tag'
+      tag_name: string
+      tag_value: string
+      op?: 'eq' | 'ne' | 'prefix' | 'suffix' | 'notexists' | 'neprefix' | 'nesuffix'
+    }
+  | { type: 'created_at' | 'updated_at'; after?: string; before?: string }
+  | { type: 'resource_type'; resource_type: string; prefix: boolean }
+
 export type SFFSRawResourceTextContent = {
   id: string
   resource_id: string
@@ -72,6 +162,12 @@ export interface SFFSRawPostProcessingJob {
   resource_id: string
   content_hash: string
   state: ResourceProcessingState
+  attempts: number
+  max_attempts: number
+  next_run_at: string | null
+  last_error: string | null
+  priority: ProcessingPriority
+  batch_id: string | null
 }
 
 export interface SFFSRawCompositeResource {
@@ -82,6 +178,54 @@ export interface SFFSRawCompositeResource {
   resource_annotations?: SFFSRawResource[]
   post_processing_job?: SFFSRawPostProcessingJob
   space_ids?: string[]
+  thumbnail?: SFFSRawResourceThumbnail | null
+}
+
+export type SFFSRawResourceThumbnail = {
+  resource_id: string
+  path: string
+  content_hash: string
+  width: number
+  height: number
+  created_at: string
+}
+
+export interface SFFSExtractedContent {
+  // serialized ResourceTextContentType variant, e.g. 'Document' or 'GenericText'
+  content_type: string
+  content: string
+  metadata?: {
+    timestamp?: number | null
+    url?: string | null
+    page?: number | null
+    section?: string | null
+    ocr?: SFFSRawOcrResult
+  }
+}
+
+export type SFFSRawBoundingBox = {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+export type SFFSRawOcrWord = {
+  text: string
+  bbox: SFFSRawBoundingBox
+  confidence: number
+}
+
+export type SFFSRawOcrLine = SFFSRawOcrWord & {
+  words: SFFSRawOcrWord[]
+}
+
+// positions are in pixels of the original image
+export type SFFSRawOcrResult = {
+  language: string | null
+  image_width: number
+  image_height: number
+  lines: SFFSRawOcrLine[]
 }
 
 export interface SFFSRawCard {
@@ -146,6 +290,93 @@ export interface SFFSRawBookmarkFolder {
   children: SFFSRawBookmarkItem[]
 }
 
+export interface SFFSRawMailImportResult {
+  resource_ids: string[]
+  attachment_ids: string[]
+  space_ids: string[]
+  batch_id: string | null
+}
+
+export interface SFFSRawVaultImportResult {
+  resource_ids: string[]
+  attachment_ids: string[]
+  space_ids: string[]
+  root_space_id: string | null
+  batch_id: string | null
+}
+
+export interface SFFSRawVaultExportResult {
+  note_paths: string[]
+  attachment_paths: string[]
+}
+
+export interface SFFSRawWatchedFolder {
+  id: string
+  path: string
+  space_id: string
+  created_at: string
+}
+
+export interface SFFSRawWatchedFolderSyncResult {
+  created_ids: string[]
+  updated_ids: string[]
+  deleted_ids: string[]
+  batch_id: string | null
+}
+
+export interface SFFSRawResourceLink {
+  id: string
+  source_id: string
+  target_id: string
+  kind: ResourceLinkKind
+  context: string | null
+  created_at: string
+}
+
+export interface SFFSRawResourceGraph {
+  resource_ids: string[]
+  links: SFFSRawResourceLink[]
+}
+
+export interface SFFSRawResourceVersion {
+  id: string
+  resource_id: string
+  content_hash: string
+  snapshot_path: string
+  size: number
+  created_at: string
+}
+
+export interface SFFSRawResourceVersionContent {
+  version: SFFSRawResourceVersion
+  content: string
+}
+
+export interface SFFSRawResourceVersionDiff {
+  from_version_id: string
+  to_version_id: string
+  insertions: number
+  deletions: number
+  lines: { kind: DiffLineKind; text: string }[]
+}
+
+export interface SFFSRawDuplicateGroup {
+  reason: DuplicateReason
+  key: string | null
+  resource_ids: string[]
+  similarity: number | null
+}
+
+export interface SFFSRawDuplicateReport {
+  groups: SFFSRawDuplicateGroup[]
+}
+
+export interface SFFSRawResourceMergeResult {
+  resource_id: string
+  merged_ids: string[]
+  annotation_ids: string[]
+}
+
 export type AIChatRaw = {
   id: string
   title: string